@@ -0,0 +1,10 @@
+CREATE FUNCTION html_escape(text_to_escape TEXT)
+RETURNS TEXT AS $$
+    SELECT replace(replace(replace(replace(replace(
+        text_to_escape,
+        '&', '&amp;'),
+        '<', '&lt;'),
+        '>', '&gt;'),
+        '"', '&quot;'),
+        '''', '&#39;');
+$$ LANGUAGE sql IMMUTABLE;
\ No newline at end of file
@@ -0,0 +1,15 @@
+CREATE FUNCTION primary_key_condition(target_table TEXT, target_key TEXT[], target_alias TEXT)
+RETURNS TEXT AS $$
+    SELECT string_agg(
+        format('%I.%I::TEXT = %L', target_alias, a.attname, target_key[k.key_position]),
+        ' AND '
+    )
+    FROM pg_index AS i
+    CROSS JOIN LATERAL unnest(i.indkey) WITH ORDINALITY AS k (attnum, key_position)
+    INNER JOIN pg_attribute AS a
+        ON a.attrelid = i.indrelid
+        AND a.attnum = k.attnum
+    WHERE
+        i.indrelid = target_table::REGCLASS
+        AND i.indisprimary;
+$$ LANGUAGE sql STABLE;
\ No newline at end of file
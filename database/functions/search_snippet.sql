@@ -0,0 +1,20 @@
+CREATE FUNCTION search_snippet(search_text TEXT, search_terms TEXT)
+RETURNS TEXT AS $$
+    SELECT
+        CASE
+            WHEN to_tsvector('simple', search_text) @@ to_search_query(search_terms) THEN
+                ts_headline(
+                    'simple',
+                    html_escape(search_text),
+                    to_search_query(search_terms),
+                    'StartSel=<mark>, StopSel=</mark>, MinWords=5, MaxWords=20'
+                )
+            ELSE
+                regexp_replace(
+                    html_escape(search_text),
+                    to_search_pattern(html_escape(search_terms)),
+                    '<mark>\&</mark>',
+                    'i'
+                )
+        END;
+$$ LANGUAGE sql IMMUTABLE;
\ No newline at end of file
@@ -0,0 +1,62 @@
+CREATE FUNCTION select_dependencies(target_table TEXT, target_key TEXT[], only_active BOOLEAN DEFAULT FALSE)
+RETURNS TABLE (relation TEXT, referencing_rows BIGINT) AS $$
+    DECLARE
+        target_key_condition TEXT;
+        referencing_relation RECORD;
+    BEGIN
+        target_key_condition := primary_key_condition(target_table, target_key, 't');
+
+        FOR referencing_relation IN
+            SELECT
+                c.conrelid::REGCLASS::TEXT AS name,
+                EXISTS (
+                    SELECT 1
+                    FROM pg_attribute AS da
+                    WHERE
+                        da.attrelid = c.conrelid
+                        AND da.attname = 'deleted_at'
+                        AND NOT da.attisdropped
+                ) AS is_soft_deletable,
+                string_agg(
+                    (
+                        SELECT '(' || string_agg(format('r.%I = t.%I', ra.attname, ta.attname), ' AND ') || ')'
+                        FROM unnest(c.conkey, c.confkey) AS key_columns (referencing_attnum, referenced_attnum)
+                        INNER JOIN pg_attribute AS ra
+                            ON ra.attrelid = c.conrelid
+                            AND ra.attnum = key_columns.referencing_attnum
+                        INNER JOIN pg_attribute AS ta
+                            ON ta.attrelid = c.confrelid
+                            AND ta.attnum = key_columns.referenced_attnum
+                    ),
+                    ' OR '
+                ) AS join_condition
+            FROM pg_constraint AS c
+            WHERE
+                c.contype = 'f'
+                AND c.confrelid = target_table::REGCLASS
+                AND c.confdeltype IN ('a', 'r')
+            GROUP BY c.conrelid
+            ORDER BY name
+        LOOP
+            relation := referencing_relation.name;
+
+            EXECUTE format(
+                'SELECT COUNT(*) FROM %s AS r INNER JOIN %s AS t ON %s WHERE %s %s',
+                referencing_relation.name,
+                target_table::REGCLASS,
+                referencing_relation.join_condition,
+                target_key_condition,
+                CASE
+                    WHEN only_active AND referencing_relation.is_soft_deletable
+                        THEN 'AND r.deleted_at IS NULL'
+                    ELSE ''
+                END
+            )
+            INTO referencing_rows;
+
+            IF referencing_rows > 0 THEN
+                RETURN NEXT;
+            END IF;
+        END LOOP;
+    END;
+$$ LANGUAGE plpgsql STABLE;
\ No newline at end of file
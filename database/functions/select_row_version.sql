@@ -0,0 +1,16 @@
+CREATE FUNCTION select_row_version(target_table TEXT, target_key TEXT[], lock_row BOOLEAN)
+RETURNS TEXT AS $$
+    DECLARE
+        row_version TEXT;
+    BEGIN
+        EXECUTE format(
+            'SELECT t.xmin::TEXT FROM %s AS t WHERE %s %s',
+            target_table::REGCLASS,
+            primary_key_condition(target_table, target_key, 't'),
+            CASE WHEN lock_row THEN 'FOR UPDATE' ELSE '' END
+        )
+        INTO row_version;
+
+        RETURN row_version;
+    END;
+$$ LANGUAGE plpgsql;
\ No newline at end of file
@@ -0,0 +1,32 @@
+CREATE FUNCTION select_valued_stock(as_of_date DATE)
+RETURNS TABLE (
+    product_id INTEGER,
+    dealership_rif rif,
+    product_count INTEGER,
+    product_cost NUMERIC
+) AS $$
+    SELECT
+        s.product_id,
+        s.dealership_rif,
+        s.product_count,
+        s.product_cost
+    FROM stock AS s
+    WHERE as_of_date IS NULL
+    UNION ALL
+    (
+        SELECT DISTINCT ON (sm.product_id, sm.dealership_rif)
+            sm.product_id,
+            sm.dealership_rif,
+            sm.product_count,
+            sm.product_cost
+        FROM stock_movements AS sm
+        WHERE
+            as_of_date IS NOT NULL
+            AND sm.movement_timestamp < as_of_date + 1
+        ORDER BY
+            sm.product_id,
+            sm.dealership_rif,
+            sm.movement_timestamp DESC,
+            sm.id DESC
+    );
+$$ LANGUAGE sql STABLE;
\ No newline at end of file
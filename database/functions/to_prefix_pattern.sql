@@ -0,0 +1,4 @@
+CREATE FUNCTION to_prefix_pattern(prefix TEXT)
+RETURNS TEXT AS $$
+    SELECT regexp_replace(prefix, '([\\%_])', '\\\1', 'g') || '%';
+$$ LANGUAGE sql IMMUTABLE;
\ No newline at end of file
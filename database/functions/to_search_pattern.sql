@@ -0,0 +1,4 @@
+CREATE FUNCTION to_search_pattern(search_terms TEXT)
+RETURNS TEXT AS $$
+    SELECT regexp_replace(trim(search_terms), '([][(){}.*+?^$|\\])', '\\\1', 'g');
+$$ LANGUAGE sql IMMUTABLE;
\ No newline at end of file
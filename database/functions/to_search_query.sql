@@ -0,0 +1,6 @@
+CREATE FUNCTION to_search_query(search_terms TEXT)
+RETURNS TSQUERY AS $$
+    SELECT to_tsquery('simple', string_agg(t.term || ':*', ' & '))
+    FROM regexp_split_to_table(lower(search_terms), '[^[:alnum:]]+') AS t (term)
+    WHERE t.term <> '';
+$$ LANGUAGE sql IMMUTABLE;
\ No newline at end of file
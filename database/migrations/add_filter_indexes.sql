@@ -0,0 +1,24 @@
+BEGIN;
+
+CREATE INDEX payments_invoice_id_idx
+    ON payments (invoice_id);
+
+CREATE INDEX cities_state_id_idx
+    ON cities (state_id);
+
+CREATE INDEX activities_service_id_idx
+    ON activities (service_id);
+
+CREATE INDEX vehicles_owner_national_id_idx
+    ON vehicles (owner_national_id);
+
+CREATE INDEX stock_dealership_rif_idx
+    ON stock (dealership_rif);
+
+CREATE INDEX discounts_dealership_rif_idx
+    ON discounts (dealership_rif);
+
+CREATE INDEX activities_prices_dealership_rif_idx
+    ON activities_prices (dealership_rif);
+
+COMMIT;
\ No newline at end of file
@@ -0,0 +1,56 @@
+BEGIN;
+
+\ir ../extensions/pg_trgm.sql
+
+\ir ../functions/html_escape.sql
+\ir ../functions/to_search_query.sql
+\ir ../functions/to_search_pattern.sql
+\ir ../functions/search_snippet.sql
+
+ALTER TABLE clients
+    ADD COLUMN search_text TEXT NOT NULL GENERATED ALWAYS AS (
+        national_id || ' '
+        || full_name || ' '
+        || main_phone_no || ' '
+        || secondary_phone_no || ' '
+        || email
+    ) STORED;
+
+CREATE INDEX clients_search_text_trgm_idx
+    ON clients USING GIN (search_text gin_trgm_ops);
+
+CREATE INDEX clients_search_text_fts_idx
+    ON clients USING GIN (to_tsvector('simple', search_text));
+
+ALTER TABLE vehicles
+    ADD COLUMN search_text TEXT NOT NULL GENERATED ALWAYS AS (
+        plate || ' '
+        || brand || ' '
+        || serial_no || ' '
+        || engine_serial_no || ' '
+        || color || ' '
+        || owner_national_id
+    ) STORED;
+
+CREATE INDEX vehicles_search_text_trgm_idx
+    ON vehicles USING GIN (search_text gin_trgm_ops);
+
+CREATE INDEX vehicles_search_text_fts_idx
+    ON vehicles USING GIN (to_tsvector('simple', search_text));
+
+ALTER TABLE staff
+    ADD COLUMN search_text TEXT NOT NULL GENERATED ALWAYS AS (
+        national_id || ' '
+        || full_name || ' '
+        || main_phone_no || ' '
+        || secondary_phone_no || ' '
+        || email
+    ) STORED;
+
+CREATE INDEX staff_search_text_trgm_idx
+    ON staff USING GIN (search_text gin_trgm_ops);
+
+CREATE INDEX staff_search_text_fts_idx
+    ON staff USING GIN (to_tsvector('simple', search_text));
+
+COMMIT;
\ No newline at end of file
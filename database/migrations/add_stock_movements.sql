@@ -0,0 +1,24 @@
+BEGIN;
+
+\ir ../tables/stock_movements.sql
+\ir ../functions/select_valued_stock.sql
+\ir ../triggers/trigger_record_stock_movement.sql
+
+INSERT INTO stock_movements (
+    product_id,
+    dealership_rif,
+    movement_timestamp,
+    product_count_change,
+    product_count,
+    product_cost
+)
+SELECT
+    product_id,
+    dealership_rif,
+    LOCALTIMESTAMP,
+    product_count,
+    product_count,
+    product_cost
+FROM stock;
+
+COMMIT;
\ No newline at end of file
@@ -0,0 +1,26 @@
+BEGIN;
+
+\ir ../functions/to_prefix_pattern.sql
+
+CREATE INDEX states_name_lower_idx
+    ON states (lower(name) text_pattern_ops);
+
+CREATE INDEX dealerships_name_lower_idx
+    ON dealerships (lower(name) text_pattern_ops);
+
+CREATE INDEX vehicle_models_name_lower_idx
+    ON vehicle_models (lower(name) text_pattern_ops);
+
+CREATE INDEX products_name_lower_idx
+    ON products (lower(name) text_pattern_ops);
+
+CREATE INDEX services_name_lower_idx
+    ON services (lower(name) text_pattern_ops);
+
+CREATE INDEX cities_state_id_name_lower_idx
+    ON cities (state_id, lower(name) text_pattern_ops);
+
+CREATE INDEX activities_service_id_description_lower_idx
+    ON activities (service_id, lower(description) text_pattern_ops);
+
+COMMIT;
\ No newline at end of file
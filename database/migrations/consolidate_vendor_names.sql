@@ -0,0 +1,37 @@
+BEGIN;
+
+\ir ../tables/vendors.sql
+\ir ../tables/vendors_supply_lines.sql
+
+INSERT INTO vendors (name)
+SELECT MODE() WITHIN GROUP (ORDER BY TRIM(vendor_name))
+FROM stock
+GROUP BY LOWER(REGEXP_REPLACE(vendor_name, '[^[:alnum:]]+', '', 'g'));
+
+ALTER TABLE stock
+    ADD COLUMN vendor_id INTEGER;
+
+UPDATE stock AS s
+SET vendor_id = v.id
+FROM vendors AS v
+WHERE
+    LOWER(REGEXP_REPLACE(s.vendor_name, '[^[:alnum:]]+', '', 'g'))
+        = LOWER(REGEXP_REPLACE(v.name, '[^[:alnum:]]+', '', 'g'));
+
+INSERT INTO vendors_supply_lines (vendor_id, supply_line_id)
+SELECT DISTINCT
+    s.vendor_id,
+    p.supply_line_id
+FROM
+    stock AS s
+    INNER JOIN products AS p ON s.product_id = p.id;
+
+ALTER TABLE stock
+    ALTER COLUMN vendor_id SET NOT NULL,
+    ADD CONSTRAINT stock_vendor_id_fk
+        FOREIGN KEY (vendor_id) REFERENCES vendors (id)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    DROP COLUMN vendor_name;
+
+COMMIT;
\ No newline at end of file
@@ -11,4 +11,10 @@ CREATE TABLE activities (
     price_per_hour NUMERIC NOT NULL
         CONSTRAINT valid_price_per_hour
             CHECK (price_per_hour >= 0)
-);
\ No newline at end of file
+);
+
+CREATE INDEX activities_service_id_idx
+    ON activities (service_id);
+
+CREATE INDEX activities_service_id_description_lower_idx
+    ON activities (service_id, lower(description) text_pattern_ops);
\ No newline at end of file
@@ -15,4 +15,7 @@ CREATE TABLE activities_prices (
     price_per_hour NUMERIC NOT NULL
         CONSTRAINT valid_price_per_hour
             CHECK (price_per_hour >= 0)
-);
\ No newline at end of file
+);
+
+CREATE INDEX activities_prices_dealership_rif_idx
+    ON activities_prices (dealership_rif);
\ No newline at end of file
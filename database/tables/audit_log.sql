@@ -0,0 +1,24 @@
+CREATE TABLE audit_log (
+    CONSTRAINT audit_log_pk
+        PRIMARY KEY (id),
+    id BIGINT NOT NULL GENERATED ALWAYS AS IDENTITY,
+    audit_timestamp TIMESTAMP NOT NULL,
+    actor_national_id TEXT,
+    request_id TEXT,
+    resource TEXT NOT NULL,
+    resource_key TEXT NOT NULL,
+    operation TEXT NOT NULL
+        CONSTRAINT valid_operation
+            CHECK (operation IN ('create', 'update', 'delete')),
+    before_data JSONB,
+    after_data JSONB,
+    CONSTRAINT consistency_between_operation_and_data
+        CHECK (
+            (operation = 'create' AND before_data IS NULL AND after_data IS NOT NULL)
+            OR (operation = 'update' AND before_data IS NOT NULL AND after_data IS NOT NULL)
+            OR (operation = 'delete' AND before_data IS NOT NULL AND after_data IS NULL)
+        )
+);
+
+CREATE INDEX audit_log_resource_key_idx
+    ON audit_log (resource, resource_key, audit_timestamp);
\ No newline at end of file
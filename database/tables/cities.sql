@@ -10,4 +10,10 @@ CREATE TABLE cities (
     state_id INTEGER NOT NULL,
     CONSTRAINT unique_name_state_id
         UNIQUE (name, state_id)
-);
\ No newline at end of file
+);
+
+CREATE INDEX cities_state_id_idx
+    ON cities (state_id);
+
+CREATE INDEX cities_state_id_name_lower_idx
+    ON cities (state_id, lower(name) text_pattern_ops);
\ No newline at end of file
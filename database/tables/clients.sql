@@ -5,5 +5,19 @@ CREATE TABLE clients (
     full_name TEXT NOT NULL,
     main_phone_no phone_no NOT NULL,
     secondary_phone_no phone_no NOT NULL,
-    email email NOT NULL
-);
\ No newline at end of file
+    email email NOT NULL,
+    deleted_at TIMESTAMP,
+    search_text TEXT NOT NULL GENERATED ALWAYS AS (
+        national_id || ' '
+        || full_name || ' '
+        || main_phone_no || ' '
+        || secondary_phone_no || ' '
+        || email
+    ) STORED
+);
+
+CREATE INDEX clients_search_text_trgm_idx
+    ON clients USING GIN (search_text gin_trgm_ops);
+
+CREATE INDEX clients_search_text_fts_idx
+    ON clients USING GIN (to_tsvector('simple', search_text));
\ No newline at end of file
@@ -8,5 +8,9 @@ CREATE TABLE dealerships (
     rif rif NOT NULL,
     name TEXT NOT NULL,
     city_number INTEGER NOT NULL,
-    state_id INTEGER NOT NULL
-);
\ No newline at end of file
+    state_id INTEGER NOT NULL,
+    deleted_at TIMESTAMP
+);
+
+CREATE INDEX dealerships_name_lower_idx
+    ON dealerships (lower(name) text_pattern_ops);
\ No newline at end of file
@@ -13,4 +13,7 @@ CREATE TABLE discounts (
     required_annual_service_usage_count SMALLINT NOT NULL
         CONSTRAINT valid_required_annual_service_usage_count
             CHECK (required_annual_service_usage_count >= 0)
-);
\ No newline at end of file
+);
+
+CREATE INDEX discounts_dealership_rif_idx
+    ON discounts (dealership_rif);
\ No newline at end of file
@@ -22,4 +22,7 @@ CREATE TABLE payments (
             )),
     card_number TEXT NOT NULL,
     card_bank TEXT NOT NULL
-);
\ No newline at end of file
+);
+
+CREATE INDEX payments_invoice_id_idx
+    ON payments (invoice_id);
\ No newline at end of file
@@ -0,0 +1,27 @@
+CREATE TABLE permissions (
+    CONSTRAINT permissions_pk
+        PRIMARY KEY (name),
+    name TEXT NOT NULL,
+    description TEXT NOT NULL
+);
+
+INSERT INTO permissions
+    (name, description)
+VALUES
+    ('catalog:read', 'Consultar estados, ciudades, modelos, productos, servicios, actividades, líneas de suministro, proveedores y descuentos.'),
+    ('catalog:write', 'Crear, modificar y eliminar estados, ciudades, modelos, productos, servicios, actividades, líneas de suministro, proveedores y descuentos.'),
+    ('clients:read', 'Consultar clientes y sus vehículos.'),
+    ('clients:write', 'Crear, modificar y eliminar clientes y sus vehículos.'),
+    ('dealerships:read', 'Consultar concesionarios.'),
+    ('dealerships:write', 'Crear, modificar y eliminar concesionarios.'),
+    ('staff:read', 'Consultar empleados, roles y cuentas.'),
+    ('staff:write', 'Crear, modificar y eliminar empleados, roles, permisos y cuentas.'),
+    ('salaries:write', 'Asignar o modificar el salario de los empleados.'),
+    ('stock:read', 'Consultar el inventario y los traslados entre concesionarios.'),
+    ('stock:write', 'Modificar el inventario y gestionar los traslados entre concesionarios.'),
+    ('invoices:read', 'Consultar facturas y pagos.'),
+    ('invoices:write', 'Crear y modificar facturas y registrar pagos.'),
+    ('invoices:delete', 'Eliminar facturas.'),
+    ('reports:read', 'Consultar los reportes y estadísticas.'),
+    ('audit:read', 'Consultar el historial de cambios realizados sobre los datos.'),
+    ('dealerships:all', 'Acceder a los datos de cualquier concesionario y no solo a los del concesionario asignado.');
\ No newline at end of file
@@ -9,5 +9,9 @@ CREATE TABLE products (
     name TEXT NOT NULL,
     description TEXT NOT NULL,
     is_ecologic BOOLEAN NOT NULL,
-    supply_line_id INTEGER NOT NULL
-);
\ No newline at end of file
+    supply_line_id INTEGER NOT NULL,
+    deleted_at TIMESTAMP
+);
+
+CREATE INDEX products_name_lower_idx
+    ON products (lower(name) text_pattern_ops);
\ No newline at end of file
@@ -0,0 +1,17 @@
+CREATE TABLE products_fluid_types (
+    CONSTRAINT products_fluid_types_pk
+        PRIMARY KEY (product_id, fluid, fluid_type),
+    CONSTRAINT products_fluid_types_product_id_fk
+        FOREIGN KEY (product_id) REFERENCES products (id)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    product_id INTEGER NOT NULL,
+    fluid TEXT NOT NULL
+        CONSTRAINT valid_fluid
+            CHECK (fluid IN (
+                'gearbox-oil',
+                'engine-oil',
+                'engine-coolant'
+            )),
+    fluid_type TEXT NOT NULL
+);
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE products_vehicle_models (
+    CONSTRAINT products_vehicle_models_pk
+        PRIMARY KEY (product_id, vehicle_model_id),
+    CONSTRAINT products_vehicle_models_product_id_fk
+        FOREIGN KEY (product_id) REFERENCES products (id)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    CONSTRAINT products_vehicle_models_vehicle_model_id_fk
+        FOREIGN KEY (vehicle_model_id) REFERENCES vehicle_models (id)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    product_id INTEGER NOT NULL,
+    vehicle_model_id INTEGER NOT NULL
+);
\ No newline at end of file
@@ -15,3 +15,9 @@ INSERT INTO roles
     (name, description)
 VALUES
     ('Analista', 'Empleado que recibe vehiculos para su revisión y genera órdenes si es necesario.');
+
+
+INSERT INTO roles
+    (name, description)
+VALUES
+    ('Oficina central', 'Empleado de la sede principal con acceso a los datos de todos los concesionarios.');
\ No newline at end of file
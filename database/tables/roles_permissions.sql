@@ -0,0 +1,48 @@
+CREATE TABLE roles_permissions (
+    CONSTRAINT roles_permissions_pk
+        PRIMARY KEY (role_id, permission),
+    CONSTRAINT roles_permissions_role_id_fk
+        FOREIGN KEY (role_id) REFERENCES roles (id)
+            ON UPDATE CASCADE
+            ON DELETE CASCADE,
+    CONSTRAINT roles_permissions_permission_fk
+        FOREIGN KEY (permission) REFERENCES permissions (name)
+            ON UPDATE CASCADE
+            ON DELETE CASCADE,
+    role_id INTEGER NOT NULL,
+    permission TEXT NOT NULL
+);
+
+INSERT INTO roles_permissions
+    (role_id, permission)
+SELECT r.id, p.name
+FROM roles AS r
+CROSS JOIN permissions AS p
+WHERE
+    r.name = 'Encargado'
+    AND p.name <> 'dealerships:all';
+
+INSERT INTO roles_permissions
+    (role_id, permission)
+SELECT r.id, p.name
+FROM roles AS r
+CROSS JOIN permissions AS p
+WHERE r.name = 'Oficina central';
+
+INSERT INTO roles_permissions
+    (role_id, permission)
+SELECT r.id, p.name
+FROM roles AS r
+CROSS JOIN permissions AS p
+WHERE
+    r.name = 'Analista'
+    AND p.name IN (
+        'catalog:read',
+        'clients:read',
+        'clients:write',
+        'dealerships:read',
+        'stock:read',
+        'invoices:read',
+        'invoices:write',
+        'reports:read'
+    );
\ No newline at end of file
@@ -9,4 +9,7 @@ CREATE TABLE services (
     name TEXT NOT NULL,
     description TEXT NOT NULL,
     coordinator_national_id national_id NOT NULL
-);
\ No newline at end of file
+);
+
+CREATE INDEX services_name_lower_idx
+    ON services (lower(name) text_pattern_ops);
\ No newline at end of file
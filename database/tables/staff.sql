@@ -19,5 +19,19 @@ CREATE TABLE staff (
     role_id INTEGER NOT NULL,
     salary NUMERIC NOT NULL
         CONSTRAINT valid_salary
-            CHECK (salary >= 0)
-);
\ No newline at end of file
+            CHECK (salary >= 0),
+    deleted_at TIMESTAMP,
+    search_text TEXT NOT NULL GENERATED ALWAYS AS (
+        national_id || ' '
+        || full_name || ' '
+        || main_phone_no || ' '
+        || secondary_phone_no || ' '
+        || email
+    ) STORED
+);
+
+CREATE INDEX staff_search_text_trgm_idx
+    ON staff USING GIN (search_text gin_trgm_ops);
+
+CREATE INDEX staff_search_text_fts_idx
+    ON staff USING GIN (to_tsvector('simple', search_text));
\ No newline at end of file
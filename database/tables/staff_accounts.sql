@@ -0,0 +1,10 @@
+CREATE TABLE staff_accounts (
+    CONSTRAINT staff_accounts_pk
+        PRIMARY KEY (national_id),
+    CONSTRAINT staff_accounts_national_id_fk
+        FOREIGN KEY (national_id) REFERENCES staff (national_id)
+            ON UPDATE CASCADE
+            ON DELETE CASCADE,
+    national_id national_id NOT NULL,
+    password_hash TEXT NOT NULL
+);
\ No newline at end of file
@@ -3,4 +3,7 @@ CREATE TABLE states (
         PRIMARY KEY (id),
     id INTEGER NOT NULL GENERATED ALWAYS AS IDENTITY,
     name TEXT NOT NULL
-);
\ No newline at end of file
+);
+
+CREATE INDEX states_name_lower_idx
+    ON states (lower(name) text_pattern_ops);
\ No newline at end of file
@@ -9,13 +9,17 @@ CREATE TABLE stock (
         FOREIGN KEY (dealership_rif) REFERENCES dealerships (rif)
             ON UPDATE CASCADE
             ON DELETE RESTRICT,
+    CONSTRAINT stock_vendor_id_fk
+        FOREIGN KEY (vendor_id) REFERENCES vendors (id)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
     product_id INTEGER NOT NULL,
     dealership_rif rif NOT NULL,
     product_cost NUMERIC NOT NULL
         CONSTRAINT valid_product_cost
             CHECK (product_cost >= 0),
     product_count INTEGER NOT NULL,
-    vendor_name TEXT NOT NULL,
+    vendor_id INTEGER NOT NULL,
     max_capacity INTEGER NOT NULL,
     min_capacity INTEGER NOT NULL
         CONSTRAINT valid_min_capacity
@@ -24,4 +28,7 @@ CREATE TABLE stock (
         CHECK (product_count >= min_capacity),
     CONSTRAINT consistency_between_min_capacity_and_max_capacity
         CHECK (max_capacity >= min_capacity)
-);
\ No newline at end of file
+);
+
+CREATE INDEX stock_dealership_rif_idx
+    ON stock (dealership_rif);
\ No newline at end of file
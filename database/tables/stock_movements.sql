@@ -0,0 +1,18 @@
+CREATE TABLE stock_movements (
+    CONSTRAINT stock_movements_pk
+        PRIMARY KEY (id),
+    id INTEGER NOT NULL GENERATED ALWAYS AS IDENTITY,
+    product_id INTEGER NOT NULL,
+    dealership_rif rif NOT NULL,
+    movement_timestamp TIMESTAMP NOT NULL,
+    product_count_change INTEGER NOT NULL,
+    product_count INTEGER NOT NULL
+        CONSTRAINT valid_product_count
+            CHECK (product_count >= 0),
+    product_cost NUMERIC NOT NULL
+        CONSTRAINT valid_product_cost
+            CHECK (product_cost >= 0)
+);
+
+CREATE INDEX stock_movements_stock_item_timestamp_idx
+    ON stock_movements (product_id, dealership_rif, movement_timestamp);
\ No newline at end of file
@@ -0,0 +1,34 @@
+CREATE TABLE stock_transfers (
+    CONSTRAINT stock_transfers_pk
+        PRIMARY KEY (id),
+    CONSTRAINT stock_transfers_product_id_source_dealership_rif_fk
+        FOREIGN KEY (product_id, source_dealership_rif) REFERENCES stock (product_id, dealership_rif)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    CONSTRAINT stock_transfers_product_id_destination_dealership_rif_fk
+        FOREIGN KEY (product_id, destination_dealership_rif) REFERENCES stock (product_id, dealership_rif)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    id INTEGER NOT NULL GENERATED ALWAYS AS IDENTITY,
+    product_id INTEGER NOT NULL,
+    source_dealership_rif rif NOT NULL,
+    destination_dealership_rif rif NOT NULL,
+    product_count INTEGER NOT NULL
+        CONSTRAINT valid_product_count
+            CHECK (product_count > 0),
+    status TEXT NOT NULL
+        CONSTRAINT valid_status
+            CHECK (status IN (
+                'in-transit',
+                'received',
+                'cancelled'
+            )),
+    dispatch_timestamp TIMESTAMP NOT NULL,
+    resolution_timestamp TIMESTAMP,
+    CONSTRAINT different_source_and_destination_dealerships
+        CHECK (source_dealership_rif <> destination_dealership_rif),
+    CONSTRAINT consistency_between_status_and_resolution_timestamp
+        CHECK ((status = 'in-transit') = (resolution_timestamp IS NULL)),
+    CONSTRAINT consistency_between_dispatch_and_resolution_timestamps
+        CHECK (dispatch_timestamp <= resolution_timestamp)
+);
\ No newline at end of file
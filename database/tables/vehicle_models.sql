@@ -15,4 +15,7 @@ CREATE TABLE vehicle_models (
     gearbox_oil_type TEXT NOT NULL,
     engine_oil_type TEXT NOT NULL,
     engine_coolant_type TEXT NOT NULL
-);
\ No newline at end of file
+);
+
+CREATE INDEX vehicle_models_name_lower_idx
+    ON vehicle_models (lower(name) text_pattern_ops);
\ No newline at end of file
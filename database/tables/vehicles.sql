@@ -18,5 +18,23 @@ CREATE TABLE vehicles (
     purchase_date DATE NOT NULL,
     additional_info TEXT,
     maintenance_summary TEXT,
-    owner_national_id national_id NOT NULL
-);
\ No newline at end of file
+    owner_national_id national_id NOT NULL,
+    deleted_at TIMESTAMP,
+    search_text TEXT NOT NULL GENERATED ALWAYS AS (
+        plate || ' '
+        || brand || ' '
+        || serial_no || ' '
+        || engine_serial_no || ' '
+        || color || ' '
+        || owner_national_id
+    ) STORED
+);
+
+CREATE INDEX vehicles_search_text_trgm_idx
+    ON vehicles USING GIN (search_text gin_trgm_ops);
+
+CREATE INDEX vehicles_search_text_fts_idx
+    ON vehicles USING GIN (to_tsvector('simple', search_text));
+
+CREATE INDEX vehicles_owner_national_id_idx
+    ON vehicles (owner_national_id);
\ No newline at end of file
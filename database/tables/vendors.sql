@@ -0,0 +1,14 @@
+CREATE TABLE vendors (
+    CONSTRAINT vendors_pk
+        PRIMARY KEY (id),
+    CONSTRAINT unique_rif
+        UNIQUE (rif),
+    id INTEGER NOT NULL GENERATED ALWAYS AS IDENTITY,
+    rif rif,
+    name TEXT NOT NULL,
+    phone_no phone_no,
+    email email
+);
+
+CREATE UNIQUE INDEX vendors_normalized_name_idx
+    ON vendors (LOWER(REGEXP_REPLACE(name, '[^[:alnum:]]+', '', 'g')));
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE vendors_supply_lines (
+    CONSTRAINT vendors_supply_lines_pk
+        PRIMARY KEY (vendor_id, supply_line_id),
+    CONSTRAINT vendors_supply_lines_vendor_id_fk
+        FOREIGN KEY (vendor_id) REFERENCES vendors (id)
+            ON UPDATE CASCADE
+            ON DELETE CASCADE,
+    CONSTRAINT vendors_supply_lines_supply_line_id_fk
+        FOREIGN KEY (supply_line_id) REFERENCES supply_lines (id)
+            ON UPDATE CASCADE
+            ON DELETE RESTRICT,
+    vendor_id INTEGER NOT NULL,
+    supply_line_id INTEGER NOT NULL
+);
\ No newline at end of file
@@ -0,0 +1,178 @@
+CREATE FUNCTION record_audit_entry() RETURNS trigger AS $$
+    DECLARE
+        before_data JSONB;
+        after_data JSONB;
+        resource_key TEXT;
+    BEGIN
+        IF TG_OP IN ('UPDATE', 'DELETE') THEN
+            before_data := to_jsonb(OLD) - 'password_hash' - 'search_text';
+        END IF;
+
+        IF TG_OP IN ('INSERT', 'UPDATE') THEN
+            after_data := to_jsonb(NEW) - 'password_hash' - 'search_text';
+        END IF;
+
+        SELECT string_agg(COALESCE(after_data, before_data) ->> key_column, ',' ORDER BY key_position)
+        INTO resource_key
+        FROM unnest(TG_ARGV) WITH ORDINALITY AS key_columns (key_column, key_position);
+
+        INSERT INTO audit_log (
+            audit_timestamp,
+            actor_national_id,
+            request_id,
+            resource,
+            resource_key,
+            operation,
+            before_data,
+            after_data
+        )
+        VALUES (
+            LOCALTIMESTAMP,
+            NULLIF(current_setting('audit.actor_national_id', true), ''),
+            NULLIF(current_setting('audit.request_id', true), ''),
+            TG_TABLE_NAME,
+            resource_key,
+            CASE TG_OP
+                WHEN 'INSERT' THEN 'create'
+                WHEN 'UPDATE' THEN 'update'
+                ELSE 'delete'
+            END,
+            before_data,
+            after_data
+        );
+
+        RETURN NULL;
+    END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON activities
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('activity_number', 'service_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON activities_prices
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('activity_number', 'service_id', 'dealership_rif');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON cities
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('city_number', 'state_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON clients
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('national_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON dealerships
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('rif');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON discounts
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('discount_number', 'dealership_rif');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON invoices
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON managers
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('national_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON offered_services
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('service_id', 'dealership_rif');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON operatives
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('national_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON orders
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON orders_details
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('order_id', 'activity_number', 'service_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON payments
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('payment_number', 'invoice_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON permissions
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('name');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON products
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON products_applications
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('order_id', 'activity_number', 'service_id', 'product_id', 'employee_national_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON products_fluid_types
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('product_id', 'fluid', 'fluid_type');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON products_vehicle_models
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('product_id', 'vehicle_model_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON recommended_services
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('service_id', 'vehicle_model_id', 'required_usage_time', 'required_kilometrage');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON roles
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON roles_permissions
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('role_id', 'permission');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON services
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON specializations
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('employee_national_id', 'service_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON staff
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('national_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON staff_accounts
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('national_id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON states
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON stock
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('product_id', 'dealership_rif');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON stock_transfers
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON supply_lines
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON vehicle_models
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON vehicles
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('plate');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON vendors
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('id');
+
+CREATE TRIGGER trigger_record_audit_entry
+AFTER INSERT OR UPDATE OR DELETE ON vendors_supply_lines
+FOR EACH ROW EXECUTE FUNCTION record_audit_entry('vendor_id', 'supply_line_id');
\ No newline at end of file
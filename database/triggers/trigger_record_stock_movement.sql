@@ -0,0 +1,77 @@
+CREATE FUNCTION record_stock_movement() RETURNS trigger AS $$
+    BEGIN
+        IF TG_OP IN ('UPDATE', 'DELETE')
+            AND (
+                TG_OP = 'DELETE'
+                OR OLD.product_id <> NEW.product_id
+                OR OLD.dealership_rif <> NEW.dealership_rif
+            )
+        THEN
+            INSERT INTO stock_movements (
+                product_id,
+                dealership_rif,
+                movement_timestamp,
+                product_count_change,
+                product_count,
+                product_cost
+            )
+            VALUES (
+                OLD.product_id,
+                OLD.dealership_rif,
+                LOCALTIMESTAMP,
+                -OLD.product_count,
+                0,
+                OLD.product_cost
+            );
+        END IF;
+
+        IF TG_OP = 'DELETE' THEN
+            RETURN OLD;
+        END IF;
+
+        IF TG_OP = 'INSERT'
+            OR OLD.product_id <> NEW.product_id
+            OR OLD.dealership_rif <> NEW.dealership_rif
+        THEN
+            INSERT INTO stock_movements (
+                product_id,
+                dealership_rif,
+                movement_timestamp,
+                product_count_change,
+                product_count,
+                product_cost
+            )
+            VALUES (
+                NEW.product_id,
+                NEW.dealership_rif,
+                LOCALTIMESTAMP,
+                NEW.product_count,
+                NEW.product_count,
+                NEW.product_cost
+            );
+        ELSIF OLD.product_count <> NEW.product_count OR OLD.product_cost <> NEW.product_cost THEN
+            INSERT INTO stock_movements (
+                product_id,
+                dealership_rif,
+                movement_timestamp,
+                product_count_change,
+                product_count,
+                product_cost
+            )
+            VALUES (
+                NEW.product_id,
+                NEW.dealership_rif,
+                LOCALTIMESTAMP,
+                NEW.product_count - OLD.product_count,
+                NEW.product_count,
+                NEW.product_cost
+            );
+        END IF;
+
+        RETURN NEW;
+    END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_record_stock_movement
+AFTER INSERT OR UPDATE OR DELETE ON stock
+FOR EACH ROW EXECUTE FUNCTION record_stock_movement();
\ No newline at end of file
@@ -11,10 +11,15 @@ use actix_web::{
 };
 use anyhow::Context;
 use env_logger::Env;
-use sqlx::postgres::PgPoolOptions;
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 use tracing_actix_web::TracingLogger;
 
+use models::staff_account::InsertStaffAccount;
 use services::*;
+use utils::{
+    authentication::{hash_password, Authentication, TokenKeys},
+    extractors::{json_config, path_config},
+};
 
 #[actix_web::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -22,8 +27,6 @@ async fn main() -> Result<(), anyhow::Error> {
 
     let database_url =
         dotenvy::var("DATABASE_URL").context("DATABASE_URL environment variable not found")?;
-    let frontend_url =
-        dotenvy::var("FRONTEND_URL").context("FRONTEND_URL environment variable not found")?;
 
     env_logger::init_from_env(Env::default().default_filter_or("info"));
 
@@ -32,25 +35,63 @@ async fn main() -> Result<(), anyhow::Error> {
         .connect(database_url.as_str())
         .await
         .context("Couldn't connect to the database")?;
+
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, command, national_id] = args.as_slice() {
+        if command == "set-staff-password" {
+            return set_staff_password(national_id.clone(), &db).await;
+        }
+    }
+
+    let frontend_url =
+        dotenvy::var("FRONTEND_URL").context("FRONTEND_URL environment variable not found")?;
+    let jwt_secret =
+        dotenvy::var("JWT_SECRET").context("JWT_SECRET environment variable not found")?;
+
     let db = Data::new(db);
+    let token_keys = Data::new(TokenKeys::new(jwt_secret.as_bytes()));
 
     HttpServer::new(move || {
         App::new()
             .app_data(db.clone())
+            .app_data(token_keys.clone())
+            .app_data(json_config())
+            .app_data(path_config())
+            .wrap(Authentication)
             .wrap(TracingLogger::default())
             .wrap(NormalizePath::new(TrailingSlash::Always))
             .wrap(Cors::permissive().allowed_origin(frontend_url.as_str()))
+            .service(web::scope("/auth").configure(auth::configure))
+            .service(web::scope("/audit").configure(audit::configure))
+            .service(web::scope("/search").configure(search::configure))
             .configure(cities::configure)
             .configure(clients::configure)
             .configure(vehicles::configure)
             .configure(states::configure)
             .configure(vehicle_models::configure)
             .configure(roles::configure)
+            .service(web::scope("/roles-permissions").configure(roles_permissions::configure))
             .configure(supply_lines::configure)
             .service(web::scope("/products").configure(products::configure))
+            .service(
+                web::scope("/products-vehicle-models").configure(products_vehicle_models::configure),
+            )
+            .service(
+                web::scope("/products-fluid-types").configure(products_fluid_types::configure),
+            )
             .service(web::scope("/staff").configure(staff::configure))
+            .service(web::scope("/staff-accounts").configure(staff_accounts::configure))
             .service(web::scope("/activities").configure(activities::configure))
-            .service(web::scope("/dealerships").configure(dealerships::configure))
+            .service(
+                web::scope("/dealerships")
+                    .configure(dealerships::configure)
+                    .configure(services::discounts::configure_nested)
+                    .configure(stock::configure_nested)
+                    .configure(services::activities_prices::configure_nested),
+            )
+            .service(
+                web::scope("/inventory-valuations").configure(inventory_valuations::configure),
+            )
             .service(
                 web::scope("/least-employed-employees")
                     .configure(least_employed_employees::configure),
@@ -89,14 +130,24 @@ async fn main() -> Result<(), anyhow::Error> {
                 web::scope("/vehicle-applied-services")
                     .configure(vehicle_applied_services::configure),
             )
-            .service(web::scope("/services").configure(services::services::configure))
+            .service(
+                web::scope("/services")
+                    .configure(services::services::configure)
+                    .configure(activities::configure_nested),
+            )
             .service(
                 web::scope("/activities-prices").configure(services::activities_prices::configure),
             )
             .service(web::scope("/discounts").configure(services::discounts::configure))
-            .service(web::scope("/invoices").configure(services::invoices::configure))
+            .service(
+                web::scope("/invoices")
+                    .configure(services::invoices::configure)
+                    .configure(services::payments::configure_nested),
+            )
             .service(web::scope("/payments").configure(services::payments::configure))
             .service(web::scope("/stock").configure(stock::configure))
+            .service(web::scope("/stock-transfers").configure(stock_transfers::configure))
+            .service(web::scope("/vendors").configure(vendors::configure))
     })
     .bind(("localhost", 8080))
     .context("Couldn't start the server")?
@@ -104,3 +155,23 @@ async fn main() -> Result<(), anyhow::Error> {
     .await
     .context("Something failed during the server execution")
 }
+
+async fn set_staff_password(national_id: String, db: &Pool<Postgres>) -> Result<(), anyhow::Error> {
+    let mut password = String::new();
+    std::io::stdin()
+        .read_line(&mut password)
+        .context("Couldn't read the password from the standard input")?;
+
+    let password_hash = hash_password(password.trim_end_matches(['\r', '\n']))
+        .context("Couldn't hash the password")?;
+
+    InsertStaffAccount {
+        national_id,
+        password_hash,
+    }
+    .upsert(db)
+    .await
+    .context("Couldn't save the staff account")?;
+
+    Ok(())
+}
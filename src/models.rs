@@ -1,17 +1,29 @@
 pub mod activity;
 pub mod activity_price;
+pub mod audit_entry;
 pub mod city;
 pub mod client;
 pub mod dealership;
+pub mod dependency;
+pub mod domains;
 pub mod discount;
 pub mod employee;
 pub mod product;
+pub mod product_fluid_type;
+pub mod product_vehicle_model;
 pub mod role;
+pub mod role_permission;
+pub mod row_version;
 pub mod service;
 pub mod state;
+pub mod suggestion;
 pub mod supply_line;
 pub mod vehicle;
 pub mod vehicle_model;
 pub mod invoice;
+pub mod order;
 pub mod payment;
-pub mod stock_item;
\ No newline at end of file
+pub mod stock_item;
+pub mod stock_transfer;
+pub mod vendor;
+pub mod staff_account;
\ No newline at end of file
@@ -1,9 +1,16 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::suggestion::Suggestion,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -41,8 +48,16 @@ impl Activity {
     }
 
     pub async fn select_all(
+        filter: &ListFilter<ActivityFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Activity>, sqlx::Error> {
+        Activity::stream_all(filter, connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<ActivityFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Activity, sqlx::Error>> {
         sqlx::query_as!(
             Activity,
             r#"
@@ -52,25 +67,95 @@ impl Activity {
                 description,
                 price_per_hour
             FROM activities
-            "#
+            WHERE
+                ($1::INTEGER IS NULL OR service_id = $1)
+                AND ($2::INTEGER IS NULL OR service_id <> $2)
+                AND ($3::NUMERIC IS NULL OR price_per_hour = $3)
+                AND ($4::NUMERIC IS NULL OR price_per_hour <> $4)
+                AND ($5::NUMERIC IS NULL OR price_per_hour < $5)
+                AND ($6::NUMERIC IS NULL OR price_per_hour <= $6)
+                AND ($7::NUMERIC IS NULL OR price_per_hour > $7)
+                AND ($8::NUMERIC IS NULL OR price_per_hour >= $8)
+            ORDER BY
+                CASE WHEN $9::TEXT = 'activity-number' AND NOT $10 THEN activity_number END,
+                CASE WHEN $9 = 'activity-number' AND $10 THEN activity_number END DESC,
+                CASE WHEN $9 = 'service-id' AND NOT $10 THEN service_id END,
+                CASE WHEN $9 = 'service-id' AND $10 THEN service_id END DESC,
+                CASE WHEN $9 = 'price-per-hour' AND NOT $10 THEN price_per_hour END,
+                CASE WHEN $9 = 'price-per-hour' AND $10 THEN price_per_hour END DESC,
+                activity_number,
+                service_id
+            "#,
+            filter.conditions.service_id.eq,
+            filter.conditions.service_id.ne,
+            filter.conditions.price_per_hour.eq,
+            filter.conditions.price_per_hour.ne,
+            filter.conditions.price_per_hour.lt,
+            filter.conditions.price_per_hour.lte,
+            filter.conditions.price_per_hour.gt,
+            filter.conditions.price_per_hour.gte,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        filter: &ListFilter<ActivityFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_activities!"
             FROM activities
-            "#
+            WHERE
+                ($1::INTEGER IS NULL OR service_id = $1)
+                AND ($2::INTEGER IS NULL OR service_id <> $2)
+                AND ($3::NUMERIC IS NULL OR price_per_hour = $3)
+                AND ($4::NUMERIC IS NULL OR price_per_hour <> $4)
+                AND ($5::NUMERIC IS NULL OR price_per_hour < $5)
+                AND ($6::NUMERIC IS NULL OR price_per_hour <= $6)
+                AND ($7::NUMERIC IS NULL OR price_per_hour > $7)
+                AND ($8::NUMERIC IS NULL OR price_per_hour >= $8)
+            "#,
+            filter.conditions.service_id.eq,
+            filter.conditions.service_id.ne,
+            filter.conditions.price_per_hour.eq,
+            filter.conditions.price_per_hour.ne,
+            filter.conditions.price_per_hour.lt,
+            filter.conditions.price_per_hour.lte,
+            filter.conditions.price_per_hour.gt,
+            filter.conditions.price_per_hour.gte
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        service_id: i32,
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<i32>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT activity_number AS id, description AS label
+            FROM activities
+            WHERE
+                service_id = $1
+                AND lower(description) LIKE to_prefix_pattern(lower($2))
+            ORDER BY description, activity_number
+            LIMIT $3
+            "#,
+            service_id,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         activity_number: i32,
         service_id: i32,
@@ -97,37 +182,171 @@ impl Activity {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct ActivityFilter {
+    pub service_id: Comparison<i32>,
+    pub price_per_hour: Comparison<BigDecimal>,
+}
+
+impl Filter for ActivityFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("service-id", EQUALITY),
+        FilterField::new("price-per-hour", ORDERING),
+    ];
+    const SORT_FIELDS: &'static [&'static str] =
+        &["activity-number", "service-id", "price-per-hour"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "service-id" => self.service_id.apply(operator, value),
+            "price-per-hour" => self.price_per_hour.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<Activity> for Activity {
+    const FIELDS: &'static [&'static str] = &[
+        "activity-number",
+        "service-id",
+        "description",
+        "price-per-hour",
+    ];
+
+    type Filter = ActivityFilter;
+    type CursorKey = (i32, i32);
+
     async fn get_page(
         pages: &Pages<Activity, Activity>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Activity>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Activity,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     activity_number,
                     service_id,
                     description,
-                    price_per_hour
+                    price_per_hour,
+                    COUNT(*) OVER() AS "total!"
                 FROM activities
+                WHERE
+                    ($3::INTEGER IS NULL OR service_id = $3)
+                    AND ($4::INTEGER IS NULL OR service_id <> $4)
+                    AND ($5::NUMERIC IS NULL OR price_per_hour = $5)
+                    AND ($6::NUMERIC IS NULL OR price_per_hour <> $6)
+                    AND ($7::NUMERIC IS NULL OR price_per_hour < $7)
+                    AND ($8::NUMERIC IS NULL OR price_per_hour <= $8)
+                    AND ($9::NUMERIC IS NULL OR price_per_hour > $9)
+                    AND ($10::NUMERIC IS NULL OR price_per_hour >= $10)
+                ORDER BY
+                    CASE WHEN $11::TEXT = 'activity-number' AND NOT $12 THEN activity_number END,
+                    CASE WHEN $11 = 'activity-number' AND $12 THEN activity_number END DESC,
+                    CASE WHEN $11 = 'service-id' AND NOT $12 THEN service_id END,
+                    CASE WHEN $11 = 'service-id' AND $12 THEN service_id END DESC,
+                    CASE WHEN $11 = 'price-per-hour' AND NOT $12 THEN price_per_hour END,
+                    CASE WHEN $11 = 'price-per-hour' AND $12 THEN price_per_hour END DESC,
+                    activity_number,
+                    service_id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.service_id.eq,
+            pages.filter.conditions.service_id.ne,
+            pages.filter.conditions.price_per_hour.eq,
+            pages.filter.conditions.price_per_hour.ne,
+            pages.filter.conditions.price_per_hour.lt,
+            pages.filter.conditions.price_per_hour.lte,
+            pages.filter.conditions.price_per_hour.gt,
+            pages.filter.conditions.price_per_hour.gte,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Activity {
+                activity_number: row.activity_number,
+                service_id: row.service_id,
+                description: row.description,
+                price_per_hour: row.price_per_hour,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Activity) -> (i32, i32) {
+        (item.activity_number, item.service_id)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Activity, Activity>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, i32)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Activity>, sqlx::Error> {
+        sqlx::query_as!(
+            Activity,
+            r#"
+                SELECT
+                    activity_number,
+                    service_id,
+                    description,
+                    price_per_hour
+                FROM activities
+                WHERE
+                    ($3::INTEGER IS NULL OR service_id = $3)
+                    AND ($4::INTEGER IS NULL OR service_id <> $4)
+                    AND ($5::NUMERIC IS NULL OR price_per_hour = $5)
+                    AND ($6::NUMERIC IS NULL OR price_per_hour <> $6)
+                    AND ($7::NUMERIC IS NULL OR price_per_hour < $7)
+                    AND ($8::NUMERIC IS NULL OR price_per_hour <= $8)
+                    AND ($9::NUMERIC IS NULL OR price_per_hour > $9)
+                    AND ($10::NUMERIC IS NULL OR price_per_hour >= $10)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $11 THEN (activity_number, service_id)
+                                < ($2, $12)
+                            ELSE (activity_number, service_id)
+                                > ($2, $12)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $11 THEN activity_number END DESC,
+                    CASE WHEN $11 THEN service_id END DESC,
+                    activity_number,
+                    service_id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.service_id.eq,
+            pages.filter.conditions.service_id.ne,
+            pages.filter.conditions.price_per_hour.eq,
+            pages.filter.conditions.price_per_hour.ne,
+            pages.filter.conditions.price_per_hour.lt,
+            pages.filter.conditions.price_per_hour.lte,
+            pages.filter.conditions.price_per_hour.gt,
+            pages.filter.conditions.price_per_hour.gte,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,9 +1,16 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::domains::Rif,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -44,8 +51,20 @@ impl ActivityPrice {
     }
 
     pub async fn select_all(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<ActivityPriceFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<ActivityPrice>, sqlx::Error> {
+        ActivityPrice::stream_all(dealership_rif, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<ActivityPriceFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<ActivityPrice, sqlx::Error>> {
         sqlx::query_as!(
             ActivityPrice,
             r#"
@@ -55,20 +74,95 @@ impl ActivityPrice {
                 dealership_rif,
                 price_per_hour
             FROM activities_prices
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR dealership_rif = $1
+                )
+                AND ($2::INTEGER IS NULL OR activity_number = $2)
+                AND ($3::INTEGER IS NULL OR activity_number <> $3)
+                AND ($4::INTEGER IS NULL OR service_id = $4)
+                AND ($5::INTEGER IS NULL OR service_id <> $5)
+                AND ($6::TEXT IS NULL OR dealership_rif = $6)
+                AND ($7::TEXT IS NULL OR dealership_rif <> $7)
+                AND ($8::NUMERIC IS NULL OR price_per_hour = $8)
+                AND ($9::NUMERIC IS NULL OR price_per_hour <> $9)
+                AND ($10::NUMERIC IS NULL OR price_per_hour < $10)
+                AND ($11::NUMERIC IS NULL OR price_per_hour <= $11)
+                AND ($12::NUMERIC IS NULL OR price_per_hour > $12)
+                AND ($13::NUMERIC IS NULL OR price_per_hour >= $13)
+            ORDER BY
+                CASE WHEN $14::TEXT = 'activity-number' AND NOT $15 THEN activity_number END,
+                CASE WHEN $14 = 'activity-number' AND $15 THEN activity_number END DESC,
+                CASE WHEN $14 = 'service-id' AND NOT $15 THEN service_id END,
+                CASE WHEN $14 = 'service-id' AND $15 THEN service_id END DESC,
+                CASE WHEN $14 = 'dealership-rif' AND NOT $15 THEN dealership_rif END,
+                CASE WHEN $14 = 'dealership-rif' AND $15 THEN dealership_rif END DESC,
+                CASE WHEN $14 = 'price-per-hour' AND NOT $15 THEN price_per_hour END,
+                CASE WHEN $14 = 'price-per-hour' AND $15 THEN price_per_hour END DESC,
+                activity_number,
+                service_id,
+                dealership_rif
+            "#,
+            dealership_rif,
+            filter.conditions.activity_number.eq,
+            filter.conditions.activity_number.ne,
+            filter.conditions.service_id.eq,
+            filter.conditions.service_id.ne,
+            filter.conditions.dealership_rif.eq as _,
+            filter.conditions.dealership_rif.ne as _,
+            filter.conditions.price_per_hour.eq,
+            filter.conditions.price_per_hour.ne,
+            filter.conditions.price_per_hour.lt,
+            filter.conditions.price_per_hour.lte,
+            filter.conditions.price_per_hour.gt,
+            filter.conditions.price_per_hour.gte,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<ActivityPriceFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_activities_prices!"
             FROM activities_prices
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR dealership_rif = $1
+                )
+                AND ($2::INTEGER IS NULL OR activity_number = $2)
+                AND ($3::INTEGER IS NULL OR activity_number <> $3)
+                AND ($4::INTEGER IS NULL OR service_id = $4)
+                AND ($5::INTEGER IS NULL OR service_id <> $5)
+                AND ($6::TEXT IS NULL OR dealership_rif = $6)
+                AND ($7::TEXT IS NULL OR dealership_rif <> $7)
+                AND ($8::NUMERIC IS NULL OR price_per_hour = $8)
+                AND ($9::NUMERIC IS NULL OR price_per_hour <> $9)
+                AND ($10::NUMERIC IS NULL OR price_per_hour < $10)
+                AND ($11::NUMERIC IS NULL OR price_per_hour <= $11)
+                AND ($12::NUMERIC IS NULL OR price_per_hour > $12)
+                AND ($13::NUMERIC IS NULL OR price_per_hour >= $13)
+            "#,
+            dealership_rif,
+            filter.conditions.activity_number.eq,
+            filter.conditions.activity_number.ne,
+            filter.conditions.service_id.eq,
+            filter.conditions.service_id.ne,
+            filter.conditions.dealership_rif.eq as _,
+            filter.conditions.dealership_rif.ne as _,
+            filter.conditions.price_per_hour.eq,
+            filter.conditions.price_per_hour.ne,
+            filter.conditions.price_per_hour.lt,
+            filter.conditions.price_per_hour.lte,
+            filter.conditions.price_per_hour.gt,
+            filter.conditions.price_per_hour.gte
         )
         .fetch_one(connection)
         .await
@@ -103,37 +197,213 @@ impl ActivityPrice {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct ActivityPriceFilter {
+    pub activity_number: Comparison<i32>,
+    pub service_id: Comparison<i32>,
+    pub dealership_rif: Comparison<Rif>,
+    pub price_per_hour: Comparison<BigDecimal>,
+}
+
+impl Filter for ActivityPriceFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("activity-number", EQUALITY),
+        FilterField::new("service-id", EQUALITY),
+        FilterField::new("dealership-rif", EQUALITY),
+        FilterField::new("price-per-hour", ORDERING),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "activity-number",
+        "service-id",
+        "dealership-rif",
+        "price-per-hour",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "activity-number" => self.activity_number.apply(operator, value),
+            "service-id" => self.service_id.apply(operator, value),
+            "dealership-rif" => self.dealership_rif.apply(operator, value),
+            "price-per-hour" => self.price_per_hour.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<ActivityPrice> for ActivityPrice {
+    const FIELDS: &'static [&'static str] = &[
+        "activity-number",
+        "service-id",
+        "dealership-rif",
+        "price-per-hour",
+    ];
+
+    type Filter = ActivityPriceFilter;
+    type CursorKey = (i32, i32, String);
+
     async fn get_page(
         pages: &Pages<ActivityPrice, ActivityPrice>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<ActivityPrice>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            ActivityPrice,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     activity_number,
                     service_id,
                     dealership_rif,
-                    price_per_hour
+                    price_per_hour,
+                    COUNT(*) OVER() AS "total!"
                 FROM activities_prices
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR dealership_rif = $3
+                    )
+                    AND ($4::INTEGER IS NULL OR activity_number = $4)
+                    AND ($5::INTEGER IS NULL OR activity_number <> $5)
+                    AND ($6::INTEGER IS NULL OR service_id = $6)
+                    AND ($7::INTEGER IS NULL OR service_id <> $7)
+                    AND ($8::TEXT IS NULL OR dealership_rif = $8)
+                    AND ($9::TEXT IS NULL OR dealership_rif <> $9)
+                    AND ($10::NUMERIC IS NULL OR price_per_hour = $10)
+                    AND ($11::NUMERIC IS NULL OR price_per_hour <> $11)
+                    AND ($12::NUMERIC IS NULL OR price_per_hour < $12)
+                    AND ($13::NUMERIC IS NULL OR price_per_hour <= $13)
+                    AND ($14::NUMERIC IS NULL OR price_per_hour > $14)
+                    AND ($15::NUMERIC IS NULL OR price_per_hour >= $15)
+                ORDER BY
+                    CASE WHEN $16::TEXT = 'activity-number' AND NOT $17 THEN activity_number END,
+                    CASE WHEN $16 = 'activity-number' AND $17 THEN activity_number END DESC,
+                    CASE WHEN $16 = 'service-id' AND NOT $17 THEN service_id END,
+                    CASE WHEN $16 = 'service-id' AND $17 THEN service_id END DESC,
+                    CASE WHEN $16 = 'dealership-rif' AND NOT $17 THEN dealership_rif END,
+                    CASE WHEN $16 = 'dealership-rif' AND $17 THEN dealership_rif END DESC,
+                    CASE WHEN $16 = 'price-per-hour' AND NOT $17 THEN price_per_hour END,
+                    CASE WHEN $16 = 'price-per-hour' AND $17 THEN price_per_hour END DESC,
+                    activity_number,
+                    service_id,
+                    dealership_rif
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.dealership_rif,
+            pages.filter.conditions.activity_number.eq,
+            pages.filter.conditions.activity_number.ne,
+            pages.filter.conditions.service_id.eq,
+            pages.filter.conditions.service_id.ne,
+            pages.filter.conditions.dealership_rif.eq as _,
+            pages.filter.conditions.dealership_rif.ne as _,
+            pages.filter.conditions.price_per_hour.eq,
+            pages.filter.conditions.price_per_hour.ne,
+            pages.filter.conditions.price_per_hour.lt,
+            pages.filter.conditions.price_per_hour.lte,
+            pages.filter.conditions.price_per_hour.gt,
+            pages.filter.conditions.price_per_hour.gte,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| ActivityPrice {
+                activity_number: row.activity_number,
+                service_id: row.service_id,
+                dealership_rif: row.dealership_rif,
+                price_per_hour: row.price_per_hour,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &ActivityPrice) -> (i32, i32, String) {
+        (item.activity_number, item.service_id, item.dealership_rif.clone())
+    }
+
+    async fn get_items_after(
+        pages: &Pages<ActivityPrice, ActivityPrice>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, i32, String)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ActivityPrice>, sqlx::Error> {
+        sqlx::query_as!(
+            ActivityPrice,
+            r#"
+                SELECT
+                    activity_number,
+                    service_id,
+                    dealership_rif,
+                    price_per_hour
+                FROM activities_prices
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR dealership_rif = $3
+                    )
+                    AND ($4::INTEGER IS NULL OR activity_number = $4)
+                    AND ($5::INTEGER IS NULL OR activity_number <> $5)
+                    AND ($6::INTEGER IS NULL OR service_id = $6)
+                    AND ($7::INTEGER IS NULL OR service_id <> $7)
+                    AND ($8::TEXT IS NULL OR dealership_rif = $8)
+                    AND ($9::TEXT IS NULL OR dealership_rif <> $9)
+                    AND ($10::NUMERIC IS NULL OR price_per_hour = $10)
+                    AND ($11::NUMERIC IS NULL OR price_per_hour <> $11)
+                    AND ($12::NUMERIC IS NULL OR price_per_hour < $12)
+                    AND ($13::NUMERIC IS NULL OR price_per_hour <= $13)
+                    AND ($14::NUMERIC IS NULL OR price_per_hour > $14)
+                    AND ($15::NUMERIC IS NULL OR price_per_hour >= $15)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $16 THEN (activity_number, service_id, dealership_rif)
+                                < ($2, $17, $18)
+                            ELSE (activity_number, service_id, dealership_rif)
+                                > ($2, $17, $18)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $16 THEN activity_number END DESC,
+                    CASE WHEN $16 THEN service_id END DESC,
+                    CASE WHEN $16 THEN dealership_rif END DESC,
+                    activity_number,
+                    service_id,
+                    dealership_rif
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.dealership_rif,
+            pages.filter.conditions.activity_number.eq,
+            pages.filter.conditions.activity_number.ne,
+            pages.filter.conditions.service_id.eq,
+            pages.filter.conditions.service_id.ne,
+            pages.filter.conditions.dealership_rif.eq as _,
+            pages.filter.conditions.dealership_rif.ne as _,
+            pages.filter.conditions.price_per_hour.eq,
+            pages.filter.conditions.price_per_hour.ne,
+            pages.filter.conditions.price_per_hour.lt,
+            pages.filter.conditions.price_per_hour.lte,
+            pages.filter.conditions.price_per_hour.gt,
+            pages.filter.conditions.price_per_hour.gte,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1),
+            cursor.map(|cursor| cursor.key.2.as_str())
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
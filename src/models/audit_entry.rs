@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, Postgres};
+use time::PrimitiveDateTime;
+
+use crate::utils::{
+    filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+    pagination::{Cursor, Page, Pages, Paginable},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub audit_timestamp: PrimitiveDateTime,
+    pub actor_national_id: Option<String>,
+    pub request_id: Option<String>,
+    pub resource: String,
+    pub resource_key: String,
+    pub operation: String,
+    pub before_data: Option<Value>,
+    pub after_data: Option<Value>,
+}
+
+impl AuditEntry {
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<AuditEntryFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<AuditEntry, sqlx::Error>> {
+        sqlx::query_as!(
+            AuditEntry,
+            r#"
+            SELECT
+                id,
+                audit_timestamp,
+                actor_national_id,
+                request_id,
+                resource,
+                resource_key,
+                operation,
+                before_data,
+                after_data
+            FROM audit_log
+            WHERE
+                ($1::TEXT IS NULL OR resource = $1)
+                AND ($2::TEXT IS NULL OR resource <> $2)
+                AND ($3::TEXT IS NULL OR resource_key = $3)
+                AND ($4::TEXT IS NULL OR resource_key <> $4)
+            ORDER BY
+                CASE WHEN $5::TEXT = 'id' AND NOT $6 THEN id END,
+                id DESC
+            "#,
+            filter.conditions.resource.eq,
+            filter.conditions.resource.ne,
+            filter.conditions.resource_key.eq,
+            filter.conditions.resource_key.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        filter: &ListFilter<AuditEntryFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_audit_entries!"
+            FROM audit_log
+            WHERE
+                ($1::TEXT IS NULL OR resource = $1)
+                AND ($2::TEXT IS NULL OR resource <> $2)
+                AND ($3::TEXT IS NULL OR resource_key = $3)
+                AND ($4::TEXT IS NULL OR resource_key <> $4)
+            "#,
+            filter.conditions.resource.eq,
+            filter.conditions.resource.ne,
+            filter.conditions.resource_key.eq,
+            filter.conditions.resource_key.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn set_transaction_context(
+        actor_national_id: String,
+        request_id: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            SELECT
+                set_config('audit.actor_national_id', $1, true) AS actor_national_id,
+                set_config('audit.request_id', $2, true) AS request_id
+            "#,
+            actor_national_id,
+            request_id
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct AuditEntryFilter {
+    pub resource: Comparison<String>,
+    pub resource_key: Comparison<String>,
+}
+
+impl Filter for AuditEntryFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("resource", EQUALITY),
+        FilterField::new("resource-key", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["id"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "resource" => self.resource.apply(operator, value),
+            "resource-key" => self.resource_key.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<AuditEntry> for AuditEntry {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "audit-timestamp",
+        "actor-national-id",
+        "request-id",
+        "resource",
+        "resource-key",
+        "operation",
+        "before-data",
+        "after-data",
+    ];
+
+    type Filter = AuditEntryFilter;
+    type CursorKey = (i64,);
+
+    async fn get_page(
+        pages: &Pages<AuditEntry, AuditEntry>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<AuditEntry>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    id,
+                    audit_timestamp,
+                    actor_national_id,
+                    request_id,
+                    resource,
+                    resource_key,
+                    operation,
+                    before_data,
+                    after_data,
+                    COUNT(*) OVER() AS "total!"
+                FROM audit_log
+                WHERE
+                    ($3::TEXT IS NULL OR resource = $3)
+                    AND ($4::TEXT IS NULL OR resource <> $4)
+                    AND ($5::TEXT IS NULL OR resource_key = $5)
+                    AND ($6::TEXT IS NULL OR resource_key <> $6)
+                ORDER BY
+                    CASE WHEN $7::TEXT = 'id' AND NOT $8 THEN id END,
+                    id DESC
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.resource.eq,
+            pages.filter.conditions.resource.ne,
+            pages.filter.conditions.resource_key.eq,
+            pages.filter.conditions.resource_key.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| AuditEntry {
+                id: row.id,
+                audit_timestamp: row.audit_timestamp,
+                actor_national_id: row.actor_national_id,
+                request_id: row.request_id,
+                resource: row.resource,
+                resource_key: row.resource_key,
+                operation: row.operation,
+                before_data: row.before_data,
+                after_data: row.after_data,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &AuditEntry) -> (i64,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<AuditEntry, AuditEntry>,
+        limit: i64,
+        cursor: Option<&Cursor<(i64,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<AuditEntry>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditEntry,
+            r#"
+                SELECT
+                    id,
+                    audit_timestamp,
+                    actor_national_id,
+                    request_id,
+                    resource,
+                    resource_key,
+                    operation,
+                    before_data,
+                    after_data
+                FROM audit_log
+                WHERE
+                    ($3::TEXT IS NULL OR resource = $3)
+                    AND ($4::TEXT IS NULL OR resource <> $4)
+                    AND ($5::TEXT IS NULL OR resource_key = $5)
+                    AND ($6::TEXT IS NULL OR resource_key <> $6)
+                    AND (
+                        $2::BIGINT IS NULL
+                        OR CASE
+                            WHEN $7 THEN id > $2
+                            ELSE id < $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $7 THEN id END,
+                    id DESC
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.resource.eq,
+            pages.filter.conditions.resource.ne,
+            pages.filter.conditions.resource_key.eq,
+            pages.filter.conditions.resource_key.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
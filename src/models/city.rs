@@ -1,8 +1,15 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::suggestion::Suggestion,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -34,7 +41,9 @@ impl City {
         .await
     }
 
-    pub async fn select_all(
+    pub async fn select_all_by_keys(
+        city_numbers: Vec<i32>,
+        state_ids: Vec<i32>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<City>, sqlx::Error> {
         sqlx::query_as!(
@@ -42,25 +51,107 @@ impl City {
             r#"
             SELECT city_number, name, state_id
             FROM cities
-            "#
+            WHERE (city_number, state_id) IN (
+                SELECT *
+                FROM UNNEST($1::INTEGER[], $2::INTEGER[])
+            )
+            "#,
+            &city_numbers,
+            &state_ids
         )
         .fetch_all(connection)
         .await
     }
 
+    pub async fn select_all(
+        filter: &ListFilter<CityFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<City>, sqlx::Error> {
+        City::stream_all(filter, connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<CityFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<City, sqlx::Error>> {
+        sqlx::query_as!(
+            City,
+            r#"
+            SELECT city_number, name, state_id
+            FROM cities
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+                AND ($3::INTEGER IS NULL OR state_id = $3)
+                AND ($4::INTEGER IS NULL OR state_id <> $4)
+            ORDER BY
+                CASE WHEN $5::TEXT = 'city-number' AND NOT $6 THEN city_number END,
+                CASE WHEN $5 = 'city-number' AND $6 THEN city_number END DESC,
+                CASE WHEN $5 = 'name' AND NOT $6 THEN name END,
+                CASE WHEN $5 = 'name' AND $6 THEN name END DESC,
+                CASE WHEN $5 = 'state-id' AND NOT $6 THEN state_id END,
+                CASE WHEN $5 = 'state-id' AND $6 THEN state_id END DESC,
+                city_number,
+                state_id
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.state_id.eq,
+            filter.conditions.state_id.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
     pub async fn count(
+        filter: &ListFilter<CityFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_cities!"
             FROM cities
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+                AND ($3::INTEGER IS NULL OR state_id = $3)
+                AND ($4::INTEGER IS NULL OR state_id <> $4)
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.state_id.eq,
+            filter.conditions.state_id.ne
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        state_id: i32,
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<i32>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT city_number AS id, name AS label
+            FROM cities
+            WHERE
+                state_id = $1
+                AND lower(name) LIKE to_prefix_pattern(lower($2))
+            ORDER BY name, city_number
+            LIMIT $3
+            "#,
+            state_id,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         city_number: i32,
         state_id: i32,
@@ -83,33 +174,139 @@ impl City {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct CityFilter {
+    pub name: Comparison<String>,
+    pub state_id: Comparison<i32>,
+}
+
+impl Filter for CityFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("name", EQUALITY),
+        FilterField::new("state-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["city-number", "name", "state-id"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "name" => self.name.apply(operator, value),
+            "state-id" => self.state_id.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<City> for City {
+    const FIELDS: &'static [&'static str] = &["city-number", "name", "state-id"];
+
+    type Filter = CityFilter;
+    type CursorKey = (i32, i32);
+
     async fn get_page(
         pages: &Pages<City, City>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<City>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            City,
+        let page_rows = sqlx::query!(
             r#"
-                SELECT city_number, name, state_id
+                SELECT city_number, name, state_id, COUNT(*) OVER() AS "total!"
                 FROM cities
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                    AND ($5::INTEGER IS NULL OR state_id = $5)
+                    AND ($6::INTEGER IS NULL OR state_id <> $6)
+                ORDER BY
+                    CASE WHEN $7::TEXT = 'city-number' AND NOT $8 THEN city_number END,
+                    CASE WHEN $7 = 'city-number' AND $8 THEN city_number END DESC,
+                    CASE WHEN $7 = 'name' AND NOT $8 THEN name END,
+                    CASE WHEN $7 = 'name' AND $8 THEN name END DESC,
+                    CASE WHEN $7 = 'state-id' AND NOT $8 THEN state_id END,
+                    CASE WHEN $7 = 'state-id' AND $8 THEN state_id END DESC,
+                    city_number,
+                    state_id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.state_id.eq,
+            pages.filter.conditions.state_id.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| City {
+                city_number: row.city_number,
+                name: row.name,
+                state_id: row.state_id,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &City) -> (i32, i32) {
+        (item.city_number, item.state_id)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<City, City>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, i32)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<City>, sqlx::Error> {
+        sqlx::query_as!(
+            City,
+            r#"
+                SELECT city_number, name, state_id
+                FROM cities
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                    AND ($5::INTEGER IS NULL OR state_id = $5)
+                    AND ($6::INTEGER IS NULL OR state_id <> $6)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $7 THEN (city_number, state_id)
+                                < ($2, $8)
+                            ELSE (city_number, state_id)
+                                > ($2, $8)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $7 THEN city_number END DESC,
+                    CASE WHEN $7 THEN state_id END DESC,
+                    city_number,
+                    state_id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.state_id.eq,
+            pages.filter.conditions.state_id.ne,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,8 +1,16 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
+use time::PrimitiveDateTime;
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::domains::NationalId,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,11 +20,13 @@ pub struct Client {
     pub main_phone_no: String,
     pub secondary_phone_no: String,
     pub email: String,
+    pub deleted_at: Option<PrimitiveDateTime>,
 }
 
 impl Client {
     pub async fn select(
         national_id: String,
+        include_deleted: bool,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Client, sqlx::Error> {
         sqlx::query_as!(
@@ -27,18 +37,22 @@ impl Client {
                 full_name,
                 main_phone_no,
                 secondary_phone_no,
-                email
+                email,
+                deleted_at
             FROM clients
             WHERE
                 national_id = $1
+                AND ($2 OR deleted_at IS NULL)
             "#,
-            national_id
+            national_id,
+            include_deleted
         )
         .fetch_one(connection)
         .await
     }
 
-    pub async fn select_all(
+    pub async fn select_all_by_national_ids(
+        national_ids: Vec<String>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Client>, sqlx::Error> {
         sqlx::query_as!(
@@ -49,22 +63,100 @@ impl Client {
                 full_name,
                 main_phone_no,
                 secondary_phone_no,
-                email
+                email,
+                deleted_at
             FROM clients
-            "#
+            WHERE national_id = ANY($1)
+            "#,
+            &national_ids
         )
         .fetch_all(connection)
         .await
     }
 
+    pub async fn select_all(
+        include_deleted: bool,
+        filter: &ListFilter<ClientFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Client>, sqlx::Error> {
+        Client::stream_all(include_deleted, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        include_deleted: bool,
+        filter: &ListFilter<ClientFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Client, sqlx::Error>> {
+        sqlx::query_as!(
+            Client,
+            r#"
+            SELECT
+                national_id,
+                full_name,
+                main_phone_no,
+                secondary_phone_no,
+                email,
+                deleted_at
+            FROM clients
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR national_id = $2)
+                AND ($3::TEXT IS NULL OR national_id <> $3)
+                AND ($4::TEXT IS NULL OR full_name = $4)
+                AND ($5::TEXT IS NULL OR full_name <> $5)
+                AND ($6::TEXT IS NULL OR email = $6)
+                AND ($7::TEXT IS NULL OR email <> $7)
+            ORDER BY
+                CASE WHEN $8::TEXT = 'national-id' AND NOT $9 THEN national_id END,
+                CASE WHEN $8 = 'national-id' AND $9 THEN national_id END DESC,
+                CASE WHEN $8 = 'full-name' AND NOT $9 THEN full_name END,
+                CASE WHEN $8 = 'full-name' AND $9 THEN full_name END DESC,
+                CASE WHEN $8 = 'email' AND NOT $9 THEN email END,
+                CASE WHEN $8 = 'email' AND $9 THEN email END DESC,
+                CASE WHEN $8 = 'deleted-at' AND NOT $9 THEN deleted_at END,
+                CASE WHEN $8 = 'deleted-at' AND $9 THEN deleted_at END DESC,
+                national_id
+            "#,
+            include_deleted,
+            filter.conditions.national_id.eq as _,
+            filter.conditions.national_id.ne as _,
+            filter.conditions.full_name.eq,
+            filter.conditions.full_name.ne,
+            filter.conditions.email.eq,
+            filter.conditions.email.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
     pub async fn count(
+        include_deleted: bool,
+        filter: &ListFilter<ClientFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_clients!"
             FROM clients
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR national_id = $2)
+                AND ($3::TEXT IS NULL OR national_id <> $3)
+                AND ($4::TEXT IS NULL OR full_name = $4)
+                AND ($5::TEXT IS NULL OR full_name <> $5)
+                AND ($6::TEXT IS NULL OR email = $6)
+                AND ($7::TEXT IS NULL OR email <> $7)
+            "#,
+            include_deleted,
+            filter.conditions.national_id.eq as _,
+            filter.conditions.national_id.ne as _,
+            filter.conditions.full_name.eq,
+            filter.conditions.full_name.ne,
+            filter.conditions.email.eq,
+            filter.conditions.email.ne
         )
         .fetch_one(connection)
         .await
@@ -77,15 +169,44 @@ impl Client {
         sqlx::query_as!(
             Client,
             r#"
-            DELETE FROM clients
+            UPDATE clients
+            SET deleted_at = now()
             WHERE
                 national_id = $1
+                AND deleted_at IS NULL
             RETURNING
                 national_id,
                 full_name,
                 main_phone_no,
                 secondary_phone_no,
-                email
+                email,
+                deleted_at
+            "#,
+            national_id
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn restore(
+        national_id: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Client, sqlx::Error> {
+        sqlx::query_as!(
+            Client,
+            r#"
+            UPDATE clients
+            SET deleted_at = NULL
+            WHERE
+                national_id = $1
+                AND deleted_at IS NOT NULL
+            RETURNING
+                national_id,
+                full_name,
+                main_phone_no,
+                secondary_phone_no,
+                email,
+                deleted_at
             "#,
             national_id
         )
@@ -94,38 +215,174 @@ impl Client {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct ClientFilter {
+    pub national_id: Comparison<NationalId>,
+    pub full_name: Comparison<String>,
+    pub email: Comparison<String>,
+}
+
+impl Filter for ClientFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("national-id", EQUALITY),
+        FilterField::new("full-name", EQUALITY),
+        FilterField::new("email", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] =
+        &["national-id", "full-name", "email", "deleted-at"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "national-id" => self.national_id.apply(operator, value),
+            "full-name" => self.full_name.apply(operator, value),
+            "email" => self.email.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<Client> for Client {
+    const FIELDS: &'static [&'static str] = &[
+        "national-id",
+        "full-name",
+        "main-phone-no",
+        "secondary-phone-no",
+        "email",
+        "deleted-at",
+    ];
+
+    type Filter = ClientFilter;
+    type CursorKey = (String,);
+
     async fn get_page(
         pages: &Pages<Client, Client>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Client>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Client,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     national_id,
                     full_name,
                     main_phone_no,
                     secondary_phone_no,
-                    email
+                    email,
+                    deleted_at,
+                    COUNT(*) OVER() AS "total!"
                 FROM clients
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR national_id = $4)
+                    AND ($5::TEXT IS NULL OR national_id <> $5)
+                    AND ($6::TEXT IS NULL OR full_name = $6)
+                    AND ($7::TEXT IS NULL OR full_name <> $7)
+                    AND ($8::TEXT IS NULL OR email = $8)
+                    AND ($9::TEXT IS NULL OR email <> $9)
+                ORDER BY
+                    CASE WHEN $10::TEXT = 'national-id' AND NOT $11 THEN national_id END,
+                    CASE WHEN $10 = 'national-id' AND $11 THEN national_id END DESC,
+                    CASE WHEN $10 = 'full-name' AND NOT $11 THEN full_name END,
+                    CASE WHEN $10 = 'full-name' AND $11 THEN full_name END DESC,
+                    CASE WHEN $10 = 'email' AND NOT $11 THEN email END,
+                    CASE WHEN $10 = 'email' AND $11 THEN email END DESC,
+                    CASE WHEN $10 = 'deleted-at' AND NOT $11 THEN deleted_at END,
+                    CASE WHEN $10 = 'deleted-at' AND $11 THEN deleted_at END DESC,
+                    national_id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.include_deleted,
+            pages.filter.conditions.national_id.eq as _,
+            pages.filter.conditions.national_id.ne as _,
+            pages.filter.conditions.full_name.eq,
+            pages.filter.conditions.full_name.ne,
+            pages.filter.conditions.email.eq,
+            pages.filter.conditions.email.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Client {
+                national_id: row.national_id,
+                full_name: row.full_name,
+                main_phone_no: row.main_phone_no,
+                secondary_phone_no: row.secondary_phone_no,
+                email: row.email,
+                deleted_at: row.deleted_at,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Client) -> (String,) {
+        (item.national_id.clone(),)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Client, Client>,
+        limit: i64,
+        cursor: Option<&Cursor<(String,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Client>, sqlx::Error> {
+        sqlx::query_as!(
+            Client,
+            r#"
+                SELECT
+                    national_id,
+                    full_name,
+                    main_phone_no,
+                    secondary_phone_no,
+                    email,
+                    deleted_at
+                FROM clients
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR national_id = $4)
+                    AND ($5::TEXT IS NULL OR national_id <> $5)
+                    AND ($6::TEXT IS NULL OR full_name = $6)
+                    AND ($7::TEXT IS NULL OR full_name <> $7)
+                    AND ($8::TEXT IS NULL OR email = $8)
+                    AND ($9::TEXT IS NULL OR email <> $9)
+                    AND (
+                        $2::TEXT IS NULL
+                        OR CASE
+                            WHEN $10 THEN national_id < $2
+                            ELSE national_id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $10 THEN national_id END DESC,
+                    national_id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0.as_str()),
+            pages.include_deleted,
+            pages.filter.conditions.national_id.eq as _,
+            pages.filter.conditions.national_id.ne as _,
+            pages.filter.conditions.full_name.eq,
+            pages.filter.conditions.full_name.ne,
+            pages.filter.conditions.email.eq,
+            pages.filter.conditions.email.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -164,7 +421,8 @@ impl InsertClient {
                 full_name,
                 main_phone_no,
                 secondary_phone_no,
-                email
+                email,
+                deleted_at
             "#,
             self.national_id as _,
             self.full_name,
@@ -215,7 +473,8 @@ impl UpdateClient {
                 full_name,
                 main_phone_no,
                 secondary_phone_no,
-                email
+                email,
+                deleted_at
             "#,
             new_national_id as _,
             new_full_name,
@@ -1,8 +1,20 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, Postgres};
+use sqlx::{Executor, Pool, Postgres};
+use time::PrimitiveDateTime;
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::city::City,
+    models::domains::Rif,
+    models::state::State,
+    models::suggestion::Suggestion,
+    utils::{
+        expansion::{Expandable, Expanded, Expansion},
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,12 +22,14 @@ pub struct Dealership {
     pub rif: String,
     pub name: String,
     pub city_number: i32,
-    pub state_id: i32
+    pub state_id: i32,
+    pub deleted_at: Option<PrimitiveDateTime>,
 }
 
 impl Dealership {
     pub async fn select(
         rif: String,
+        include_deleted: bool,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Dealership, sqlx::Error> {
         sqlx::query_as!(
@@ -25,21 +39,36 @@ impl Dealership {
                 rif,
                 name,
                 city_number,
-                state_id
+                state_id,
+                deleted_at
             FROM 
                 dealerships
             WHERE
                 rif = $1
+                AND ($2 OR deleted_at IS NULL)
             "#,
             rif,
+            include_deleted
         )
         .fetch_one(connection)
         .await
     }
 
     pub async fn select_all(
+        include_deleted: bool,
+        filter: &ListFilter<DealershipFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Dealership>, sqlx::Error> {
+        Dealership::stream_all(include_deleted, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        include_deleted: bool,
+        filter: &ListFilter<DealershipFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Dealership, sqlx::Error>> {
         sqlx::query_as!(
             Dealership,
             r#"
@@ -47,16 +76,51 @@ impl Dealership {
                 rif,
                 name,
                 city_number,
-                state_id
+                state_id,
+                deleted_at
             FROM 
                 dealerships
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR rif = $2)
+                AND ($3::TEXT IS NULL OR rif <> $3)
+                AND ($4::TEXT IS NULL OR name = $4)
+                AND ($5::TEXT IS NULL OR name <> $5)
+                AND ($6::INTEGER IS NULL OR city_number = $6)
+                AND ($7::INTEGER IS NULL OR city_number <> $7)
+                AND ($8::INTEGER IS NULL OR state_id = $8)
+                AND ($9::INTEGER IS NULL OR state_id <> $9)
+            ORDER BY
+                CASE WHEN $10::TEXT = 'rif' AND NOT $11 THEN rif END,
+                CASE WHEN $10 = 'rif' AND $11 THEN rif END DESC,
+                CASE WHEN $10 = 'name' AND NOT $11 THEN name END,
+                CASE WHEN $10 = 'name' AND $11 THEN name END DESC,
+                CASE WHEN $10 = 'city-number' AND NOT $11 THEN city_number END,
+                CASE WHEN $10 = 'city-number' AND $11 THEN city_number END DESC,
+                CASE WHEN $10 = 'state-id' AND NOT $11 THEN state_id END,
+                CASE WHEN $10 = 'state-id' AND $11 THEN state_id END DESC,
+                CASE WHEN $10 = 'deleted-at' AND NOT $11 THEN deleted_at END,
+                CASE WHEN $10 = 'deleted-at' AND $11 THEN deleted_at END DESC,
+                rif
+            "#,
+            include_deleted,
+            filter.conditions.rif.eq as _,
+            filter.conditions.rif.ne as _,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.city_number.eq,
+            filter.conditions.city_number.ne,
+            filter.conditions.state_id.eq,
+            filter.conditions.state_id.ne,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        include_deleted: bool,
+        filter: &ListFilter<DealershipFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
@@ -65,12 +129,54 @@ impl Dealership {
                 COUNT(*) AS "total_dealerships!"
             FROM 
                 dealerships
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR rif = $2)
+                AND ($3::TEXT IS NULL OR rif <> $3)
+                AND ($4::TEXT IS NULL OR name = $4)
+                AND ($5::TEXT IS NULL OR name <> $5)
+                AND ($6::INTEGER IS NULL OR city_number = $6)
+                AND ($7::INTEGER IS NULL OR city_number <> $7)
+                AND ($8::INTEGER IS NULL OR state_id = $8)
+                AND ($9::INTEGER IS NULL OR state_id <> $9)
+            "#,
+            include_deleted,
+            filter.conditions.rif.eq as _,
+            filter.conditions.rif.ne as _,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.city_number.eq,
+            filter.conditions.city_number.ne,
+            filter.conditions.state_id.eq,
+            filter.conditions.state_id.ne
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<String>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT rif AS id, name AS label
+            FROM dealerships
+            WHERE
+                lower(name) LIKE to_prefix_pattern(lower($1))
+                AND deleted_at IS NULL
+            ORDER BY name, rif
+            LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         rif: String,
         connection: impl Executor<'_, Database = Postgres>,
@@ -78,54 +184,273 @@ impl Dealership {
         sqlx::query_as!(
             Dealership,
             r#"
-            DELETE FROM dealerships
-            WHERE 
+            UPDATE dealerships
+            SET deleted_at = now()
+            WHERE
                 rif = $1
+                AND deleted_at IS NULL
             RETURNING 
                 rif,
                 name,
                 city_number,
-                state_id
+                state_id,
+                deleted_at
             "#,
             rif,
         )
         .fetch_one(connection)
         .await
     }
+
+    pub async fn restore(
+        rif: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Dealership, sqlx::Error> {
+        sqlx::query_as!(
+            Dealership,
+            r#"
+            UPDATE dealerships
+            SET deleted_at = NULL
+            WHERE
+                rif = $1
+                AND deleted_at IS NOT NULL
+            RETURNING 
+                rif,
+                name,
+                city_number,
+                state_id,
+                deleted_at
+            "#,
+            rif,
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct DealershipFilter {
+    pub rif: Comparison<Rif>,
+    pub name: Comparison<String>,
+    pub city_number: Comparison<i32>,
+    pub state_id: Comparison<i32>,
+}
+
+impl Filter for DealershipFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("rif", EQUALITY),
+        FilterField::new("name", EQUALITY),
+        FilterField::new("city-number", EQUALITY),
+        FilterField::new("state-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] =
+        &["rif", "name", "city-number", "state-id", "deleted-at"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "rif" => self.rif.apply(operator, value),
+            "name" => self.name.apply(operator, value),
+            "city-number" => self.city_number.apply(operator, value),
+            "state-id" => self.state_id.apply(operator, value),
+            _ => false,
+        }
+    }
 }
 
 #[async_trait]
 impl Paginable<Dealership> for Dealership {
+    const FIELDS: &'static [&'static str] =
+        &["rif", "name", "city-number", "state-id", "deleted-at"];
+
+    type Filter = DealershipFilter;
+    type CursorKey = (String,);
+
     async fn get_page(
         pages: &Pages<Dealership, Dealership>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Dealership>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Dealership,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT 
                     rif,
                     name,
                     city_number,
-                    state_id
+                    state_id,
+                    deleted_at,
+                    COUNT(*) OVER() AS "total!"
                 FROM 
                     dealerships
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR rif = $4)
+                    AND ($5::TEXT IS NULL OR rif <> $5)
+                    AND ($6::TEXT IS NULL OR name = $6)
+                    AND ($7::TEXT IS NULL OR name <> $7)
+                    AND ($8::INTEGER IS NULL OR city_number = $8)
+                    AND ($9::INTEGER IS NULL OR city_number <> $9)
+                    AND ($10::INTEGER IS NULL OR state_id = $10)
+                    AND ($11::INTEGER IS NULL OR state_id <> $11)
+                ORDER BY
+                    CASE WHEN $12::TEXT = 'rif' AND NOT $13 THEN rif END,
+                    CASE WHEN $12 = 'rif' AND $13 THEN rif END DESC,
+                    CASE WHEN $12 = 'name' AND NOT $13 THEN name END,
+                    CASE WHEN $12 = 'name' AND $13 THEN name END DESC,
+                    CASE WHEN $12 = 'city-number' AND NOT $13 THEN city_number END,
+                    CASE WHEN $12 = 'city-number' AND $13 THEN city_number END DESC,
+                    CASE WHEN $12 = 'state-id' AND NOT $13 THEN state_id END,
+                    CASE WHEN $12 = 'state-id' AND $13 THEN state_id END DESC,
+                    CASE WHEN $12 = 'deleted-at' AND NOT $13 THEN deleted_at END,
+                    CASE WHEN $12 = 'deleted-at' AND $13 THEN deleted_at END DESC,
+                    rif
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.include_deleted,
+            pages.filter.conditions.rif.eq as _,
+            pages.filter.conditions.rif.ne as _,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.city_number.eq,
+            pages.filter.conditions.city_number.ne,
+            pages.filter.conditions.state_id.eq,
+            pages.filter.conditions.state_id.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Dealership {
+                rif: row.rif,
+                name: row.name,
+                city_number: row.city_number,
+                state_id: row.state_id,
+                deleted_at: row.deleted_at,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Dealership) -> (String,) {
+        (item.rif.clone(),)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Dealership, Dealership>,
+        limit: i64,
+        cursor: Option<&Cursor<(String,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Dealership>, sqlx::Error> {
+        sqlx::query_as!(
+            Dealership,
+            r#"
+                SELECT 
+                    rif,
+                    name,
+                    city_number,
+                    state_id,
+                    deleted_at
+                FROM 
+                    dealerships
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR rif = $4)
+                    AND ($5::TEXT IS NULL OR rif <> $5)
+                    AND ($6::TEXT IS NULL OR name = $6)
+                    AND ($7::TEXT IS NULL OR name <> $7)
+                    AND ($8::INTEGER IS NULL OR city_number = $8)
+                    AND ($9::INTEGER IS NULL OR city_number <> $9)
+                    AND ($10::INTEGER IS NULL OR state_id = $10)
+                    AND ($11::INTEGER IS NULL OR state_id <> $11)
+                    AND (
+                        $2::TEXT IS NULL
+                        OR CASE
+                            WHEN $12 THEN rif < $2
+                            ELSE rif > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $12 THEN rif END DESC,
+                    rif
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0.as_str()),
+            pages.include_deleted,
+            pages.filter.conditions.rif.eq as _,
+            pages.filter.conditions.rif.ne as _,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.city_number.eq,
+            pages.filter.conditions.city_number.ne,
+            pages.filter.conditions.state_id.eq,
+            pages.filter.conditions.state_id.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[async_trait]
+impl Expandable for Dealership {
+    const RELATIONS: &'static [&'static str] = &["city", "state"];
+
+    async fn expand(
+        dealerships: Vec<Dealership>,
+        expansion: &Expansion,
+        connection: &Pool<Postgres>,
+    ) -> Result<Vec<Expanded<Dealership>>, sqlx::Error> {
+        let mut expanded_dealerships = Expanded::wrap_all(dealerships);
+
+        if expansion.includes("city") {
+            let city_numbers = expanded_dealerships
+                .iter()
+                .map(|dealership| dealership.item.city_number)
+                .collect();
+            let state_ids = expanded_dealerships
+                .iter()
+                .map(|dealership| dealership.item.state_id)
+                .collect();
+            let cities = City::select_all_by_keys(city_numbers, state_ids, connection).await?;
+            Expanded::embed_one(
+                &mut expanded_dealerships,
+                "city",
+                cities,
+                |dealership| (dealership.city_number, dealership.state_id),
+                |city| (city.city_number, city.state_id),
+            );
+        }
+
+        if expansion.includes("state") {
+            let state_ids = expanded_dealerships
+                .iter()
+                .map(|dealership| dealership.item.state_id)
+                .collect();
+            let states = State::select_all_by_ids(state_ids, connection).await?;
+            Expanded::embed_one(
+                &mut expanded_dealerships,
+                "state",
+                states,
+                |dealership| dealership.state_id,
+                |state| state.id,
+            );
+        }
+
+        Ok(expanded_dealerships)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -152,7 +477,8 @@ impl InsertDealership {
                 rif,
                 name,
                 city_number,
-                state_id
+                state_id,
+                deleted_at
             "#,
             self.rif as _,
             self.name,
@@ -198,7 +524,8 @@ impl UpdateDealership {
                 rif,
                 name,
                 city_number,
-                state_id
+                state_id,
+                deleted_at
             "#,
             new_rif as _,
             new_name,
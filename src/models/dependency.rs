@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    pub relation: String,
+    pub referencing_rows: i64,
+}
+
+impl Dependency {
+    pub async fn select_all(
+        resource: String,
+        resource_key: Vec<String>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Dependency>, sqlx::Error> {
+        sqlx::query_as!(
+            Dependency,
+            r#"
+            SELECT
+                relation AS "relation!",
+                referencing_rows AS "referencing_rows!"
+            FROM select_dependencies($1, $2)
+            "#,
+            resource,
+            &resource_key
+        )
+        .fetch_all(connection)
+        .await
+    }
+
+    pub async fn select_all_active(
+        resource: String,
+        resource_key: Vec<String>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Dependency>, sqlx::Error> {
+        sqlx::query_as!(
+            Dependency,
+            r#"
+            SELECT
+                relation AS "relation!",
+                referencing_rows AS "referencing_rows!"
+            FROM select_dependencies($1, $2, true)
+            "#,
+            resource,
+            &resource_key
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
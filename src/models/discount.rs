@@ -1,9 +1,16 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::domains::Rif,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -42,8 +49,20 @@ impl Discount {
     }
 
     pub async fn select_all(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<DiscountFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Discount>, sqlx::Error> {
+        Discount::stream_all(dealership_rif, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<DiscountFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Discount, sqlx::Error>> {
         sqlx::query_as!(
             Discount,
             r#"
@@ -54,13 +73,61 @@ impl Discount {
                 required_annual_service_usage_count
             FROM 
                 discounts
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR dealership_rif = $1
+                )
+                AND ($2::TEXT IS NULL OR dealership_rif = $2)
+                AND ($3::TEXT IS NULL OR dealership_rif <> $3)
+                AND ($4::NUMERIC IS NULL OR discount_percentage = $4)
+                AND ($5::NUMERIC IS NULL OR discount_percentage <> $5)
+                AND ($6::NUMERIC IS NULL OR discount_percentage < $6)
+                AND ($7::NUMERIC IS NULL OR discount_percentage <= $7)
+                AND ($8::NUMERIC IS NULL OR discount_percentage > $8)
+                AND ($9::NUMERIC IS NULL OR discount_percentage >= $9)
+                AND ($10::SMALLINT IS NULL OR required_annual_service_usage_count = $10)
+                AND ($11::SMALLINT IS NULL OR required_annual_service_usage_count <> $11)
+                AND ($12::SMALLINT IS NULL OR required_annual_service_usage_count < $12)
+                AND ($13::SMALLINT IS NULL OR required_annual_service_usage_count <= $13)
+                AND ($14::SMALLINT IS NULL OR required_annual_service_usage_count > $14)
+                AND ($15::SMALLINT IS NULL OR required_annual_service_usage_count >= $15)
+            ORDER BY
+                CASE WHEN $16::TEXT = 'discount-number' AND NOT $17 THEN discount_number END,
+                CASE WHEN $16 = 'discount-number' AND $17 THEN discount_number END DESC,
+                CASE WHEN $16 = 'dealership-rif' AND NOT $17 THEN dealership_rif END,
+                CASE WHEN $16 = 'dealership-rif' AND $17 THEN dealership_rif END DESC,
+                CASE WHEN $16 = 'discount-percentage' AND NOT $17 THEN discount_percentage END,
+                CASE WHEN $16 = 'discount-percentage' AND $17 THEN discount_percentage END DESC,
+                CASE WHEN $16 = 'required-annual-service-usage-count' AND NOT $17 THEN required_annual_service_usage_count END,
+                CASE WHEN $16 = 'required-annual-service-usage-count' AND $17 THEN required_annual_service_usage_count END DESC,
+                discount_number,
+                dealership_rif
+            "#,
+            dealership_rif,
+            filter.conditions.dealership_rif.eq as _,
+            filter.conditions.dealership_rif.ne as _,
+            filter.conditions.discount_percentage.eq,
+            filter.conditions.discount_percentage.ne,
+            filter.conditions.discount_percentage.lt,
+            filter.conditions.discount_percentage.lte,
+            filter.conditions.discount_percentage.gt,
+            filter.conditions.discount_percentage.gte,
+            filter.conditions.required_annual_service_usage_count.eq,
+            filter.conditions.required_annual_service_usage_count.ne,
+            filter.conditions.required_annual_service_usage_count.lt,
+            filter.conditions.required_annual_service_usage_count.lte,
+            filter.conditions.required_annual_service_usage_count.gt,
+            filter.conditions.required_annual_service_usage_count.gte,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<DiscountFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
@@ -69,7 +136,41 @@ impl Discount {
                 COUNT(*) AS "total_discounts!"
             FROM 
                 discounts
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR dealership_rif = $1
+                )
+                AND ($2::TEXT IS NULL OR dealership_rif = $2)
+                AND ($3::TEXT IS NULL OR dealership_rif <> $3)
+                AND ($4::NUMERIC IS NULL OR discount_percentage = $4)
+                AND ($5::NUMERIC IS NULL OR discount_percentage <> $5)
+                AND ($6::NUMERIC IS NULL OR discount_percentage < $6)
+                AND ($7::NUMERIC IS NULL OR discount_percentage <= $7)
+                AND ($8::NUMERIC IS NULL OR discount_percentage > $8)
+                AND ($9::NUMERIC IS NULL OR discount_percentage >= $9)
+                AND ($10::SMALLINT IS NULL OR required_annual_service_usage_count = $10)
+                AND ($11::SMALLINT IS NULL OR required_annual_service_usage_count <> $11)
+                AND ($12::SMALLINT IS NULL OR required_annual_service_usage_count < $12)
+                AND ($13::SMALLINT IS NULL OR required_annual_service_usage_count <= $13)
+                AND ($14::SMALLINT IS NULL OR required_annual_service_usage_count > $14)
+                AND ($15::SMALLINT IS NULL OR required_annual_service_usage_count >= $15)
+            "#,
+            dealership_rif,
+            filter.conditions.dealership_rif.eq as _,
+            filter.conditions.dealership_rif.ne as _,
+            filter.conditions.discount_percentage.eq,
+            filter.conditions.discount_percentage.ne,
+            filter.conditions.discount_percentage.lt,
+            filter.conditions.discount_percentage.lte,
+            filter.conditions.discount_percentage.gt,
+            filter.conditions.discount_percentage.gte,
+            filter.conditions.required_annual_service_usage_count.eq,
+            filter.conditions.required_annual_service_usage_count.ne,
+            filter.conditions.required_annual_service_usage_count.lt,
+            filter.conditions.required_annual_service_usage_count.lte,
+            filter.conditions.required_annual_service_usage_count.gt,
+            filter.conditions.required_annual_service_usage_count.gte
         )
         .fetch_one(connection)
         .await
@@ -101,38 +202,242 @@ impl Discount {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct DiscountFilter {
+    pub dealership_rif: Comparison<Rif>,
+    pub discount_percentage: Comparison<BigDecimal>,
+    pub required_annual_service_usage_count: Comparison<i16>,
+}
+
+impl Filter for DiscountFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("dealership-rif", EQUALITY),
+        FilterField::new("discount-percentage", ORDERING),
+        FilterField::new("required-annual-service-usage-count", ORDERING),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "discount-number",
+        "dealership-rif",
+        "discount-percentage",
+        "required-annual-service-usage-count",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "dealership-rif" => self.dealership_rif.apply(operator, value),
+            "discount-percentage" => self.discount_percentage.apply(operator, value),
+            "required-annual-service-usage-count" => self
+                .required_annual_service_usage_count
+                .apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<Discount> for Discount {
+    const FIELDS: &'static [&'static str] = &[
+        "discount-number",
+        "dealership-rif",
+        "discount-percentage",
+        "required-annual-service-usage-count",
+    ];
+
+    type Filter = DiscountFilter;
+    type CursorKey = (i32, String);
+
     async fn get_page(
         pages: &Pages<Discount, Discount>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Discount>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Discount,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT 
                     discount_number,
                     dealership_rif,
                     discount_percentage,
-                    required_annual_service_usage_count
+                    required_annual_service_usage_count,
+                    COUNT(*) OVER() AS "total!"
                 FROM 
                     discounts
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR dealership_rif = $3
+                    )
+                    AND ($4::TEXT IS NULL OR dealership_rif = $4)
+                    AND ($5::TEXT IS NULL OR dealership_rif <> $5)
+                    AND ($6::NUMERIC IS NULL OR discount_percentage = $6)
+                    AND ($7::NUMERIC IS NULL OR discount_percentage <> $7)
+                    AND ($8::NUMERIC IS NULL OR discount_percentage < $8)
+                    AND ($9::NUMERIC IS NULL OR discount_percentage <= $9)
+                    AND ($10::NUMERIC IS NULL OR discount_percentage > $10)
+                    AND ($11::NUMERIC IS NULL OR discount_percentage >= $11)
+                    AND ($12::SMALLINT IS NULL OR required_annual_service_usage_count = $12)
+                    AND ($13::SMALLINT IS NULL OR required_annual_service_usage_count <> $13)
+                    AND ($14::SMALLINT IS NULL OR required_annual_service_usage_count < $14)
+                    AND ($15::SMALLINT IS NULL OR required_annual_service_usage_count <= $15)
+                    AND ($16::SMALLINT IS NULL OR required_annual_service_usage_count > $16)
+                    AND ($17::SMALLINT IS NULL OR required_annual_service_usage_count >= $17)
+                ORDER BY
+                    CASE WHEN $18::TEXT = 'discount-number' AND NOT $19 THEN discount_number END,
+                    CASE WHEN $18 = 'discount-number' AND $19 THEN discount_number END DESC,
+                    CASE WHEN $18 = 'dealership-rif' AND NOT $19 THEN dealership_rif END,
+                    CASE WHEN $18 = 'dealership-rif' AND $19 THEN dealership_rif END DESC,
+                    CASE WHEN $18 = 'discount-percentage' AND NOT $19 THEN discount_percentage END,
+                    CASE WHEN $18 = 'discount-percentage' AND $19 THEN discount_percentage END DESC,
+                    CASE WHEN $18 = 'required-annual-service-usage-count' AND NOT $19 THEN required_annual_service_usage_count END,
+                    CASE WHEN $18 = 'required-annual-service-usage-count' AND $19 THEN required_annual_service_usage_count END DESC,
+                    discount_number,
+                    dealership_rif
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.dealership_rif,
+            pages.filter.conditions.dealership_rif.eq as _,
+            pages.filter.conditions.dealership_rif.ne as _,
+            pages.filter.conditions.discount_percentage.eq,
+            pages.filter.conditions.discount_percentage.ne,
+            pages.filter.conditions.discount_percentage.lt,
+            pages.filter.conditions.discount_percentage.lte,
+            pages.filter.conditions.discount_percentage.gt,
+            pages.filter.conditions.discount_percentage.gte,
+            pages.filter.conditions.required_annual_service_usage_count.eq,
+            pages.filter.conditions.required_annual_service_usage_count.ne,
+            pages.filter.conditions.required_annual_service_usage_count.lt,
+            pages.filter.conditions.required_annual_service_usage_count.lte,
+            pages.filter.conditions.required_annual_service_usage_count.gt,
+            pages.filter.conditions.required_annual_service_usage_count.gte,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Discount {
+                discount_number: row.discount_number,
+                dealership_rif: row.dealership_rif,
+                discount_percentage: row.discount_percentage,
+                required_annual_service_usage_count: row.required_annual_service_usage_count,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Discount) -> (i32, String) {
+        (item.discount_number, item.dealership_rif.clone())
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Discount, Discount>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, String)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Discount>, sqlx::Error> {
+        sqlx::query_as!(
+            Discount,
+            r#"
+                SELECT 
+                    discount_number,
+                    dealership_rif,
+                    discount_percentage,
+                    required_annual_service_usage_count
+                FROM 
+                    discounts
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR dealership_rif = $3
+                    )
+                    AND ($4::TEXT IS NULL OR dealership_rif = $4)
+                    AND ($5::TEXT IS NULL OR dealership_rif <> $5)
+                    AND ($6::NUMERIC IS NULL OR discount_percentage = $6)
+                    AND ($7::NUMERIC IS NULL OR discount_percentage <> $7)
+                    AND ($8::NUMERIC IS NULL OR discount_percentage < $8)
+                    AND ($9::NUMERIC IS NULL OR discount_percentage <= $9)
+                    AND ($10::NUMERIC IS NULL OR discount_percentage > $10)
+                    AND ($11::NUMERIC IS NULL OR discount_percentage >= $11)
+                    AND ($12::SMALLINT IS NULL OR required_annual_service_usage_count = $12)
+                    AND ($13::SMALLINT IS NULL OR required_annual_service_usage_count <> $13)
+                    AND ($14::SMALLINT IS NULL OR required_annual_service_usage_count < $14)
+                    AND ($15::SMALLINT IS NULL OR required_annual_service_usage_count <= $15)
+                    AND ($16::SMALLINT IS NULL OR required_annual_service_usage_count > $16)
+                    AND ($17::SMALLINT IS NULL OR required_annual_service_usage_count >= $17)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $18 THEN (discount_number, dealership_rif)
+                                < ($2, $19)
+                            ELSE (discount_number, dealership_rif)
+                                > ($2, $19)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $18 THEN discount_number END DESC,
+                    CASE WHEN $18 THEN dealership_rif END DESC,
+                    discount_number,
+                    dealership_rif
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.dealership_rif,
+            pages.filter.conditions.dealership_rif.eq as _,
+            pages.filter.conditions.dealership_rif.ne as _,
+            pages.filter.conditions.discount_percentage.eq,
+            pages.filter.conditions.discount_percentage.ne,
+            pages.filter.conditions.discount_percentage.lt,
+            pages.filter.conditions.discount_percentage.lte,
+            pages.filter.conditions.discount_percentage.gt,
+            pages.filter.conditions.discount_percentage.gte,
+            pages
+                .filter
+                .conditions
+                .required_annual_service_usage_count
+                .eq,
+            pages
+                .filter
+                .conditions
+                .required_annual_service_usage_count
+                .ne,
+            pages
+                .filter
+                .conditions
+                .required_annual_service_usage_count
+                .lt,
+            pages
+                .filter
+                .conditions
+                .required_annual_service_usage_count
+                .lte,
+            pages
+                .filter
+                .conditions
+                .required_annual_service_usage_count
+                .gt,
+            pages
+                .filter
+                .conditions
+                .required_annual_service_usage_count
+                .gte,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1.as_str())
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,129 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+static NATIONAL_ID_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[VE]-[0-9]+$").unwrap());
+
+static RIF_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[VEJ]-[0-9]+$").unwrap());
+
+static PHONE_NO_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\+?[0-9]{1,4}?[-.\s]?\(?[0-9]{1,3}?\)?[-.\s]?[0-9]{1,4}[-.\s]?[0-9]{1,4}[-.\s]?[0-9]{1,9}$",
+    )
+    .unwrap()
+});
+
+static EMAIL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-zA-Z0-9.!#$%&’*+/=?^_`{|}~-]+@[a-zA-Z0-9-]+(?:\.[a-zA-Z0-9-]+)*$").unwrap()
+});
+
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+pub struct NationalId(String);
+
+impl TryFrom<String> for NationalId {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<NationalId, String> {
+        let national_id = value.trim().to_uppercase();
+
+        if !NATIONAL_ID_PATTERN.is_match(&national_id) {
+            return Err("expected a national id such as V-12345678".to_string());
+        }
+
+        Ok(NationalId(national_id))
+    }
+}
+
+impl From<NationalId> for String {
+    fn from(value: NationalId) -> String {
+        value.0
+    }
+}
+
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+pub struct Rif(String);
+
+impl TryFrom<String> for Rif {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Rif, String> {
+        let rif = value.trim().to_uppercase();
+
+        if !RIF_PATTERN.is_match(&rif) {
+            return Err("expected a rif such as J-12345678".to_string());
+        }
+
+        Ok(Rif(rif))
+    }
+}
+
+impl Rif {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Rif> for String {
+    fn from(value: Rif) -> String {
+        value.0
+    }
+}
+
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+pub struct PhoneNo(String);
+
+impl TryFrom<String> for PhoneNo {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<PhoneNo, String> {
+        let phone_no = value.trim();
+
+        if !PHONE_NO_PATTERN.is_match(phone_no) {
+            return Err("expected a phone number such as +58 412 1234567".to_string());
+        }
+
+        Ok(PhoneNo(phone_no.to_string()))
+    }
+}
+
+impl From<PhoneNo> for String {
+    fn from(value: PhoneNo) -> String {
+        value.0
+    }
+}
+
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+pub struct Email(String);
+
+impl TryFrom<String> for Email {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Email, String> {
+        let email = match value.trim().rsplit_once('@') {
+            Some((local_part, domain)) => format!("{}@{}", local_part, domain.to_lowercase()),
+            None => value.trim().to_string(),
+        };
+
+        if !EMAIL_PATTERN.is_match(&email) {
+            return Err("expected an email address such as name@example.com".to_string());
+        }
+
+        Ok(Email(email))
+    }
+}
+
+impl From<Email> for String {
+    fn from(value: Email) -> String {
+        value.0
+    }
+}
@@ -1,9 +1,17 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
+use time::PrimitiveDateTime;
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::domains::{NationalId, Rif},
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,11 +25,13 @@ pub struct Employee {
     pub helped_dealership_rif: Option<String>,
     pub role_id: i32,
     pub salary: BigDecimal,
+    pub deleted_at: Option<PrimitiveDateTime>,
 }
 
 impl Employee {
     pub async fn select(
         national_id: String,
+        include_deleted: bool,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Employee, sqlx::Error> {
         sqlx::query_as!(
@@ -36,19 +46,35 @@ impl Employee {
                 address,
                 helped_dealership_rif,
                 role_id,
-                salary
+                salary,
+                deleted_at
             FROM staff
-            WHERE national_id = $1
+            WHERE
+                national_id = $1
+                AND ($2 OR deleted_at IS NULL)
             "#,
             national_id,
+            include_deleted
         )
         .fetch_one(connection)
         .await
     }
 
     pub async fn select_all(
+        include_deleted: bool,
+        filter: &ListFilter<EmployeeFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Employee>, sqlx::Error> {
+        Employee::stream_all(include_deleted, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        include_deleted: bool,
+        filter: &ListFilter<EmployeeFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Employee, sqlx::Error>> {
         sqlx::query_as!(
             Employee,
             r#"
@@ -61,22 +87,126 @@ impl Employee {
                 address,
                 helped_dealership_rif,
                 role_id,
-                salary
+                salary,
+                deleted_at
             FROM staff
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR national_id = $2)
+                AND ($3::TEXT IS NULL OR national_id <> $3)
+                AND ($4::TEXT IS NULL OR full_name = $4)
+                AND ($5::TEXT IS NULL OR full_name <> $5)
+                AND ($6::TEXT IS NULL OR helped_dealership_rif = $6)
+                AND ($7::TEXT IS NULL OR helped_dealership_rif <> $7)
+                AND ($8::INTEGER IS NULL OR role_id = $8)
+                AND ($9::INTEGER IS NULL OR role_id <> $9)
+                AND ($10::NUMERIC IS NULL OR salary = $10)
+                AND ($11::NUMERIC IS NULL OR salary <> $11)
+                AND ($12::NUMERIC IS NULL OR salary < $12)
+                AND ($13::NUMERIC IS NULL OR salary <= $13)
+                AND ($14::NUMERIC IS NULL OR salary > $14)
+                AND ($15::NUMERIC IS NULL OR salary >= $15)
+            ORDER BY
+                CASE WHEN $16::TEXT = 'national-id' AND NOT $17 THEN national_id END,
+                CASE WHEN $16 = 'national-id' AND $17 THEN national_id END DESC,
+                CASE WHEN $16 = 'full-name' AND NOT $17 THEN full_name END,
+                CASE WHEN $16 = 'full-name' AND $17 THEN full_name END DESC,
+                CASE WHEN $16 = 'helped-dealership-rif' AND NOT $17 THEN helped_dealership_rif END,
+                CASE WHEN $16 = 'helped-dealership-rif' AND $17 THEN helped_dealership_rif END DESC,
+                CASE WHEN $16 = 'role-id' AND NOT $17 THEN role_id END,
+                CASE WHEN $16 = 'role-id' AND $17 THEN role_id END DESC,
+                CASE WHEN $16 = 'salary' AND NOT $17 THEN salary END,
+                CASE WHEN $16 = 'salary' AND $17 THEN salary END DESC,
+                CASE WHEN $16 = 'deleted-at' AND NOT $17 THEN deleted_at END,
+                CASE WHEN $16 = 'deleted-at' AND $17 THEN deleted_at END DESC,
+                national_id
+            "#,
+            include_deleted,
+            filter.conditions.national_id.eq as _,
+            filter.conditions.national_id.ne as _,
+            filter.conditions.full_name.eq,
+            filter.conditions.full_name.ne,
+            filter.conditions.helped_dealership_rif.eq as _,
+            filter.conditions.helped_dealership_rif.ne as _,
+            filter.conditions.role_id.eq,
+            filter.conditions.role_id.ne,
+            filter.conditions.salary.eq,
+            filter.conditions.salary.ne,
+            filter.conditions.salary.lt,
+            filter.conditions.salary.lte,
+            filter.conditions.salary.gt,
+            filter.conditions.salary.gte,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        include_deleted: bool,
+        filter: &ListFilter<EmployeeFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_staff!"
             FROM staff
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR national_id = $2)
+                AND ($3::TEXT IS NULL OR national_id <> $3)
+                AND ($4::TEXT IS NULL OR full_name = $4)
+                AND ($5::TEXT IS NULL OR full_name <> $5)
+                AND ($6::TEXT IS NULL OR helped_dealership_rif = $6)
+                AND ($7::TEXT IS NULL OR helped_dealership_rif <> $7)
+                AND ($8::INTEGER IS NULL OR role_id = $8)
+                AND ($9::INTEGER IS NULL OR role_id <> $9)
+                AND ($10::NUMERIC IS NULL OR salary = $10)
+                AND ($11::NUMERIC IS NULL OR salary <> $11)
+                AND ($12::NUMERIC IS NULL OR salary < $12)
+                AND ($13::NUMERIC IS NULL OR salary <= $13)
+                AND ($14::NUMERIC IS NULL OR salary > $14)
+                AND ($15::NUMERIC IS NULL OR salary >= $15)
+            "#,
+            include_deleted,
+            filter.conditions.national_id.eq as _,
+            filter.conditions.national_id.ne as _,
+            filter.conditions.full_name.eq,
+            filter.conditions.full_name.ne,
+            filter.conditions.helped_dealership_rif.eq as _,
+            filter.conditions.helped_dealership_rif.ne as _,
+            filter.conditions.role_id.eq,
+            filter.conditions.role_id.ne,
+            filter.conditions.salary.eq,
+            filter.conditions.salary.ne,
+            filter.conditions.salary.lt,
+            filter.conditions.salary.lte,
+            filter.conditions.salary.gt,
+            filter.conditions.salary.gte
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_assigned_dealership_rif(
+        national_id: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(
+                m.dealership_rif,
+                o.dealership_rif,
+                s.helped_dealership_rif
+            ) AS "dealership_rif"
+            FROM staff AS s
+            LEFT JOIN managers AS m
+                ON m.national_id = s.national_id
+            LEFT JOIN operatives AS o
+                ON o.national_id = s.national_id
+            WHERE s.national_id = $1
+            "#,
+            national_id as _,
         )
         .fetch_one(connection)
         .await
@@ -89,8 +219,11 @@ impl Employee {
         sqlx::query_as!(
             Employee,
             r#"
-            DELETE FROM staff
-            WHERE national_id = $1
+            UPDATE staff
+            SET deleted_at = now()
+            WHERE
+                national_id = $1
+                AND deleted_at IS NULL
             RETURNING
                 national_id,
                 full_name,
@@ -100,24 +233,108 @@ impl Employee {
                 address,
                 helped_dealership_rif,
                 role_id,
-                salary
+                salary,
+                deleted_at
             "#,
             national_id,
         )
         .fetch_one(connection)
         .await
     }
+
+    pub async fn restore(
+        national_id: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Employee, sqlx::Error> {
+        sqlx::query_as!(
+            Employee,
+            r#"
+            UPDATE staff
+            SET deleted_at = NULL
+            WHERE
+                national_id = $1
+                AND deleted_at IS NOT NULL
+            RETURNING
+                national_id,
+                full_name,
+                main_phone_no,
+                secondary_phone_no,
+                email,
+                address,
+                helped_dealership_rif,
+                role_id,
+                salary,
+                deleted_at
+            "#,
+            national_id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct EmployeeFilter {
+    pub national_id: Comparison<NationalId>,
+    pub full_name: Comparison<String>,
+    pub helped_dealership_rif: Comparison<Rif>,
+    pub role_id: Comparison<i32>,
+    pub salary: Comparison<BigDecimal>,
+}
+
+impl Filter for EmployeeFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("national-id", EQUALITY),
+        FilterField::new("full-name", EQUALITY),
+        FilterField::new("helped-dealership-rif", EQUALITY),
+        FilterField::new("role-id", EQUALITY),
+        FilterField::new("salary", ORDERING),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "national-id",
+        "full-name",
+        "helped-dealership-rif",
+        "role-id",
+        "salary",
+        "deleted-at",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "national-id" => self.national_id.apply(operator, value),
+            "full-name" => self.full_name.apply(operator, value),
+            "helped-dealership-rif" => self.helped_dealership_rif.apply(operator, value),
+            "role-id" => self.role_id.apply(operator, value),
+            "salary" => self.salary.apply(operator, value),
+            _ => false,
+        }
+    }
 }
 
 #[async_trait]
 impl Paginable<Employee> for Employee {
+    const FIELDS: &'static [&'static str] = &[
+        "national-id",
+        "full-name",
+        "main-phone-no",
+        "secondary-phone-no",
+        "email",
+        "address",
+        "helped-dealership-rif",
+        "role-id",
+        "salary",
+        "deleted-at",
+    ];
+
+    type Filter = EmployeeFilter;
+    type CursorKey = (String,);
+
     async fn get_page(
         pages: &Pages<Employee, Employee>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Employee>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Employee,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     national_id,
@@ -128,23 +345,166 @@ impl Paginable<Employee> for Employee {
                     address,
                     helped_dealership_rif,
                     role_id,
-                    salary
+                    salary,
+                    deleted_at,
+                    COUNT(*) OVER() AS "total!"
                 FROM staff
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR national_id = $4)
+                    AND ($5::TEXT IS NULL OR national_id <> $5)
+                    AND ($6::TEXT IS NULL OR full_name = $6)
+                    AND ($7::TEXT IS NULL OR full_name <> $7)
+                    AND ($8::TEXT IS NULL OR helped_dealership_rif = $8)
+                    AND ($9::TEXT IS NULL OR helped_dealership_rif <> $9)
+                    AND ($10::INTEGER IS NULL OR role_id = $10)
+                    AND ($11::INTEGER IS NULL OR role_id <> $11)
+                    AND ($12::NUMERIC IS NULL OR salary = $12)
+                    AND ($13::NUMERIC IS NULL OR salary <> $13)
+                    AND ($14::NUMERIC IS NULL OR salary < $14)
+                    AND ($15::NUMERIC IS NULL OR salary <= $15)
+                    AND ($16::NUMERIC IS NULL OR salary > $16)
+                    AND ($17::NUMERIC IS NULL OR salary >= $17)
+                ORDER BY
+                    CASE WHEN $18::TEXT = 'national-id' AND NOT $19 THEN national_id END,
+                    CASE WHEN $18 = 'national-id' AND $19 THEN national_id END DESC,
+                    CASE WHEN $18 = 'full-name' AND NOT $19 THEN full_name END,
+                    CASE WHEN $18 = 'full-name' AND $19 THEN full_name END DESC,
+                    CASE WHEN $18 = 'helped-dealership-rif' AND NOT $19 THEN helped_dealership_rif END,
+                    CASE WHEN $18 = 'helped-dealership-rif' AND $19 THEN helped_dealership_rif END DESC,
+                    CASE WHEN $18 = 'role-id' AND NOT $19 THEN role_id END,
+                    CASE WHEN $18 = 'role-id' AND $19 THEN role_id END DESC,
+                    CASE WHEN $18 = 'salary' AND NOT $19 THEN salary END,
+                    CASE WHEN $18 = 'salary' AND $19 THEN salary END DESC,
+                    CASE WHEN $18 = 'deleted-at' AND NOT $19 THEN deleted_at END,
+                    CASE WHEN $18 = 'deleted-at' AND $19 THEN deleted_at END DESC,
+                    national_id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.include_deleted,
+            pages.filter.conditions.national_id.eq as _,
+            pages.filter.conditions.national_id.ne as _,
+            pages.filter.conditions.full_name.eq,
+            pages.filter.conditions.full_name.ne,
+            pages.filter.conditions.helped_dealership_rif.eq as _,
+            pages.filter.conditions.helped_dealership_rif.ne as _,
+            pages.filter.conditions.role_id.eq,
+            pages.filter.conditions.role_id.ne,
+            pages.filter.conditions.salary.eq,
+            pages.filter.conditions.salary.ne,
+            pages.filter.conditions.salary.lt,
+            pages.filter.conditions.salary.lte,
+            pages.filter.conditions.salary.gt,
+            pages.filter.conditions.salary.gte,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Employee {
+                national_id: row.national_id,
+                full_name: row.full_name,
+                main_phone_no: row.main_phone_no,
+                secondary_phone_no: row.secondary_phone_no,
+                email: row.email,
+                address: row.address,
+                helped_dealership_rif: row.helped_dealership_rif,
+                role_id: row.role_id,
+                salary: row.salary,
+                deleted_at: row.deleted_at,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Employee) -> (String,) {
+        (item.national_id.clone(),)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Employee, Employee>,
+        limit: i64,
+        cursor: Option<&Cursor<(String,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Employee>, sqlx::Error> {
+        sqlx::query_as!(
+            Employee,
+            r#"
+                SELECT
+                    national_id,
+                    full_name,
+                    main_phone_no,
+                    secondary_phone_no,
+                    email,
+                    address,
+                    helped_dealership_rif,
+                    role_id,
+                    salary,
+                    deleted_at
+                FROM staff
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR national_id = $4)
+                    AND ($5::TEXT IS NULL OR national_id <> $5)
+                    AND ($6::TEXT IS NULL OR full_name = $6)
+                    AND ($7::TEXT IS NULL OR full_name <> $7)
+                    AND ($8::TEXT IS NULL OR helped_dealership_rif = $8)
+                    AND ($9::TEXT IS NULL OR helped_dealership_rif <> $9)
+                    AND ($10::INTEGER IS NULL OR role_id = $10)
+                    AND ($11::INTEGER IS NULL OR role_id <> $11)
+                    AND ($12::NUMERIC IS NULL OR salary = $12)
+                    AND ($13::NUMERIC IS NULL OR salary <> $13)
+                    AND ($14::NUMERIC IS NULL OR salary < $14)
+                    AND ($15::NUMERIC IS NULL OR salary <= $15)
+                    AND ($16::NUMERIC IS NULL OR salary > $16)
+                    AND ($17::NUMERIC IS NULL OR salary >= $17)
+                    AND (
+                        $2::TEXT IS NULL
+                        OR CASE
+                            WHEN $18 THEN national_id < $2
+                            ELSE national_id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $18 THEN national_id END DESC,
+                    national_id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0.as_str()),
+            pages.include_deleted,
+            pages.filter.conditions.national_id.eq as _,
+            pages.filter.conditions.national_id.ne as _,
+            pages.filter.conditions.full_name.eq,
+            pages.filter.conditions.full_name.ne,
+            pages.filter.conditions.helped_dealership_rif.eq as _,
+            pages.filter.conditions.helped_dealership_rif.ne as _,
+            pages.filter.conditions.role_id.eq,
+            pages.filter.conditions.role_id.ne,
+            pages.filter.conditions.salary.eq,
+            pages.filter.conditions.salary.ne,
+            pages.filter.conditions.salary.lt,
+            pages.filter.conditions.salary.lte,
+            pages.filter.conditions.salary.gt,
+            pages.filter.conditions.salary.gte,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -199,7 +559,8 @@ impl InsertEmployee {
                 address,
                 helped_dealership_rif,
                 role_id,
-                salary
+                salary,
+                deleted_at
             "#,
             self.national_id as _,
             self.full_name,
@@ -269,7 +630,8 @@ impl UpdateEmployee {
                 address,
                 helped_dealership_rif,
                 role_id,
-                salary
+                salary,
+                deleted_at
             "#,
             new_national_id as _,
             new_full_name,
@@ -1,10 +1,19 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, Postgres};
+use sqlx::{Executor, Pool, Postgres};
 use time::Date;
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::order::Order,
+    models::payment::Payment,
+    utils::{
+        expansion::{Expandable, Expanded, Expansion},
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -40,8 +49,20 @@ impl Invoice {
     }
 
     pub async fn select_all(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<InvoiceFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Invoice>, sqlx::Error> {
+        Invoice::stream_all(dealership_rif, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<InvoiceFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Invoice, sqlx::Error>> {
         sqlx::query_as!(
             Invoice,
             r#"
@@ -52,25 +73,174 @@ impl Invoice {
                 discount,
                 issue_date
             FROM invoices
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR order_id IN (
+                        SELECT id
+                        FROM orders
+                        WHERE dealership_rif = $1
+                    )
+                )
+                AND ($2::INTEGER IS NULL OR order_id = $2)
+                AND ($3::INTEGER IS NULL OR order_id <> $3)
+                AND ($4::NUMERIC IS NULL OR amount_due = $4)
+                AND ($5::NUMERIC IS NULL OR amount_due <> $5)
+                AND ($6::NUMERIC IS NULL OR amount_due < $6)
+                AND ($7::NUMERIC IS NULL OR amount_due <= $7)
+                AND ($8::NUMERIC IS NULL OR amount_due > $8)
+                AND ($9::NUMERIC IS NULL OR amount_due >= $9)
+                AND ($10::NUMERIC IS NULL OR discount = $10)
+                AND ($11::NUMERIC IS NULL OR discount <> $11)
+                AND ($12::NUMERIC IS NULL OR discount < $12)
+                AND ($13::NUMERIC IS NULL OR discount <= $13)
+                AND ($14::NUMERIC IS NULL OR discount > $14)
+                AND ($15::NUMERIC IS NULL OR discount >= $15)
+                AND ($16::DATE IS NULL OR issue_date = $16)
+                AND ($17::DATE IS NULL OR issue_date <> $17)
+                AND ($18::DATE IS NULL OR issue_date < $18)
+                AND ($19::DATE IS NULL OR issue_date <= $19)
+                AND ($20::DATE IS NULL OR issue_date > $20)
+                AND ($21::DATE IS NULL OR issue_date >= $21)
+            ORDER BY
+                CASE WHEN $22::TEXT = 'id' AND NOT $23 THEN id END,
+                CASE WHEN $22 = 'id' AND $23 THEN id END DESC,
+                CASE WHEN $22 = 'order-id' AND NOT $23 THEN order_id END,
+                CASE WHEN $22 = 'order-id' AND $23 THEN order_id END DESC,
+                CASE WHEN $22 = 'amount-due' AND NOT $23 THEN amount_due END,
+                CASE WHEN $22 = 'amount-due' AND $23 THEN amount_due END DESC,
+                CASE WHEN $22 = 'discount' AND NOT $23 THEN discount END,
+                CASE WHEN $22 = 'discount' AND $23 THEN discount END DESC,
+                CASE WHEN $22 = 'issue-date' AND NOT $23 THEN issue_date END,
+                CASE WHEN $22 = 'issue-date' AND $23 THEN issue_date END DESC,
+                id
+            "#,
+            dealership_rif,
+            filter.conditions.order_id.eq,
+            filter.conditions.order_id.ne,
+            filter.conditions.amount_due.eq,
+            filter.conditions.amount_due.ne,
+            filter.conditions.amount_due.lt,
+            filter.conditions.amount_due.lte,
+            filter.conditions.amount_due.gt,
+            filter.conditions.amount_due.gte,
+            filter.conditions.discount.eq,
+            filter.conditions.discount.ne,
+            filter.conditions.discount.lt,
+            filter.conditions.discount.lte,
+            filter.conditions.discount.gt,
+            filter.conditions.discount.gte,
+            filter.conditions.issue_date.eq,
+            filter.conditions.issue_date.ne,
+            filter.conditions.issue_date.lt,
+            filter.conditions.issue_date.lte,
+            filter.conditions.issue_date.gt,
+            filter.conditions.issue_date.gte,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<InvoiceFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_invoices!"
             FROM invoices
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR order_id IN (
+                        SELECT id
+                        FROM orders
+                        WHERE dealership_rif = $1
+                    )
+                )
+                AND ($2::INTEGER IS NULL OR order_id = $2)
+                AND ($3::INTEGER IS NULL OR order_id <> $3)
+                AND ($4::NUMERIC IS NULL OR amount_due = $4)
+                AND ($5::NUMERIC IS NULL OR amount_due <> $5)
+                AND ($6::NUMERIC IS NULL OR amount_due < $6)
+                AND ($7::NUMERIC IS NULL OR amount_due <= $7)
+                AND ($8::NUMERIC IS NULL OR amount_due > $8)
+                AND ($9::NUMERIC IS NULL OR amount_due >= $9)
+                AND ($10::NUMERIC IS NULL OR discount = $10)
+                AND ($11::NUMERIC IS NULL OR discount <> $11)
+                AND ($12::NUMERIC IS NULL OR discount < $12)
+                AND ($13::NUMERIC IS NULL OR discount <= $13)
+                AND ($14::NUMERIC IS NULL OR discount > $14)
+                AND ($15::NUMERIC IS NULL OR discount >= $15)
+                AND ($16::DATE IS NULL OR issue_date = $16)
+                AND ($17::DATE IS NULL OR issue_date <> $17)
+                AND ($18::DATE IS NULL OR issue_date < $18)
+                AND ($19::DATE IS NULL OR issue_date <= $19)
+                AND ($20::DATE IS NULL OR issue_date > $20)
+                AND ($21::DATE IS NULL OR issue_date >= $21)
+            "#,
+            dealership_rif,
+            filter.conditions.order_id.eq,
+            filter.conditions.order_id.ne,
+            filter.conditions.amount_due.eq,
+            filter.conditions.amount_due.ne,
+            filter.conditions.amount_due.lt,
+            filter.conditions.amount_due.lte,
+            filter.conditions.amount_due.gt,
+            filter.conditions.amount_due.gte,
+            filter.conditions.discount.eq,
+            filter.conditions.discount.ne,
+            filter.conditions.discount.lt,
+            filter.conditions.discount.lte,
+            filter.conditions.discount.gt,
+            filter.conditions.discount.gte,
+            filter.conditions.issue_date.eq,
+            filter.conditions.issue_date.ne,
+            filter.conditions.issue_date.lt,
+            filter.conditions.issue_date.lte,
+            filter.conditions.issue_date.gt,
+            filter.conditions.issue_date.gte
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_dealership_rif(
+        id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT o.dealership_rif AS "dealership_rif!"
+            FROM invoices AS i
+            INNER JOIN orders AS o
+                ON o.id = i.order_id
+            WHERE i.id = $1
+            "#,
+            id,
+        )
+        .fetch_optional(connection)
+        .await
+    }
+
+    pub async fn select_order_dealership_rif(
+        order_id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT dealership_rif AS "dealership_rif!"
+            FROM orders
+            WHERE id = $1
+            "#,
+            order_id,
+        )
+        .fetch_optional(connection)
+        .await
+    }
+
     pub async fn delete(
         id: i32,
         connection: impl Executor<'_, Database = Postgres>,
@@ -94,38 +264,285 @@ impl Invoice {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct InvoiceFilter {
+    pub order_id: Comparison<i32>,
+    pub amount_due: Comparison<BigDecimal>,
+    pub discount: Comparison<BigDecimal>,
+    pub issue_date: Comparison<Date>,
+}
+
+impl Filter for InvoiceFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("order-id", EQUALITY),
+        FilterField::new("amount-due", ORDERING),
+        FilterField::new("discount", ORDERING),
+        FilterField::new("issue-date", ORDERING),
+    ];
+    const SORT_FIELDS: &'static [&'static str] =
+        &["id", "order-id", "amount-due", "discount", "issue-date"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "order-id" => self.order_id.apply(operator, value),
+            "amount-due" => self.amount_due.apply(operator, value),
+            "discount" => self.discount.apply(operator, value),
+            "issue-date" => self.issue_date.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<Invoice> for Invoice {
+    const FIELDS: &'static [&'static str] =
+        &["id", "order-id", "amount-due", "discount", "issue-date"];
+
+    type Filter = InvoiceFilter;
+    type CursorKey = (i32,);
+
     async fn get_page(
         pages: &Pages<Invoice, Invoice>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Invoice>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Invoice,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     id,
                     order_id,
                     amount_due,
                     discount,
-                    issue_date
+                    issue_date,
+                    COUNT(*) OVER() AS "total!"
                 FROM invoices
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR order_id IN (
+                            SELECT id
+                            FROM orders
+                            WHERE dealership_rif = $3
+                        )
+                    )
+                    AND ($4::INTEGER IS NULL OR order_id = $4)
+                    AND ($5::INTEGER IS NULL OR order_id <> $5)
+                    AND ($6::NUMERIC IS NULL OR amount_due = $6)
+                    AND ($7::NUMERIC IS NULL OR amount_due <> $7)
+                    AND ($8::NUMERIC IS NULL OR amount_due < $8)
+                    AND ($9::NUMERIC IS NULL OR amount_due <= $9)
+                    AND ($10::NUMERIC IS NULL OR amount_due > $10)
+                    AND ($11::NUMERIC IS NULL OR amount_due >= $11)
+                    AND ($12::NUMERIC IS NULL OR discount = $12)
+                    AND ($13::NUMERIC IS NULL OR discount <> $13)
+                    AND ($14::NUMERIC IS NULL OR discount < $14)
+                    AND ($15::NUMERIC IS NULL OR discount <= $15)
+                    AND ($16::NUMERIC IS NULL OR discount > $16)
+                    AND ($17::NUMERIC IS NULL OR discount >= $17)
+                    AND ($18::DATE IS NULL OR issue_date = $18)
+                    AND ($19::DATE IS NULL OR issue_date <> $19)
+                    AND ($20::DATE IS NULL OR issue_date < $20)
+                    AND ($21::DATE IS NULL OR issue_date <= $21)
+                    AND ($22::DATE IS NULL OR issue_date > $22)
+                    AND ($23::DATE IS NULL OR issue_date >= $23)
+                ORDER BY
+                    CASE WHEN $24::TEXT = 'id' AND NOT $25 THEN id END,
+                    CASE WHEN $24 = 'id' AND $25 THEN id END DESC,
+                    CASE WHEN $24 = 'order-id' AND NOT $25 THEN order_id END,
+                    CASE WHEN $24 = 'order-id' AND $25 THEN order_id END DESC,
+                    CASE WHEN $24 = 'amount-due' AND NOT $25 THEN amount_due END,
+                    CASE WHEN $24 = 'amount-due' AND $25 THEN amount_due END DESC,
+                    CASE WHEN $24 = 'discount' AND NOT $25 THEN discount END,
+                    CASE WHEN $24 = 'discount' AND $25 THEN discount END DESC,
+                    CASE WHEN $24 = 'issue-date' AND NOT $25 THEN issue_date END,
+                    CASE WHEN $24 = 'issue-date' AND $25 THEN issue_date END DESC,
+                    id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.dealership_rif,
+            pages.filter.conditions.order_id.eq,
+            pages.filter.conditions.order_id.ne,
+            pages.filter.conditions.amount_due.eq,
+            pages.filter.conditions.amount_due.ne,
+            pages.filter.conditions.amount_due.lt,
+            pages.filter.conditions.amount_due.lte,
+            pages.filter.conditions.amount_due.gt,
+            pages.filter.conditions.amount_due.gte,
+            pages.filter.conditions.discount.eq,
+            pages.filter.conditions.discount.ne,
+            pages.filter.conditions.discount.lt,
+            pages.filter.conditions.discount.lte,
+            pages.filter.conditions.discount.gt,
+            pages.filter.conditions.discount.gte,
+            pages.filter.conditions.issue_date.eq,
+            pages.filter.conditions.issue_date.ne,
+            pages.filter.conditions.issue_date.lt,
+            pages.filter.conditions.issue_date.lte,
+            pages.filter.conditions.issue_date.gt,
+            pages.filter.conditions.issue_date.gte,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Invoice {
+                id: row.id,
+                order_id: row.order_id,
+                amount_due: row.amount_due,
+                discount: row.discount,
+                issue_date: row.issue_date,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Invoice) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Invoice, Invoice>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Invoice>, sqlx::Error> {
+        sqlx::query_as!(
+            Invoice,
+            r#"
+                SELECT
+                    id,
+                    order_id,
+                    amount_due,
+                    discount,
+                    issue_date
+                FROM invoices
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR order_id IN (
+                            SELECT id
+                            FROM orders
+                            WHERE dealership_rif = $3
+                        )
+                    )
+                    AND ($4::INTEGER IS NULL OR order_id = $4)
+                    AND ($5::INTEGER IS NULL OR order_id <> $5)
+                    AND ($6::NUMERIC IS NULL OR amount_due = $6)
+                    AND ($7::NUMERIC IS NULL OR amount_due <> $7)
+                    AND ($8::NUMERIC IS NULL OR amount_due < $8)
+                    AND ($9::NUMERIC IS NULL OR amount_due <= $9)
+                    AND ($10::NUMERIC IS NULL OR amount_due > $10)
+                    AND ($11::NUMERIC IS NULL OR amount_due >= $11)
+                    AND ($12::NUMERIC IS NULL OR discount = $12)
+                    AND ($13::NUMERIC IS NULL OR discount <> $13)
+                    AND ($14::NUMERIC IS NULL OR discount < $14)
+                    AND ($15::NUMERIC IS NULL OR discount <= $15)
+                    AND ($16::NUMERIC IS NULL OR discount > $16)
+                    AND ($17::NUMERIC IS NULL OR discount >= $17)
+                    AND ($18::DATE IS NULL OR issue_date = $18)
+                    AND ($19::DATE IS NULL OR issue_date <> $19)
+                    AND ($20::DATE IS NULL OR issue_date < $20)
+                    AND ($21::DATE IS NULL OR issue_date <= $21)
+                    AND ($22::DATE IS NULL OR issue_date > $22)
+                    AND ($23::DATE IS NULL OR issue_date >= $23)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $24 THEN id < $2
+                            ELSE id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $24 THEN id END DESC,
+                    id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.dealership_rif,
+            pages.filter.conditions.order_id.eq,
+            pages.filter.conditions.order_id.ne,
+            pages.filter.conditions.amount_due.eq,
+            pages.filter.conditions.amount_due.ne,
+            pages.filter.conditions.amount_due.lt,
+            pages.filter.conditions.amount_due.lte,
+            pages.filter.conditions.amount_due.gt,
+            pages.filter.conditions.amount_due.gte,
+            pages.filter.conditions.discount.eq,
+            pages.filter.conditions.discount.ne,
+            pages.filter.conditions.discount.lt,
+            pages.filter.conditions.discount.lte,
+            pages.filter.conditions.discount.gt,
+            pages.filter.conditions.discount.gte,
+            pages.filter.conditions.issue_date.eq,
+            pages.filter.conditions.issue_date.ne,
+            pages.filter.conditions.issue_date.lt,
+            pages.filter.conditions.issue_date.lte,
+            pages.filter.conditions.issue_date.gt,
+            pages.filter.conditions.issue_date.gte,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[async_trait]
+impl Expandable for Invoice {
+    const RELATIONS: &'static [&'static str] = &["order", "payments"];
+
+    async fn expand(
+        invoices: Vec<Invoice>,
+        expansion: &Expansion,
+        connection: &Pool<Postgres>,
+    ) -> Result<Vec<Expanded<Invoice>>, sqlx::Error> {
+        let mut expanded_invoices = Expanded::wrap_all(invoices);
+
+        if expansion.includes("order") {
+            let order_ids = expanded_invoices
+                .iter()
+                .map(|invoice| invoice.item.order_id)
+                .collect();
+            let orders = Order::select_all_by_ids(order_ids, connection).await?;
+            Expanded::embed_one(
+                &mut expanded_invoices,
+                "order",
+                orders,
+                |invoice| invoice.order_id,
+                |order| order.id,
+            );
+        }
+
+        if expansion.includes("payments") {
+            let invoice_ids = expanded_invoices
+                .iter()
+                .map(|invoice| invoice.item.id)
+                .collect();
+            let payments = Payment::select_all_by_invoice_ids(invoice_ids, connection).await?;
+            Expanded::embed_many(
+                &mut expanded_invoices,
+                "payments",
+                payments,
+                |invoice| invoice.id,
+                |payment| payment.invoice_id,
+            );
+        }
+
+        Ok(expanded_invoices)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
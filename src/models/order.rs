@@ -0,0 +1,50 @@
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+use sqlx::{Executor, Postgres};
+use time::PrimitiveDateTime;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub id: i32,
+    pub vehicle_plate: String,
+    pub reservation_timestamp: PrimitiveDateTime,
+    pub checkin_timestamp: Option<PrimitiveDateTime>,
+    pub estimated_checkout_timestamp: Option<PrimitiveDateTime>,
+    pub checkout_timestamp: Option<PrimitiveDateTime>,
+    pub analist_national_id: String,
+    pub dealership_rif: String,
+    pub vehicle_caretaker_national_id: Option<String>,
+    pub vehicle_caretaker_name: Option<String>,
+    pub vehicle_kilometrage: BigDecimal,
+}
+
+impl Order {
+    pub async fn select_all_by_ids(
+        ids: Vec<i32>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        sqlx::query_as!(
+            Order,
+            r#"
+            SELECT
+                id,
+                vehicle_plate,
+                reservation_timestamp,
+                checkin_timestamp,
+                estimated_checkout_timestamp,
+                checkout_timestamp,
+                analist_national_id,
+                dealership_rif,
+                vehicle_caretaker_national_id,
+                vehicle_caretaker_name,
+                vehicle_kilometrage
+            FROM orders
+            WHERE id = ANY($1)
+            "#,
+            &ids
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
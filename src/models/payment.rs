@@ -1,10 +1,14 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 use time::Date;
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::utils::{
+    filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+    pagination::{Cursor, Page, Pages, Paginable},
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -47,7 +51,8 @@ impl Payment {
         .await
     }
 
-    pub async fn select_all(
+    pub async fn select_all_by_invoice_ids(
+        invoice_ids: Vec<i32>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Payment>, sqlx::Error> {
         sqlx::query_as!(
@@ -62,20 +67,162 @@ impl Payment {
                 card_number,
                 card_bank
             FROM payments
-            "#
+            WHERE invoice_id = ANY($1)
+            ORDER BY
+                invoice_id,
+                payment_number
+            "#,
+            &invoice_ids
         )
         .fetch_all(connection)
         .await
     }
 
+    pub async fn select_all(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<PaymentFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Payment>, sqlx::Error> {
+        Payment::stream_all(dealership_rif, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<PaymentFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Payment, sqlx::Error>> {
+        sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT
+                payment_number,
+                invoice_id,
+                amount_paid,
+                payment_date,
+                payment_type,
+                card_number,
+                card_bank
+            FROM payments
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR invoice_id IN (
+                        SELECT i.id
+                        FROM invoices AS i
+                        INNER JOIN orders AS o
+                            ON o.id = i.order_id
+                        WHERE o.dealership_rif = $1
+                    )
+                )
+                AND ($2::INTEGER IS NULL OR invoice_id = $2)
+                AND ($3::INTEGER IS NULL OR invoice_id <> $3)
+                AND ($4::NUMERIC IS NULL OR amount_paid = $4)
+                AND ($5::NUMERIC IS NULL OR amount_paid <> $5)
+                AND ($6::NUMERIC IS NULL OR amount_paid < $6)
+                AND ($7::NUMERIC IS NULL OR amount_paid <= $7)
+                AND ($8::NUMERIC IS NULL OR amount_paid > $8)
+                AND ($9::NUMERIC IS NULL OR amount_paid >= $9)
+                AND ($10::DATE IS NULL OR payment_date = $10)
+                AND ($11::DATE IS NULL OR payment_date <> $11)
+                AND ($12::DATE IS NULL OR payment_date < $12)
+                AND ($13::DATE IS NULL OR payment_date <= $13)
+                AND ($14::DATE IS NULL OR payment_date > $14)
+                AND ($15::DATE IS NULL OR payment_date >= $15)
+                AND ($16::TEXT IS NULL OR payment_type = $16)
+                AND ($17::TEXT IS NULL OR payment_type <> $17)
+            ORDER BY
+                CASE WHEN $18::TEXT = 'payment-number' AND NOT $19 THEN payment_number END,
+                CASE WHEN $18 = 'payment-number' AND $19 THEN payment_number END DESC,
+                CASE WHEN $18 = 'invoice-id' AND NOT $19 THEN invoice_id END,
+                CASE WHEN $18 = 'invoice-id' AND $19 THEN invoice_id END DESC,
+                CASE WHEN $18 = 'amount-paid' AND NOT $19 THEN amount_paid END,
+                CASE WHEN $18 = 'amount-paid' AND $19 THEN amount_paid END DESC,
+                CASE WHEN $18 = 'payment-date' AND NOT $19 THEN payment_date END,
+                CASE WHEN $18 = 'payment-date' AND $19 THEN payment_date END DESC,
+                CASE WHEN $18 = 'payment-type' AND NOT $19 THEN payment_type END,
+                CASE WHEN $18 = 'payment-type' AND $19 THEN payment_type END DESC,
+                payment_number,
+                invoice_id
+            "#,
+            dealership_rif,
+            filter.conditions.invoice_id.eq,
+            filter.conditions.invoice_id.ne,
+            filter.conditions.amount_paid.eq,
+            filter.conditions.amount_paid.ne,
+            filter.conditions.amount_paid.lt,
+            filter.conditions.amount_paid.lte,
+            filter.conditions.amount_paid.gt,
+            filter.conditions.amount_paid.gte,
+            filter.conditions.payment_date.eq,
+            filter.conditions.payment_date.ne,
+            filter.conditions.payment_date.lt,
+            filter.conditions.payment_date.lte,
+            filter.conditions.payment_date.gt,
+            filter.conditions.payment_date.gte,
+            filter.conditions.payment_type.eq,
+            filter.conditions.payment_type.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
     pub async fn count(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<PaymentFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_payments!"
             FROM payments
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR invoice_id IN (
+                        SELECT i.id
+                        FROM invoices AS i
+                        INNER JOIN orders AS o
+                            ON o.id = i.order_id
+                        WHERE o.dealership_rif = $1
+                    )
+                )
+                AND ($2::INTEGER IS NULL OR invoice_id = $2)
+                AND ($3::INTEGER IS NULL OR invoice_id <> $3)
+                AND ($4::NUMERIC IS NULL OR amount_paid = $4)
+                AND ($5::NUMERIC IS NULL OR amount_paid <> $5)
+                AND ($6::NUMERIC IS NULL OR amount_paid < $6)
+                AND ($7::NUMERIC IS NULL OR amount_paid <= $7)
+                AND ($8::NUMERIC IS NULL OR amount_paid > $8)
+                AND ($9::NUMERIC IS NULL OR amount_paid >= $9)
+                AND ($10::DATE IS NULL OR payment_date = $10)
+                AND ($11::DATE IS NULL OR payment_date <> $11)
+                AND ($12::DATE IS NULL OR payment_date < $12)
+                AND ($13::DATE IS NULL OR payment_date <= $13)
+                AND ($14::DATE IS NULL OR payment_date > $14)
+                AND ($15::DATE IS NULL OR payment_date >= $15)
+                AND ($16::TEXT IS NULL OR payment_type = $16)
+                AND ($17::TEXT IS NULL OR payment_type <> $17)
+            "#,
+            dealership_rif,
+            filter.conditions.invoice_id.eq,
+            filter.conditions.invoice_id.ne,
+            filter.conditions.amount_paid.eq,
+            filter.conditions.amount_paid.ne,
+            filter.conditions.amount_paid.lt,
+            filter.conditions.amount_paid.lte,
+            filter.conditions.amount_paid.gt,
+            filter.conditions.amount_paid.gte,
+            filter.conditions.payment_date.eq,
+            filter.conditions.payment_date.ne,
+            filter.conditions.payment_date.lt,
+            filter.conditions.payment_date.lte,
+            filter.conditions.payment_date.gt,
+            filter.conditions.payment_date.gte,
+            filter.conditions.payment_type.eq,
+            filter.conditions.payment_type.ne
         )
         .fetch_one(connection)
         .await
@@ -110,15 +257,61 @@ impl Payment {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct PaymentFilter {
+    pub invoice_id: Comparison<i32>,
+    pub amount_paid: Comparison<BigDecimal>,
+    pub payment_date: Comparison<Date>,
+    pub payment_type: Comparison<String>,
+}
+
+impl Filter for PaymentFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("invoice-id", EQUALITY),
+        FilterField::new("amount-paid", ORDERING),
+        FilterField::new("payment-date", ORDERING),
+        FilterField::new("payment-type", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "payment-number",
+        "invoice-id",
+        "amount-paid",
+        "payment-date",
+        "payment-type",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "invoice-id" => self.invoice_id.apply(operator, value),
+            "amount-paid" => self.amount_paid.apply(operator, value),
+            "payment-date" => self.payment_date.apply(operator, value),
+            "payment-type" => self.payment_type.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<Payment> for Payment {
+    const FIELDS: &'static [&'static str] = &[
+        "payment-number",
+        "invoice-id",
+        "amount-paid",
+        "payment-date",
+        "payment-type",
+        "card-number",
+        "card-bank",
+    ];
+
+    type Filter = PaymentFilter;
+    type CursorKey = (i32, i32);
+
     async fn get_page(
         pages: &Pages<Payment, Payment>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Payment>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Payment,
+        let page_rows = sqlx::query!(
             r#"
             SELECT
                 payment_number,
@@ -127,23 +320,189 @@ impl Paginable<Payment> for Payment {
                 payment_date,
                 payment_type,
                 card_number,
-                card_bank
+                card_bank,
+                COUNT(*) OVER() AS "total!"
             FROM payments
+            WHERE
+                (
+                    $3::TEXT IS NULL
+                    OR invoice_id IN (
+                        SELECT i.id
+                        FROM invoices AS i
+                        INNER JOIN orders AS o
+                            ON o.id = i.order_id
+                        WHERE o.dealership_rif = $3
+                    )
+                )
+                AND ($4::INTEGER IS NULL OR invoice_id = $4)
+                AND ($5::INTEGER IS NULL OR invoice_id <> $5)
+                AND ($6::NUMERIC IS NULL OR amount_paid = $6)
+                AND ($7::NUMERIC IS NULL OR amount_paid <> $7)
+                AND ($8::NUMERIC IS NULL OR amount_paid < $8)
+                AND ($9::NUMERIC IS NULL OR amount_paid <= $9)
+                AND ($10::NUMERIC IS NULL OR amount_paid > $10)
+                AND ($11::NUMERIC IS NULL OR amount_paid >= $11)
+                AND ($12::DATE IS NULL OR payment_date = $12)
+                AND ($13::DATE IS NULL OR payment_date <> $13)
+                AND ($14::DATE IS NULL OR payment_date < $14)
+                AND ($15::DATE IS NULL OR payment_date <= $15)
+                AND ($16::DATE IS NULL OR payment_date > $16)
+                AND ($17::DATE IS NULL OR payment_date >= $17)
+                AND ($18::TEXT IS NULL OR payment_type = $18)
+                AND ($19::TEXT IS NULL OR payment_type <> $19)
+            ORDER BY
+                CASE WHEN $20::TEXT = 'payment-number' AND NOT $21 THEN payment_number END,
+                CASE WHEN $20 = 'payment-number' AND $21 THEN payment_number END DESC,
+                CASE WHEN $20 = 'invoice-id' AND NOT $21 THEN invoice_id END,
+                CASE WHEN $20 = 'invoice-id' AND $21 THEN invoice_id END DESC,
+                CASE WHEN $20 = 'amount-paid' AND NOT $21 THEN amount_paid END,
+                CASE WHEN $20 = 'amount-paid' AND $21 THEN amount_paid END DESC,
+                CASE WHEN $20 = 'payment-date' AND NOT $21 THEN payment_date END,
+                CASE WHEN $20 = 'payment-date' AND $21 THEN payment_date END DESC,
+                CASE WHEN $20 = 'payment-type' AND NOT $21 THEN payment_type END,
+                CASE WHEN $20 = 'payment-type' AND $21 THEN payment_type END DESC,
+                payment_number,
+                invoice_id
             LIMIT $1
             OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.dealership_rif,
+            pages.filter.conditions.invoice_id.eq,
+            pages.filter.conditions.invoice_id.ne,
+            pages.filter.conditions.amount_paid.eq,
+            pages.filter.conditions.amount_paid.ne,
+            pages.filter.conditions.amount_paid.lt,
+            pages.filter.conditions.amount_paid.lte,
+            pages.filter.conditions.amount_paid.gt,
+            pages.filter.conditions.amount_paid.gte,
+            pages.filter.conditions.payment_date.eq,
+            pages.filter.conditions.payment_date.ne,
+            pages.filter.conditions.payment_date.lt,
+            pages.filter.conditions.payment_date.lte,
+            pages.filter.conditions.payment_date.gt,
+            pages.filter.conditions.payment_date.gte,
+            pages.filter.conditions.payment_type.eq,
+            pages.filter.conditions.payment_type.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Payment {
+                payment_number: row.payment_number,
+                invoice_id: row.invoice_id,
+                amount_paid: row.amount_paid,
+                payment_date: row.payment_date,
+                payment_type: row.payment_type,
+                card_number: row.card_number,
+                card_bank: row.card_bank,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Payment) -> (i32, i32) {
+        (item.payment_number, item.invoice_id)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Payment, Payment>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, i32)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Payment>, sqlx::Error> {
+        sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT
+                payment_number,
+                invoice_id,
+                amount_paid,
+                payment_date,
+                payment_type,
+                card_number,
+                card_bank
+            FROM payments
+            WHERE
+                (
+                    $3::TEXT IS NULL
+                    OR invoice_id IN (
+                        SELECT i.id
+                        FROM invoices AS i
+                        INNER JOIN orders AS o
+                            ON o.id = i.order_id
+                        WHERE o.dealership_rif = $3
+                    )
+                )
+                AND ($4::INTEGER IS NULL OR invoice_id = $4)
+                AND ($5::INTEGER IS NULL OR invoice_id <> $5)
+                AND ($6::NUMERIC IS NULL OR amount_paid = $6)
+                AND ($7::NUMERIC IS NULL OR amount_paid <> $7)
+                AND ($8::NUMERIC IS NULL OR amount_paid < $8)
+                AND ($9::NUMERIC IS NULL OR amount_paid <= $9)
+                AND ($10::NUMERIC IS NULL OR amount_paid > $10)
+                AND ($11::NUMERIC IS NULL OR amount_paid >= $11)
+                AND ($12::DATE IS NULL OR payment_date = $12)
+                AND ($13::DATE IS NULL OR payment_date <> $13)
+                AND ($14::DATE IS NULL OR payment_date < $14)
+                AND ($15::DATE IS NULL OR payment_date <= $15)
+                AND ($16::DATE IS NULL OR payment_date > $16)
+                AND ($17::DATE IS NULL OR payment_date >= $17)
+                AND ($18::TEXT IS NULL OR payment_type = $18)
+                AND ($19::TEXT IS NULL OR payment_type <> $19)
+                AND (
+                    $2::INTEGER IS NULL
+                    OR CASE
+                        WHEN $20 THEN (payment_number, invoice_id)
+                            < ($2, $21)
+                        ELSE (payment_number, invoice_id)
+                            > ($2, $21)
+                    END
+                )
+            ORDER BY
+                CASE WHEN $20 THEN payment_number END DESC,
+                CASE WHEN $20 THEN invoice_id END DESC,
+                payment_number,
+                invoice_id
+            LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.dealership_rif,
+            pages.filter.conditions.invoice_id.eq,
+            pages.filter.conditions.invoice_id.ne,
+            pages.filter.conditions.amount_paid.eq,
+            pages.filter.conditions.amount_paid.ne,
+            pages.filter.conditions.amount_paid.lt,
+            pages.filter.conditions.amount_paid.lte,
+            pages.filter.conditions.amount_paid.gt,
+            pages.filter.conditions.amount_paid.gte,
+            pages.filter.conditions.payment_date.eq,
+            pages.filter.conditions.payment_date.ne,
+            pages.filter.conditions.payment_date.lt,
+            pages.filter.conditions.payment_date.lte,
+            pages.filter.conditions.payment_date.gt,
+            pages.filter.conditions.payment_date.gte,
+            pages.filter.conditions.payment_type.eq,
+            pages.filter.conditions.payment_type.ne,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
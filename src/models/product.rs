@@ -1,8 +1,16 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
+use time::PrimitiveDateTime;
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::suggestion::Suggestion,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,11 +20,13 @@ pub struct Product {
     pub description: String,
     pub is_ecologic: bool,
     pub supply_line_id: i32,
+    pub deleted_at: Option<PrimitiveDateTime>,
 }
 
 impl Product {
     pub async fn select(
         id: i32,
+        include_deleted: bool,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Product, sqlx::Error> {
         sqlx::query_as!(
@@ -27,17 +37,22 @@ impl Product {
                 name,
                 description,
                 is_ecologic,
-                supply_line_id
+                supply_line_id,
+                deleted_at
             FROM products
-            WHERE id = $1
+            WHERE
+                id = $1
+                AND ($2 OR deleted_at IS NULL)
             "#,
             id,
+            include_deleted
         )
         .fetch_one(connection)
         .await
     }
 
-    pub async fn select_all(
+    pub async fn select_all_by_ids(
+        ids: Vec<i32>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Product>, sqlx::Error> {
         sqlx::query_as!(
@@ -48,27 +63,128 @@ impl Product {
                 name,
                 description,
                 is_ecologic,
-                supply_line_id
+                supply_line_id,
+                deleted_at
             FROM products
-            "#
+            WHERE id = ANY($1)
+            "#,
+            &ids
         )
         .fetch_all(connection)
         .await
     }
 
+    pub async fn select_all(
+        include_deleted: bool,
+        filter: &ListFilter<ProductFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        Product::stream_all(include_deleted, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        include_deleted: bool,
+        filter: &ListFilter<ProductFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Product, sqlx::Error>> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                is_ecologic,
+                supply_line_id,
+                deleted_at
+            FROM products
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR name = $2)
+                AND ($3::TEXT IS NULL OR name <> $3)
+                AND ($4::BOOLEAN IS NULL OR is_ecologic = $4)
+                AND ($5::BOOLEAN IS NULL OR is_ecologic <> $5)
+                AND ($6::INTEGER IS NULL OR supply_line_id = $6)
+                AND ($7::INTEGER IS NULL OR supply_line_id <> $7)
+            ORDER BY
+                CASE WHEN $8::TEXT = 'id' AND NOT $9 THEN id END,
+                CASE WHEN $8 = 'id' AND $9 THEN id END DESC,
+                CASE WHEN $8 = 'name' AND NOT $9 THEN name END,
+                CASE WHEN $8 = 'name' AND $9 THEN name END DESC,
+                CASE WHEN $8 = 'supply-line-id' AND NOT $9 THEN supply_line_id END,
+                CASE WHEN $8 = 'supply-line-id' AND $9 THEN supply_line_id END DESC,
+                CASE WHEN $8 = 'deleted-at' AND NOT $9 THEN deleted_at END,
+                CASE WHEN $8 = 'deleted-at' AND $9 THEN deleted_at END DESC,
+                id
+            "#,
+            include_deleted,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.is_ecologic.eq,
+            filter.conditions.is_ecologic.ne,
+            filter.conditions.supply_line_id.eq,
+            filter.conditions.supply_line_id.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
     pub async fn count(
+        include_deleted: bool,
+        filter: &ListFilter<ProductFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_products!"
             FROM products
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR name = $2)
+                AND ($3::TEXT IS NULL OR name <> $3)
+                AND ($4::BOOLEAN IS NULL OR is_ecologic = $4)
+                AND ($5::BOOLEAN IS NULL OR is_ecologic <> $5)
+                AND ($6::INTEGER IS NULL OR supply_line_id = $6)
+                AND ($7::INTEGER IS NULL OR supply_line_id <> $7)
+            "#,
+            include_deleted,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.is_ecologic.eq,
+            filter.conditions.is_ecologic.ne,
+            filter.conditions.supply_line_id.eq,
+            filter.conditions.supply_line_id.ne
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<i32>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT id, name AS label
+            FROM products
+            WHERE
+                lower(name) LIKE to_prefix_pattern(lower($1))
+                AND deleted_at IS NULL
+            ORDER BY name, id
+            LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         id: i32,
         connection: impl Executor<'_, Database = Postgres>,
@@ -76,54 +192,219 @@ impl Product {
         sqlx::query_as!(
             Product,
             r#"
-            DELETE FROM products
-            WHERE id = $1
+            UPDATE products
+            SET deleted_at = now()
+            WHERE
+                id = $1
+                AND deleted_at IS NULL
             RETURNING
                 id,
                 name,
                 description,
                 is_ecologic,
-                supply_line_id
+                supply_line_id,
+                deleted_at
             "#,
             id,
         )
         .fetch_one(connection)
         .await
     }
+
+    pub async fn restore(
+        id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Product, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            UPDATE products
+            SET deleted_at = NULL
+            WHERE
+                id = $1
+                AND deleted_at IS NOT NULL
+            RETURNING
+                id,
+                name,
+                description,
+                is_ecologic,
+                supply_line_id,
+                deleted_at
+            "#,
+            id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct ProductFilter {
+    pub name: Comparison<String>,
+    pub is_ecologic: Comparison<bool>,
+    pub supply_line_id: Comparison<i32>,
+}
+
+impl Filter for ProductFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("name", EQUALITY),
+        FilterField::new("is-ecologic", EQUALITY),
+        FilterField::new("supply-line-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name", "supply-line-id", "deleted-at"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "name" => self.name.apply(operator, value),
+            "is-ecologic" => self.is_ecologic.apply(operator, value),
+            "supply-line-id" => self.supply_line_id.apply(operator, value),
+            _ => false,
+        }
+    }
 }
 
 #[async_trait]
 impl Paginable<Product> for Product {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "description",
+        "is-ecologic",
+        "supply-line-id",
+        "deleted-at",
+    ];
+
+    type Filter = ProductFilter;
+    type CursorKey = (i32,);
+
     async fn get_page(
         pages: &Pages<Product, Product>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Product>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Product,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     id,
                     name,
                     description,
                     is_ecologic,
-                    supply_line_id
+                    supply_line_id,
+                    deleted_at,
+                    COUNT(*) OVER() AS "total!"
                 FROM products
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR name = $4)
+                    AND ($5::TEXT IS NULL OR name <> $5)
+                    AND ($6::BOOLEAN IS NULL OR is_ecologic = $6)
+                    AND ($7::BOOLEAN IS NULL OR is_ecologic <> $7)
+                    AND ($8::INTEGER IS NULL OR supply_line_id = $8)
+                    AND ($9::INTEGER IS NULL OR supply_line_id <> $9)
+                ORDER BY
+                    CASE WHEN $10::TEXT = 'id' AND NOT $11 THEN id END,
+                    CASE WHEN $10 = 'id' AND $11 THEN id END DESC,
+                    CASE WHEN $10 = 'name' AND NOT $11 THEN name END,
+                    CASE WHEN $10 = 'name' AND $11 THEN name END DESC,
+                    CASE WHEN $10 = 'supply-line-id' AND NOT $11 THEN supply_line_id END,
+                    CASE WHEN $10 = 'supply-line-id' AND $11 THEN supply_line_id END DESC,
+                    CASE WHEN $10 = 'deleted-at' AND NOT $11 THEN deleted_at END,
+                    CASE WHEN $10 = 'deleted-at' AND $11 THEN deleted_at END DESC,
+                    id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.include_deleted,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.is_ecologic.eq,
+            pages.filter.conditions.is_ecologic.ne,
+            pages.filter.conditions.supply_line_id.eq,
+            pages.filter.conditions.supply_line_id.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Product {
+                id: row.id,
+                name: row.name,
+                description: row.description,
+                is_ecologic: row.is_ecologic,
+                supply_line_id: row.supply_line_id,
+                deleted_at: row.deleted_at,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Product) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Product, Product>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        sqlx::query_as!(
+            Product,
+            r#"
+                SELECT
+                    id,
+                    name,
+                    description,
+                    is_ecologic,
+                    supply_line_id,
+                    deleted_at
+                FROM products
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR name = $4)
+                    AND ($5::TEXT IS NULL OR name <> $5)
+                    AND ($6::BOOLEAN IS NULL OR is_ecologic = $6)
+                    AND ($7::BOOLEAN IS NULL OR is_ecologic <> $7)
+                    AND ($8::INTEGER IS NULL OR supply_line_id = $8)
+                    AND ($9::INTEGER IS NULL OR supply_line_id <> $9)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $10 THEN id < $2
+                            ELSE id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $10 THEN id END DESC,
+                    id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.include_deleted,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.is_ecologic.eq,
+            pages.filter.conditions.is_ecologic.ne,
+            pages.filter.conditions.supply_line_id.eq,
+            pages.filter.conditions.supply_line_id.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -159,7 +440,8 @@ impl InsertProduct {
                 name,
                 description,
                 is_ecologic,
-                supply_line_id
+                supply_line_id,
+                deleted_at
             "#,
             self.name,
             self.description,
@@ -205,7 +487,8 @@ impl UpdateProduct {
                 name,
                 description,
                 is_ecologic,
-                supply_line_id
+                supply_line_id,
+                deleted_at
             "#,
             new_name,
             new_description,
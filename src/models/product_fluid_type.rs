@@ -0,0 +1,347 @@
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+
+use crate::utils::{
+    filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+    pagination::{Cursor, Page, Pages, Paginable},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductFluidType {
+    pub product_id: i32,
+    pub fluid: String,
+    pub fluid_type: String,
+}
+
+impl ProductFluidType {
+    pub async fn select(
+        product_id: i32,
+        fluid: String,
+        fluid_type: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<ProductFluidType, sqlx::Error> {
+        sqlx::query_as!(
+            ProductFluidType,
+            r#"
+            SELECT
+                product_id,
+                fluid,
+                fluid_type
+            FROM products_fluid_types
+            WHERE
+                product_id = $1
+                AND fluid = $2
+                AND fluid_type = $3
+            "#,
+            product_id,
+            fluid,
+            fluid_type
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_all(
+        filter: &ListFilter<ProductFluidTypeFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ProductFluidType>, sqlx::Error> {
+        ProductFluidType::stream_all(filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<ProductFluidTypeFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<ProductFluidType, sqlx::Error>> {
+        sqlx::query_as!(
+            ProductFluidType,
+            r#"
+            SELECT
+                product_id,
+                fluid,
+                fluid_type
+            FROM products_fluid_types
+            WHERE
+                ($1::INTEGER IS NULL OR product_id = $1)
+                AND ($2::INTEGER IS NULL OR product_id <> $2)
+                AND ($3::TEXT IS NULL OR fluid = $3)
+                AND ($4::TEXT IS NULL OR fluid <> $4)
+                AND ($5::TEXT IS NULL OR fluid_type = $5)
+                AND ($6::TEXT IS NULL OR fluid_type <> $6)
+            ORDER BY
+                CASE WHEN $7::TEXT = 'product-id' AND NOT $8 THEN product_id END,
+                CASE WHEN $7 = 'product-id' AND $8 THEN product_id END DESC,
+                CASE WHEN $7 = 'fluid' AND NOT $8 THEN fluid END,
+                CASE WHEN $7 = 'fluid' AND $8 THEN fluid END DESC,
+                CASE WHEN $7 = 'fluid-type' AND NOT $8 THEN fluid_type END,
+                CASE WHEN $7 = 'fluid-type' AND $8 THEN fluid_type END DESC,
+                product_id,
+                fluid,
+                fluid_type
+            "#,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.fluid.eq,
+            filter.conditions.fluid.ne,
+            filter.conditions.fluid_type.eq,
+            filter.conditions.fluid_type.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        filter: &ListFilter<ProductFluidTypeFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_products_fluid_types!"
+            FROM products_fluid_types
+            WHERE
+                ($1::INTEGER IS NULL OR product_id = $1)
+                AND ($2::INTEGER IS NULL OR product_id <> $2)
+                AND ($3::TEXT IS NULL OR fluid = $3)
+                AND ($4::TEXT IS NULL OR fluid <> $4)
+                AND ($5::TEXT IS NULL OR fluid_type = $5)
+                AND ($6::TEXT IS NULL OR fluid_type <> $6)
+            "#,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.fluid.eq,
+            filter.conditions.fluid.ne,
+            filter.conditions.fluid_type.eq,
+            filter.conditions.fluid_type.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn delete(
+        product_id: i32,
+        fluid: String,
+        fluid_type: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<ProductFluidType, sqlx::Error> {
+        sqlx::query_as!(
+            ProductFluidType,
+            r#"
+            DELETE FROM products_fluid_types
+            WHERE
+                product_id = $1
+                AND fluid = $2
+                AND fluid_type = $3
+            RETURNING
+                product_id,
+                fluid,
+                fluid_type
+            "#,
+            product_id,
+            fluid,
+            fluid_type
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct ProductFluidTypeFilter {
+    pub product_id: Comparison<i32>,
+    pub fluid: Comparison<String>,
+    pub fluid_type: Comparison<String>,
+}
+
+impl Filter for ProductFluidTypeFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("product-id", EQUALITY),
+        FilterField::new("fluid", EQUALITY),
+        FilterField::new("fluid-type", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["product-id", "fluid", "fluid-type"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "product-id" => self.product_id.apply(operator, value),
+            "fluid" => self.fluid.apply(operator, value),
+            "fluid-type" => self.fluid_type.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<ProductFluidType> for ProductFluidType {
+    const FIELDS: &'static [&'static str] = &["product-id", "fluid", "fluid-type"];
+
+    type Filter = ProductFluidTypeFilter;
+    type CursorKey = (i32, String, String);
+
+    async fn get_page(
+        pages: &Pages<ProductFluidType, ProductFluidType>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<ProductFluidType>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    product_id,
+                    fluid,
+                    fluid_type,
+                    COUNT(*) OVER() AS "total!"
+                FROM products_fluid_types
+                WHERE
+                    ($3::INTEGER IS NULL OR product_id = $3)
+                    AND ($4::INTEGER IS NULL OR product_id <> $4)
+                    AND ($5::TEXT IS NULL OR fluid = $5)
+                    AND ($6::TEXT IS NULL OR fluid <> $6)
+                    AND ($7::TEXT IS NULL OR fluid_type = $7)
+                    AND ($8::TEXT IS NULL OR fluid_type <> $8)
+                ORDER BY
+                    CASE WHEN $9::TEXT = 'product-id' AND NOT $10 THEN product_id END,
+                    CASE WHEN $9 = 'product-id' AND $10 THEN product_id END DESC,
+                    CASE WHEN $9 = 'fluid' AND NOT $10 THEN fluid END,
+                    CASE WHEN $9 = 'fluid' AND $10 THEN fluid END DESC,
+                    CASE WHEN $9 = 'fluid-type' AND NOT $10 THEN fluid_type END,
+                    CASE WHEN $9 = 'fluid-type' AND $10 THEN fluid_type END DESC,
+                    product_id,
+                    fluid,
+                    fluid_type
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.fluid.eq,
+            pages.filter.conditions.fluid.ne,
+            pages.filter.conditions.fluid_type.eq,
+            pages.filter.conditions.fluid_type.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| ProductFluidType {
+                product_id: row.product_id,
+                fluid: row.fluid,
+                fluid_type: row.fluid_type,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &ProductFluidType) -> (i32, String, String) {
+        (item.product_id, item.fluid.clone(), item.fluid_type.clone())
+    }
+
+    async fn get_items_after(
+        pages: &Pages<ProductFluidType, ProductFluidType>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, String, String)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ProductFluidType>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductFluidType,
+            r#"
+                SELECT
+                    product_id,
+                    fluid,
+                    fluid_type
+                FROM products_fluid_types
+                WHERE
+                    ($3::INTEGER IS NULL OR product_id = $3)
+                    AND ($4::INTEGER IS NULL OR product_id <> $4)
+                    AND ($5::TEXT IS NULL OR fluid = $5)
+                    AND ($6::TEXT IS NULL OR fluid <> $6)
+                    AND ($7::TEXT IS NULL OR fluid_type = $7)
+                    AND ($8::TEXT IS NULL OR fluid_type <> $8)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $9 THEN (product_id, fluid, fluid_type)
+                                < ($2, $10, $11)
+                            ELSE (product_id, fluid, fluid_type)
+                                > ($2, $10, $11)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $9 THEN product_id END DESC,
+                    CASE WHEN $9 THEN fluid END DESC,
+                    CASE WHEN $9 THEN fluid_type END DESC,
+                    product_id,
+                    fluid,
+                    fluid_type
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.fluid.eq,
+            pages.filter.conditions.fluid.ne,
+            pages.filter.conditions.fluid_type.eq,
+            pages.filter.conditions.fluid_type.ne,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1.as_str()),
+            cursor.map(|cursor| cursor.key.2.as_str())
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InsertProductFluidType {
+    pub product_id: i32,
+    pub fluid: String,
+    pub fluid_type: String,
+}
+
+impl InsertProductFluidType {
+    pub async fn insert(
+        self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<ProductFluidType, sqlx::Error> {
+        sqlx::query_as!(
+            ProductFluidType,
+            r#"
+            INSERT INTO products_fluid_types (
+                product_id,
+                fluid,
+                fluid_type
+            )
+            VALUES (
+                $1,
+                $2,
+                $3
+            )
+            RETURNING
+                product_id,
+                fluid,
+                fluid_type
+            "#,
+            self.product_id,
+            self.fluid,
+            self.fluid_type
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
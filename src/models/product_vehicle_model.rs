@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+
+use crate::utils::{
+    filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+    pagination::{Cursor, Page, Pages, Paginable},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductVehicleModel {
+    pub product_id: i32,
+    pub vehicle_model_id: i32,
+}
+
+impl ProductVehicleModel {
+    pub async fn select(
+        product_id: i32,
+        vehicle_model_id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<ProductVehicleModel, sqlx::Error> {
+        sqlx::query_as!(
+            ProductVehicleModel,
+            r#"
+            SELECT
+                product_id,
+                vehicle_model_id
+            FROM products_vehicle_models
+            WHERE
+                product_id = $1
+                AND vehicle_model_id = $2
+            "#,
+            product_id,
+            vehicle_model_id
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_all(
+        filter: &ListFilter<ProductVehicleModelFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ProductVehicleModel>, sqlx::Error> {
+        ProductVehicleModel::stream_all(filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<ProductVehicleModelFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<ProductVehicleModel, sqlx::Error>> {
+        sqlx::query_as!(
+            ProductVehicleModel,
+            r#"
+            SELECT
+                product_id,
+                vehicle_model_id
+            FROM products_vehicle_models
+            WHERE
+                ($1::INTEGER IS NULL OR product_id = $1)
+                AND ($2::INTEGER IS NULL OR product_id <> $2)
+                AND ($3::INTEGER IS NULL OR vehicle_model_id = $3)
+                AND ($4::INTEGER IS NULL OR vehicle_model_id <> $4)
+            ORDER BY
+                CASE WHEN $5::TEXT = 'product-id' AND NOT $6 THEN product_id END,
+                CASE WHEN $5 = 'product-id' AND $6 THEN product_id END DESC,
+                CASE WHEN $5 = 'vehicle-model-id' AND NOT $6 THEN vehicle_model_id END,
+                CASE WHEN $5 = 'vehicle-model-id' AND $6 THEN vehicle_model_id END DESC,
+                product_id,
+                vehicle_model_id
+            "#,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.vehicle_model_id.eq,
+            filter.conditions.vehicle_model_id.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        filter: &ListFilter<ProductVehicleModelFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_products_vehicle_models!"
+            FROM products_vehicle_models
+            WHERE
+                ($1::INTEGER IS NULL OR product_id = $1)
+                AND ($2::INTEGER IS NULL OR product_id <> $2)
+                AND ($3::INTEGER IS NULL OR vehicle_model_id = $3)
+                AND ($4::INTEGER IS NULL OR vehicle_model_id <> $4)
+            "#,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.vehicle_model_id.eq,
+            filter.conditions.vehicle_model_id.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn delete(
+        product_id: i32,
+        vehicle_model_id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<ProductVehicleModel, sqlx::Error> {
+        sqlx::query_as!(
+            ProductVehicleModel,
+            r#"
+            DELETE FROM products_vehicle_models
+            WHERE
+                product_id = $1
+                AND vehicle_model_id = $2
+            RETURNING
+                product_id,
+                vehicle_model_id
+            "#,
+            product_id,
+            vehicle_model_id
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct ProductVehicleModelFilter {
+    pub product_id: Comparison<i32>,
+    pub vehicle_model_id: Comparison<i32>,
+}
+
+impl Filter for ProductVehicleModelFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("product-id", EQUALITY),
+        FilterField::new("vehicle-model-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["product-id", "vehicle-model-id"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "product-id" => self.product_id.apply(operator, value),
+            "vehicle-model-id" => self.vehicle_model_id.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<ProductVehicleModel> for ProductVehicleModel {
+    const FIELDS: &'static [&'static str] = &["product-id", "vehicle-model-id"];
+
+    type Filter = ProductVehicleModelFilter;
+    type CursorKey = (i32, i32);
+
+    async fn get_page(
+        pages: &Pages<ProductVehicleModel, ProductVehicleModel>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<ProductVehicleModel>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    product_id,
+                    vehicle_model_id,
+                    COUNT(*) OVER() AS "total!"
+                FROM products_vehicle_models
+                WHERE
+                    ($3::INTEGER IS NULL OR product_id = $3)
+                    AND ($4::INTEGER IS NULL OR product_id <> $4)
+                    AND ($5::INTEGER IS NULL OR vehicle_model_id = $5)
+                    AND ($6::INTEGER IS NULL OR vehicle_model_id <> $6)
+                ORDER BY
+                    CASE WHEN $7::TEXT = 'product-id' AND NOT $8 THEN product_id END,
+                    CASE WHEN $7 = 'product-id' AND $8 THEN product_id END DESC,
+                    CASE WHEN $7 = 'vehicle-model-id' AND NOT $8 THEN vehicle_model_id END,
+                    CASE WHEN $7 = 'vehicle-model-id' AND $8 THEN vehicle_model_id END DESC,
+                    product_id,
+                    vehicle_model_id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.vehicle_model_id.eq,
+            pages.filter.conditions.vehicle_model_id.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| ProductVehicleModel {
+                product_id: row.product_id,
+                vehicle_model_id: row.vehicle_model_id,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &ProductVehicleModel) -> (i32, i32) {
+        (item.product_id, item.vehicle_model_id)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<ProductVehicleModel, ProductVehicleModel>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, i32)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ProductVehicleModel>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductVehicleModel,
+            r#"
+                SELECT
+                    product_id,
+                    vehicle_model_id
+                FROM products_vehicle_models
+                WHERE
+                    ($3::INTEGER IS NULL OR product_id = $3)
+                    AND ($4::INTEGER IS NULL OR product_id <> $4)
+                    AND ($5::INTEGER IS NULL OR vehicle_model_id = $5)
+                    AND ($6::INTEGER IS NULL OR vehicle_model_id <> $6)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $7 THEN (product_id, vehicle_model_id)
+                                < ($2, $8)
+                            ELSE (product_id, vehicle_model_id)
+                                > ($2, $8)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $7 THEN product_id END DESC,
+                    CASE WHEN $7 THEN vehicle_model_id END DESC,
+                    product_id,
+                    vehicle_model_id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.vehicle_model_id.eq,
+            pages.filter.conditions.vehicle_model_id.ne,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InsertProductVehicleModel {
+    pub product_id: i32,
+    pub vehicle_model_id: i32,
+}
+
+impl InsertProductVehicleModel {
+    pub async fn insert(
+        self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<ProductVehicleModel, sqlx::Error> {
+        sqlx::query_as!(
+            ProductVehicleModel,
+            r#"
+            INSERT INTO products_vehicle_models (
+                product_id,
+                vehicle_model_id
+            )
+            VALUES (
+                $1,
+                $2
+            )
+            RETURNING
+                product_id,
+                vehicle_model_id
+            "#,
+            self.product_id,
+            self.vehicle_model_id
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
@@ -0,0 +1,326 @@
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+
+use crate::utils::{
+    filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+    pagination::{Cursor, Page, Pages, Paginable},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolePermission {
+    pub role_id: i32,
+    pub permission: String,
+}
+
+impl RolePermission {
+    pub async fn select(
+        role_id: i32,
+        permission: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<RolePermission, sqlx::Error> {
+        sqlx::query_as!(
+            RolePermission,
+            r#"
+            SELECT
+                role_id,
+                permission
+            FROM roles_permissions
+            WHERE
+                role_id = $1
+                AND permission = $2
+            "#,
+            role_id,
+            permission
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_all(
+        filter: &ListFilter<RolePermissionFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<RolePermission>, sqlx::Error> {
+        RolePermission::stream_all(filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<RolePermissionFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<RolePermission, sqlx::Error>> {
+        sqlx::query_as!(
+            RolePermission,
+            r#"
+            SELECT
+                role_id,
+                permission
+            FROM roles_permissions
+            WHERE
+                ($1::INTEGER IS NULL OR role_id = $1)
+                AND ($2::INTEGER IS NULL OR role_id <> $2)
+                AND ($3::TEXT IS NULL OR permission = $3)
+                AND ($4::TEXT IS NULL OR permission <> $4)
+            ORDER BY
+                CASE WHEN $5::TEXT = 'role-id' AND NOT $6 THEN role_id END,
+                CASE WHEN $5 = 'role-id' AND $6 THEN role_id END DESC,
+                CASE WHEN $5 = 'permission' AND NOT $6 THEN permission END,
+                CASE WHEN $5 = 'permission' AND $6 THEN permission END DESC,
+                role_id,
+                permission
+            "#,
+            filter.conditions.role_id.eq,
+            filter.conditions.role_id.ne,
+            filter.conditions.permission.eq,
+            filter.conditions.permission.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        filter: &ListFilter<RolePermissionFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_roles_permissions!"
+            FROM roles_permissions
+            WHERE
+                ($1::INTEGER IS NULL OR role_id = $1)
+                AND ($2::INTEGER IS NULL OR role_id <> $2)
+                AND ($3::TEXT IS NULL OR permission = $3)
+                AND ($4::TEXT IS NULL OR permission <> $4)
+            "#,
+            filter.conditions.role_id.eq,
+            filter.conditions.role_id.ne,
+            filter.conditions.permission.eq,
+            filter.conditions.permission.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn is_granted_to_employee(
+        national_id: String,
+        permission: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM staff AS s
+                INNER JOIN roles_permissions AS rp
+                    ON rp.role_id = s.role_id
+                WHERE
+                    s.national_id = $1
+                    AND rp.permission = $2
+                    AND s.deleted_at IS NULL
+            ) AS "is_granted!"
+            "#,
+            national_id as _,
+            permission
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn delete(
+        role_id: i32,
+        permission: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<RolePermission, sqlx::Error> {
+        sqlx::query_as!(
+            RolePermission,
+            r#"
+            DELETE FROM roles_permissions
+            WHERE
+                role_id = $1
+                AND permission = $2
+            RETURNING
+                role_id,
+                permission
+            "#,
+            role_id,
+            permission
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct RolePermissionFilter {
+    pub role_id: Comparison<i32>,
+    pub permission: Comparison<String>,
+}
+
+impl Filter for RolePermissionFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("role-id", EQUALITY),
+        FilterField::new("permission", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["role-id", "permission"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "role-id" => self.role_id.apply(operator, value),
+            "permission" => self.permission.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<RolePermission> for RolePermission {
+    const FIELDS: &'static [&'static str] = &["role-id", "permission"];
+
+    type Filter = RolePermissionFilter;
+    type CursorKey = (i32, String);
+
+    async fn get_page(
+        pages: &Pages<RolePermission, RolePermission>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<RolePermission>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    role_id,
+                    permission,
+                    COUNT(*) OVER() AS "total!"
+                FROM roles_permissions
+                WHERE
+                    ($3::INTEGER IS NULL OR role_id = $3)
+                    AND ($4::INTEGER IS NULL OR role_id <> $4)
+                    AND ($5::TEXT IS NULL OR permission = $5)
+                    AND ($6::TEXT IS NULL OR permission <> $6)
+                ORDER BY
+                    CASE WHEN $7::TEXT = 'role-id' AND NOT $8 THEN role_id END,
+                    CASE WHEN $7 = 'role-id' AND $8 THEN role_id END DESC,
+                    CASE WHEN $7 = 'permission' AND NOT $8 THEN permission END,
+                    CASE WHEN $7 = 'permission' AND $8 THEN permission END DESC,
+                    role_id,
+                    permission
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.role_id.eq,
+            pages.filter.conditions.role_id.ne,
+            pages.filter.conditions.permission.eq,
+            pages.filter.conditions.permission.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| RolePermission {
+                role_id: row.role_id,
+                permission: row.permission,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &RolePermission) -> (i32, String) {
+        (item.role_id, item.permission.clone())
+    }
+
+    async fn get_items_after(
+        pages: &Pages<RolePermission, RolePermission>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, String)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<RolePermission>, sqlx::Error> {
+        sqlx::query_as!(
+            RolePermission,
+            r#"
+                SELECT
+                    role_id,
+                    permission
+                FROM roles_permissions
+                WHERE
+                    ($3::INTEGER IS NULL OR role_id = $3)
+                    AND ($4::INTEGER IS NULL OR role_id <> $4)
+                    AND ($5::TEXT IS NULL OR permission = $5)
+                    AND ($6::TEXT IS NULL OR permission <> $6)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $7 THEN (role_id, permission)
+                                < ($2, $8)
+                            ELSE (role_id, permission)
+                                > ($2, $8)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $7 THEN role_id END DESC,
+                    CASE WHEN $7 THEN permission END DESC,
+                    role_id,
+                    permission
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.role_id.eq,
+            pages.filter.conditions.role_id.ne,
+            pages.filter.conditions.permission.eq,
+            pages.filter.conditions.permission.ne,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1.as_str())
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InsertRolePermission {
+    pub role_id: i32,
+    pub permission: String,
+}
+
+impl InsertRolePermission {
+    pub async fn insert(
+        self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<RolePermission, sqlx::Error> {
+        sqlx::query_as!(
+            RolePermission,
+            r#"
+            INSERT INTO roles_permissions (
+                role_id,
+                permission
+            )
+            VALUES (
+                $1,
+                $2
+            )
+            RETURNING
+                role_id,
+                permission
+            "#,
+            self.role_id,
+            self.permission
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
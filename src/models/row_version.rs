@@ -0,0 +1,37 @@
+use sqlx::{Executor, Postgres};
+
+pub struct RowVersion;
+
+impl RowVersion {
+    pub async fn select(
+        resource: String,
+        resource_key: Vec<String>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT select_row_version($1, $2, false) AS "row_version"
+            "#,
+            resource,
+            &resource_key
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_for_update(
+        resource: String,
+        resource_key: Vec<String>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT select_row_version($1, $2, true) AS "row_version"
+            "#,
+            resource,
+            &resource_key
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
@@ -1,8 +1,16 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::domains::NationalId,
+    models::suggestion::Suggestion,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -38,8 +46,16 @@ impl Service {
     }
 
     pub async fn select_all(
+        filter: &ListFilter<ServiceFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Service>, sqlx::Error> {
+        Service::stream_all(filter, connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<ServiceFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Service, sqlx::Error>> {
         sqlx::query_as!(
             Service,
             r#"
@@ -50,25 +66,74 @@ impl Service {
                 coordinator_national_id
             FROM 
                 services
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+                AND ($3::TEXT IS NULL OR coordinator_national_id = $3)
+                AND ($4::TEXT IS NULL OR coordinator_national_id <> $4)
+            ORDER BY
+                CASE WHEN $5::TEXT = 'id' AND NOT $6 THEN id END,
+                CASE WHEN $5 = 'id' AND $6 THEN id END DESC,
+                CASE WHEN $5 = 'name' AND NOT $6 THEN name END,
+                CASE WHEN $5 = 'name' AND $6 THEN name END DESC,
+                CASE WHEN $5 = 'coordinator-national-id' AND NOT $6 THEN coordinator_national_id END,
+                CASE WHEN $5 = 'coordinator-national-id' AND $6 THEN coordinator_national_id END DESC,
+                id
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.coordinator_national_id.eq as _,
+            filter.conditions.coordinator_national_id.ne as _,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        filter: &ListFilter<ServiceFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_services!"
             FROM services
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+                AND ($3::TEXT IS NULL OR coordinator_national_id = $3)
+                AND ($4::TEXT IS NULL OR coordinator_national_id <> $4)
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.coordinator_national_id.eq as _,
+            filter.conditions.coordinator_national_id.ne as _
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<i32>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT id, name AS label
+            FROM services
+            WHERE lower(name) LIKE to_prefix_pattern(lower($1))
+            ORDER BY name, id
+            LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         id: i32,
         connection: impl Executor<'_, Database = Postgres>,
@@ -91,38 +156,146 @@ impl Service {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct ServiceFilter {
+    pub name: Comparison<String>,
+    pub coordinator_national_id: Comparison<NationalId>,
+}
+
+impl Filter for ServiceFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("name", EQUALITY),
+        FilterField::new("coordinator-national-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name", "coordinator-national-id"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "name" => self.name.apply(operator, value),
+            "coordinator-national-id" => self.coordinator_national_id.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<Service> for Service {
+    const FIELDS: &'static [&'static str] =
+        &["id", "name", "description", "coordinator-national-id"];
+
+    type Filter = ServiceFilter;
+    type CursorKey = (i32,);
+
     async fn get_page(
         pages: &Pages<Service, Service>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Service>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Service,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT 
                     id,
                     name,
                     description,
-                    coordinator_national_id
+                    coordinator_national_id,
+                    COUNT(*) OVER() AS "total!"
                 FROM 
                     services
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                    AND ($5::TEXT IS NULL OR coordinator_national_id = $5)
+                    AND ($6::TEXT IS NULL OR coordinator_national_id <> $6)
+                ORDER BY
+                    CASE WHEN $7::TEXT = 'id' AND NOT $8 THEN id END,
+                    CASE WHEN $7 = 'id' AND $8 THEN id END DESC,
+                    CASE WHEN $7 = 'name' AND NOT $8 THEN name END,
+                    CASE WHEN $7 = 'name' AND $8 THEN name END DESC,
+                    CASE WHEN $7 = 'coordinator-national-id' AND NOT $8 THEN coordinator_national_id END,
+                    CASE WHEN $7 = 'coordinator-national-id' AND $8 THEN coordinator_national_id END DESC,
+                    id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.coordinator_national_id.eq as _,
+            pages.filter.conditions.coordinator_national_id.ne as _,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Service {
+                id: row.id,
+                name: row.name,
+                description: row.description,
+                coordinator_national_id: row.coordinator_national_id,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Service) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Service, Service>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Service>, sqlx::Error> {
+        sqlx::query_as!(
+            Service,
+            r#"
+                SELECT 
+                    id,
+                    name,
+                    description,
+                    coordinator_national_id
+                FROM 
+                    services
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                    AND ($5::TEXT IS NULL OR coordinator_national_id = $5)
+                    AND ($6::TEXT IS NULL OR coordinator_national_id <> $6)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $7 THEN id < $2
+                            ELSE id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $7 THEN id END DESC,
+                    id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.coordinator_national_id.eq as _,
+            pages.filter.conditions.coordinator_national_id.ne as _,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,324 @@
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+
+use crate::{
+    models::domains::NationalId,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaffAccount {
+    pub national_id: String,
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+}
+
+impl StaffAccount {
+    pub async fn select(
+        national_id: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StaffAccount, sqlx::Error> {
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+            SELECT
+                national_id,
+                password_hash
+            FROM staff_accounts
+            WHERE national_id = $1
+            "#,
+            national_id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_all(
+        filter: &ListFilter<StaffAccountFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<StaffAccount>, sqlx::Error> {
+        StaffAccount::stream_all(filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<StaffAccountFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<StaffAccount, sqlx::Error>> {
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+            SELECT
+                national_id,
+                password_hash
+            FROM staff_accounts
+            WHERE
+                ($1::TEXT IS NULL OR national_id = $1)
+                AND ($2::TEXT IS NULL OR national_id <> $2)
+            ORDER BY
+                CASE WHEN $3::TEXT = 'national-id' AND NOT $4 THEN national_id END,
+                CASE WHEN $3 = 'national-id' AND $4 THEN national_id END DESC,
+                national_id
+            "#,
+            filter.conditions.national_id.eq as _,
+            filter.conditions.national_id.ne as _,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        filter: &ListFilter<StaffAccountFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_staff_accounts!"
+            FROM staff_accounts
+            WHERE
+                ($1::TEXT IS NULL OR national_id = $1)
+                AND ($2::TEXT IS NULL OR national_id <> $2)
+            "#,
+            filter.conditions.national_id.eq as _,
+            filter.conditions.national_id.ne as _
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn delete(
+        national_id: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StaffAccount, sqlx::Error> {
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+            DELETE FROM staff_accounts
+            WHERE national_id = $1
+            RETURNING
+                national_id,
+                password_hash
+            "#,
+            national_id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct StaffAccountFilter {
+    pub national_id: Comparison<NationalId>,
+}
+
+impl Filter for StaffAccountFilter {
+    const FIELDS: &'static [FilterField] = &[FilterField::new("national-id", EQUALITY)];
+    const SORT_FIELDS: &'static [&'static str] = &["national-id"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "national-id" => self.national_id.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<StaffAccount> for StaffAccount {
+    const FIELDS: &'static [&'static str] = &["national-id"];
+
+    type Filter = StaffAccountFilter;
+    type CursorKey = (String,);
+
+    async fn get_page(
+        pages: &Pages<StaffAccount, StaffAccount>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<StaffAccount>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    national_id,
+                    password_hash,
+                    COUNT(*) OVER() AS "total!"
+                FROM staff_accounts
+                WHERE
+                    ($3::TEXT IS NULL OR national_id = $3)
+                    AND ($4::TEXT IS NULL OR national_id <> $4)
+                ORDER BY
+                    CASE WHEN $5::TEXT = 'national-id' AND NOT $6 THEN national_id END,
+                    CASE WHEN $5 = 'national-id' AND $6 THEN national_id END DESC,
+                    national_id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.national_id.eq as _,
+            pages.filter.conditions.national_id.ne as _,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| StaffAccount {
+                national_id: row.national_id,
+                password_hash: row.password_hash,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &StaffAccount) -> (String,) {
+        (item.national_id.clone(),)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<StaffAccount, StaffAccount>,
+        limit: i64,
+        cursor: Option<&Cursor<(String,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<StaffAccount>, sqlx::Error> {
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+                SELECT
+                    national_id,
+                    password_hash
+                FROM staff_accounts
+                WHERE
+                    ($3::TEXT IS NULL OR national_id = $3)
+                    AND ($4::TEXT IS NULL OR national_id <> $4)
+                    AND (
+                        $2::TEXT IS NULL
+                        OR CASE
+                            WHEN $5 THEN national_id < $2
+                            ELSE national_id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $5 THEN national_id END DESC,
+                    national_id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0.as_str()),
+            pages.filter.conditions.national_id.eq as _,
+            pages.filter.conditions.national_id.ne as _,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InsertStaffAccount {
+    pub national_id: String,
+    pub password_hash: String,
+}
+
+impl InsertStaffAccount {
+    pub async fn insert(
+        self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StaffAccount, sqlx::Error> {
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+            INSERT INTO staff_accounts (
+                national_id,
+                password_hash
+            )
+            VALUES (
+                $1,
+                $2
+            )
+            RETURNING
+                national_id,
+                password_hash
+            "#,
+            self.national_id as _,
+            self.password_hash
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn upsert(
+        self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StaffAccount, sqlx::Error> {
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+            INSERT INTO staff_accounts (
+                national_id,
+                password_hash
+            )
+            VALUES (
+                $1,
+                $2
+            )
+            ON CONFLICT (national_id) DO UPDATE
+            SET password_hash = EXCLUDED.password_hash
+            RETURNING
+                national_id,
+                password_hash
+            "#,
+            self.national_id as _,
+            self.password_hash
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateStaffAccount {
+    pub password_hash: Option<String>,
+}
+
+impl UpdateStaffAccount {
+    pub async fn update(
+        self,
+        target: StaffAccount,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StaffAccount, sqlx::Error> {
+        let new_password_hash = self.password_hash.unwrap_or(target.password_hash);
+
+        sqlx::query_as!(
+            StaffAccount,
+            r#"
+            UPDATE staff_accounts
+            SET password_hash = $1
+            WHERE national_id = $2
+            RETURNING
+                national_id,
+                password_hash
+            "#,
+            new_password_hash,
+            target.national_id
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
@@ -1,8 +1,15 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::suggestion::Suggestion,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -32,7 +39,8 @@ impl State {
         .await
     }
 
-    pub async fn select_all(
+    pub async fn select_all_by_ids(
+        ids: Vec<i32>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<State>, sqlx::Error> {
         sqlx::query_as!(
@@ -42,25 +50,90 @@ impl State {
                 id,
                 name
             FROM states
-            "#
+            WHERE id = ANY($1)
+            "#,
+            &ids
         )
         .fetch_all(connection)
         .await
     }
 
+    pub async fn select_all(
+        filter: &ListFilter<StateFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<State>, sqlx::Error> {
+        State::stream_all(filter, connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<StateFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<State, sqlx::Error>> {
+        sqlx::query_as!(
+            State,
+            r#"
+            SELECT
+                id,
+                name
+            FROM states
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+            ORDER BY
+                CASE WHEN $3::TEXT = 'id' AND NOT $4 THEN id END,
+                CASE WHEN $3 = 'id' AND $4 THEN id END DESC,
+                CASE WHEN $3 = 'name' AND NOT $4 THEN name END,
+                CASE WHEN $3 = 'name' AND $4 THEN name END DESC,
+                id
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
     pub async fn count(
+        filter: &ListFilter<StateFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_states!"
             FROM states
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<i32>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT id, name AS label
+            FROM states
+            WHERE lower(name) LIKE to_prefix_pattern(lower($1))
+            ORDER BY name, id
+            LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         id: i32,
         connection: impl Executor<'_, Database = Postgres>,
@@ -82,35 +155,122 @@ impl State {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct StateFilter {
+    pub name: Comparison<String>,
+}
+
+impl Filter for StateFilter {
+    const FIELDS: &'static [FilterField] = &[FilterField::new("name", EQUALITY)];
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "name" => self.name.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<State> for State {
+    const FIELDS: &'static [&'static str] = &["id", "name"];
+
+    type Filter = StateFilter;
+    type CursorKey = (i32,);
+
     async fn get_page(
         pages: &Pages<State, State>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<State>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            State,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     id,
-                    name
+                    name,
+                    COUNT(*) OVER() AS "total!"
                 FROM states
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                ORDER BY
+                    CASE WHEN $5::TEXT = 'id' AND NOT $6 THEN id END,
+                    CASE WHEN $5 = 'id' AND $6 THEN id END DESC,
+                    CASE WHEN $5 = 'name' AND NOT $6 THEN name END,
+                    CASE WHEN $5 = 'name' AND $6 THEN name END DESC,
+                    id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| State {
+                id: row.id,
+                name: row.name,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &State) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<State, State>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<State>, sqlx::Error> {
+        sqlx::query_as!(
+            State,
+            r#"
+                SELECT
+                    id,
+                    name
+                FROM states
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $5 THEN id < $2
+                            ELSE id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $5 THEN id END DESC,
+                    id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
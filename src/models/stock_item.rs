@@ -1,9 +1,18 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, Postgres};
+use sqlx::{Executor, Pool, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::domains::Rif,
+    models::product::Product,
+    utils::{
+        expansion::{Expandable, Expanded, Expansion},
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,7 +21,7 @@ pub struct StockItem {
     pub dealership_rif: String,
     pub product_cost: BigDecimal,
     pub product_count: i32,
-    pub vendor_name: String,
+    pub vendor_id: i32,
     pub max_capacity: i32,
     pub min_capacity: i32,
 }
@@ -31,7 +40,7 @@ impl StockItem {
                 dealership_rif,
                 product_cost,
                 product_count,
-                vendor_name,
+                vendor_id,
                 max_capacity,
                 min_capacity
             FROM stock
@@ -46,9 +55,50 @@ impl StockItem {
         .await
     }
 
+    pub async fn select_for_update(
+        product_id: i32,
+        dealership_rif: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StockItem, sqlx::Error> {
+        sqlx::query_as!(
+            StockItem,
+            r#"
+            SELECT
+                product_id,
+                dealership_rif,
+                product_cost,
+                product_count,
+                vendor_id,
+                max_capacity,
+                min_capacity
+            FROM stock
+            WHERE
+                product_id = $1
+                AND dealership_rif = $2
+            FOR UPDATE
+            "#,
+            product_id,
+            dealership_rif
+        )
+        .fetch_one(connection)
+        .await
+    }
+
     pub async fn select_all(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<StockItemFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<StockItem>, sqlx::Error> {
+        StockItem::stream_all(dealership_rif, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<StockItemFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<StockItem, sqlx::Error>> {
         sqlx::query_as!(
             StockItem,
             r#"
@@ -57,24 +107,155 @@ impl StockItem {
                 dealership_rif,
                 product_cost,
                 product_count,
-                vendor_name,
+                vendor_id,
                 max_capacity,
                 min_capacity
             FROM stock
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR dealership_rif = $1
+                )
+                AND ($2::INTEGER IS NULL OR product_id = $2)
+                AND ($3::INTEGER IS NULL OR product_id <> $3)
+                AND ($4::TEXT IS NULL OR dealership_rif = $4)
+                AND ($5::TEXT IS NULL OR dealership_rif <> $5)
+                AND ($6::NUMERIC IS NULL OR product_cost = $6)
+                AND ($7::NUMERIC IS NULL OR product_cost <> $7)
+                AND ($8::NUMERIC IS NULL OR product_cost < $8)
+                AND ($9::NUMERIC IS NULL OR product_cost <= $9)
+                AND ($10::NUMERIC IS NULL OR product_cost > $10)
+                AND ($11::NUMERIC IS NULL OR product_cost >= $11)
+                AND ($12::INTEGER IS NULL OR product_count = $12)
+                AND ($13::INTEGER IS NULL OR product_count <> $13)
+                AND ($14::INTEGER IS NULL OR product_count < $14)
+                AND ($15::INTEGER IS NULL OR product_count <= $15)
+                AND ($16::INTEGER IS NULL OR product_count > $16)
+                AND ($17::INTEGER IS NULL OR product_count >= $17)
+                AND ($18::INTEGER IS NULL OR vendor_id = $18)
+                AND ($19::INTEGER IS NULL OR vendor_id <> $19)
+            ORDER BY
+                CASE WHEN $20::TEXT = 'product-id' AND NOT $21 THEN product_id END,
+                CASE WHEN $20 = 'product-id' AND $21 THEN product_id END DESC,
+                CASE WHEN $20 = 'dealership-rif' AND NOT $21 THEN dealership_rif END,
+                CASE WHEN $20 = 'dealership-rif' AND $21 THEN dealership_rif END DESC,
+                CASE WHEN $20 = 'product-cost' AND NOT $21 THEN product_cost END,
+                CASE WHEN $20 = 'product-cost' AND $21 THEN product_cost END DESC,
+                CASE WHEN $20 = 'product-count' AND NOT $21 THEN product_count END,
+                CASE WHEN $20 = 'product-count' AND $21 THEN product_count END DESC,
+                CASE WHEN $20 = 'vendor-id' AND NOT $21 THEN vendor_id END,
+                CASE WHEN $20 = 'vendor-id' AND $21 THEN vendor_id END DESC,
+                product_id,
+                dealership_rif
+            "#,
+            dealership_rif,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.dealership_rif.eq as _,
+            filter.conditions.dealership_rif.ne as _,
+            filter.conditions.product_cost.eq,
+            filter.conditions.product_cost.ne,
+            filter.conditions.product_cost.lt,
+            filter.conditions.product_cost.lte,
+            filter.conditions.product_cost.gt,
+            filter.conditions.product_cost.gte,
+            filter.conditions.product_count.eq,
+            filter.conditions.product_count.ne,
+            filter.conditions.product_count.lt,
+            filter.conditions.product_count.lte,
+            filter.conditions.product_count.gt,
+            filter.conditions.product_count.gte,
+            filter.conditions.vendor_id.eq,
+            filter.conditions.vendor_id.ne,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<StockItemFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_stock!"
             FROM stock
-            "#
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR dealership_rif = $1
+                )
+                AND ($2::INTEGER IS NULL OR product_id = $2)
+                AND ($3::INTEGER IS NULL OR product_id <> $3)
+                AND ($4::TEXT IS NULL OR dealership_rif = $4)
+                AND ($5::TEXT IS NULL OR dealership_rif <> $5)
+                AND ($6::NUMERIC IS NULL OR product_cost = $6)
+                AND ($7::NUMERIC IS NULL OR product_cost <> $7)
+                AND ($8::NUMERIC IS NULL OR product_cost < $8)
+                AND ($9::NUMERIC IS NULL OR product_cost <= $9)
+                AND ($10::NUMERIC IS NULL OR product_cost > $10)
+                AND ($11::NUMERIC IS NULL OR product_cost >= $11)
+                AND ($12::INTEGER IS NULL OR product_count = $12)
+                AND ($13::INTEGER IS NULL OR product_count <> $13)
+                AND ($14::INTEGER IS NULL OR product_count < $14)
+                AND ($15::INTEGER IS NULL OR product_count <= $15)
+                AND ($16::INTEGER IS NULL OR product_count > $16)
+                AND ($17::INTEGER IS NULL OR product_count >= $17)
+                AND ($18::INTEGER IS NULL OR vendor_id = $18)
+                AND ($19::INTEGER IS NULL OR vendor_id <> $19)
+            "#,
+            dealership_rif,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.dealership_rif.eq as _,
+            filter.conditions.dealership_rif.ne as _,
+            filter.conditions.product_cost.eq,
+            filter.conditions.product_cost.ne,
+            filter.conditions.product_cost.lt,
+            filter.conditions.product_cost.lte,
+            filter.conditions.product_cost.gt,
+            filter.conditions.product_cost.gte,
+            filter.conditions.product_count.eq,
+            filter.conditions.product_count.ne,
+            filter.conditions.product_count.lt,
+            filter.conditions.product_count.lte,
+            filter.conditions.product_count.gt,
+            filter.conditions.product_count.gte,
+            filter.conditions.vendor_id.eq,
+            filter.conditions.vendor_id.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn adjust_product_count(
+        product_id: i32,
+        dealership_rif: String,
+        product_count_delta: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StockItem, sqlx::Error> {
+        sqlx::query_as!(
+            StockItem,
+            r#"
+            UPDATE stock
+            SET product_count = product_count + $3
+            WHERE
+                product_id = $1
+                AND dealership_rif = $2
+            RETURNING
+                product_id,
+                dealership_rif,
+                product_cost,
+                product_count,
+                vendor_id,
+                max_capacity,
+                min_capacity
+            "#,
+            product_id,
+            dealership_rif as _,
+            product_count_delta
         )
         .fetch_one(connection)
         .await
@@ -97,7 +278,7 @@ impl StockItem {
                 dealership_rif,
                 product_cost,
                 product_count,
-                vendor_name,
+                vendor_id,
                 max_capacity,
                 min_capacity
             "#,
@@ -109,40 +290,281 @@ impl StockItem {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct StockItemFilter {
+    pub product_id: Comparison<i32>,
+    pub dealership_rif: Comparison<Rif>,
+    pub product_cost: Comparison<BigDecimal>,
+    pub product_count: Comparison<i32>,
+    pub vendor_id: Comparison<i32>,
+}
+
+impl Filter for StockItemFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("product-id", EQUALITY),
+        FilterField::new("dealership-rif", EQUALITY),
+        FilterField::new("product-cost", ORDERING),
+        FilterField::new("product-count", ORDERING),
+        FilterField::new("vendor-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "product-id",
+        "dealership-rif",
+        "product-cost",
+        "product-count",
+        "vendor-id",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "product-id" => self.product_id.apply(operator, value),
+            "dealership-rif" => self.dealership_rif.apply(operator, value),
+            "product-cost" => self.product_cost.apply(operator, value),
+            "product-count" => self.product_count.apply(operator, value),
+            "vendor-id" => self.vendor_id.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<StockItem> for StockItem {
+    const FIELDS: &'static [&'static str] = &[
+        "product-id",
+        "dealership-rif",
+        "product-cost",
+        "product-count",
+        "vendor-id",
+        "max-capacity",
+        "min-capacity",
+    ];
+
+    type Filter = StockItemFilter;
+    type CursorKey = (i32, String);
+
     async fn get_page(
         pages: &Pages<StockItem, StockItem>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<StockItem>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            StockItem,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     product_id,
                     dealership_rif,
                     product_cost,
                     product_count,
-                    vendor_name,
+                    vendor_id,
                     max_capacity,
-                    min_capacity
+                    min_capacity,
+                    COUNT(*) OVER() AS "total!"
                 FROM stock
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR dealership_rif = $3
+                    )
+                    AND ($4::INTEGER IS NULL OR product_id = $4)
+                    AND ($5::INTEGER IS NULL OR product_id <> $5)
+                    AND ($6::TEXT IS NULL OR dealership_rif = $6)
+                    AND ($7::TEXT IS NULL OR dealership_rif <> $7)
+                    AND ($8::NUMERIC IS NULL OR product_cost = $8)
+                    AND ($9::NUMERIC IS NULL OR product_cost <> $9)
+                    AND ($10::NUMERIC IS NULL OR product_cost < $10)
+                    AND ($11::NUMERIC IS NULL OR product_cost <= $11)
+                    AND ($12::NUMERIC IS NULL OR product_cost > $12)
+                    AND ($13::NUMERIC IS NULL OR product_cost >= $13)
+                    AND ($14::INTEGER IS NULL OR product_count = $14)
+                    AND ($15::INTEGER IS NULL OR product_count <> $15)
+                    AND ($16::INTEGER IS NULL OR product_count < $16)
+                    AND ($17::INTEGER IS NULL OR product_count <= $17)
+                    AND ($18::INTEGER IS NULL OR product_count > $18)
+                    AND ($19::INTEGER IS NULL OR product_count >= $19)
+                    AND ($20::INTEGER IS NULL OR vendor_id = $20)
+                    AND ($21::INTEGER IS NULL OR vendor_id <> $21)
+                ORDER BY
+                    CASE WHEN $22::TEXT = 'product-id' AND NOT $23 THEN product_id END,
+                    CASE WHEN $22 = 'product-id' AND $23 THEN product_id END DESC,
+                    CASE WHEN $22 = 'dealership-rif' AND NOT $23 THEN dealership_rif END,
+                    CASE WHEN $22 = 'dealership-rif' AND $23 THEN dealership_rif END DESC,
+                    CASE WHEN $22 = 'product-cost' AND NOT $23 THEN product_cost END,
+                    CASE WHEN $22 = 'product-cost' AND $23 THEN product_cost END DESC,
+                    CASE WHEN $22 = 'product-count' AND NOT $23 THEN product_count END,
+                    CASE WHEN $22 = 'product-count' AND $23 THEN product_count END DESC,
+                    CASE WHEN $22 = 'vendor-id' AND NOT $23 THEN vendor_id END,
+                    CASE WHEN $22 = 'vendor-id' AND $23 THEN vendor_id END DESC,
+                    product_id,
+                    dealership_rif
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.dealership_rif,
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.dealership_rif.eq as _,
+            pages.filter.conditions.dealership_rif.ne as _,
+            pages.filter.conditions.product_cost.eq,
+            pages.filter.conditions.product_cost.ne,
+            pages.filter.conditions.product_cost.lt,
+            pages.filter.conditions.product_cost.lte,
+            pages.filter.conditions.product_cost.gt,
+            pages.filter.conditions.product_cost.gte,
+            pages.filter.conditions.product_count.eq,
+            pages.filter.conditions.product_count.ne,
+            pages.filter.conditions.product_count.lt,
+            pages.filter.conditions.product_count.lte,
+            pages.filter.conditions.product_count.gt,
+            pages.filter.conditions.product_count.gte,
+            pages.filter.conditions.vendor_id.eq,
+            pages.filter.conditions.vendor_id.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| StockItem {
+                product_id: row.product_id,
+                dealership_rif: row.dealership_rif,
+                product_cost: row.product_cost,
+                product_count: row.product_count,
+                vendor_id: row.vendor_id,
+                max_capacity: row.max_capacity,
+                min_capacity: row.min_capacity,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &StockItem) -> (i32, String) {
+        (item.product_id, item.dealership_rif.clone())
+    }
+
+    async fn get_items_after(
+        pages: &Pages<StockItem, StockItem>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32, String)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<StockItem>, sqlx::Error> {
+        sqlx::query_as!(
+            StockItem,
+            r#"
+                SELECT
+                    product_id,
+                    dealership_rif,
+                    product_cost,
+                    product_count,
+                    vendor_id,
+                    max_capacity,
+                    min_capacity
+                FROM stock
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR dealership_rif = $3
+                    )
+                    AND ($4::INTEGER IS NULL OR product_id = $4)
+                    AND ($5::INTEGER IS NULL OR product_id <> $5)
+                    AND ($6::TEXT IS NULL OR dealership_rif = $6)
+                    AND ($7::TEXT IS NULL OR dealership_rif <> $7)
+                    AND ($8::NUMERIC IS NULL OR product_cost = $8)
+                    AND ($9::NUMERIC IS NULL OR product_cost <> $9)
+                    AND ($10::NUMERIC IS NULL OR product_cost < $10)
+                    AND ($11::NUMERIC IS NULL OR product_cost <= $11)
+                    AND ($12::NUMERIC IS NULL OR product_cost > $12)
+                    AND ($13::NUMERIC IS NULL OR product_cost >= $13)
+                    AND ($14::INTEGER IS NULL OR product_count = $14)
+                    AND ($15::INTEGER IS NULL OR product_count <> $15)
+                    AND ($16::INTEGER IS NULL OR product_count < $16)
+                    AND ($17::INTEGER IS NULL OR product_count <= $17)
+                    AND ($18::INTEGER IS NULL OR product_count > $18)
+                    AND ($19::INTEGER IS NULL OR product_count >= $19)
+                    AND ($20::INTEGER IS NULL OR vendor_id = $20)
+                    AND ($21::INTEGER IS NULL OR vendor_id <> $21)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $22 THEN (product_id, dealership_rif)
+                                < ($2, $23)
+                            ELSE (product_id, dealership_rif)
+                                > ($2, $23)
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $22 THEN product_id END DESC,
+                    CASE WHEN $22 THEN dealership_rif END DESC,
+                    product_id,
+                    dealership_rif
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.dealership_rif,
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.dealership_rif.eq as _,
+            pages.filter.conditions.dealership_rif.ne as _,
+            pages.filter.conditions.product_cost.eq,
+            pages.filter.conditions.product_cost.ne,
+            pages.filter.conditions.product_cost.lt,
+            pages.filter.conditions.product_cost.lte,
+            pages.filter.conditions.product_cost.gt,
+            pages.filter.conditions.product_cost.gte,
+            pages.filter.conditions.product_count.eq,
+            pages.filter.conditions.product_count.ne,
+            pages.filter.conditions.product_count.lt,
+            pages.filter.conditions.product_count.lte,
+            pages.filter.conditions.product_count.gt,
+            pages.filter.conditions.product_count.gte,
+            pages.filter.conditions.vendor_id.eq,
+            pages.filter.conditions.vendor_id.ne,
+            cursor.is_some_and(|cursor| cursor.backward),
+            cursor.map(|cursor| cursor.key.1.as_str())
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[async_trait]
+impl Expandable for StockItem {
+    const RELATIONS: &'static [&'static str] = &["product"];
+
+    async fn expand(
+        stock_items: Vec<StockItem>,
+        expansion: &Expansion,
+        connection: &Pool<Postgres>,
+    ) -> Result<Vec<Expanded<StockItem>>, sqlx::Error> {
+        let mut expanded_stock_items = Expanded::wrap_all(stock_items);
+
+        if expansion.includes("product") {
+            let product_ids = expanded_stock_items
+                .iter()
+                .map(|stock_item| stock_item.item.product_id)
+                .collect();
+            let products = Product::select_all_by_ids(product_ids, connection).await?;
+            Expanded::embed_one(
+                &mut expanded_stock_items,
+                "product",
+                products,
+                |stock_item| stock_item.product_id,
+                |product| product.id,
+            );
+        }
+
+        Ok(expanded_stock_items)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -151,7 +573,7 @@ pub struct InsertStockItem {
     pub dealership_rif: String,
     pub product_cost: BigDecimal,
     pub product_count: i32,
-    pub vendor_name: String,
+    pub vendor_id: i32,
     pub max_capacity: i32,
     pub min_capacity: i32
 }
@@ -169,7 +591,7 @@ impl InsertStockItem {
                 dealership_rif,
                 product_cost,
                 product_count,
-                vendor_name,
+                vendor_id,
                 max_capacity,
                 min_capacity
             )
@@ -187,7 +609,7 @@ impl InsertStockItem {
                 dealership_rif,
                 product_cost,
                 product_count,
-                vendor_name,
+                vendor_id,
                 max_capacity,
                 min_capacity
             "#,
@@ -195,7 +617,7 @@ impl InsertStockItem {
             self.dealership_rif as _,
             self.product_cost,
             self.product_count,
-            self.vendor_name,
+            self.vendor_id,
             self.max_capacity,
             self.min_capacity,
         )
@@ -210,7 +632,7 @@ pub struct UpdateStockItem {
     pub dealership_rif: Option<String>,
     pub product_cost: Option<BigDecimal>,
     pub product_count: Option<i32>,
-    pub vendor_name: Option<String>,
+    pub vendor_id: Option<i32>,
     pub max_capacity: Option<i32>,
     pub min_capacity: Option<i32>
 }
@@ -225,7 +647,7 @@ impl UpdateStockItem {
         let new_dealership_rif = self.dealership_rif.as_ref().unwrap_or(&target.dealership_rif);
         let new_product_cost = self.product_cost.unwrap_or(target.product_cost);
         let new_product_count = self.product_count.unwrap_or(target.product_count);
-        let new_vendor_name = self.vendor_name.unwrap_or(target.vendor_name);
+        let new_vendor_id = self.vendor_id.unwrap_or(target.vendor_id);
         let new_max_capacity = self.max_capacity.unwrap_or(target.max_capacity);
         let new_min_capacity = self.min_capacity.unwrap_or(target.min_capacity);
 
@@ -238,7 +660,7 @@ impl UpdateStockItem {
                 dealership_rif = $2,
                 product_cost = $3,
                 product_count = $4,
-                vendor_name = $5,
+                vendor_id = $5,
                 max_capacity = $6,
                 min_capacity = $7
             WHERE
@@ -249,7 +671,7 @@ impl UpdateStockItem {
                 dealership_rif,
                 product_cost,
                 product_count,
-                vendor_name,
+                vendor_id,
                 max_capacity,
                 min_capacity
             "#,
@@ -257,7 +679,7 @@ impl UpdateStockItem {
             new_dealership_rif as _,
             new_product_cost,
             new_product_count,
-            new_vendor_name,
+            new_vendor_id,
             new_max_capacity,
             new_min_capacity,
             target.product_id,
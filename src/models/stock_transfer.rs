@@ -0,0 +1,550 @@
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+use time::PrimitiveDateTime;
+
+use crate::{
+    models::domains::Rif,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StockTransfer {
+    pub id: i32,
+    pub product_id: i32,
+    pub source_dealership_rif: String,
+    pub destination_dealership_rif: String,
+    pub product_count: i32,
+    pub status: String,
+    pub dispatch_timestamp: PrimitiveDateTime,
+    pub resolution_timestamp: Option<PrimitiveDateTime>,
+}
+
+impl StockTransfer {
+    pub async fn select(
+        id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        sqlx::query_as!(
+            StockTransfer,
+            r#"
+            SELECT
+                id,
+                product_id,
+                source_dealership_rif,
+                destination_dealership_rif,
+                product_count,
+                status,
+                dispatch_timestamp,
+                resolution_timestamp
+            FROM stock_transfers
+            WHERE id = $1
+            "#,
+            id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_for_update(
+        id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        sqlx::query_as!(
+            StockTransfer,
+            r#"
+            SELECT
+                id,
+                product_id,
+                source_dealership_rif,
+                destination_dealership_rif,
+                product_count,
+                status,
+                dispatch_timestamp,
+                resolution_timestamp
+            FROM stock_transfers
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_all(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<StockTransferFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<StockTransfer>, sqlx::Error> {
+        StockTransfer::stream_all(dealership_rif, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<StockTransferFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<StockTransfer, sqlx::Error>> {
+        sqlx::query_as!(
+            StockTransfer,
+            r#"
+            SELECT
+                id,
+                product_id,
+                source_dealership_rif,
+                destination_dealership_rif,
+                product_count,
+                status,
+                dispatch_timestamp,
+                resolution_timestamp
+            FROM stock_transfers
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR source_dealership_rif = $1
+                    OR destination_dealership_rif = $1
+                )
+                AND ($2::INTEGER IS NULL OR product_id = $2)
+                AND ($3::INTEGER IS NULL OR product_id <> $3)
+                AND ($4::TEXT IS NULL OR source_dealership_rif = $4)
+                AND ($5::TEXT IS NULL OR source_dealership_rif <> $5)
+                AND ($6::TEXT IS NULL OR destination_dealership_rif = $6)
+                AND ($7::TEXT IS NULL OR destination_dealership_rif <> $7)
+                AND ($8::INTEGER IS NULL OR product_count = $8)
+                AND ($9::INTEGER IS NULL OR product_count <> $9)
+                AND ($10::INTEGER IS NULL OR product_count < $10)
+                AND ($11::INTEGER IS NULL OR product_count <= $11)
+                AND ($12::INTEGER IS NULL OR product_count > $12)
+                AND ($13::INTEGER IS NULL OR product_count >= $13)
+                AND ($14::TEXT IS NULL OR status = $14)
+                AND ($15::TEXT IS NULL OR status <> $15)
+            ORDER BY
+                CASE WHEN $16::TEXT = 'id' AND NOT $17 THEN id END,
+                CASE WHEN $16 = 'id' AND $17 THEN id END DESC,
+                CASE WHEN $16 = 'product-id' AND NOT $17 THEN product_id END,
+                CASE WHEN $16 = 'product-id' AND $17 THEN product_id END DESC,
+                CASE WHEN $16 = 'product-count' AND NOT $17 THEN product_count END,
+                CASE WHEN $16 = 'product-count' AND $17 THEN product_count END DESC,
+                CASE WHEN $16 = 'status' AND NOT $17 THEN status END,
+                CASE WHEN $16 = 'status' AND $17 THEN status END DESC,
+                CASE WHEN $16 = 'dispatch-timestamp' AND NOT $17 THEN dispatch_timestamp END,
+                CASE WHEN $16 = 'dispatch-timestamp' AND $17 THEN dispatch_timestamp END DESC,
+                CASE WHEN $16 = 'resolution-timestamp' AND NOT $17 THEN resolution_timestamp END,
+                CASE WHEN $16 = 'resolution-timestamp' AND $17 THEN resolution_timestamp END DESC,
+                id
+            "#,
+            dealership_rif,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.source_dealership_rif.eq as _,
+            filter.conditions.source_dealership_rif.ne as _,
+            filter.conditions.destination_dealership_rif.eq as _,
+            filter.conditions.destination_dealership_rif.ne as _,
+            filter.conditions.product_count.eq,
+            filter.conditions.product_count.ne,
+            filter.conditions.product_count.lt,
+            filter.conditions.product_count.lte,
+            filter.conditions.product_count.gt,
+            filter.conditions.product_count.gte,
+            filter.conditions.status.eq,
+            filter.conditions.status.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        dealership_rif: Option<String>,
+        filter: &ListFilter<StockTransferFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_stock_transfers!"
+            FROM stock_transfers
+            WHERE
+                (
+                    $1::TEXT IS NULL
+                    OR source_dealership_rif = $1
+                    OR destination_dealership_rif = $1
+                )
+                AND ($2::INTEGER IS NULL OR product_id = $2)
+                AND ($3::INTEGER IS NULL OR product_id <> $3)
+                AND ($4::TEXT IS NULL OR source_dealership_rif = $4)
+                AND ($5::TEXT IS NULL OR source_dealership_rif <> $5)
+                AND ($6::TEXT IS NULL OR destination_dealership_rif = $6)
+                AND ($7::TEXT IS NULL OR destination_dealership_rif <> $7)
+                AND ($8::INTEGER IS NULL OR product_count = $8)
+                AND ($9::INTEGER IS NULL OR product_count <> $9)
+                AND ($10::INTEGER IS NULL OR product_count < $10)
+                AND ($11::INTEGER IS NULL OR product_count <= $11)
+                AND ($12::INTEGER IS NULL OR product_count > $12)
+                AND ($13::INTEGER IS NULL OR product_count >= $13)
+                AND ($14::TEXT IS NULL OR status = $14)
+                AND ($15::TEXT IS NULL OR status <> $15)
+            "#,
+            dealership_rif,
+            filter.conditions.product_id.eq,
+            filter.conditions.product_id.ne,
+            filter.conditions.source_dealership_rif.eq as _,
+            filter.conditions.source_dealership_rif.ne as _,
+            filter.conditions.destination_dealership_rif.eq as _,
+            filter.conditions.destination_dealership_rif.ne as _,
+            filter.conditions.product_count.eq,
+            filter.conditions.product_count.ne,
+            filter.conditions.product_count.lt,
+            filter.conditions.product_count.lte,
+            filter.conditions.product_count.gt,
+            filter.conditions.product_count.gte,
+            filter.conditions.status.eq,
+            filter.conditions.status.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn count_incoming_products(
+        product_id: i32,
+        destination_dealership_rif: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(product_count), 0) AS "incoming_products!"
+            FROM stock_transfers
+            WHERE
+                product_id = $1
+                AND destination_dealership_rif = $2
+                AND status = 'in-transit'
+            "#,
+            product_id,
+            destination_dealership_rif as _
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn resolve(
+        id: i32,
+        status: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        sqlx::query_as!(
+            StockTransfer,
+            r#"
+            UPDATE stock_transfers
+            SET
+                status = $1,
+                resolution_timestamp = LOCALTIMESTAMP
+            WHERE id = $2
+            RETURNING
+                id,
+                product_id,
+                source_dealership_rif,
+                destination_dealership_rif,
+                product_count,
+                status,
+                dispatch_timestamp,
+                resolution_timestamp
+            "#,
+            status,
+            id
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct StockTransferFilter {
+    pub product_id: Comparison<i32>,
+    pub source_dealership_rif: Comparison<Rif>,
+    pub destination_dealership_rif: Comparison<Rif>,
+    pub product_count: Comparison<i32>,
+    pub status: Comparison<String>,
+}
+
+impl Filter for StockTransferFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("product-id", EQUALITY),
+        FilterField::new("source-dealership-rif", EQUALITY),
+        FilterField::new("destination-dealership-rif", EQUALITY),
+        FilterField::new("product-count", ORDERING),
+        FilterField::new("status", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "id",
+        "product-id",
+        "product-count",
+        "status",
+        "dispatch-timestamp",
+        "resolution-timestamp",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "product-id" => self.product_id.apply(operator, value),
+            "source-dealership-rif" => self.source_dealership_rif.apply(operator, value),
+            "destination-dealership-rif" => self.destination_dealership_rif.apply(operator, value),
+            "product-count" => self.product_count.apply(operator, value),
+            "status" => self.status.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<StockTransfer> for StockTransfer {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "product-id",
+        "source-dealership-rif",
+        "destination-dealership-rif",
+        "product-count",
+        "status",
+        "dispatch-timestamp",
+        "resolution-timestamp",
+    ];
+
+    type Filter = StockTransferFilter;
+    type CursorKey = (i32,);
+
+    async fn get_page(
+        pages: &Pages<StockTransfer, StockTransfer>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<StockTransfer>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    id,
+                    product_id,
+                    source_dealership_rif,
+                    destination_dealership_rif,
+                    product_count,
+                    status,
+                    dispatch_timestamp,
+                    resolution_timestamp,
+                    COUNT(*) OVER() AS "total!"
+                FROM stock_transfers
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR source_dealership_rif = $3
+                        OR destination_dealership_rif = $3
+                    )
+                    AND ($4::INTEGER IS NULL OR product_id = $4)
+                    AND ($5::INTEGER IS NULL OR product_id <> $5)
+                    AND ($6::TEXT IS NULL OR source_dealership_rif = $6)
+                    AND ($7::TEXT IS NULL OR source_dealership_rif <> $7)
+                    AND ($8::TEXT IS NULL OR destination_dealership_rif = $8)
+                    AND ($9::TEXT IS NULL OR destination_dealership_rif <> $9)
+                    AND ($10::INTEGER IS NULL OR product_count = $10)
+                    AND ($11::INTEGER IS NULL OR product_count <> $11)
+                    AND ($12::INTEGER IS NULL OR product_count < $12)
+                    AND ($13::INTEGER IS NULL OR product_count <= $13)
+                    AND ($14::INTEGER IS NULL OR product_count > $14)
+                    AND ($15::INTEGER IS NULL OR product_count >= $15)
+                    AND ($16::TEXT IS NULL OR status = $16)
+                    AND ($17::TEXT IS NULL OR status <> $17)
+                ORDER BY
+                    CASE WHEN $18::TEXT = 'id' AND NOT $19 THEN id END,
+                    CASE WHEN $18 = 'id' AND $19 THEN id END DESC,
+                    CASE WHEN $18 = 'product-id' AND NOT $19 THEN product_id END,
+                    CASE WHEN $18 = 'product-id' AND $19 THEN product_id END DESC,
+                    CASE WHEN $18 = 'product-count' AND NOT $19 THEN product_count END,
+                    CASE WHEN $18 = 'product-count' AND $19 THEN product_count END DESC,
+                    CASE WHEN $18 = 'status' AND NOT $19 THEN status END,
+                    CASE WHEN $18 = 'status' AND $19 THEN status END DESC,
+                    CASE WHEN $18 = 'dispatch-timestamp' AND NOT $19 THEN dispatch_timestamp END,
+                    CASE WHEN $18 = 'dispatch-timestamp' AND $19 THEN dispatch_timestamp END DESC,
+                    CASE WHEN $18 = 'resolution-timestamp' AND NOT $19 THEN resolution_timestamp END,
+                    CASE WHEN $18 = 'resolution-timestamp' AND $19 THEN resolution_timestamp END DESC,
+                    id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.dealership_rif,
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.source_dealership_rif.eq as _,
+            pages.filter.conditions.source_dealership_rif.ne as _,
+            pages.filter.conditions.destination_dealership_rif.eq as _,
+            pages.filter.conditions.destination_dealership_rif.ne as _,
+            pages.filter.conditions.product_count.eq,
+            pages.filter.conditions.product_count.ne,
+            pages.filter.conditions.product_count.lt,
+            pages.filter.conditions.product_count.lte,
+            pages.filter.conditions.product_count.gt,
+            pages.filter.conditions.product_count.gte,
+            pages.filter.conditions.status.eq,
+            pages.filter.conditions.status.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| StockTransfer {
+                id: row.id,
+                product_id: row.product_id,
+                source_dealership_rif: row.source_dealership_rif,
+                destination_dealership_rif: row.destination_dealership_rif,
+                product_count: row.product_count,
+                status: row.status,
+                dispatch_timestamp: row.dispatch_timestamp,
+                resolution_timestamp: row.resolution_timestamp,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &StockTransfer) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<StockTransfer, StockTransfer>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<StockTransfer>, sqlx::Error> {
+        sqlx::query_as!(
+            StockTransfer,
+            r#"
+                SELECT
+                    id,
+                    product_id,
+                    source_dealership_rif,
+                    destination_dealership_rif,
+                    product_count,
+                    status,
+                    dispatch_timestamp,
+                    resolution_timestamp
+                FROM stock_transfers
+                WHERE
+                    (
+                        $3::TEXT IS NULL
+                        OR source_dealership_rif = $3
+                        OR destination_dealership_rif = $3
+                    )
+                    AND ($4::INTEGER IS NULL OR product_id = $4)
+                    AND ($5::INTEGER IS NULL OR product_id <> $5)
+                    AND ($6::TEXT IS NULL OR source_dealership_rif = $6)
+                    AND ($7::TEXT IS NULL OR source_dealership_rif <> $7)
+                    AND ($8::TEXT IS NULL OR destination_dealership_rif = $8)
+                    AND ($9::TEXT IS NULL OR destination_dealership_rif <> $9)
+                    AND ($10::INTEGER IS NULL OR product_count = $10)
+                    AND ($11::INTEGER IS NULL OR product_count <> $11)
+                    AND ($12::INTEGER IS NULL OR product_count < $12)
+                    AND ($13::INTEGER IS NULL OR product_count <= $13)
+                    AND ($14::INTEGER IS NULL OR product_count > $14)
+                    AND ($15::INTEGER IS NULL OR product_count >= $15)
+                    AND ($16::TEXT IS NULL OR status = $16)
+                    AND ($17::TEXT IS NULL OR status <> $17)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $18 THEN id < $2
+                            ELSE id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $18 THEN id END DESC,
+                    id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.dealership_rif,
+            pages.filter.conditions.product_id.eq,
+            pages.filter.conditions.product_id.ne,
+            pages.filter.conditions.source_dealership_rif.eq as _,
+            pages.filter.conditions.source_dealership_rif.ne as _,
+            pages.filter.conditions.destination_dealership_rif.eq as _,
+            pages.filter.conditions.destination_dealership_rif.ne as _,
+            pages.filter.conditions.product_count.eq,
+            pages.filter.conditions.product_count.ne,
+            pages.filter.conditions.product_count.lt,
+            pages.filter.conditions.product_count.lte,
+            pages.filter.conditions.product_count.gt,
+            pages.filter.conditions.product_count.gte,
+            pages.filter.conditions.status.eq,
+            pages.filter.conditions.status.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InsertStockTransfer {
+    pub product_id: i32,
+    pub source_dealership_rif: String,
+    pub destination_dealership_rif: String,
+    pub product_count: i32,
+}
+
+impl InsertStockTransfer {
+    pub async fn insert(
+        self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        sqlx::query_as!(
+            StockTransfer,
+            r#"
+            INSERT INTO stock_transfers (
+                product_id,
+                source_dealership_rif,
+                destination_dealership_rif,
+                product_count,
+                status,
+                dispatch_timestamp
+            )
+            VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                'in-transit',
+                LOCALTIMESTAMP
+            )
+            RETURNING
+                id,
+                product_id,
+                source_dealership_rif,
+                destination_dealership_rif,
+                product_count,
+                status,
+                dispatch_timestamp,
+                resolution_timestamp
+            "#,
+            self.product_id,
+            self.source_dealership_rif as _,
+            self.destination_dealership_rif as _,
+            self.product_count
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
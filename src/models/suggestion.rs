@@ -0,0 +1,7 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Suggestion<K> {
+    pub id: K,
+    pub label: String,
+}
@@ -1,8 +1,12 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::utils::{
+    filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+    pagination::{Cursor, Page, Pages, Paginable},
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -30,27 +34,55 @@ impl SupplyLine {
     }
 
     pub async fn select_all(
+        filter: &ListFilter<SupplyLineFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<SupplyLine>, sqlx::Error> {
+        SupplyLine::stream_all(filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<SupplyLineFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<SupplyLine, sqlx::Error>> {
         sqlx::query_as!(
             SupplyLine,
             r#"
             SELECT id, name
             FROM supply_lines
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+            ORDER BY
+                CASE WHEN $3::TEXT = 'id' AND NOT $4 THEN id END,
+                CASE WHEN $3 = 'id' AND $4 THEN id END DESC,
+                CASE WHEN $3 = 'name' AND NOT $4 THEN name END,
+                CASE WHEN $3 = 'name' AND $4 THEN name END DESC,
+                id
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        filter: &ListFilter<SupplyLineFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_supply_lines!"
             FROM supply_lines
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne
         )
         .fetch_one(connection)
         .await
@@ -74,33 +106,117 @@ impl SupplyLine {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct SupplyLineFilter {
+    pub name: Comparison<String>,
+}
+
+impl Filter for SupplyLineFilter {
+    const FIELDS: &'static [FilterField] = &[FilterField::new("name", EQUALITY)];
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "name" => self.name.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<SupplyLine> for SupplyLine {
+    const FIELDS: &'static [&'static str] = &["id", "name"];
+
+    type Filter = SupplyLineFilter;
+    type CursorKey = (i32,);
+
     async fn get_page(
         pages: &Pages<SupplyLine, SupplyLine>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<SupplyLine>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            SupplyLine,
+        let page_rows = sqlx::query!(
             r#"
-                SELECT id, name
+                SELECT id, name, COUNT(*) OVER() AS "total!"
                 FROM supply_lines
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                ORDER BY
+                    CASE WHEN $5::TEXT = 'id' AND NOT $6 THEN id END,
+                    CASE WHEN $5 = 'id' AND $6 THEN id END DESC,
+                    CASE WHEN $5 = 'name' AND NOT $6 THEN name END,
+                    CASE WHEN $5 = 'name' AND $6 THEN name END DESC,
+                    id
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| SupplyLine {
+                id: row.id,
+                name: row.name,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &SupplyLine) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<SupplyLine, SupplyLine>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<SupplyLine>, sqlx::Error> {
+        sqlx::query_as!(
+            SupplyLine,
+            r#"
+                SELECT id, name
+                FROM supply_lines
+                WHERE
+                    ($3::TEXT IS NULL OR name = $3)
+                    AND ($4::TEXT IS NULL OR name <> $4)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $5 THEN id < $2
+                            ELSE id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $5 THEN id END DESC,
+                    id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,9 +1,19 @@
 use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, Postgres};
-use time::Date;
+use sqlx::{Executor, Pool, Postgres};
+use time::{Date, PrimitiveDateTime};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::client::Client,
+    models::domains::NationalId,
+    models::vehicle_model::VehicleModel,
+    utils::{
+        expansion::{Expandable, Expanded, Expansion},
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,11 +28,13 @@ pub struct Vehicle {
     pub additional_info: Option<String>,
     pub maintenance_summary: Option<String>,
     pub owner_national_id: String,
+    pub deleted_at: Option<PrimitiveDateTime>,
 }
 
 impl Vehicle {
     pub async fn select(
         plate: String,
+        include_deleted: bool,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vehicle, sqlx::Error> {
         sqlx::query_as!(
@@ -38,20 +50,35 @@ impl Vehicle {
                 purchase_date,
                 additional_info,
                 maintenance_summary,
-                owner_national_id
+                owner_national_id,
+                deleted_at
             FROM vehicles
             WHERE
                 plate = $1
+                AND ($2 OR deleted_at IS NULL)
             "#,
-            plate
+            plate,
+            include_deleted
         )
         .fetch_one(connection)
         .await
     }
 
     pub async fn select_all(
+        include_deleted: bool,
+        filter: &ListFilter<VehicleFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<Vehicle>, sqlx::Error> {
+        Vehicle::stream_all(include_deleted, filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        include_deleted: bool,
+        filter: &ListFilter<VehicleFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Vehicle, sqlx::Error>> {
         sqlx::query_as!(
             Vehicle,
             r#"
@@ -65,22 +92,104 @@ impl Vehicle {
                 purchase_date,
                 additional_info,
                 maintenance_summary,
-                owner_national_id
+                owner_national_id,
+                deleted_at
             FROM vehicles
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR brand = $2)
+                AND ($3::TEXT IS NULL OR brand <> $3)
+                AND ($4::INTEGER IS NULL OR model_id = $4)
+                AND ($5::INTEGER IS NULL OR model_id <> $5)
+                AND ($6::TEXT IS NULL OR color = $6)
+                AND ($7::TEXT IS NULL OR color <> $7)
+                AND ($8::DATE IS NULL OR purchase_date = $8)
+                AND ($9::DATE IS NULL OR purchase_date <> $9)
+                AND ($10::DATE IS NULL OR purchase_date < $10)
+                AND ($11::DATE IS NULL OR purchase_date <= $11)
+                AND ($12::DATE IS NULL OR purchase_date > $12)
+                AND ($13::DATE IS NULL OR purchase_date >= $13)
+                AND ($14::TEXT IS NULL OR owner_national_id = $14)
+                AND ($15::TEXT IS NULL OR owner_national_id <> $15)
+            ORDER BY
+                CASE WHEN $16::TEXT = 'plate' AND NOT $17 THEN plate END,
+                CASE WHEN $16 = 'plate' AND $17 THEN plate END DESC,
+                CASE WHEN $16 = 'brand' AND NOT $17 THEN brand END,
+                CASE WHEN $16 = 'brand' AND $17 THEN brand END DESC,
+                CASE WHEN $16 = 'model-id' AND NOT $17 THEN model_id END,
+                CASE WHEN $16 = 'model-id' AND $17 THEN model_id END DESC,
+                CASE WHEN $16 = 'color' AND NOT $17 THEN color END,
+                CASE WHEN $16 = 'color' AND $17 THEN color END DESC,
+                CASE WHEN $16 = 'purchase-date' AND NOT $17 THEN purchase_date END,
+                CASE WHEN $16 = 'purchase-date' AND $17 THEN purchase_date END DESC,
+                CASE WHEN $16 = 'owner-national-id' AND NOT $17 THEN owner_national_id END,
+                CASE WHEN $16 = 'owner-national-id' AND $17 THEN owner_national_id END DESC,
+                CASE WHEN $16 = 'deleted-at' AND NOT $17 THEN deleted_at END,
+                CASE WHEN $16 = 'deleted-at' AND $17 THEN deleted_at END DESC,
+                plate
+            "#,
+            include_deleted,
+            filter.conditions.brand.eq,
+            filter.conditions.brand.ne,
+            filter.conditions.model_id.eq,
+            filter.conditions.model_id.ne,
+            filter.conditions.color.eq,
+            filter.conditions.color.ne,
+            filter.conditions.purchase_date.eq,
+            filter.conditions.purchase_date.ne,
+            filter.conditions.purchase_date.lt,
+            filter.conditions.purchase_date.lte,
+            filter.conditions.purchase_date.gt,
+            filter.conditions.purchase_date.gte,
+            filter.conditions.owner_national_id.eq as _,
+            filter.conditions.owner_national_id.ne as _,
+            filter.sort_field,
+            filter.sort_descending
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn count(
+        include_deleted: bool,
+        filter: &ListFilter<VehicleFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_vehicles!"
             FROM vehicles
-            "#
+            WHERE
+                ($1 OR deleted_at IS NULL)
+                AND ($2::TEXT IS NULL OR brand = $2)
+                AND ($3::TEXT IS NULL OR brand <> $3)
+                AND ($4::INTEGER IS NULL OR model_id = $4)
+                AND ($5::INTEGER IS NULL OR model_id <> $5)
+                AND ($6::TEXT IS NULL OR color = $6)
+                AND ($7::TEXT IS NULL OR color <> $7)
+                AND ($8::DATE IS NULL OR purchase_date = $8)
+                AND ($9::DATE IS NULL OR purchase_date <> $9)
+                AND ($10::DATE IS NULL OR purchase_date < $10)
+                AND ($11::DATE IS NULL OR purchase_date <= $11)
+                AND ($12::DATE IS NULL OR purchase_date > $12)
+                AND ($13::DATE IS NULL OR purchase_date >= $13)
+                AND ($14::TEXT IS NULL OR owner_national_id = $14)
+                AND ($15::TEXT IS NULL OR owner_national_id <> $15)
+            "#,
+            include_deleted,
+            filter.conditions.brand.eq,
+            filter.conditions.brand.ne,
+            filter.conditions.model_id.eq,
+            filter.conditions.model_id.ne,
+            filter.conditions.color.eq,
+            filter.conditions.color.ne,
+            filter.conditions.purchase_date.eq,
+            filter.conditions.purchase_date.ne,
+            filter.conditions.purchase_date.lt,
+            filter.conditions.purchase_date.lte,
+            filter.conditions.purchase_date.gt,
+            filter.conditions.purchase_date.gte,
+            filter.conditions.owner_national_id.eq as _,
+            filter.conditions.owner_national_id.ne as _
         )
         .fetch_one(connection)
         .await
@@ -93,9 +202,11 @@ impl Vehicle {
         sqlx::query_as!(
             Vehicle,
             r#"
-            DELETE FROM vehicles
+            UPDATE vehicles
+            SET deleted_at = now()
             WHERE
                 plate = $1
+                AND deleted_at IS NULL
             RETURNING
                 plate,
                 brand,
@@ -106,24 +217,111 @@ impl Vehicle {
                 purchase_date,
                 additional_info,
                 maintenance_summary,
-                owner_national_id
+                owner_national_id,
+                deleted_at
             "#,
             plate
         )
         .fetch_one(connection)
         .await
     }
+
+    pub async fn restore(
+        plate: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vehicle, sqlx::Error> {
+        sqlx::query_as!(
+            Vehicle,
+            r#"
+            UPDATE vehicles
+            SET deleted_at = NULL
+            WHERE
+                plate = $1
+                AND deleted_at IS NOT NULL
+            RETURNING
+                plate,
+                brand,
+                model_id,
+                serial_no,
+                engine_serial_no,
+                color,
+                purchase_date,
+                additional_info,
+                maintenance_summary,
+                owner_national_id,
+                deleted_at
+            "#,
+            plate
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct VehicleFilter {
+    pub brand: Comparison<String>,
+    pub model_id: Comparison<i32>,
+    pub color: Comparison<String>,
+    pub purchase_date: Comparison<Date>,
+    pub owner_national_id: Comparison<NationalId>,
+}
+
+impl Filter for VehicleFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("brand", EQUALITY),
+        FilterField::new("model-id", EQUALITY),
+        FilterField::new("color", EQUALITY),
+        FilterField::new("purchase-date", ORDERING),
+        FilterField::new("owner-national-id", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "plate",
+        "brand",
+        "model-id",
+        "color",
+        "purchase-date",
+        "owner-national-id",
+        "deleted-at",
+    ];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "brand" => self.brand.apply(operator, value),
+            "model-id" => self.model_id.apply(operator, value),
+            "color" => self.color.apply(operator, value),
+            "purchase-date" => self.purchase_date.apply(operator, value),
+            "owner-national-id" => self.owner_national_id.apply(operator, value),
+            _ => false,
+        }
+    }
 }
 
 #[async_trait]
 impl Paginable<Vehicle> for Vehicle {
+    const FIELDS: &'static [&'static str] = &[
+        "plate",
+        "brand",
+        "model-id",
+        "serial-no",
+        "engine-serial-no",
+        "color",
+        "purchase-date",
+        "additional-info",
+        "maintenance-summary",
+        "owner-national-id",
+        "deleted-at",
+    ];
+
+    type Filter = VehicleFilter;
+    type CursorKey = (String,);
+
     async fn get_page(
         pages: &Pages<Vehicle, Vehicle>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<Vehicle>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            Vehicle,
+        let page_rows = sqlx::query!(
             r#"
                 SELECT
                     plate,
@@ -135,23 +333,215 @@ impl Paginable<Vehicle> for Vehicle {
                     purchase_date,
                     additional_info,
                     maintenance_summary,
-                    owner_national_id
+                    owner_national_id,
+                    deleted_at,
+                    COUNT(*) OVER() AS "total!"
                 FROM vehicles
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR brand = $4)
+                    AND ($5::TEXT IS NULL OR brand <> $5)
+                    AND ($6::INTEGER IS NULL OR model_id = $6)
+                    AND ($7::INTEGER IS NULL OR model_id <> $7)
+                    AND ($8::TEXT IS NULL OR color = $8)
+                    AND ($9::TEXT IS NULL OR color <> $9)
+                    AND ($10::DATE IS NULL OR purchase_date = $10)
+                    AND ($11::DATE IS NULL OR purchase_date <> $11)
+                    AND ($12::DATE IS NULL OR purchase_date < $12)
+                    AND ($13::DATE IS NULL OR purchase_date <= $13)
+                    AND ($14::DATE IS NULL OR purchase_date > $14)
+                    AND ($15::DATE IS NULL OR purchase_date >= $15)
+                    AND ($16::TEXT IS NULL OR owner_national_id = $16)
+                    AND ($17::TEXT IS NULL OR owner_national_id <> $17)
+                ORDER BY
+                    CASE WHEN $18::TEXT = 'plate' AND NOT $19 THEN plate END,
+                    CASE WHEN $18 = 'plate' AND $19 THEN plate END DESC,
+                    CASE WHEN $18 = 'brand' AND NOT $19 THEN brand END,
+                    CASE WHEN $18 = 'brand' AND $19 THEN brand END DESC,
+                    CASE WHEN $18 = 'model-id' AND NOT $19 THEN model_id END,
+                    CASE WHEN $18 = 'model-id' AND $19 THEN model_id END DESC,
+                    CASE WHEN $18 = 'color' AND NOT $19 THEN color END,
+                    CASE WHEN $18 = 'color' AND $19 THEN color END DESC,
+                    CASE WHEN $18 = 'purchase-date' AND NOT $19 THEN purchase_date END,
+                    CASE WHEN $18 = 'purchase-date' AND $19 THEN purchase_date END DESC,
+                    CASE WHEN $18 = 'owner-national-id' AND NOT $19 THEN owner_national_id END,
+                    CASE WHEN $18 = 'owner-national-id' AND $19 THEN owner_national_id END DESC,
+                    CASE WHEN $18 = 'deleted-at' AND NOT $19 THEN deleted_at END,
+                    CASE WHEN $18 = 'deleted-at' AND $19 THEN deleted_at END DESC,
+                    plate
                 LIMIT $1
                 OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.include_deleted,
+            pages.filter.conditions.brand.eq,
+            pages.filter.conditions.brand.ne,
+            pages.filter.conditions.model_id.eq,
+            pages.filter.conditions.model_id.ne,
+            pages.filter.conditions.color.eq,
+            pages.filter.conditions.color.ne,
+            pages.filter.conditions.purchase_date.eq,
+            pages.filter.conditions.purchase_date.ne,
+            pages.filter.conditions.purchase_date.lt,
+            pages.filter.conditions.purchase_date.lte,
+            pages.filter.conditions.purchase_date.gt,
+            pages.filter.conditions.purchase_date.gte,
+            pages.filter.conditions.owner_national_id.eq as _,
+            pages.filter.conditions.owner_national_id.ne as _,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Vehicle {
+                plate: row.plate,
+                brand: row.brand,
+                model_id: row.model_id,
+                serial_no: row.serial_no,
+                engine_serial_no: row.engine_serial_no,
+                color: row.color,
+                purchase_date: row.purchase_date,
+                additional_info: row.additional_info,
+                maintenance_summary: row.maintenance_summary,
+                owner_national_id: row.owner_national_id,
+                deleted_at: row.deleted_at,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &Vehicle) -> (String,) {
+        (item.plate.clone(),)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Vehicle, Vehicle>,
+        limit: i64,
+        cursor: Option<&Cursor<(String,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Vehicle>, sqlx::Error> {
+        sqlx::query_as!(
+            Vehicle,
+            r#"
+                SELECT
+                    plate,
+                    brand,
+                    model_id,
+                    serial_no,
+                    engine_serial_no,
+                    color,
+                    purchase_date,
+                    additional_info,
+                    maintenance_summary,
+                    owner_national_id,
+                    deleted_at
+                FROM vehicles
+                WHERE
+                    ($3 OR deleted_at IS NULL)
+                    AND ($4::TEXT IS NULL OR brand = $4)
+                    AND ($5::TEXT IS NULL OR brand <> $5)
+                    AND ($6::INTEGER IS NULL OR model_id = $6)
+                    AND ($7::INTEGER IS NULL OR model_id <> $7)
+                    AND ($8::TEXT IS NULL OR color = $8)
+                    AND ($9::TEXT IS NULL OR color <> $9)
+                    AND ($10::DATE IS NULL OR purchase_date = $10)
+                    AND ($11::DATE IS NULL OR purchase_date <> $11)
+                    AND ($12::DATE IS NULL OR purchase_date < $12)
+                    AND ($13::DATE IS NULL OR purchase_date <= $13)
+                    AND ($14::DATE IS NULL OR purchase_date > $14)
+                    AND ($15::DATE IS NULL OR purchase_date >= $15)
+                    AND ($16::TEXT IS NULL OR owner_national_id = $16)
+                    AND ($17::TEXT IS NULL OR owner_national_id <> $17)
+                    AND (
+                        $2::TEXT IS NULL
+                        OR CASE
+                            WHEN $18 THEN plate < $2
+                            ELSE plate > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $18 THEN plate END DESC,
+                    plate
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0.as_str()),
+            pages.include_deleted,
+            pages.filter.conditions.brand.eq,
+            pages.filter.conditions.brand.ne,
+            pages.filter.conditions.model_id.eq,
+            pages.filter.conditions.model_id.ne,
+            pages.filter.conditions.color.eq,
+            pages.filter.conditions.color.ne,
+            pages.filter.conditions.purchase_date.eq,
+            pages.filter.conditions.purchase_date.ne,
+            pages.filter.conditions.purchase_date.lt,
+            pages.filter.conditions.purchase_date.lte,
+            pages.filter.conditions.purchase_date.gt,
+            pages.filter.conditions.purchase_date.gte,
+            pages.filter.conditions.owner_national_id.eq as _,
+            pages.filter.conditions.owner_national_id.ne as _,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[async_trait]
+impl Expandable for Vehicle {
+    const RELATIONS: &'static [&'static str] = &["owner", "model"];
+
+    async fn expand(
+        vehicles: Vec<Vehicle>,
+        expansion: &Expansion,
+        connection: &Pool<Postgres>,
+    ) -> Result<Vec<Expanded<Vehicle>>, sqlx::Error> {
+        let mut expanded_vehicles = Expanded::wrap_all(vehicles);
+
+        if expansion.includes("owner") {
+            let owner_national_ids = expanded_vehicles
+                .iter()
+                .map(|vehicle| vehicle.item.owner_national_id.clone())
+                .collect();
+            let owners = Client::select_all_by_national_ids(owner_national_ids, connection).await?;
+            Expanded::embed_one(
+                &mut expanded_vehicles,
+                "owner",
+                owners,
+                |vehicle| vehicle.owner_national_id.clone(),
+                |owner| owner.national_id.clone(),
+            );
+        }
+
+        if expansion.includes("model") {
+            let model_ids = expanded_vehicles
+                .iter()
+                .map(|vehicle| vehicle.item.model_id)
+                .collect();
+            let models = VehicleModel::select_all_by_ids(model_ids, connection).await?;
+            Expanded::embed_one(
+                &mut expanded_vehicles,
+                "model",
+                models,
+                |vehicle| vehicle.model_id,
+                |model| model.id,
+            );
+        }
+
+        Ok(expanded_vehicles)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -210,7 +600,8 @@ impl InsertVehicle {
                 purchase_date,
                 additional_info,
                 maintenance_summary,
-                owner_national_id
+                owner_national_id,
+                deleted_at
             "#,
             self.plate,
             self.brand,
@@ -288,7 +679,8 @@ impl UpdateVehicle {
                 purchase_date,
                 additional_info,
                 maintenance_summary,
-                owner_national_id
+                owner_national_id,
+                deleted_at
             "#,
             new_plate,
             new_brand,
@@ -1,9 +1,16 @@
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
-use crate::utils::pagination::{Page, Pages, Paginable};
+use crate::{
+    models::suggestion::Suggestion,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY, ORDERING},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -45,7 +52,8 @@ impl VehicleModel {
         .await
     }
 
-    pub async fn select_all(
+    pub async fn select_all_by_ids(
+        ids: Vec<i32>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<VehicleModel>, sqlx::Error> {
         sqlx::query_as!(
@@ -61,25 +69,176 @@ impl VehicleModel {
                 engine_oil_type,
                 engine_coolant_type
             FROM vehicle_models
-            "#
+            WHERE id = ANY($1)
+            "#,
+            &ids
         )
         .fetch_all(connection)
         .await
     }
 
+    pub async fn select_all(
+        filter: &ListFilter<VehicleModelFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<VehicleModel>, sqlx::Error> {
+        VehicleModel::stream_all(filter, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<VehicleModelFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<VehicleModel, sqlx::Error>> {
+        sqlx::query_as!(
+            VehicleModel,
+            r#"
+            SELECT
+                id,
+                name,
+                seat_count,
+                weight_in_kg,
+                octane_rating,
+                gearbox_oil_type,
+                engine_oil_type,
+                engine_coolant_type
+            FROM vehicle_models
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+                AND ($3::INTEGER IS NULL OR seat_count = $3)
+                AND ($4::INTEGER IS NULL OR seat_count <> $4)
+                AND ($5::INTEGER IS NULL OR seat_count < $5)
+                AND ($6::INTEGER IS NULL OR seat_count <= $6)
+                AND ($7::INTEGER IS NULL OR seat_count > $7)
+                AND ($8::INTEGER IS NULL OR seat_count >= $8)
+                AND ($9::NUMERIC IS NULL OR weight_in_kg = $9)
+                AND ($10::NUMERIC IS NULL OR weight_in_kg <> $10)
+                AND ($11::NUMERIC IS NULL OR weight_in_kg < $11)
+                AND ($12::NUMERIC IS NULL OR weight_in_kg <= $12)
+                AND ($13::NUMERIC IS NULL OR weight_in_kg > $13)
+                AND ($14::NUMERIC IS NULL OR weight_in_kg >= $14)
+                AND ($15::SMALLINT IS NULL OR octane_rating = $15)
+                AND ($16::SMALLINT IS NULL OR octane_rating <> $16)
+                AND ($17::SMALLINT IS NULL OR octane_rating < $17)
+                AND ($18::SMALLINT IS NULL OR octane_rating <= $18)
+                AND ($19::SMALLINT IS NULL OR octane_rating > $19)
+                AND ($20::SMALLINT IS NULL OR octane_rating >= $20)
+            ORDER BY
+                CASE WHEN $21::TEXT = 'id' AND NOT $22 THEN id END,
+                CASE WHEN $21 = 'id' AND $22 THEN id END DESC,
+                CASE WHEN $21 = 'name' AND NOT $22 THEN name END,
+                CASE WHEN $21 = 'name' AND $22 THEN name END DESC,
+                CASE WHEN $21 = 'seat-count' AND NOT $22 THEN seat_count END,
+                CASE WHEN $21 = 'seat-count' AND $22 THEN seat_count END DESC,
+                CASE WHEN $21 = 'weight-in-kg' AND NOT $22 THEN weight_in_kg END,
+                CASE WHEN $21 = 'weight-in-kg' AND $22 THEN weight_in_kg END DESC,
+                CASE WHEN $21 = 'octane-rating' AND NOT $22 THEN octane_rating END,
+                CASE WHEN $21 = 'octane-rating' AND $22 THEN octane_rating END DESC,
+                id
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.seat_count.eq,
+            filter.conditions.seat_count.ne,
+            filter.conditions.seat_count.lt,
+            filter.conditions.seat_count.lte,
+            filter.conditions.seat_count.gt,
+            filter.conditions.seat_count.gte,
+            filter.conditions.weight_in_kg.eq,
+            filter.conditions.weight_in_kg.ne,
+            filter.conditions.weight_in_kg.lt,
+            filter.conditions.weight_in_kg.lte,
+            filter.conditions.weight_in_kg.gt,
+            filter.conditions.weight_in_kg.gte,
+            filter.conditions.octane_rating.eq,
+            filter.conditions.octane_rating.ne,
+            filter.conditions.octane_rating.lt,
+            filter.conditions.octane_rating.lte,
+            filter.conditions.octane_rating.gt,
+            filter.conditions.octane_rating.gte,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
     pub async fn count(
+        filter: &ListFilter<VehicleModelFilter>,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "total_vehicle_models!"
             FROM vehicle_models
-            "#
+            WHERE
+                ($1::TEXT IS NULL OR name = $1)
+                AND ($2::TEXT IS NULL OR name <> $2)
+                AND ($3::INTEGER IS NULL OR seat_count = $3)
+                AND ($4::INTEGER IS NULL OR seat_count <> $4)
+                AND ($5::INTEGER IS NULL OR seat_count < $5)
+                AND ($6::INTEGER IS NULL OR seat_count <= $6)
+                AND ($7::INTEGER IS NULL OR seat_count > $7)
+                AND ($8::INTEGER IS NULL OR seat_count >= $8)
+                AND ($9::NUMERIC IS NULL OR weight_in_kg = $9)
+                AND ($10::NUMERIC IS NULL OR weight_in_kg <> $10)
+                AND ($11::NUMERIC IS NULL OR weight_in_kg < $11)
+                AND ($12::NUMERIC IS NULL OR weight_in_kg <= $12)
+                AND ($13::NUMERIC IS NULL OR weight_in_kg > $13)
+                AND ($14::NUMERIC IS NULL OR weight_in_kg >= $14)
+                AND ($15::SMALLINT IS NULL OR octane_rating = $15)
+                AND ($16::SMALLINT IS NULL OR octane_rating <> $16)
+                AND ($17::SMALLINT IS NULL OR octane_rating < $17)
+                AND ($18::SMALLINT IS NULL OR octane_rating <= $18)
+                AND ($19::SMALLINT IS NULL OR octane_rating > $19)
+                AND ($20::SMALLINT IS NULL OR octane_rating >= $20)
+            "#,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.conditions.seat_count.eq,
+            filter.conditions.seat_count.ne,
+            filter.conditions.seat_count.lt,
+            filter.conditions.seat_count.lte,
+            filter.conditions.seat_count.gt,
+            filter.conditions.seat_count.gte,
+            filter.conditions.weight_in_kg.eq,
+            filter.conditions.weight_in_kg.ne,
+            filter.conditions.weight_in_kg.lt,
+            filter.conditions.weight_in_kg.lte,
+            filter.conditions.weight_in_kg.gt,
+            filter.conditions.weight_in_kg.gte,
+            filter.conditions.octane_rating.eq,
+            filter.conditions.octane_rating.ne,
+            filter.conditions.octane_rating.lt,
+            filter.conditions.octane_rating.lte,
+            filter.conditions.octane_rating.gt,
+            filter.conditions.octane_rating.gte
         )
         .fetch_one(connection)
         .await
     }
 
+    pub async fn select_suggestions(
+        prefix: String,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Suggestion<i32>>, sqlx::Error> {
+        sqlx::query_as!(
+            Suggestion,
+            r#"
+            SELECT id, name AS label
+            FROM vehicle_models
+            WHERE lower(name) LIKE to_prefix_pattern(lower($1))
+            ORDER BY name, id
+            LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+
     pub async fn delete(
         id: i32,
         connection: impl Executor<'_, Database = Postgres>,
@@ -107,15 +266,57 @@ impl VehicleModel {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct VehicleModelFilter {
+    pub name: Comparison<String>,
+    pub seat_count: Comparison<i32>,
+    pub weight_in_kg: Comparison<BigDecimal>,
+    pub octane_rating: Comparison<i16>,
+}
+
+impl Filter for VehicleModelFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("name", EQUALITY),
+        FilterField::new("seat-count", ORDERING),
+        FilterField::new("weight-in-kg", ORDERING),
+        FilterField::new("octane-rating", ORDERING),
+    ];
+    const SORT_FIELDS: &'static [&'static str] =
+        &["id", "name", "seat-count", "weight-in-kg", "octane-rating"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "name" => self.name.apply(operator, value),
+            "seat-count" => self.seat_count.apply(operator, value),
+            "weight-in-kg" => self.weight_in_kg.apply(operator, value),
+            "octane-rating" => self.octane_rating.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
 #[async_trait]
 impl Paginable<VehicleModel> for VehicleModel {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "seat-count",
+        "weight-in-kg",
+        "octane-rating",
+        "gearbox-oil-type",
+        "engine-oil-type",
+        "engine-coolant-type",
+    ];
+
+    type Filter = VehicleModelFilter;
+    type CursorKey = (i32,);
+
     async fn get_page(
         pages: &Pages<VehicleModel, VehicleModel>,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<VehicleModel>, sqlx::Error> {
-        let page_items = sqlx::query_as!(
-            VehicleModel,
+        let page_rows = sqlx::query!(
             r#"
             SELECT
                 id,
@@ -125,23 +326,179 @@ impl Paginable<VehicleModel> for VehicleModel {
                 octane_rating,
                 gearbox_oil_type,
                 engine_oil_type,
-                engine_coolant_type
+                engine_coolant_type,
+                COUNT(*) OVER() AS "total!"
             FROM vehicle_models
+            WHERE
+                ($3::TEXT IS NULL OR name = $3)
+                AND ($4::TEXT IS NULL OR name <> $4)
+                AND ($5::INTEGER IS NULL OR seat_count = $5)
+                AND ($6::INTEGER IS NULL OR seat_count <> $6)
+                AND ($7::INTEGER IS NULL OR seat_count < $7)
+                AND ($8::INTEGER IS NULL OR seat_count <= $8)
+                AND ($9::INTEGER IS NULL OR seat_count > $9)
+                AND ($10::INTEGER IS NULL OR seat_count >= $10)
+                AND ($11::NUMERIC IS NULL OR weight_in_kg = $11)
+                AND ($12::NUMERIC IS NULL OR weight_in_kg <> $12)
+                AND ($13::NUMERIC IS NULL OR weight_in_kg < $13)
+                AND ($14::NUMERIC IS NULL OR weight_in_kg <= $14)
+                AND ($15::NUMERIC IS NULL OR weight_in_kg > $15)
+                AND ($16::NUMERIC IS NULL OR weight_in_kg >= $16)
+                AND ($17::SMALLINT IS NULL OR octane_rating = $17)
+                AND ($18::SMALLINT IS NULL OR octane_rating <> $18)
+                AND ($19::SMALLINT IS NULL OR octane_rating < $19)
+                AND ($20::SMALLINT IS NULL OR octane_rating <= $20)
+                AND ($21::SMALLINT IS NULL OR octane_rating > $21)
+                AND ($22::SMALLINT IS NULL OR octane_rating >= $22)
+            ORDER BY
+                CASE WHEN $23::TEXT = 'id' AND NOT $24 THEN id END,
+                CASE WHEN $23 = 'id' AND $24 THEN id END DESC,
+                CASE WHEN $23 = 'name' AND NOT $24 THEN name END,
+                CASE WHEN $23 = 'name' AND $24 THEN name END DESC,
+                CASE WHEN $23 = 'seat-count' AND NOT $24 THEN seat_count END,
+                CASE WHEN $23 = 'seat-count' AND $24 THEN seat_count END DESC,
+                CASE WHEN $23 = 'weight-in-kg' AND NOT $24 THEN weight_in_kg END,
+                CASE WHEN $23 = 'weight-in-kg' AND $24 THEN weight_in_kg END DESC,
+                CASE WHEN $23 = 'octane-rating' AND NOT $24 THEN octane_rating END,
+                CASE WHEN $23 = 'octane-rating' AND $24 THEN octane_rating END DESC,
+                id
             LIMIT $1
             OFFSET $2
             "#,
             pages.per_page,
-            (page_no - 1) * pages.per_page
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.seat_count.eq,
+            pages.filter.conditions.seat_count.ne,
+            pages.filter.conditions.seat_count.lt,
+            pages.filter.conditions.seat_count.lte,
+            pages.filter.conditions.seat_count.gt,
+            pages.filter.conditions.seat_count.gte,
+            pages.filter.conditions.weight_in_kg.eq,
+            pages.filter.conditions.weight_in_kg.ne,
+            pages.filter.conditions.weight_in_kg.lt,
+            pages.filter.conditions.weight_in_kg.lte,
+            pages.filter.conditions.weight_in_kg.gt,
+            pages.filter.conditions.weight_in_kg.gte,
+            pages.filter.conditions.octane_rating.eq,
+            pages.filter.conditions.octane_rating.ne,
+            pages.filter.conditions.octane_rating.lt,
+            pages.filter.conditions.octane_rating.lte,
+            pages.filter.conditions.octane_rating.gt,
+            pages.filter.conditions.octane_rating.gte,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
         )
         .fetch_all(connection)
         .await?;
 
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| VehicleModel {
+                id: row.id,
+                name: row.name,
+                seat_count: row.seat_count,
+                weight_in_kg: row.weight_in_kg,
+                octane_rating: row.octane_rating,
+                gearbox_oil_type: row.gearbox_oil_type,
+                engine_oil_type: row.engine_oil_type,
+                engine_coolant_type: row.engine_coolant_type,
+            })
+            .collect();
+
         Ok(Page {
             per_page: pages.per_page,
             page_no,
             items: page_items,
+            total,
         })
     }
+
+    fn cursor_key(item: &VehicleModel) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<VehicleModel, VehicleModel>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<VehicleModel>, sqlx::Error> {
+        sqlx::query_as!(
+            VehicleModel,
+            r#"
+            SELECT
+                id,
+                name,
+                seat_count,
+                weight_in_kg,
+                octane_rating,
+                gearbox_oil_type,
+                engine_oil_type,
+                engine_coolant_type
+            FROM vehicle_models
+            WHERE
+                ($3::TEXT IS NULL OR name = $3)
+                AND ($4::TEXT IS NULL OR name <> $4)
+                AND ($5::INTEGER IS NULL OR seat_count = $5)
+                AND ($6::INTEGER IS NULL OR seat_count <> $6)
+                AND ($7::INTEGER IS NULL OR seat_count < $7)
+                AND ($8::INTEGER IS NULL OR seat_count <= $8)
+                AND ($9::INTEGER IS NULL OR seat_count > $9)
+                AND ($10::INTEGER IS NULL OR seat_count >= $10)
+                AND ($11::NUMERIC IS NULL OR weight_in_kg = $11)
+                AND ($12::NUMERIC IS NULL OR weight_in_kg <> $12)
+                AND ($13::NUMERIC IS NULL OR weight_in_kg < $13)
+                AND ($14::NUMERIC IS NULL OR weight_in_kg <= $14)
+                AND ($15::NUMERIC IS NULL OR weight_in_kg > $15)
+                AND ($16::NUMERIC IS NULL OR weight_in_kg >= $16)
+                AND ($17::SMALLINT IS NULL OR octane_rating = $17)
+                AND ($18::SMALLINT IS NULL OR octane_rating <> $18)
+                AND ($19::SMALLINT IS NULL OR octane_rating < $19)
+                AND ($20::SMALLINT IS NULL OR octane_rating <= $20)
+                AND ($21::SMALLINT IS NULL OR octane_rating > $21)
+                AND ($22::SMALLINT IS NULL OR octane_rating >= $22)
+                AND (
+                    $2::INTEGER IS NULL
+                    OR CASE
+                        WHEN $23 THEN id < $2
+                        ELSE id > $2
+                    END
+                )
+            ORDER BY
+                CASE WHEN $23 THEN id END DESC,
+                id
+            LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.conditions.seat_count.eq,
+            pages.filter.conditions.seat_count.ne,
+            pages.filter.conditions.seat_count.lt,
+            pages.filter.conditions.seat_count.lte,
+            pages.filter.conditions.seat_count.gt,
+            pages.filter.conditions.seat_count.gte,
+            pages.filter.conditions.weight_in_kg.eq,
+            pages.filter.conditions.weight_in_kg.ne,
+            pages.filter.conditions.weight_in_kg.lt,
+            pages.filter.conditions.weight_in_kg.lte,
+            pages.filter.conditions.weight_in_kg.gt,
+            pages.filter.conditions.weight_in_kg.gte,
+            pages.filter.conditions.octane_rating.eq,
+            pages.filter.conditions.octane_rating.ne,
+            pages.filter.conditions.octane_rating.lt,
+            pages.filter.conditions.octane_rating.lte,
+            pages.filter.conditions.octane_rating.gt,
+            pages.filter.conditions.octane_rating.gte,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,459 @@
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres};
+
+use crate::{
+    models::domains::Rif,
+    utils::{
+        filtering::{Comparison, Filter, FilterField, ListFilter, Operator, EQUALITY},
+        pagination::{Cursor, Page, Pages, Paginable},
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vendor {
+    pub id: i32,
+    pub rif: Option<String>,
+    pub name: String,
+    pub phone_no: Option<String>,
+    pub email: Option<String>,
+    pub supply_line_ids: Vec<i32>,
+}
+
+impl Vendor {
+    pub async fn select(
+        id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vendor, sqlx::Error> {
+        sqlx::query_as!(
+            Vendor,
+            r#"
+            SELECT
+                v.id,
+                v.rif,
+                v.name,
+                v.phone_no,
+                v.email,
+                ARRAY(
+                    SELECT vsl.supply_line_id
+                    FROM vendors_supply_lines AS vsl
+                    WHERE vsl.vendor_id = v.id
+                    ORDER BY vsl.supply_line_id
+                ) AS "supply_line_ids!"
+            FROM vendors AS v
+            WHERE v.id = $1
+            "#,
+            id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn select_all(
+        filter: &ListFilter<VendorFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Vendor>, sqlx::Error> {
+        Vendor::stream_all(filter, connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        filter: &ListFilter<VendorFilter>,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<Vendor, sqlx::Error>> {
+        sqlx::query_as!(
+            Vendor,
+            r#"
+            SELECT
+                v.id,
+                v.rif,
+                v.name,
+                v.phone_no,
+                v.email,
+                ARRAY(
+                    SELECT vsl.supply_line_id
+                    FROM vendors_supply_lines AS vsl
+                    WHERE vsl.vendor_id = v.id
+                    ORDER BY vsl.supply_line_id
+                ) AS "supply_line_ids!"
+            FROM vendors AS v
+            WHERE
+                ($1::TEXT IS NULL OR v.rif = $1)
+                AND ($2::TEXT IS NULL OR v.rif <> $2)
+                AND ($3::TEXT IS NULL OR v.name = $3)
+                AND ($4::TEXT IS NULL OR v.name <> $4)
+            ORDER BY
+                CASE WHEN $5::TEXT = 'id' AND NOT $6 THEN v.id END,
+                CASE WHEN $5 = 'id' AND $6 THEN v.id END DESC,
+                CASE WHEN $5 = 'rif' AND NOT $6 THEN v.rif END,
+                CASE WHEN $5 = 'rif' AND $6 THEN v.rif END DESC,
+                CASE WHEN $5 = 'name' AND NOT $6 THEN v.name END,
+                CASE WHEN $5 = 'name' AND $6 THEN v.name END DESC,
+                v.id
+            "#,
+            filter.conditions.rif.eq as _,
+            filter.conditions.rif.ne as _,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne,
+            filter.sort_field,
+            filter.sort_descending
+        )
+        .fetch(connection)
+    }
+
+    pub async fn count(
+        filter: &ListFilter<VendorFilter>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total_vendors!"
+            FROM vendors AS v
+            WHERE
+                ($1::TEXT IS NULL OR v.rif = $1)
+                AND ($2::TEXT IS NULL OR v.rif <> $2)
+                AND ($3::TEXT IS NULL OR v.name = $3)
+                AND ($4::TEXT IS NULL OR v.name <> $4)
+            "#,
+            filter.conditions.rif.eq as _,
+            filter.conditions.rif.ne as _,
+            filter.conditions.name.eq,
+            filter.conditions.name.ne
+        )
+        .fetch_one(connection)
+        .await
+    }
+
+    pub async fn delete(
+        id: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vendor, sqlx::Error> {
+        sqlx::query_as!(
+            Vendor,
+            r#"
+            DELETE FROM vendors AS v
+            WHERE v.id = $1
+            RETURNING
+                v.id,
+                v.rif,
+                v.name,
+                v.phone_no,
+                v.email,
+                ARRAY(
+                    SELECT vsl.supply_line_id
+                    FROM vendors_supply_lines AS vsl
+                    WHERE vsl.vendor_id = v.id
+                    ORDER BY vsl.supply_line_id
+                ) AS "supply_line_ids!"
+            "#,
+            id,
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct VendorFilter {
+    pub rif: Comparison<Rif>,
+    pub name: Comparison<String>,
+}
+
+impl Filter for VendorFilter {
+    const FIELDS: &'static [FilterField] = &[
+        FilterField::new("rif", EQUALITY),
+        FilterField::new("name", EQUALITY),
+    ];
+    const SORT_FIELDS: &'static [&'static str] = &["id", "rif", "name"];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool {
+        match field {
+            "rif" => self.rif.apply(operator, value),
+            "name" => self.name.apply(operator, value),
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl Paginable<Vendor> for Vendor {
+    const FIELDS: &'static [&'static str] = &["id", "rif", "name", "phone-no", "email"];
+
+    type Filter = VendorFilter;
+    type CursorKey = (i32,);
+
+    async fn get_page(
+        pages: &Pages<Vendor, Vendor>,
+        page_no: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Page<Vendor>, sqlx::Error> {
+        let page_rows = sqlx::query!(
+            r#"
+                SELECT
+                    v.id,
+                    v.rif,
+                    v.name,
+                    v.phone_no,
+                    v.email,
+                    ARRAY(
+                        SELECT vsl.supply_line_id
+                        FROM vendors_supply_lines AS vsl
+                        WHERE vsl.vendor_id = v.id
+                        ORDER BY vsl.supply_line_id
+                    ) AS "supply_line_ids!",
+                    COUNT(*) OVER() AS "total!"
+                FROM vendors AS v
+                WHERE
+                    ($3::TEXT IS NULL OR v.rif = $3)
+                    AND ($4::TEXT IS NULL OR v.rif <> $4)
+                    AND ($5::TEXT IS NULL OR v.name = $5)
+                    AND ($6::TEXT IS NULL OR v.name <> $6)
+                ORDER BY
+                    CASE WHEN $7::TEXT = 'id' AND NOT $8 THEN v.id END,
+                    CASE WHEN $7 = 'id' AND $8 THEN v.id END DESC,
+                    CASE WHEN $7 = 'rif' AND NOT $8 THEN v.rif END,
+                    CASE WHEN $7 = 'rif' AND $8 THEN v.rif END DESC,
+                    CASE WHEN $7 = 'name' AND NOT $8 THEN v.name END,
+                    CASE WHEN $7 = 'name' AND $8 THEN v.name END DESC,
+                    v.id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            pages.per_page,
+            (page_no - 1) * pages.per_page,
+            pages.filter.conditions.rif.eq as _,
+            pages.filter.conditions.rif.ne as _,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            pages.filter.sort_field,
+            pages.filter.sort_descending
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let total = page_rows.first().map(|row| row.total);
+        let page_items = page_rows
+            .into_iter()
+            .map(|row| Vendor {
+                id: row.id,
+                rif: row.rif,
+                name: row.name,
+                phone_no: row.phone_no,
+                email: row.email,
+                supply_line_ids: row.supply_line_ids,
+            })
+            .collect();
+
+        Ok(Page {
+            per_page: pages.per_page,
+            page_no,
+            items: page_items,
+            total,
+        })
+    }
+
+    fn cursor_key(item: &Vendor) -> (i32,) {
+        (item.id,)
+    }
+
+    async fn get_items_after(
+        pages: &Pages<Vendor, Vendor>,
+        limit: i64,
+        cursor: Option<&Cursor<(i32,)>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<Vendor>, sqlx::Error> {
+        sqlx::query_as!(
+            Vendor,
+            r#"
+                SELECT
+                    v.id,
+                    v.rif,
+                    v.name,
+                    v.phone_no,
+                    v.email,
+                    ARRAY(
+                        SELECT vsl.supply_line_id
+                        FROM vendors_supply_lines AS vsl
+                        WHERE vsl.vendor_id = v.id
+                        ORDER BY vsl.supply_line_id
+                    ) AS "supply_line_ids!"
+                FROM vendors AS v
+                WHERE
+                    ($3::TEXT IS NULL OR v.rif = $3)
+                    AND ($4::TEXT IS NULL OR v.rif <> $4)
+                    AND ($5::TEXT IS NULL OR v.name = $5)
+                    AND ($6::TEXT IS NULL OR v.name <> $6)
+                    AND (
+                        $2::INTEGER IS NULL
+                        OR CASE
+                            WHEN $7 THEN v.id < $2
+                            ELSE v.id > $2
+                        END
+                    )
+                ORDER BY
+                    CASE WHEN $7 THEN v.id END DESC,
+                    v.id
+                LIMIT $1
+            "#,
+            limit,
+            cursor.map(|cursor| cursor.key.0),
+            pages.filter.conditions.rif.eq as _,
+            pages.filter.conditions.rif.ne as _,
+            pages.filter.conditions.name.eq,
+            pages.filter.conditions.name.ne,
+            cursor.is_some_and(|cursor| cursor.backward)
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InsertVendor {
+    pub rif: Option<String>,
+    pub name: String,
+    pub phone_no: Option<String>,
+    pub email: Option<String>,
+    pub supply_line_ids: Vec<i32>,
+}
+
+impl InsertVendor {
+    pub async fn insert(
+        mut self,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vendor, sqlx::Error> {
+        self.supply_line_ids.sort_unstable();
+        self.supply_line_ids.dedup();
+
+        sqlx::query_as!(
+            Vendor,
+            r#"
+            WITH inserted_vendor AS (
+                INSERT INTO vendors (
+                    rif,
+                    name,
+                    phone_no,
+                    email
+                )
+                VALUES (
+                    $1,
+                    $2,
+                    $3,
+                    $4
+                )
+                RETURNING
+                    id,
+                    rif,
+                    name,
+                    phone_no,
+                    email
+            ),
+            inserted_supply_lines AS (
+                INSERT INTO vendors_supply_lines (
+                    vendor_id,
+                    supply_line_id
+                )
+                SELECT
+                    iv.id,
+                    UNNEST($5::INTEGER[])
+                FROM inserted_vendor AS iv
+            )
+            SELECT
+                id AS "id!",
+                rif,
+                name AS "name!",
+                phone_no,
+                email,
+                $5::INTEGER[] AS "supply_line_ids!"
+            FROM inserted_vendor
+            "#,
+            self.rif as _,
+            self.name,
+            self.phone_no as _,
+            self.email as _,
+            &self.supply_line_ids
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateVendor {
+    pub rif: Option<Option<String>>,
+    pub name: Option<String>,
+    pub phone_no: Option<Option<String>>,
+    pub email: Option<Option<String>>,
+    pub supply_line_ids: Option<Vec<i32>>,
+}
+
+impl UpdateVendor {
+    pub async fn update(
+        self,
+        target: Vendor,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vendor, sqlx::Error> {
+        let new_rif = self.rif.unwrap_or(target.rif);
+        let new_name = self.name.unwrap_or(target.name);
+        let new_phone_no = self.phone_no.unwrap_or(target.phone_no);
+        let new_email = self.email.unwrap_or(target.email);
+        let mut new_supply_line_ids = self.supply_line_ids.unwrap_or(target.supply_line_ids);
+        new_supply_line_ids.sort_unstable();
+        new_supply_line_ids.dedup();
+
+        sqlx::query_as!(
+            Vendor,
+            r#"
+            WITH updated_vendor AS (
+                UPDATE vendors
+                SET
+                    rif = $1,
+                    name = $2,
+                    phone_no = $3,
+                    email = $4
+                WHERE id = $5
+                RETURNING
+                    id,
+                    rif,
+                    name,
+                    phone_no,
+                    email
+            ),
+            deleted_supply_lines AS (
+                DELETE FROM vendors_supply_lines
+                WHERE
+                    vendor_id IN (SELECT id FROM updated_vendor)
+                    AND supply_line_id <> ALL($6::INTEGER[])
+            ),
+            inserted_supply_lines AS (
+                INSERT INTO vendors_supply_lines (
+                    vendor_id,
+                    supply_line_id
+                )
+                SELECT
+                    uv.id,
+                    UNNEST($6::INTEGER[])
+                FROM updated_vendor AS uv
+                ON CONFLICT DO NOTHING
+            )
+            SELECT
+                id AS "id!",
+                rif,
+                name AS "name!",
+                phone_no,
+                email,
+                $6::INTEGER[] AS "supply_line_ids!"
+            FROM updated_vendor
+            "#,
+            new_rif as _,
+            new_name,
+            new_phone_no as _,
+            new_email as _,
+            target.id,
+            &new_supply_line_ids
+        )
+        .fetch_one(connection)
+        .await
+    }
+}
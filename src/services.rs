@@ -1,9 +1,12 @@
 pub mod activities;
 pub mod activities_prices;
+pub mod audit;
+pub mod auth;
 pub mod cities;
 pub mod clients;
 pub mod dealerships;
 pub mod discounts;
+pub mod inventory_valuations;
 pub mod least_employed_employees;
 pub mod least_profitable_dealerships;
 pub mod least_requested_services;
@@ -17,16 +20,24 @@ pub mod most_used_products;
 pub mod no_show_clients;
 pub mod pagination_params;
 pub mod products;
+pub mod products_fluid_types;
+pub mod products_vehicle_models;
 pub mod responses_dto;
 pub mod roles;
+pub mod roles_permissions;
+pub mod search;
 pub mod service_error;
 pub mod services;
 pub mod staff;
+pub mod staff_accounts;
 pub mod states;
+pub mod suggestion_params;
 pub mod supply_lines;
 pub mod vehicle_applied_services;
 pub mod vehicle_models;
 pub mod vehicles;
 pub mod invoices;
 pub mod payments;
-pub mod stock;
\ No newline at end of file
+pub mod stock;
+pub mod stock_transfers;
+pub mod vendors;
\ No newline at end of file
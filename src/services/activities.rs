@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -11,16 +14,33 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::activity::{Activity, InsertActivity, UpdateActivity},
+    models::activity::{Activity, ActivityFilter, InsertActivity, UpdateActivity},
+    models::dependency::Dependency,
+    models::service::Service,
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    services::suggestion_params::parse_suggestion_limit,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_activities)
+        .service(suggest_activities)
+        .service(fetch_activity_dependencies)
         .service(fetch_activity)
         .service(create_activity)
         .service(update_activity_partially)
@@ -28,6 +48,12 @@ pub fn configure(configuration: &mut ServiceConfig) {
         .service(delete_activity);
 }
 
+pub fn configure_nested(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_service_activities)
+        .service(create_service_activity);
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -39,15 +65,37 @@ struct CreateActivityPayload {
 
 #[post("/")]
 async fn create_activity(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateActivityPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_activity(payload, audit, db).await
+}
+
+#[post("/{service_id}/activities/")]
+async fn create_service_activity(
+    _: Authorized<CatalogWrite>,
+    PathJson(payload): PathJson<CreateActivityPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    insert_activity(payload, audit, db).await
+}
+
+async fn insert_activity(
+    payload: CreateActivityPayload,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_activity = InsertActivity {
         service_id: payload.service_id,
         description: payload.description,
         price_per_hour: payload.price_per_hour,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -56,11 +104,19 @@ async fn create_activity(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("activities", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the activity into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_activity,
     }))
@@ -68,9 +124,87 @@ async fn create_activity(
 
 #[get("/")]
 async fn fetch_activities(
+    _: Authorized<CatalogRead>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ActivityFilter>()?;
+
+    list_activities(
+        pagination_params,
+        list_filter,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/{service_id}/activities/")]
+async fn fetch_service_activities(
+    _: Authorized<CatalogRead>,
+    service_id: Path<i32>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let service_id = service_id.into_inner();
+
+    Service::select(service_id, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the service from the database"),
+            ),
+        })?;
+
+    let mut list_filter = pagination_params.list_filter::<ActivityFilter>()?;
+    list_filter.conditions.service_id.eq = Some(service_id);
+
+    list_activities(
+        pagination_params,
+        list_filter,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_activities(
+    pagination_params: PaginationParams,
+    list_filter: ListFilter<ActivityFilter>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(Activity::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_activities =
+            fetch_activities_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_activities.prev_cursor,
+            fetched_activities.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_activities.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -102,35 +236,50 @@ async fn fetch_activities(
         }
 
         let fetched_activities =
-            fetch_activities_paginated(per_page, page_no, db.get_ref()).await?;
+            fetch_activities_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_activities = Activity::count(db.get_ref())
-            .await
-            .context("Failed to count the activities from the database")?;
+        let total_activities = match fetched_activities.total {
+            Some(total_activities) => total_activities,
+            None => Activity::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the activities from the database")?,
+        };
 
+        let pagination = Pagination::new(total_activities, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_activities,
-                pagination: Pagination::new(total_activities, page_no, per_page),
+                data: fieldset.select_all(fetched_activities.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_activities = fetch_all_activities(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let activities = Activity::stream_all(&list_filter, db);
+            fieldset.select_stream(activities)
+        }));
+    }
+
+    let fetched_activities = fetch_all_activities(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_activities,
+            data: fieldset.select_all(fetched_activities),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_activities(db: &Pool<Postgres>) -> Result<Vec<Activity>, ServiceError> {
-    let fetched_activities = Activity::select_all(db)
+async fn fetch_all_activities(
+    list_filter: &ListFilter<ActivityFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Activity>, ServiceError> {
+    let fetched_activities = Activity::select_all(list_filter, db)
         .await
         .context("Failed to fetch the activities from the database")?;
     Ok(fetched_activities)
@@ -139,14 +288,59 @@ async fn fetch_all_activities(db: &Pool<Postgres>) -> Result<Vec<Activity>, Serv
 async fn fetch_activities_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<ActivityFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Activity>, ServiceError> {
+) -> Result<Page<Activity>, ServiceError> {
     let fetched_activities = Activity::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the activities from the database for the provided page")?;
 
-    Ok(fetched_activities.items)
+    Ok(fetched_activities)
+}
+
+async fn fetch_activities_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Activity>>,
+    list_filter: &ListFilter<ActivityFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Activity>, ServiceError> {
+    let fetched_activities = Activity::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the activities from the database for the provided cursor")?;
+
+    Ok(fetched_activities)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct ActivitySuggestionParams {
+    service_id: i32,
+    #[serde(default)]
+    prefix: String,
+    limit: Option<i64>,
+}
+
+#[get("/suggest/")]
+async fn suggest_activities(
+    _: Authorized<CatalogRead>,
+    Query(params): Query<ActivitySuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = parse_suggestion_limit(params.limit)?;
+
+    let suggested_activities =
+        Activity::select_suggestions(params.service_id, params.prefix, limit, db.get_ref())
+            .await
+            .context("Failed to fetch the activity suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_activities,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -157,13 +351,29 @@ struct ActivityManipulationParams {
     service_id: i32,
 }
 
+#[routes]
 #[get("/view/")]
+#[get("/{service_id}/{activity_number}/")]
 async fn fetch_activity(
-    Query(params): Query<ActivityManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ActivityManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "activity",
+        "activities",
+        vec![
+            params.activity_number.to_string(),
+            params.service_id.to_string(),
+        ],
+        &mut *transaction,
+    )
+    .await?;
+
     let fetched_activity =
-        Activity::select(params.activity_number, params.service_id, db.get_ref())
+        Activity::select(params.activity_number, params.service_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -174,9 +384,19 @@ async fn fetch_activity(
                 ),
             })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_activity,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_activity,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -189,14 +409,33 @@ struct UpdateActivityPartiallyPayload {
     price_per_hour: MaybeAbsent<BigDecimal>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{service_id}/{activity_number}/")]
 async fn update_activity_partially(
-    Query(params): Query<ActivityManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ActivityManipulationParams>,
     Json(payload): Json<UpdateActivityPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "activity",
+            "activities",
+            vec![
+                params.activity_number.to_string(),
+                params.service_id.to_string(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
     let activity_to_update =
-        Activity::select(params.activity_number, params.service_id, db.get_ref())
+        Activity::select(params.activity_number, params.service_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -213,7 +452,7 @@ async fn update_activity_partially(
         description: payload.description.into(),
         price_per_hour: payload.price_per_hour.into(),
     }
-    .update(activity_to_update, db.get_ref())
+    .update(activity_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -222,11 +461,19 @@ async fn update_activity_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("activities", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the activity from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_activity,
     }))
@@ -241,14 +488,33 @@ struct UpdateActivityCompletelyPayload {
     price_per_hour: BigDecimal,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{service_id}/{activity_number}/")]
 async fn update_activity_completely(
-    Query(params): Query<ActivityManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ActivityManipulationParams>,
     Json(payload): Json<UpdateActivityCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "activity",
+            "activities",
+            vec![
+                params.activity_number.to_string(),
+                params.service_id.to_string(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
     let activity_to_update =
-        Activity::select(params.activity_number, params.service_id, db.get_ref())
+        Activity::select(params.activity_number, params.service_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -265,7 +531,7 @@ async fn update_activity_completely(
         description: Some(payload.description),
         price_per_hour: Some(payload.price_per_hour),
     }
-    .update(activity_to_update, db.get_ref())
+    .update(activity_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -274,34 +540,93 @@ async fn update_activity_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("activities", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the activity from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_activity,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{service_id}/{activity_number}/")]
 async fn delete_activity(
-    Query(params): Query<ActivityManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ActivityManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "activity",
+        "activities",
+        vec![
+            params.activity_number.to_string(),
+            params.service_id.to_string(),
+        ],
+        &mut *transaction,
+    )
+    .await?;
+
     let deleted_activity =
-        Activity::delete(params.activity_number, params.service_id, db.get_ref())
+        Activity::delete(params.activity_number, params.service_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
                     ServiceError::ResourceNotFound("activity".to_string(), anyhow!(err))
                 }
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    ServiceError::DeleteConflictError(
+                        "The activity is still referenced by other resources".to_string(),
+                    )
+                }
                 _ => ServiceError::UnexpectedError(
                     anyhow!(err).context("Failed to get the activity to delete from the database"),
                 ),
             })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_activity,
     }))
 }
+
+#[routes]
+#[get("/dependencies/")]
+#[get("/{service_id}/{activity_number}/dependencies/")]
+async fn fetch_activity_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ActivityManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "activities".to_string(),
+        vec![
+            params.activity_number.to_string(),
+            params.service_id.to_string(),
+        ],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the activity from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -11,11 +14,26 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::activity_price::{ActivityPrice, InsertActivityPrice, UpdateActivityPrice},
+    models::activity_price::{
+        ActivityPrice, ActivityPriceFilter, InsertActivityPrice, UpdateActivityPrice,
+    },
+    models::dealership::Dealership,
+    models::domains::Rif,
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite, DealershipScope},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
@@ -28,28 +46,61 @@ pub fn configure(configuration: &mut ServiceConfig) {
         .service(delete_activity_price);
 }
 
+pub fn configure_nested(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_dealership_activities_prices)
+        .service(create_dealership_activity_price);
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct CreateActivityPricePayload {
     activity_number: i32,
     service_id: i32,
-    dealership_rif: String,
+    dealership_rif: Rif,
     price_per_hour: BigDecimal,
 }
 
 #[post("/")]
 async fn create_activity_price(
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
     Json(payload): Json<CreateActivityPricePayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_activity_price(payload, scope, audit, db).await
+}
+
+#[post("/{dealership_rif}/activities-prices/")]
+async fn create_dealership_activity_price(
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathJson(payload): PathJson<CreateActivityPricePayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_activity_price(payload, scope, audit, db).await
+}
+
+async fn insert_activity_price(
+    payload: CreateActivityPricePayload,
+    scope: DealershipScope,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(payload.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_activity_price = InsertActivityPrice {
         activity_number: payload.activity_number,
         service_id: payload.service_id,
-        dealership_rif: payload.dealership_rif,
+        dealership_rif: payload.dealership_rif.into(),
         price_per_hour: payload.price_per_hour,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -58,11 +109,19 @@ async fn create_activity_price(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("activities_prices", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the activity price into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_activity_price,
     }))
@@ -70,9 +129,100 @@ async fn create_activity_price(
 
 #[get("/")]
 async fn fetch_activities_prices(
+    _: Authorized<CatalogRead>,
+    scope: DealershipScope,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ActivityPriceFilter>()?;
+
+    list_activities_prices(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/{dealership_rif}/activities-prices/")]
+async fn fetch_dealership_activities_prices(
+    _: Authorized<CatalogRead>,
+    scope: DealershipScope,
+    dealership_rif: Path<Rif>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let dealership_rif = dealership_rif.into_inner();
+
+    scope.ensure_includes(dealership_rif.as_str())?;
+
+    Dealership::select(dealership_rif.clone().into(), false, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("dealership".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the dealership from the database"),
+            ),
+        })?;
+
+    let mut list_filter = pagination_params.list_filter::<ActivityPriceFilter>()?;
+    list_filter.conditions.dealership_rif.eq = Some(dealership_rif);
+
+    list_activities_prices(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_activities_prices(
+    pagination_params: PaginationParams,
+    list_filter: ListFilter<ActivityPriceFilter>,
+    scope: DealershipScope,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(ActivityPrice::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_activities_prices = fetch_activities_prices_after_cursor(
+            limit,
+            cursor,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_activities_prices.prev_cursor,
+            fetched_activities_prices.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_activities_prices.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -104,37 +254,60 @@ async fn fetch_activities_prices(
         }
 
         let fetched_activities_prices =
-            fetch_activities_prices_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_activities_prices = ActivityPrice::count(db.get_ref())
-            .await
-            .context("Failed to count the activities prices from the database")?;
-
+            fetch_activities_prices_paginated(
+            per_page,
+            page_no,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_activities_prices = match fetched_activities_prices.total {
+            Some(total_activities_prices) => total_activities_prices,
+            None => ActivityPrice::count(scope.dealership_rif, &list_filter, db.get_ref())
+                .await
+                .context("Failed to count the activities prices from the database")?,
+        };
+
+        let pagination = Pagination::new(total_activities_prices, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_activities_prices,
-                pagination: Pagination::new(total_activities_prices, page_no, per_page),
+                data: fieldset.select_all(fetched_activities_prices.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_activities_prices = fetch_all_activities_prices(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let activities_prices =
+                ActivityPrice::stream_all(scope.dealership_rif, &list_filter, db);
+            fieldset.select_stream(activities_prices)
+        }));
+    }
+
+    let fetched_activities_prices =
+        fetch_all_activities_prices(scope.dealership_rif, &list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_activities_prices,
+            data: fieldset.select_all(fetched_activities_prices),
         });
 
     Ok(response)
 }
 
 async fn fetch_all_activities_prices(
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<ActivityPriceFilter>,
     db: &Pool<Postgres>,
 ) -> Result<Vec<ActivityPrice>, ServiceError> {
-    let fetched_activities_prices = ActivityPrice::select_all(db)
+    let fetched_activities_prices = ActivityPrice::select_all(dealership_rif, list_filter, db)
         .await
         .context("Failed to fetch the activities prices from the database")?;
     Ok(fetched_activities_prices)
@@ -143,14 +316,35 @@ async fn fetch_all_activities_prices(
 async fn fetch_activities_prices_paginated(
     per_page: i64,
     page_no: i64,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<ActivityPriceFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<ActivityPrice>, ServiceError> {
+) -> Result<Page<ActivityPrice>, ServiceError> {
     let fetched_activities_prices = ActivityPrice::paginate(per_page)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the activities prices from the database for the provided page")?;
 
-    Ok(fetched_activities_prices.items)
+    Ok(fetched_activities_prices)
+}
+
+async fn fetch_activities_prices_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<ActivityPrice>>,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<ActivityPriceFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<ActivityPrice>, ServiceError> {
+    let fetched_activities_prices = ActivityPrice::paginate(limit)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the activities prices from the database for the provided cursor")?;
+
+    Ok(fetched_activities_prices)
 }
 
 #[derive(Deserialize)]
@@ -159,19 +353,39 @@ async fn fetch_activities_prices_paginated(
 struct ActivityPriceManipulationParams {
     activity_number: i32,
     service_id: i32,
-    dealership_rif: String,
+    dealership_rif: Rif,
 }
 
+#[routes]
 #[get("/view/")]
+#[get("/{dealership_rif}/{service_id}/{activity_number}/")]
 async fn fetch_activity_price(
-    Query(params): Query<ActivityPriceManipulationParams>,
+    _: Authorized<CatalogRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<ActivityPriceManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "activity price",
+        "activities_prices",
+        vec![
+            params.activity_number.to_string(),
+            params.service_id.to_string(),
+            params.dealership_rif.clone().into(),
+        ],
+        &mut *transaction,
+    )
+    .await?;
+
     let fetched_activity_price = ActivityPrice::select(
         params.activity_number,
         params.service_id,
-        params.dealership_rif,
-        db.get_ref(),
+        params.dealership_rif.into(),
+        &mut *transaction,
     )
     .await
     .map_err(|err| match &err {
@@ -183,9 +397,19 @@ async fn fetch_activity_price(
         ),
     })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_activity_price,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_activity_price,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -195,21 +419,49 @@ async fn fetch_activity_price(
 struct UpdateActivityPricePartiallyPayload {
     activity_number: MaybeAbsent<i32>,
     service_id: MaybeAbsent<i32>,
-    dealership_rif: MaybeAbsent<String>,
+    dealership_rif: MaybeAbsent<Rif>,
     price_per_hour: MaybeAbsent<BigDecimal>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{dealership_rif}/{service_id}/{activity_number}/")]
 async fn update_activity_price_partially(
-    Query(params): Query<ActivityPriceManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<ActivityPriceManipulationParams>,
     Json(payload): Json<UpdateActivityPricePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let new_dealership_rif: Option<String> = payload.dealership_rif.map(String::from).into();
+    if let Some(dealership_rif) = &new_dealership_rif {
+        scope.ensure_includes(dealership_rif)?;
+    }
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "activity price",
+            "activities_prices",
+            vec![
+                params.activity_number.to_string(),
+                params.service_id.to_string(),
+                params.dealership_rif.clone().into(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
     let activity_to_update = ActivityPrice::select(
         params.activity_number,
         params.service_id,
-        params.dealership_rif,
-        db.get_ref(),
+        params.dealership_rif.into(),
+        &mut *transaction,
     )
     .await
     .map_err(|err| match &err {
@@ -224,10 +476,10 @@ async fn update_activity_price_partially(
     let updated_activity_price = UpdateActivityPrice {
         activity_number: payload.activity_number.into(),
         service_id: payload.service_id.into(),
-        dealership_rif: payload.dealership_rif.into(),
+        dealership_rif: new_dealership_rif,
         price_per_hour: payload.price_per_hour.into(),
     }
-    .update(activity_to_update, db.get_ref())
+    .update(activity_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -236,11 +488,19 @@ async fn update_activity_price_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("activities_prices", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the activity price from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_activity_price,
     }))
@@ -252,21 +512,45 @@ async fn update_activity_price_partially(
 struct UpdateActivityPriceCompletelyPayload {
     activity_number: i32,
     service_id: i32,
-    dealership_rif: String,
+    dealership_rif: Rif,
     price_per_hour: BigDecimal,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{dealership_rif}/{service_id}/{activity_number}/")]
 async fn update_activity_price_completely(
-    Query(params): Query<ActivityPriceManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<ActivityPriceManipulationParams>,
     Json(payload): Json<UpdateActivityPriceCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+    scope.ensure_includes(payload.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "activity price",
+            "activities_prices",
+            vec![
+                params.activity_number.to_string(),
+                params.service_id.to_string(),
+                params.dealership_rif.clone().into(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
     let activity_to_update = ActivityPrice::select(
         params.activity_number,
         params.service_id,
-        params.dealership_rif,
-        db.get_ref(),
+        params.dealership_rif.into(),
+        &mut *transaction,
     )
     .await
     .map_err(|err| match &err {
@@ -281,10 +565,10 @@ async fn update_activity_price_completely(
     let updated_activity_price = UpdateActivityPrice {
         activity_number: Some(payload.activity_number),
         service_id: Some(payload.service_id),
-        dealership_rif: Some(payload.dealership_rif),
+        dealership_rif: Some(payload.dealership_rif.into()),
         price_per_hour: Some(payload.price_per_hour),
     }
-    .update(activity_to_update, db.get_ref())
+    .update(activity_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -293,26 +577,43 @@ async fn update_activity_price_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("activities_prices", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the activity price from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_activity_price,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{dealership_rif}/{service_id}/{activity_number}/")]
 async fn delete_activity_price(
-    Query(params): Query<ActivityPriceManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<ActivityPriceManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let deleted_activity_price = ActivityPrice::delete(
         params.activity_number,
         params.service_id,
-        params.dealership_rif,
-        db.get_ref(),
+        params.dealership_rif.into(),
+        &mut *transaction,
     )
     .await
     .map_err(|err| match &err {
@@ -324,6 +625,11 @@ async fn delete_activity_price(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_activity_price,
     }))
@@ -0,0 +1,164 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, LINK},
+        StatusCode,
+    },
+    web::{Data, ServiceConfig},
+    HttpResponse,
+};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::audit_entry::{AuditEntry, AuditEntryFilter},
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        authorization::{AuditRead, Authorized},
+        extractors::Query,
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+const DEFAULT_AUDIT_ENTRIES_PER_PAGE: i64 = 50;
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration.service(fetch_audit_entries);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AuditEntriesParams {
+    resource: String,
+    key: Option<String>,
+    #[serde(flatten)]
+    pagination_params: PaginationParams,
+}
+
+#[get("/")]
+async fn fetch_audit_entries(
+    _: Authorized<AuditRead>,
+    Query(params): Query<AuditEntriesParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let pagination_params = params.pagination_params;
+    let mut list_filter = pagination_params.list_filter::<AuditEntryFilter>()?;
+    list_filter.conditions.resource.eq = Some(params.resource);
+    list_filter.conditions.resource_key.eq = params.key;
+    let fieldset = pagination_params.fieldset(AuditEntry::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_audit_entries =
+            fetch_audit_entries_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_audit_entries.prev_cursor,
+            fetched_audit_entries.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_audit_entries.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    let (per_page, page_no) = match (pagination_params.per_page, pagination_params.page_no) {
+        (None, None) if response_format != ResponseFormat::Ndjson => {
+            (Some(DEFAULT_AUDIT_ENTRIES_PER_PAGE), Some(1))
+        }
+        pagination => pagination,
+    };
+
+    if per_page.is_some() && page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if per_page.is_none() && page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (per_page, page_no) {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_audit_entries =
+            fetch_audit_entries_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
+
+        let total_audit_entries = match fetched_audit_entries.total {
+            Some(total_audit_entries) => total_audit_entries,
+            None => AuditEntry::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the audit entries from the database")?,
+        };
+
+        let pagination = Pagination::new(total_audit_entries, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_audit_entries.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    Ok(ndjson_response(db, move |db| {
+        let audit_entries = AuditEntry::stream_all(&list_filter, db);
+        fieldset.select_stream(audit_entries)
+    }))
+}
+
+async fn fetch_audit_entries_paginated(
+    per_page: i64,
+    page_no: i64,
+    list_filter: &ListFilter<AuditEntryFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<AuditEntry>, ServiceError> {
+    let fetched_audit_entries = AuditEntry::paginate(per_page)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context("Failed to fetch the audit entries from the database for the provided page")?;
+
+    Ok(fetched_audit_entries)
+}
+
+async fn fetch_audit_entries_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<AuditEntry>>,
+    list_filter: &ListFilter<AuditEntryFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<AuditEntry>, ServiceError> {
+    let fetched_audit_entries = AuditEntry::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the audit entries from the database for the provided cursor")?;
+
+    Ok(fetched_audit_entries)
+}
@@ -0,0 +1,162 @@
+use actix_web::{
+    get, post,
+    web::{self, Data, ServiceConfig},
+    Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::domains::NationalId,
+    models::employee::Employee,
+    models::staff_account::StaffAccount,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::authentication::{
+        verify_password, AuthenticatedEmployee, TokenKeys, TokenKind, DUMMY_PASSWORD_HASH,
+    },
+    utils::extractors::Json,
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(login)
+        .service(refresh)
+        .service(fetch_authenticated_staff_account);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct LoginPayload {
+    national_id: NationalId,
+    password: String,
+}
+
+#[post("/login/")]
+async fn login(
+    Json(payload): Json<LoginPayload>,
+    db: Data<Pool<Postgres>>,
+    token_keys: Data<TokenKeys>,
+) -> Result<impl Responder, ServiceError> {
+    let staff_account = match StaffAccount::select(payload.national_id.into(), db.get_ref()).await {
+        Ok(staff_account) => Some(staff_account),
+        Err(sqlx::Error::RowNotFound) => None,
+        Err(err) => {
+            return Err(ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the staff account from the database"),
+            ))
+        }
+    };
+
+    let password_hash = staff_account
+        .as_ref()
+        .map_or(DUMMY_PASSWORD_HASH.to_string(), |staff_account| {
+            staff_account.password_hash.clone()
+        });
+    let is_valid_password = web::block(move || verify_password(&payload.password, &password_hash))
+        .await
+        .context("Failed to verify the password")?
+        .context("Failed to verify the password")?;
+
+    let staff_account = match staff_account {
+        Some(staff_account) if is_valid_password => staff_account,
+        _ => {
+            return Err(ServiceError::AuthenticationError(
+                "Invalid nationalId or password".to_string(),
+                anyhow!("The nationalId or password does not match"),
+            ))
+        }
+    };
+
+    ensure_active_employee(
+        &staff_account.national_id,
+        "Invalid nationalId or password",
+        db.get_ref(),
+    )
+    .await?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: token_keys.issue(&staff_account.national_id)?,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct RefreshPayload {
+    refresh_token: String,
+}
+
+#[post("/refresh/")]
+async fn refresh(
+    Json(payload): Json<RefreshPayload>,
+    db: Data<Pool<Postgres>>,
+    token_keys: Data<TokenKeys>,
+) -> Result<impl Responder, ServiceError> {
+    let claims = token_keys.verify(&payload.refresh_token, TokenKind::Refresh)?;
+
+    let staff_account = StaffAccount::select(claims.sub, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => ServiceError::AuthenticationError(
+                "The staff account of the provided token no longer exists".to_string(),
+                anyhow!(err),
+            ),
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the staff account from the database"),
+            ),
+        })?;
+
+    ensure_active_employee(
+        &staff_account.national_id,
+        "The staff account of the provided token no longer exists",
+        db.get_ref(),
+    )
+    .await?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: token_keys.issue(&staff_account.national_id)?,
+    }))
+}
+
+#[get("/me/")]
+async fn fetch_authenticated_staff_account(
+    employee: AuthenticatedEmployee,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_staff_account = StaffAccount::select(employee.national_id, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("staff account".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the staff account from the database"),
+            ),
+        })?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_staff_account,
+    }))
+}
+
+async fn ensure_active_employee(
+    national_id: &str,
+    message: &str,
+    db: &Pool<Postgres>,
+) -> Result<(), ServiceError> {
+    Employee::select(national_id.to_string(), false, db)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::AuthenticationError(message.to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the employee from the database"),
+            ),
+        })?;
+
+    Ok(())
+}
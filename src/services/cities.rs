@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,21 +13,39 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::city::{City, InsertCity, UpdateCity},
+    models::city::{City, CityFilter, InsertCity, UpdateCity},
+    models::dependency::Dependency,
+    models::state::State,
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    services::suggestion_params::parse_suggestion_limit,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_cities)
+        .service(suggest_cities)
+        .service(fetch_city_dependencies)
         .service(fetch_city)
         .service(create_city)
         .service(update_city_partially)
         .service(update_city_completely)
-        .service(delete_city);
+        .service(delete_city)
+        .service(fetch_state_cities)
+        .service(create_state_city);
 }
 
 #[derive(Deserialize)]
@@ -37,14 +58,36 @@ struct CreateCityPayload {
 
 #[post("/cities/")]
 async fn create_city(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateCityPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    insert_city(payload, audit, db).await
+}
+
+#[post("/states/{state_id}/cities/")]
+async fn create_state_city(
+    _: Authorized<CatalogWrite>,
+    PathJson(payload): PathJson<CreateCityPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_city(payload, audit, db).await
+}
+
+async fn insert_city(
+    payload: CreateCityPayload,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_city = InsertCity {
         name: payload.name,
         state_id: payload.state_id,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -58,14 +101,97 @@ async fn create_city(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: created_city }))
 }
 
 #[get("/cities/")]
 async fn fetch_cities(
+    _: Authorized<CatalogRead>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<CityFilter>()?;
+
+    list_cities(
+        pagination_params,
+        list_filter,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/states/{state_id}/cities/")]
+async fn fetch_state_cities(
+    _: Authorized<CatalogRead>,
+    state_id: Path<i32>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let state_id = state_id.into_inner();
+
+    State::select(state_id, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the state from the database"),
+            ),
+        })?;
+
+    let mut list_filter = pagination_params.list_filter::<CityFilter>()?;
+    list_filter.conditions.state_id.eq = Some(state_id);
+
+    list_cities(
+        pagination_params,
+        list_filter,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_cities(
+    pagination_params: PaginationParams,
+    list_filter: ListFilter<CityFilter>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(City::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_cities =
+            fetch_cities_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_cities.prev_cursor,
+            fetched_cities.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_cities.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -96,35 +222,51 @@ async fn fetch_cities(
             ));
         }
 
-        let fetched_cities = fetch_cities_paginated(per_page, page_no, db.get_ref()).await?;
+        let fetched_cities =
+            fetch_cities_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_cities = City::count(db.get_ref())
-            .await
-            .context("Failed to count the cities from the database")?;
+        let total_cities = match fetched_cities.total {
+            Some(total_cities) => total_cities,
+            None => City::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the cities from the database")?,
+        };
 
+        let pagination = Pagination::new(total_cities, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_cities,
-                pagination: Pagination::new(total_cities, page_no, per_page),
+                data: fieldset.select_all(fetched_cities.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_cities = fetch_all_cities(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let cities = City::stream_all(&list_filter, db);
+            fieldset.select_stream(cities)
+        }));
+    }
+
+    let fetched_cities = fetch_all_cities(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_cities,
+            data: fieldset.select_all(fetched_cities),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_cities(db: &Pool<Postgres>) -> Result<Vec<City>, ServiceError> {
-    let fetched_cities = City::select_all(db)
+async fn fetch_all_cities(
+    list_filter: &ListFilter<CityFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<City>, ServiceError> {
+    let fetched_cities = City::select_all(list_filter, db)
         .await
         .context("Failed to fetch the cities from the database")?;
     Ok(fetched_cities)
@@ -133,14 +275,59 @@ async fn fetch_all_cities(db: &Pool<Postgres>) -> Result<Vec<City>, ServiceError
 async fn fetch_cities_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<CityFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<City>, ServiceError> {
+) -> Result<Page<City>, ServiceError> {
     let fetched_cities = City::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the cities from the database for the provided page")?;
 
-    Ok(fetched_cities.items)
+    Ok(fetched_cities)
+}
+
+async fn fetch_cities_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<City>>,
+    list_filter: &ListFilter<CityFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<City>, ServiceError> {
+    let fetched_cities = City::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the cities from the database for the provided cursor")?;
+
+    Ok(fetched_cities)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct CitySuggestionParams {
+    state_id: i32,
+    #[serde(default)]
+    prefix: String,
+    limit: Option<i64>,
+}
+
+#[get("/cities/suggest/")]
+async fn suggest_cities(
+    _: Authorized<CatalogRead>,
+    Query(params): Query<CitySuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = parse_suggestion_limit(params.limit)?;
+
+    let suggested_cities =
+        City::select_suggestions(params.state_id, params.prefix, limit, db.get_ref())
+            .await
+            .context("Failed to fetch the city suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_cities,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -151,12 +338,25 @@ struct CityManipulationParams {
     state_id: i32,
 }
 
+#[routes]
 #[get("/cities/view/")]
+#[get("/cities/{state_id}/{city_number}/")]
 async fn fetch_city(
-    Query(params): Query<CityManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<CityManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_city = City::select(params.city_number, params.state_id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "city",
+        "cities",
+        vec![params.city_number.to_string(), params.state_id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_city = City::select(params.city_number, params.state_id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -167,7 +367,17 @@ async fn fetch_city(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto { data: fetched_city }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto { data: fetched_city });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -179,13 +389,29 @@ struct UpdateCityPartiallyPayload {
     state_id: MaybeAbsent<i32>,
 }
 
+#[routes]
 #[patch("/cities/")]
+#[patch("/cities/{state_id}/{city_number}/")]
 async fn update_city_partially(
-    Query(params): Query<CityManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<CityManipulationParams>,
     Json(payload): Json<UpdateCityPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update = City::select(params.city_number, params.state_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "city",
+            "cities",
+            vec![params.city_number.to_string(), params.state_id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = City::select(params.city_number, params.state_id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -200,7 +426,7 @@ async fn update_city_partially(
         name: payload.name.into(),
         state_id: payload.state_id.into(),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -214,6 +440,11 @@ async fn update_city_partially(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: updated_city }))
 }
 
@@ -225,13 +456,29 @@ struct UpdateCityCompletelyPayload {
     state_id: i32,
 }
 
+#[routes]
 #[put("/cities/")]
+#[put("/cities/{state_id}/{city_number}/")]
 async fn update_city_completely(
-    Query(params): Query<CityManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<CityManipulationParams>,
     Json(payload): Json<UpdateCityCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update = City::select(params.city_number, params.state_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "city",
+            "cities",
+            vec![params.city_number.to_string(), params.state_id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = City::select(params.city_number, params.state_id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -246,7 +493,7 @@ async fn update_city_completely(
         name: Some(payload.name),
         state_id: Some(payload.state_id),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -260,24 +507,74 @@ async fn update_city_completely(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: updated_city }))
 }
 
+#[routes]
 #[delete("/cities/")]
+#[delete("/cities/{state_id}/{city_number}/")]
 async fn delete_city(
-    Query(params): Query<CityManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<CityManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_city = City::delete(params.city_number, params.state_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "city",
+        "cities",
+        vec![params.city_number.to_string(), params.state_id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_city = City::delete(params.city_number, params.state_id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
                 ServiceError::ResourceNotFound("city".to_string(), anyhow!(err))
             }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ServiceError::DeleteConflictError(
+                    "The city is still referenced by other resources".to_string(),
+                )
+            }
             _ => ServiceError::UnexpectedError(
                 anyhow!(err).context("Failed to fetch the city to delete from the database"),
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: deleted_city }))
 }
+
+#[routes]
+#[get("/cities/dependencies/")]
+#[get("/cities/{state_id}/{city_number}/dependencies/")]
+async fn fetch_city_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<CityManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "cities".to_string(),
+        vec![params.city_number.to_string(), params.state_id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the city from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
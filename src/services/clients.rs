@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,47 +13,67 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::client::{Client, InsertClient, UpdateClient},
-    services::pagination_params::PaginationParams,
+    models::client::{Client, ClientFilter, InsertClient, UpdateClient},
+    models::dependency::Dependency,
+    models::domains::{Email, NationalId, PhoneNo},
+    services::pagination_params::SoftDeletablePaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, ClientsRead, ClientsWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_soft_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_clients)
+        .service(fetch_client_dependencies)
         .service(fetch_client)
         .service(create_client)
         .service(update_client_partially)
         .service(update_client_completely)
-        .service(delete_client);
+        .service(delete_client)
+        .service(restore_client);
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct CreateClientPayload {
-    national_id: String,
+    national_id: NationalId,
     full_name: String,
-    main_phone_no: String,
-    secondary_phone_no: String,
-    email: String,
+    main_phone_no: PhoneNo,
+    secondary_phone_no: PhoneNo,
+    email: Email,
 }
 
 #[post("/clients/")]
 async fn create_client(
+    _: Authorized<ClientsWrite>,
     Json(payload): Json<CreateClientPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_client = InsertClient {
-        national_id: payload.national_id,
+        national_id: payload.national_id.into(),
         full_name: payload.full_name,
-        main_phone_no: payload.main_phone_no,
-        secondary_phone_no: payload.secondary_phone_no,
-        email: payload.email,
+        main_phone_no: payload.main_phone_no.into(),
+        secondary_phone_no: payload.secondary_phone_no.into(),
+        email: payload.email.into(),
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -59,11 +82,19 @@ async fn create_client(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("clients", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to create the client from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_client,
     }))
@@ -71,9 +102,40 @@ async fn create_client(
 
 #[get("/clients/")]
 async fn fetch_clients(
-    Query(pagination_params): Query<PaginationParams>,
+    _: Authorized<ClientsRead>,
+    Query(pagination_params): Query<SoftDeletablePaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ClientFilter>()?;
+    let fieldset = pagination_params.fieldset(Client::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_clients = fetch_clients_after_cursor(
+            limit,
+            cursor,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_clients.prev_cursor,
+            fetched_clients.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_clients.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -104,35 +166,67 @@ async fn fetch_clients(
             ));
         }
 
-        let fetched_clients = fetch_clients_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_clients = Client::count(db.get_ref())
+        let fetched_clients = fetch_clients_paginated(
+            per_page,
+            page_no,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_clients = match fetched_clients.total {
+            Some(total_clients) => total_clients,
+            None => Client::count(
+                pagination_params.include_deleted,
+                &list_filter,
+                db.get_ref(),
+            )
             .await
-            .context("Failed to count the clients from the database")?;
+            .context("Failed to count the clients from the database")?,
+        };
 
+        let pagination = Pagination::new(total_clients, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_clients,
-                pagination: Pagination::new(total_clients, page_no, per_page),
+                data: fieldset.select_all(fetched_clients.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_clients = fetch_all_clients(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let clients = Client::stream_all(pagination_params.include_deleted, &list_filter, db);
+            fieldset.select_stream(clients)
+        }));
+    }
+
+    let fetched_clients = fetch_all_clients(
+        pagination_params.include_deleted,
+        &list_filter,
+        db.get_ref(),
+    )
+    .await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_clients,
+            data: fieldset.select_all(fetched_clients),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_clients(db: &Pool<Postgres>) -> Result<Vec<Client>, ServiceError> {
-    let fetched_clients = Client::select_all(db)
+async fn fetch_all_clients(
+    include_deleted: bool,
+    list_filter: &ListFilter<ClientFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Client>, ServiceError> {
+    let fetched_clients = Client::select_all(include_deleted, list_filter, db)
         .await
         .context("Failed to fetch the clients from the database")?;
     Ok(fetched_clients)
@@ -141,42 +235,99 @@ async fn fetch_all_clients(db: &Pool<Postgres>) -> Result<Vec<Client>, ServiceEr
 async fn fetch_clients_paginated(
     per_page: i64,
     page_no: i64,
+    include_deleted: bool,
+    list_filter: &ListFilter<ClientFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Client>, ServiceError> {
+) -> Result<Page<Client>, ServiceError> {
     let fetched_clients = Client::paginate(per_page)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the clients from the database for the provided page")?;
 
-    Ok(fetched_clients.items)
+    Ok(fetched_clients)
+}
+
+async fn fetch_clients_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Client>>,
+    include_deleted: bool,
+    list_filter: &ListFilter<ClientFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Client>, ServiceError> {
+    let fetched_clients = Client::paginate(limit)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the clients from the database for the provided cursor")?;
+
+    Ok(fetched_clients)
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 struct ClientManipulationParams {
-    national_id: String,
+    national_id: NationalId,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct ClientViewParams {
+    national_id: NationalId,
+    #[serde(default)]
+    include_deleted: bool,
+}
+
+#[routes]
 #[get("/clients/view/")]
+#[get("/clients/{national_id}/")]
 async fn fetch_client(
-    Query(params): Query<ClientManipulationParams>,
+    _: Authorized<ClientsRead>,
+    PathQuery(params): PathQuery<ClientViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_client = Client::select(params.national_id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "client",
+        "clients",
+        vec![params.national_id.clone().into()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_client = Client::select(
+        params.national_id.into(),
+        params.include_deleted,
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("client".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the client from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
         .await
-        .map_err(|err| match &err {
-            sqlx::Error::RowNotFound => {
-                ServiceError::ResourceNotFound("client".to_string(), anyhow!(err))
-            }
-            _ => ServiceError::UnexpectedError(
-                anyhow!(err).context("Failed to fetch the client from the database"),
-            ),
-        })?;
+        .context("Failed to commit the transaction")?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_client,
-    }))
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_client,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -184,20 +335,36 @@ async fn fetch_client(
 #[serde(deny_unknown_fields)]
 #[serde(default)]
 struct UpdateClientPartiallyPayload {
-    national_id: MaybeAbsent<String>,
+    national_id: MaybeAbsent<NationalId>,
     full_name: MaybeAbsent<String>,
-    main_phone_no: MaybeAbsent<String>,
-    secondary_phone_no: MaybeAbsent<String>,
-    email: MaybeAbsent<String>,
+    main_phone_no: MaybeAbsent<PhoneNo>,
+    secondary_phone_no: MaybeAbsent<PhoneNo>,
+    email: MaybeAbsent<Email>,
 }
 
+#[routes]
 #[patch("/clients/")]
+#[patch("/clients/{national_id}/")]
 async fn update_client_partially(
-    Query(params): Query<ClientManipulationParams>,
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<ClientManipulationParams>,
     Json(payload): Json<UpdateClientPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update = Client::select(params.national_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "client",
+            "clients",
+            vec![params.national_id.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Client::select(params.national_id.into(), false, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -209,13 +376,13 @@ async fn update_client_partially(
         })?;
 
     let updated_client = UpdateClient {
-        national_id: payload.national_id.into(),
+        national_id: payload.national_id.map(String::from).into(),
         full_name: payload.full_name.into(),
-        main_phone_no: payload.main_phone_no.into(),
-        secondary_phone_no: payload.secondary_phone_no.into(),
-        email: payload.email.into(),
+        main_phone_no: payload.main_phone_no.map(String::from).into(),
+        secondary_phone_no: payload.secondary_phone_no.map(String::from).into(),
+        email: payload.email.map(String::from).into(),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -224,11 +391,19 @@ async fn update_client_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("clients", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the client from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_client,
     }))
@@ -238,20 +413,36 @@ async fn update_client_partially(
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct UpdateClientCompletelyPayload {
-    national_id: String,
+    national_id: NationalId,
     full_name: String,
-    main_phone_no: String,
-    secondary_phone_no: String,
-    email: String,
+    main_phone_no: PhoneNo,
+    secondary_phone_no: PhoneNo,
+    email: Email,
 }
 
+#[routes]
 #[put("/clients/")]
+#[put("/clients/{national_id}/")]
 async fn update_client_completely(
-    Query(params): Query<ClientManipulationParams>,
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<ClientManipulationParams>,
     Json(payload): Json<UpdateClientCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update = Client::select(params.national_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "client",
+            "clients",
+            vec![params.national_id.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Client::select(params.national_id.into(), false, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -263,13 +454,13 @@ async fn update_client_completely(
         })?;
 
     let updated_client = UpdateClient {
-        national_id: Some(payload.national_id),
+        national_id: Some(payload.national_id.into()),
         full_name: Some(payload.full_name),
-        main_phone_no: Some(payload.main_phone_no),
-        secondary_phone_no: Some(payload.secondary_phone_no),
-        email: Some(payload.email),
+        main_phone_no: Some(payload.main_phone_no.into()),
+        secondary_phone_no: Some(payload.secondary_phone_no.into()),
+        email: Some(payload.email.into()),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -278,22 +469,45 @@ async fn update_client_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("clients", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the client from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_client,
     }))
 }
 
+#[routes]
 #[delete("/clients/")]
+#[delete("/clients/{national_id}/")]
 async fn delete_client(
-    Query(params): Query<ClientManipulationParams>,
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<ClientManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_client = Client::delete(params.national_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_soft_deletable(
+        "client",
+        "clients",
+        vec![params.national_id.clone().into()],
+        &["vehicles"],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_client = Client::delete(params.national_id.into(), &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -304,7 +518,65 @@ async fn delete_client(
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_client,
     }))
 }
+
+#[routes]
+#[post("/clients/restore/")]
+#[post("/clients/{national_id}/restore/")]
+async fn restore_client(
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<ClientManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let restored_client = Client::restore(params.national_id.into(), &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("deleted client".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to restore the client from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: restored_client,
+    }))
+}
+
+#[routes]
+#[get("/clients/dependencies/")]
+#[get("/clients/{national_id}/dependencies/")]
+async fn fetch_client_dependencies(
+    _: Authorized<ClientsRead>,
+    PathQuery(params): PathQuery<ClientManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "clients".to_string(),
+        vec![params.national_id.into()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the client from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,31 +13,47 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::dealership::{Dealership, InsertDealership, UpdateDealership},
-    services::pagination_params::PaginationParams,
+    models::dealership::{Dealership, DealershipFilter, InsertDealership, UpdateDealership},
+    models::dependency::Dependency,
+    models::domains::Rif,
+    services::pagination_params::SoftDeletablePaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
+    services::suggestion_params::SuggestionParams,
     utils::{
+        audit::AuditContext,
+        authorization::{Authorized, DealershipsRead, DealershipsWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_soft_deletable,
         deserialization::MaybeAbsent,
-        pagination::Paginable,
+        expansion::{Expandable, Expanded, Expansion},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
     },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_dealerships)
+        .service(suggest_dealerships)
+        .service(fetch_dealership_dependencies)
         .service(fetch_dealership)
         .service(create_dealership)
         .service(update_dealership_partially)
         .service(update_dealership_completely)
-        .service(delete_dealership);
+        .service(delete_dealership)
+        .service(restore_dealership);
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct CreateDealershipPayload {
-    rif: String,
+    rif: Rif,
     name: String,
     city_number: i32,
     state_id: i32
@@ -42,16 +61,20 @@ struct CreateDealershipPayload {
 
 #[post("/")]
 async fn create_dealership(
+    _: Authorized<DealershipsWrite>,
     Json(payload): Json<CreateDealershipPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_dealership = InsertDealership {
-        rif: payload.rif,
+        rif: payload.rif.into(),
         name: payload.name,
         city_number: payload.city_number,
         state_id: payload.state_id
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -66,11 +89,19 @@ async fn create_dealership(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("dealerships", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the dealership into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_dealership,
     }))
@@ -78,9 +109,44 @@ async fn create_dealership(
 
 #[get("/")]
 async fn fetch_dealerships(
-    Query(pagination_params): Query<PaginationParams>,
+    _: Authorized<DealershipsRead>,
+    Query(pagination_params): Query<SoftDeletablePaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<DealershipFilter>()?;
+    let fieldset = pagination_params.fieldset(Dealership::FIELDS)?;
+    let expansion = pagination_params.expansion(Dealership::RELATIONS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_dealerships = fetch_dealerships_after_cursor(
+            limit,
+            cursor,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let expanded_dealerships =
+            expand_dealerships(fetched_dealerships.items, &expansion, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_dealerships.prev_cursor,
+            fetched_dealerships.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(expanded_dealerships),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -111,36 +177,71 @@ async fn fetch_dealerships(
             ));
         }
 
-        let fetched_dealerships =
-            fetch_dealerships_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_dealerships = Dealership::count(db.get_ref())
+        let fetched_dealerships = fetch_dealerships_paginated(
+            per_page,
+            page_no,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_dealerships = match fetched_dealerships.total {
+            Some(total_dealerships) => total_dealerships,
+            None => Dealership::count(
+                pagination_params.include_deleted,
+                &list_filter,
+                db.get_ref(),
+            )
             .await
-            .context("Failed to count the dealerships from the database")?;
+            .context("Failed to count the dealerships from the database")?,
+        };
 
+        let expanded_dealerships =
+            expand_dealerships(fetched_dealerships.items, &expansion, db.get_ref()).await?;
+
+        let pagination = Pagination::new(total_dealerships, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_dealerships,
-                pagination: Pagination::new(total_dealerships, page_no, per_page),
+                data: fieldset.select_all(expanded_dealerships),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_dealerships = fetch_all_dealerships(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        expansion.ensure_streamable()?;
+
+        return Ok(ndjson_response(db, move |db| {
+            let dealerships =
+                Dealership::stream_all(pagination_params.include_deleted, &list_filter, db);
+            fieldset.select_stream(dealerships)
+        }));
+    }
+
+    let fetched_dealerships =
+        fetch_all_dealerships(pagination_params.include_deleted, &list_filter, db.get_ref()).await?;
+    let expanded_dealerships =
+        expand_dealerships(fetched_dealerships, &expansion, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_dealerships,
+            data: fieldset.select_all(expanded_dealerships),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_dealerships(db: &Pool<Postgres>) -> Result<Vec<Dealership>, ServiceError> {
-    let fetched_dealerships = Dealership::select_all(db)
+async fn fetch_all_dealerships(
+    include_deleted: bool,
+    list_filter: &ListFilter<DealershipFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Dealership>, ServiceError> {
+    let fetched_dealerships = Dealership::select_all(include_deleted, list_filter, db)
         .await
         .context("Failed to fetch the dealerships from the database")?;
     Ok(fetched_dealerships)
@@ -149,29 +250,104 @@ async fn fetch_all_dealerships(db: &Pool<Postgres>) -> Result<Vec<Dealership>, S
 async fn fetch_dealerships_paginated(
     per_page: i64,
     page_no: i64,
+    include_deleted: bool,
+    list_filter: &ListFilter<DealershipFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Dealership>, ServiceError> {
+) -> Result<Page<Dealership>, ServiceError> {
     let fetched_dealerships = Dealership::paginate(per_page)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the dealerships from the database for the provided page")?;
 
-    Ok(fetched_dealerships.items)
+    Ok(fetched_dealerships)
+}
+
+async fn fetch_dealerships_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Dealership>>,
+    include_deleted: bool,
+    list_filter: &ListFilter<DealershipFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Dealership>, ServiceError> {
+    let fetched_dealerships = Dealership::paginate(limit)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the dealerships from the database for the provided cursor")?;
+
+    Ok(fetched_dealerships)
+}
+
+async fn expand_dealerships(
+    dealerships: Vec<Dealership>,
+    expansion: &Expansion,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Expanded<Dealership>>, ServiceError> {
+    let expanded_dealerships = Dealership::expand(dealerships, expansion, db)
+        .await
+        .context("Failed to fetch the related resources of the dealerships from the database")?;
+
+    Ok(expanded_dealerships)
+}
+
+#[get("/suggest/")]
+async fn suggest_dealerships(
+    _: Authorized<DealershipsRead>,
+    Query(params): Query<SuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = params.limit()?;
+
+    let suggested_dealerships = Dealership::select_suggestions(params.prefix, limit, db.get_ref())
+        .await
+        .context("Failed to fetch the dealership suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_dealerships,
+    }))
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 struct DealershipManipulationParams {
-    rif: String,
+    rif: Rif,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct DealershipViewParams {
+    rif: Rif,
+    #[serde(default)]
+    include_deleted: bool,
+    expand: Option<String>,
+}
+
+#[routes]
 #[get("/view/")]
+#[get("/{rif}/")]
 async fn fetch_dealership(
-    Query(params): Query<DealershipManipulationParams>,
+    _: Authorized<DealershipsRead>,
+    PathQuery(params): PathQuery<DealershipViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_dealership = Dealership::select(params.rif, db.get_ref())
+    let expansion = Expansion::parse(Dealership::RELATIONS, params.expand.as_deref())?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "dealership",
+        "dealerships",
+        vec![params.rif.clone().into()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_dealership = Dealership::select(params.rif.into(), params.include_deleted, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -182,9 +358,23 @@ async fn fetch_dealership(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_dealership,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let expanded_dealership = Dealership::expand_one(fetched_dealership, &expansion, db.get_ref())
+        .await
+        .context("Failed to fetch the related resources of the dealership from the database")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: expanded_dealership,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -192,20 +382,36 @@ async fn fetch_dealership(
 #[serde(deny_unknown_fields)]
 #[serde(default)]
 struct UpdateDealershipPartiallyPayload {
-    rif: MaybeAbsent<String>,
+    rif: MaybeAbsent<Rif>,
     name: MaybeAbsent<String>,
     city_number: MaybeAbsent<i32>,
     state_id: MaybeAbsent<i32>
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{rif}/")]
 async fn update_dealership_partially(
-    Query(params): Query<DealershipManipulationParams>,
+    _: Authorized<DealershipsWrite>,
+    PathQuery(params): PathQuery<DealershipManipulationParams>,
     Json(payload): Json<UpdateDealershipPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "dealership",
+            "dealerships",
+            vec![params.rif.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
     let dealership_to_update =
-        Dealership::select(params.rif, db.get_ref())
+        Dealership::select(params.rif.into(), false, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -218,12 +424,12 @@ async fn update_dealership_partially(
             })?;
 
     let updated_dealership = UpdateDealership {
-        rif: payload.rif.into(),
+        rif: payload.rif.map(String::from).into(),
         name: payload.name.into(),
         city_number: payload.city_number.into(),
         state_id: payload.state_id.into()
     }
-    .update(dealership_to_update, db.get_ref())
+    .update(dealership_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -238,11 +444,19 @@ async fn update_dealership_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("dealerships", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the dealership from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_dealership,
     }))
@@ -252,19 +466,35 @@ async fn update_dealership_partially(
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct UpdateDealershipCompletelyPayload {
-    rif: String,
+    rif: Rif,
     name: String,
     city_number: i32,
     state_id: i32
 }
 
+#[routes]
 #[put("/")]
+#[put("/{rif}/")]
 async fn update_dealership_completely(
-    Query(params): Query<DealershipManipulationParams>,
+    _: Authorized<DealershipsWrite>,
+    PathQuery(params): PathQuery<DealershipManipulationParams>,
     Json(payload): Json<UpdateDealershipCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update = Dealership::select(params.rif, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "dealership",
+            "dealerships",
+            vec![params.rif.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Dealership::select(params.rif.into(), false, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -276,12 +506,12 @@ async fn update_dealership_completely(
         })?;
 
     let updated_dealership = UpdateDealership {
-        rif: Some(payload.rif),
+        rif: Some(payload.rif.into()),
         name: Some(payload.name),
         city_number: Some(payload.city_number),
         state_id: Some(payload.state_id)
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -296,22 +526,45 @@ async fn update_dealership_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("dealerships", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the dealership from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_dealership,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{rif}/")]
 async fn delete_dealership(
-    Query(params): Query<DealershipManipulationParams>,
+    _: Authorized<DealershipsWrite>,
+    PathQuery(params): PathQuery<DealershipManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_dealership = Dealership::delete(params.rif, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_soft_deletable(
+        "dealership",
+        "dealerships",
+        vec![params.rif.clone().into()],
+        &["stock", "staff"],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_dealership = Dealership::delete(params.rif.into(), &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -322,7 +575,65 @@ async fn delete_dealership(
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_dealership,
     }))
 }
+
+#[routes]
+#[post("/restore/")]
+#[post("/{rif}/restore/")]
+async fn restore_dealership(
+    _: Authorized<DealershipsWrite>,
+    PathQuery(params): PathQuery<DealershipManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let restored_dealership = Dealership::restore(params.rif.into(), &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("deleted dealership".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to restore the dealership from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: restored_dealership,
+    }))
+}
+
+#[routes]
+#[get("/dependencies/")]
+#[get("/{rif}/dependencies/")]
+async fn fetch_dealership_dependencies(
+    _: Authorized<DealershipsRead>,
+    PathQuery(params): PathQuery<DealershipManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "dealerships".to_string(),
+        vec![params.rif.into()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the dealership from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
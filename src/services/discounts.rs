@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -11,11 +14,24 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::discount::{Discount, InsertDiscount, UpdateDiscount},
+    models::dealership::Dealership,
+    models::discount::{Discount, DiscountFilter, InsertDiscount, UpdateDiscount},
+    models::domains::Rif,
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite, DealershipScope},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
@@ -28,26 +44,59 @@ pub fn configure(configuration: &mut ServiceConfig) {
         .service(delete_discount);
 }
 
+pub fn configure_nested(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_dealership_discounts)
+        .service(create_dealership_discount);
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct CreateDiscountPayload {
-    dealership_rif: String,
+    dealership_rif: Rif,
     discount_percentage: BigDecimal,
     required_annual_service_usage_count: i16,
 }
 
 #[post("/")]
 async fn create_discount(
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
     Json(payload): Json<CreateDiscountPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_discount(payload, scope, audit, db).await
+}
+
+#[post("/{dealership_rif}/discounts/")]
+async fn create_dealership_discount(
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathJson(payload): PathJson<CreateDiscountPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    insert_discount(payload, scope, audit, db).await
+}
+
+async fn insert_discount(
+    payload: CreateDiscountPayload,
+    scope: DealershipScope,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(payload.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_discount = InsertDiscount {
-        dealership_rif: payload.dealership_rif,
+        dealership_rif: payload.dealership_rif.into(),
         discount_percentage: payload.discount_percentage,
         required_annual_service_usage_count: payload.required_annual_service_usage_count,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -56,11 +105,19 @@ async fn create_discount(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("discounts", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the discount into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_discount,
     }))
@@ -68,9 +125,100 @@ async fn create_discount(
 
 #[get("/")]
 async fn fetch_discounts(
+    _: Authorized<CatalogRead>,
+    scope: DealershipScope,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<DiscountFilter>()?;
+
+    list_discounts(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/{dealership_rif}/discounts/")]
+async fn fetch_dealership_discounts(
+    _: Authorized<CatalogRead>,
+    scope: DealershipScope,
+    dealership_rif: Path<Rif>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let dealership_rif = dealership_rif.into_inner();
+
+    scope.ensure_includes(dealership_rif.as_str())?;
+
+    Dealership::select(dealership_rif.clone().into(), false, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("dealership".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the dealership from the database"),
+            ),
+        })?;
+
+    let mut list_filter = pagination_params.list_filter::<DiscountFilter>()?;
+    list_filter.conditions.dealership_rif.eq = Some(dealership_rif);
+
+    list_discounts(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_discounts(
+    pagination_params: PaginationParams,
+    list_filter: ListFilter<DiscountFilter>,
+    scope: DealershipScope,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(Discount::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_discounts = fetch_discounts_after_cursor(
+            limit,
+            cursor,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_discounts.prev_cursor,
+            fetched_discounts.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_discounts.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -101,35 +249,59 @@ async fn fetch_discounts(
             ));
         }
 
-        let fetched_discounts = fetch_discounts_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_discounts = Discount::count(db.get_ref())
-            .await
-            .context("Failed to count the discounts from the database")?;
-
+        let fetched_discounts = fetch_discounts_paginated(
+            per_page,
+            page_no,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_discounts = match fetched_discounts.total {
+            Some(total_discounts) => total_discounts,
+            None => Discount::count(scope.dealership_rif, &list_filter, db.get_ref())
+                .await
+                .context("Failed to count the discounts from the database")?,
+        };
+
+        let pagination = Pagination::new(total_discounts, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_discounts,
-                pagination: Pagination::new(total_discounts, page_no, per_page),
+                data: fieldset.select_all(fetched_discounts.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_discounts = fetch_all_discounts(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let discounts = Discount::stream_all(scope.dealership_rif, &list_filter, db);
+            fieldset.select_stream(discounts)
+        }));
+    }
+
+    let fetched_discounts =
+        fetch_all_discounts(scope.dealership_rif, &list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_discounts,
+            data: fieldset.select_all(fetched_discounts),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_discounts(db: &Pool<Postgres>) -> Result<Vec<Discount>, ServiceError> {
-    let fetched_discounts = Discount::select_all(db)
+async fn fetch_all_discounts(
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<DiscountFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Discount>, ServiceError> {
+    let fetched_discounts = Discount::select_all(dealership_rif, list_filter, db)
         .await
         .context("Failed to fetch the discounts from the database")?;
     Ok(fetched_discounts)
@@ -138,14 +310,35 @@ async fn fetch_all_discounts(db: &Pool<Postgres>) -> Result<Vec<Discount>, Servi
 async fn fetch_discounts_paginated(
     per_page: i64,
     page_no: i64,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<DiscountFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Discount>, ServiceError> {
+) -> Result<Page<Discount>, ServiceError> {
     let fetched_discounts = Discount::paginate(per_page)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the discounts from the database for the provided page")?;
 
-    Ok(fetched_discounts.items)
+    Ok(fetched_discounts)
+}
+
+async fn fetch_discounts_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Discount>>,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<DiscountFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Discount>, ServiceError> {
+    let fetched_discounts = Discount::paginate(limit)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the discounts from the database for the provided cursor")?;
+
+    Ok(fetched_discounts)
 }
 
 #[derive(Deserialize)]
@@ -153,29 +346,61 @@ async fn fetch_discounts_paginated(
 #[serde(deny_unknown_fields)]
 struct DiscountManipulationParams {
     discount_number: i32,
-    dealership_rif: String,
+    dealership_rif: Rif,
 }
 
+#[routes]
 #[get("/view/")]
+#[get("/{dealership_rif}/{discount_number}/")]
 async fn fetch_discount(
-    Query(params): Query<DiscountManipulationParams>,
+    _: Authorized<CatalogRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<DiscountManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_discount =
-        Discount::select(params.discount_number, params.dealership_rif, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the discount from the database"),
-                ),
-            })?;
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "discount",
+        "discounts",
+        vec![
+            params.discount_number.to_string(),
+            params.dealership_rif.clone().into(),
+        ],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_discount = Discount::select(
+        params.discount_number,
+        params.dealership_rif.into(),
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the discount from the database"),
+        ),
+    })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_discount,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_discount,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -183,36 +408,65 @@ async fn fetch_discount(
 #[serde(deny_unknown_fields)]
 #[serde(default)]
 struct UpdateDiscountPartiallyPayload {
-    dealership_rif: MaybeAbsent<String>,
+    dealership_rif: MaybeAbsent<Rif>,
     discount_percentage: MaybeAbsent<BigDecimal>,
     required_annual_service_usage_count: MaybeAbsent<i16>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{dealership_rif}/{discount_number}/")]
 async fn update_discount_partially(
-    Query(params): Query<DiscountManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<DiscountManipulationParams>,
     Json(payload): Json<UpdateDiscountPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let dealership_to_update =
-        Discount::select(params.discount_number, params.dealership_rif, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err)
-                        .context("Failed to fetch the discount to update from the database"),
-                ),
-            })?;
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let new_dealership_rif: Option<String> = payload.dealership_rif.map(String::from).into();
+    if let Some(dealership_rif) = &new_dealership_rif {
+        scope.ensure_includes(dealership_rif)?;
+    }
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "discount",
+            "discounts",
+            vec![
+                params.discount_number.to_string(),
+                params.dealership_rif.clone().into(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
+    let dealership_to_update = Discount::select(
+        params.discount_number,
+        params.dealership_rif.into(),
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the discount to update from the database"),
+        ),
+    })?;
 
     let updated_discount = UpdateDiscount {
-        dealership_rif: payload.dealership_rif.into(),
+        dealership_rif: new_dealership_rif,
         discount_percentage: payload.discount_percentage.into(),
         required_annual_service_usage_count: payload.required_annual_service_usage_count.into(),
     }
-    .update(dealership_to_update, db.get_ref())
+    .update(dealership_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -221,11 +475,19 @@ async fn update_discount_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("discounts", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the dealership from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_discount,
     }))
@@ -235,36 +497,61 @@ async fn update_discount_partially(
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct UpdateDiscountCompletelyPayload {
-    dealership_rif: String,
+    dealership_rif: Rif,
     discount_percentage: BigDecimal,
     required_annual_service_usage_count: i16,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{dealership_rif}/{discount_number}/")]
 async fn update_discount_completely(
-    Query(params): Query<DiscountManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<DiscountManipulationParams>,
     Json(payload): Json<UpdateDiscountCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update =
-        Discount::select(params.discount_number, params.dealership_rif, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err)
-                        .context("Failed to fetch the discount to update from the database"),
-                ),
-            })?;
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+    scope.ensure_includes(payload.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "discount",
+            "discounts",
+            vec![
+                params.discount_number.to_string(),
+                params.dealership_rif.clone().into(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Discount::select(
+        params.discount_number,
+        params.dealership_rif.into(),
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the discount to update from the database"),
+        ),
+    })?;
 
     let updated_discount = UpdateDiscount {
-        dealership_rif: Some(payload.dealership_rif),
+        dealership_rif: Some(payload.dealership_rif.into()),
         discount_percentage: Some(payload.discount_percentage),
         required_annual_service_usage_count: Some(payload.required_annual_service_usage_count),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -273,33 +560,57 @@ async fn update_discount_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("discounts", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the discount from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_discount,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{dealership_rif}/{discount_number}/")]
 async fn delete_discount(
-    Query(params): Query<DiscountManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<DiscountManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_discount =
-        Discount::delete(params.discount_number, params.dealership_rif, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err)
-                        .context("Failed to fetch the discount to delete from the database"),
-                ),
-            })?;
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_discount = Discount::delete(
+        params.discount_number,
+        params.dealership_rif.into(),
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("discount".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the discount to delete from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
 
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_discount,
@@ -0,0 +1,87 @@
+use actix_web::{
+    get,
+    web::{Data, ServiceConfig},
+    Responder,
+};
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use time::Date;
+
+use crate::{
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::authorization::{Authorized, ReportsRead},
+    utils::extractors::{Json, Query},
+    utils::snapshot::begin_read_only_snapshot,
+    views::inventory_valuation::{
+        DealershipInventoryValuation, ProductInventoryValuation, SupplyLineInventoryValuation,
+    },
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration.service(fetch_inventory_valuation);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct FetchInventoryValuationParams {
+    as_of_date: Option<Date>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InventoryValuationDto {
+    as_of_date: Option<Date>,
+    total_value: BigDecimal,
+    dealerships: Vec<DealershipInventoryValuation>,
+    supply_lines: Vec<SupplyLineInventoryValuation>,
+    products: Vec<ProductInventoryValuation>,
+}
+
+#[get("/")]
+async fn fetch_inventory_valuation(
+    _: Authorized<ReportsRead>,
+    Query(params): Query<FetchInventoryValuationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let fetched_dealerships =
+        DealershipInventoryValuation::select_all(params.as_of_date, &mut *transaction)
+            .await
+            .context("Failed to fetch the dealerships valuation from the database")?;
+
+    let fetched_supply_lines =
+        SupplyLineInventoryValuation::select_all(params.as_of_date, &mut *transaction)
+            .await
+            .context("Failed to fetch the supply lines valuation from the database")?;
+
+    let fetched_products =
+        ProductInventoryValuation::select_all(params.as_of_date, &mut *transaction)
+            .await
+            .context("Failed to fetch the products valuation from the database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let total_value = fetched_dealerships
+        .iter()
+        .fold(BigDecimal::from(0), |total, dealership| {
+            total + &dealership.total_value
+        });
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: InventoryValuationDto {
+            as_of_date: params.as_of_date,
+            total_value,
+            dealerships: fetched_dealerships,
+            supply_lines: fetched_supply_lines,
+            products: fetched_products,
+        },
+    }))
+}
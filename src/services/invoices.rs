@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -11,16 +14,30 @@ use sqlx::{Pool, Postgres};
 use time::Date;
 
 use crate::{
-    models::invoice::{Invoice, InsertInvoice, UpdateInvoice},
+    models::dependency::Dependency,
+    models::invoice::{InsertInvoice, Invoice, InvoiceFilter, UpdateInvoice},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, DealershipScope, InvoicesDelete, InvoicesRead, InvoicesWrite},
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        expansion::{Expandable, Expanded, Expansion},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_invoices)
+        .service(fetch_invoice_dependencies)
         .service(fetch_invoice)
         .service(create_invoice)
         .service(update_invoice_partially)
@@ -38,14 +55,23 @@ struct CreateInvoicePayload {
 
 #[post("/")]
 async fn create_invoice(
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
     Json(payload): Json<CreateInvoicePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    scope
+        .ensure_includes_order(payload.order_id, db.get_ref())
+        .await?;
+
     let created_invoice = InsertInvoice {
         order_id: payload.order_id,
         issue_date: payload.issue_date,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -59,6 +85,11 @@ async fn create_invoice(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_invoice,
     }))
@@ -66,9 +97,46 @@ async fn create_invoice(
 
 #[get("/")]
 async fn fetch_invoices(
+    _: Authorized<InvoicesRead>,
+    scope: DealershipScope,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<InvoiceFilter>()?;
+    let fieldset = pagination_params.fieldset(Invoice::FIELDS)?;
+    let expansion = pagination_params.expansion(Invoice::RELATIONS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_invoices = fetch_invoices_after_cursor(
+            limit,
+            cursor,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let expanded_invoices =
+            expand_invoices(fetched_invoices.items, &expansion, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_invoices.prev_cursor,
+            fetched_invoices.next_cursor,
+        );
+
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(expanded_invoices),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -99,35 +167,65 @@ async fn fetch_invoices(
             ));
         }
 
-        let fetched_invoices = fetch_invoices_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_invoices = Invoice::count(db.get_ref())
-            .await
-            .context("Failed to count the invoices from the database")?;
-
+        let fetched_invoices = fetch_invoices_paginated(
+            per_page,
+            page_no,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_invoices = match fetched_invoices.total {
+            Some(total_invoices) => total_invoices,
+            None => Invoice::count(scope.dealership_rif, &list_filter, db.get_ref())
+                .await
+                .context("Failed to count the invoices from the database")?,
+        };
+
+        let expanded_invoices =
+            expand_invoices(fetched_invoices.items, &expansion, db.get_ref()).await?;
+
+        let pagination = Pagination::new(total_invoices, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_invoices,
-                pagination: Pagination::new(total_invoices, page_no, per_page),
+                data: fieldset.select_all(expanded_invoices),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_invoices = fetch_all_invoices(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        expansion.ensure_streamable()?;
+        return Ok(ndjson_response(db, move |db| {
+            let invoices = Invoice::stream_all(scope.dealership_rif, &list_filter, db);
+            fieldset.select_stream(invoices)
+        }));
+    }
+
+    let fetched_invoices =
+        fetch_all_invoices(scope.dealership_rif, &list_filter, db.get_ref()).await?;
+
+    let expanded_invoices = expand_invoices(fetched_invoices, &expansion, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_invoices,
+            data: fieldset.select_all(expanded_invoices),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_invoices(db: &Pool<Postgres>) -> Result<Vec<Invoice>, ServiceError> {
-    let fetched_invoices = Invoice::select_all(db)
+async fn fetch_all_invoices(
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<InvoiceFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Invoice>, ServiceError> {
+    let fetched_invoices = Invoice::select_all(dealership_rif, list_filter, db)
         .await
         .context("Failed to fetch the invoices from the database")?;
     Ok(fetched_invoices)
@@ -136,14 +234,46 @@ async fn fetch_all_invoices(db: &Pool<Postgres>) -> Result<Vec<Invoice>, Service
 async fn fetch_invoices_paginated(
     per_page: i64,
     page_no: i64,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<InvoiceFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Invoice>, ServiceError> {
+) -> Result<Page<Invoice>, ServiceError> {
     let fetched_invoices = Invoice::paginate(per_page)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the invoices from the database for the provided page")?;
 
-    Ok(fetched_invoices.items)
+    Ok(fetched_invoices)
+}
+
+async fn fetch_invoices_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Invoice>>,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<InvoiceFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Invoice>, ServiceError> {
+    let fetched_invoices = Invoice::paginate(limit)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the invoices from the database for the provided cursor")?;
+
+    Ok(fetched_invoices)
+}
+
+async fn expand_invoices(
+    invoices: Vec<Invoice>,
+    expansion: &Expansion,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Expanded<Invoice>>, ServiceError> {
+    let expanded_invoices = Invoice::expand(invoices, expansion, db)
+        .await
+        .context("Failed to fetch the related resources of the invoices from the database")?;
+    Ok(expanded_invoices)
 }
 
 #[derive(Deserialize)]
@@ -153,13 +283,41 @@ struct InvoiceManipulationParams {
     id: i32
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct InvoiceViewParams {
+    id: i32,
+    expand: Option<String>
+}
+
+#[routes]
 #[get("/view/")]
+#[get("/{id}/")]
 async fn fetch_invoice(
-    Query(params): Query<InvoiceManipulationParams>,
+    _: Authorized<InvoicesRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<InvoiceViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let expansion = Expansion::parse(Invoice::RELATIONS, params.expand.as_deref())?;
+
+    scope
+        .ensure_includes_invoice(params.id, db.get_ref())
+        .await?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "invoice",
+        "invoices",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
     let fetched_invoice =
-        Invoice::select(params.id, db.get_ref())
+        Invoice::select(params.id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -170,9 +328,23 @@ async fn fetch_invoice(
                 ),
             })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_invoice,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let expanded_invoice = Invoice::expand_one(fetched_invoice, &expansion, db.get_ref())
+        .await
+        .context("Failed to fetch the related resources of the invoice from the database")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: expanded_invoice,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -184,14 +356,42 @@ struct UpdateInvoicePartiallyPayload {
     issue_date: MaybeAbsent<Date>
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{id}/")]
 async fn update_invoice_partially(
-    Query(params): Query<InvoiceManipulationParams>,
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<InvoiceManipulationParams>,
     Json(payload): Json<UpdateInvoicePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "invoice",
+            "invoices",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    scope
+        .ensure_includes_invoice(params.id, db.get_ref())
+        .await?;
+
+    let new_order_id: Option<i32> = payload.order_id.into();
+    if let Some(order_id) = new_order_id {
+        scope
+            .ensure_includes_order(order_id, db.get_ref())
+            .await?;
+    }
+
     let dealership_to_update =
-        Invoice::select(params.id, db.get_ref())
+        Invoice::select(params.id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -204,10 +404,10 @@ async fn update_invoice_partially(
             })?;
 
     let updated_invoice = UpdateInvoice {
-        order_id: payload.order_id.into(),
+        order_id: new_order_id,
         issue_date: payload.issue_date.into(),
     }
-    .update(dealership_to_update, db.get_ref())
+    .update(dealership_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -221,6 +421,11 @@ async fn update_invoice_partially(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_invoice,
     }))
@@ -234,14 +439,38 @@ struct UpdateInvoiceCompletelyPayload {
     issue_date: Date
 }
 
+#[routes]
 #[put("/")]
+#[put("/{id}/")]
 async fn update_invoice_completely(
-    Query(params): Query<InvoiceManipulationParams>,
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<InvoiceManipulationParams>,
     Json(payload): Json<UpdateInvoiceCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "invoice",
+            "invoices",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    scope
+        .ensure_includes_invoice(params.id, db.get_ref())
+        .await?;
+    scope
+        .ensure_includes_order(payload.order_id, db.get_ref())
+        .await?;
+
     let city_to_update =
-        Invoice::select(params.id, db.get_ref())
+        Invoice::select(params.id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -257,7 +486,7 @@ async fn update_invoice_completely(
         order_id: Some(payload.order_id),
         issue_date: Some(payload.issue_date),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -271,30 +500,90 @@ async fn update_invoice_completely(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_invoice,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{id}/")]
 async fn delete_invoice(
-    Query(params): Query<InvoiceManipulationParams>,
+    _: Authorized<InvoicesDelete>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<InvoiceManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    scope
+        .ensure_includes_invoice(params.id, db.get_ref())
+        .await?;
+
+    ensure_deletable(
+        "invoice",
+        "invoices",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
     let deleted_invoice =
-        Invoice::delete(params.id, db.get_ref())
+        Invoice::delete(params.id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
                     ServiceError::ResourceNotFound("invoice".to_string(), anyhow!(err))
                 }
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    ServiceError::DeleteConflictError(
+                        "The invoice is still referenced by other resources".to_string(),
+                    )
+                }
                 _ => ServiceError::UnexpectedError(
                     anyhow!(err)
                         .context("Failed to fetch the invoice to delete from the database"),
                 ),
             })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_invoice,
     }))
 }
+
+#[routes]
+#[get("/dependencies/")]
+#[get("/{id}/dependencies/")]
+async fn fetch_invoice_dependencies(
+    _: Authorized<InvoicesRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<InvoiceManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    scope
+        .ensure_includes_invoice(params.id, db.get_ref())
+        .await?;
+
+    let fetched_dependencies = Dependency::select_all(
+        "invoices".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the invoice from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,7 +1,7 @@
 use actix_web::{
     get,
-    web::{Data, Json, Query, ServiceConfig},
-    Responder,
+    web::{Data, ServiceConfig},
+    Either, HttpResponse, Responder,
 };
 use anyhow::Context;
 use serde::Deserialize;
@@ -9,7 +9,11 @@ use sqlx::{Pool, Postgres};
 use time::Date;
 
 use crate::{
-    services::responses_dto::*, services::service_error::ServiceError,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::authorization::{Authorized, ReportsRead},
+    utils::extractors::{Json, Query},
+    utils::streaming::{ndjson_response, ResponseFormat},
     views::most_attended_vehicle_model::MostAttendedVehicleModel,
 };
 
@@ -29,9 +33,17 @@ struct FetchMostAttendedVehicleModelsInRangeParams {
 
 #[get("/")]
 async fn fetch_most_profitable_vehicle_models_in_range(
+    _: Authorized<ReportsRead>,
     Query(params): Query<FetchMostAttendedVehicleModelsInRangeParams>,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
-) -> Result<impl Responder, ServiceError> {
+) -> Result<Either<HttpResponse, impl Responder>, ServiceError> {
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(Either::Left(ndjson_response(db, move |db| {
+            MostAttendedVehicleModel::stream_all_in_range(params.from_date, params.to_date, db)
+        })));
+    }
+
     let fetched_vehicle_models = MostAttendedVehicleModel::select_all_in_range(
         params.from_date,
         params.to_date,
@@ -39,9 +51,9 @@ async fn fetch_most_profitable_vehicle_models_in_range(
     )
     .await
     .context("Failed to fetch the vehicle models from the database")?;
-    Ok(Json(NonPaginatedResponseDto {
+    Ok(Either::Right(Json(NonPaginatedResponseDto {
         data: fetched_vehicle_models,
-    }))
+    })))
 }
 
 #[derive(Deserialize)]
@@ -53,14 +65,22 @@ struct FetchMostAttendedVehicleModelsByNameParams {
 
 #[get("/")]
 async fn fetch_most_profitable_vehicle_models_by_name(
+    _: Authorized<ReportsRead>,
     Query(params): Query<FetchMostAttendedVehicleModelsByNameParams>,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
-) -> Result<impl Responder, ServiceError> {
+) -> Result<Either<HttpResponse, impl Responder>, ServiceError> {
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(Either::Left(ndjson_response(db, move |db| {
+            MostAttendedVehicleModel::stream_all_by_name(params.name, db)
+        })));
+    }
+
     let fetched_vehicle_models =
         MostAttendedVehicleModel::select_all_by_name(params.name, db.get_ref())
             .await
             .context("Failed to fetch the vehicle models from the database")?;
-    Ok(Json(NonPaginatedResponseDto {
+    Ok(Either::Right(Json(NonPaginatedResponseDto {
         data: fetched_vehicle_models,
-    }))
+    })))
 }
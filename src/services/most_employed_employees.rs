@@ -1,13 +1,17 @@
 use actix_web::{
     get,
-    web::{Data, Json, ServiceConfig},
-    Responder,
+    web::{Data, ServiceConfig},
+    Either, HttpResponse, Responder,
 };
 use anyhow::Context;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    services::responses_dto::*, services::service_error::ServiceError,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::authorization::{Authorized, ReportsRead},
+    utils::extractors::Json,
+    utils::streaming::{ndjson_response, ResponseFormat},
     views::most_employed_employee::MostEmployedEmployee,
 };
 
@@ -17,12 +21,20 @@ pub fn configure(configuration: &mut ServiceConfig) {
 
 #[get("/")]
 async fn fetch_most_employed_employees(
+    _: Authorized<ReportsRead>,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
-) -> Result<impl Responder, ServiceError> {
+) -> Result<Either<HttpResponse, impl Responder>, ServiceError> {
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(Either::Left(ndjson_response(db, move |db| {
+            MostEmployedEmployee::stream_all(db)
+        })));
+    }
+
     let fetched_employees = MostEmployedEmployee::select_all(db.get_ref())
         .await
         .context("Failed to fetch the employees from the database")?;
-    Ok(Json(NonPaginatedResponseDto {
+    Ok(Either::Right(Json(NonPaginatedResponseDto {
         data: fetched_employees,
-    }))
+    })))
 }
@@ -1,7 +1,7 @@
 use actix_web::{
     get,
-    web::{Data, Json, Query, ServiceConfig},
-    Responder,
+    web::{Data, ServiceConfig},
+    Either, HttpResponse, Responder,
 };
 use anyhow::Context;
 use serde::Deserialize;
@@ -9,7 +9,11 @@ use sqlx::{Pool, Postgres};
 use time::Date;
 
 use crate::{
-    services::responses_dto::*, services::service_error::ServiceError,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::authorization::{Authorized, ReportsRead},
+    utils::extractors::{Json, Query},
+    utils::streaming::{ndjson_response, ResponseFormat},
     views::most_profitable_dealership::MostProfitableDealership,
 };
 
@@ -27,9 +31,17 @@ struct FetchMostProfitableDealershipsParams {
 
 #[get("/")]
 async fn fetch_most_profitable_dealerships(
+    _: Authorized<ReportsRead>,
     Query(params): Query<FetchMostProfitableDealershipsParams>,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
-) -> Result<impl Responder, ServiceError> {
+) -> Result<Either<HttpResponse, impl Responder>, ServiceError> {
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(Either::Left(ndjson_response(db, move |db| {
+            MostProfitableDealership::stream_all_in_range(params.from_date, params.to_date, db)
+        })));
+    }
+
     let fetched_dealerships = MostProfitableDealership::select_all_in_range(
         params.from_date,
         params.to_date,
@@ -37,7 +49,7 @@ async fn fetch_most_profitable_dealerships(
     )
     .await
     .context("Failed to fetch the dealerships from the database")?;
-    Ok(Json(NonPaginatedResponseDto {
+    Ok(Either::Right(Json(NonPaginatedResponseDto {
         data: fetched_dealerships,
-    }))
+    })))
 }
@@ -1,9 +1,115 @@
-use serde::Deserialize;
+use std::{collections::BTreeMap, ops::Deref};
 
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::{
+    services::service_error::ServiceError,
+    utils::{
+        expansion::Expansion,
+        fieldsets::Fieldset,
+        filtering::{Filter, ListFilter},
+        pagination::Cursor,
+    },
+};
+
+type CursorParams<K> = (i64, Option<Cursor<K>>);
+
+#[serde_as]
 #[derive(Deserialize)]
 #[serde(rename_all = "kebab-case")]
-#[serde(deny_unknown_fields)]
 pub struct PaginationParams {
+    #[serde_as(as = "Option<DisplayFromStr>")]
     pub per_page: Option<i64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
     pub page_no: Option<i64>,
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub limit: Option<i64>,
+    pub expand: Option<String>,
+    pub fields: Option<String>,
+    #[serde(flatten)]
+    pub filter: BTreeMap<String, String>,
+}
+
+impl PaginationParams {
+    pub fn list_filter<F: Filter>(&self) -> Result<ListFilter<F>, ServiceError> {
+        ListFilter::parse(&self.filter, self.sort.as_deref())
+    }
+
+    pub fn expansion(&self, relations: &[&str]) -> Result<Expansion, ServiceError> {
+        Expansion::parse(relations, self.expand.as_deref())
+    }
+
+    pub fn fieldset(&self, fields: &[&str]) -> Result<Fieldset, ServiceError> {
+        Fieldset::parse(fields, self.fields.as_deref())
+    }
+
+    pub fn cursor_pagination<K: Serialize + DeserializeOwned>(
+        &self,
+    ) -> Result<Option<CursorParams<K>>, ServiceError> {
+        if self.per_page.is_some() || self.page_no.is_some() {
+            return match self.cursor.is_some() || self.limit.is_some() {
+                true => Err(ServiceError::InvalidQueryParamValueError(
+                    "Query params cursor and limit can't be combined with per-page or page-no"
+                        .to_string(),
+                )),
+                false => Ok(None),
+            };
+        }
+
+        parse_cursor_pagination(self.cursor.as_deref(), self.limit, self.sort.as_deref())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SoftDeletablePaginationParams {
+    #[serde(default)]
+    pub include_deleted: bool,
+    #[serde(flatten)]
+    pub pagination: PaginationParams,
+}
+
+impl Deref for SoftDeletablePaginationParams {
+    type Target = PaginationParams;
+
+    fn deref(&self) -> &PaginationParams {
+        &self.pagination
+    }
+}
+
+fn parse_cursor_pagination<K: Serialize + DeserializeOwned>(
+    cursor: Option<&str>,
+    limit: Option<i64>,
+    sort: Option<&str>,
+) -> Result<Option<CursorParams<K>>, ServiceError> {
+    let limit = match (cursor, limit) {
+        (None, None) => return Ok(None),
+        (Some(_), None) => {
+            return Err(ServiceError::MissingQueryParamError(
+                "Missing query param limit".to_string(),
+            ))
+        }
+        (_, Some(limit)) => limit,
+    };
+
+    if limit <= 0 {
+        return Err(ServiceError::InvalidQueryParamValueError(
+            "Query param limit must be greater than 0".to_string(),
+        ));
+    }
+    if sort.is_some() {
+        return Err(ServiceError::InvalidQueryParamValueError(
+            "Query param sort can't be combined with cursor and limit".to_string(),
+        ));
+    }
+
+    let cursor = match cursor {
+        Some(cursor) if !cursor.is_empty() => Some(Cursor::decode(cursor)?),
+        _ => None,
+    };
+
+    Ok(Some((limit, cursor)))
 }
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -12,11 +15,23 @@ use sqlx::{Pool, Postgres};
 use time::Date;
 
 use crate::{
-    models::payment::{InsertPayment, Payment, UpdatePayment},
+    models::invoice::Invoice,
+    models::payment::{InsertPayment, Payment, PaymentFilter, UpdatePayment},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, DealershipScope, InvoicesRead, InvoicesWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
@@ -29,6 +44,12 @@ pub fn configure(configuration: &mut ServiceConfig) {
         .service(delete_payment);
 }
 
+pub fn configure_nested(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_invoice_payments)
+        .service(create_invoice_payment);
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -43,9 +64,38 @@ struct CreatePaymentPayload {
 
 #[post("/")]
 async fn create_payment(
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
     Json(payload): Json<CreatePaymentPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_payment(payload, scope, audit, db).await
+}
+
+#[post("/{invoice_id}/payments/")]
+async fn create_invoice_payment(
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
+    PathJson(payload): PathJson<CreatePaymentPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_payment(payload, scope, audit, db).await
+}
+
+async fn insert_payment(
+    payload: CreatePaymentPayload,
+    scope: DealershipScope,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    scope
+        .ensure_includes_invoice(payload.invoice_id, db.get_ref())
+        .await?;
+
     let created_payment = InsertPayment {
         invoice_id: payload.invoice_id,
         amount_paid: payload.amount_paid,
@@ -54,7 +104,7 @@ async fn create_payment(
         card_number: payload.card_number,
         card_bank: payload.card_bank
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -63,11 +113,19 @@ async fn create_payment(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("payments", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the payment into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_payment,
     }))
@@ -75,9 +133,102 @@ async fn create_payment(
 
 #[get("/")]
 async fn fetch_payments(
+    _: Authorized<InvoicesRead>,
+    scope: DealershipScope,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<PaymentFilter>()?;
+
+    list_payments(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/{invoice_id}/payments/")]
+async fn fetch_invoice_payments(
+    _: Authorized<InvoicesRead>,
+    scope: DealershipScope,
+    invoice_id: Path<i32>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let invoice_id = invoice_id.into_inner();
+
+    scope
+        .ensure_includes_invoice(invoice_id, db.get_ref())
+        .await?;
+
+    Invoice::select(invoice_id, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("invoice".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the invoice from the database"),
+            ),
+        })?;
+
+    let mut list_filter = pagination_params.list_filter::<PaymentFilter>()?;
+    list_filter.conditions.invoice_id.eq = Some(invoice_id);
+
+    list_payments(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_payments(
+    pagination_params: PaginationParams,
+    list_filter: ListFilter<PaymentFilter>,
+    scope: DealershipScope,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(Payment::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_payments = fetch_payments_after_cursor(
+            limit,
+            cursor,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_payments.prev_cursor,
+            fetched_payments.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_payments.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -108,35 +259,58 @@ async fn fetch_payments(
             ));
         }
 
-        let fetched_payments = fetch_payments_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_payments = Payment::count(db.get_ref())
-            .await
-            .context("Failed to count the products from the database")?;
-
+        let fetched_payments = fetch_payments_paginated(
+            per_page,
+            page_no,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_payments = match fetched_payments.total {
+            Some(total_payments) => total_payments,
+            None => Payment::count(scope.dealership_rif, &list_filter, db.get_ref())
+                .await
+                .context("Failed to count the products from the database")?,
+        };
+
+        let pagination = Pagination::new(total_payments, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_payments,
-                pagination: Pagination::new(total_payments, page_no, per_page),
+                data: fieldset.select_all(fetched_payments.items),
+                pagination,
             });
-
         return Ok(response);
     }
 
-    let fetched_payments = fetch_all_payments(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let payments = Payment::stream_all(scope.dealership_rif, &list_filter, db);
+            fieldset.select_stream(payments)
+        }));
+    }
+
+    let fetched_payments =
+        fetch_all_payments(scope.dealership_rif, &list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_payments,
+            data: fieldset.select_all(fetched_payments),
         });
-
     Ok(response)
+
 }
 
-async fn fetch_all_payments(db: &Pool<Postgres>) -> Result<Vec<Payment>, ServiceError> {
-    let fetched_payments = Payment::select_all(db)
+async fn fetch_all_payments(
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<PaymentFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Payment>, ServiceError> {
+    let fetched_payments = Payment::select_all(dealership_rif, list_filter, db)
         .await
         .context("Failed to fetch the payments from the database")?;
     Ok(fetched_payments)
@@ -145,14 +319,35 @@ async fn fetch_all_payments(db: &Pool<Postgres>) -> Result<Vec<Payment>, Service
 async fn fetch_payments_paginated(
     per_page: i64,
     page_no: i64,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<PaymentFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Payment>, ServiceError> {
+) -> Result<Page<Payment>, ServiceError> {
     let fetched_payments = Payment::paginate(per_page)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the payments from the database for the provided page")?;
 
-    Ok(fetched_payments.items)
+    Ok(fetched_payments)
+}
+
+async fn fetch_payments_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Payment>>,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<PaymentFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Payment>, ServiceError> {
+    let fetched_payments = Payment::paginate(limit)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the payments from the database for the provided cursor")?;
+
+    Ok(fetched_payments)
 }
 
 #[derive(Deserialize)]
@@ -163,13 +358,34 @@ struct PaymentManipulationParams {
     invoice_id: i32
 }
 
+#[routes]
 #[get("/view/")]
+#[get("/{invoice_id}/{payment_number}/")]
 async fn fetch_payment(
-    Query(params): Query<PaymentManipulationParams>,
+    _: Authorized<InvoicesRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<PaymentManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope
+        .ensure_includes_invoice(params.invoice_id, db.get_ref())
+        .await?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "payment",
+        "payments",
+        vec![
+            params.payment_number.to_string(),
+            params.invoice_id.to_string(),
+        ],
+        &mut *transaction,
+    )
+    .await?;
+
     let fetched_payment =
-        Payment::select(params.payment_number, params.invoice_id, db.get_ref())
+        Payment::select(params.payment_number, params.invoice_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -180,9 +396,19 @@ async fn fetch_payment(
                 ),
             })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_payment,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_payment,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -198,14 +424,45 @@ struct UpdatePaymentPartiallyPayload {
     card_bank: MaybeAbsent<String>
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{invoice_id}/{payment_number}/")]
 async fn update_payment_partially(
-    Query(params): Query<PaymentManipulationParams>,
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<PaymentManipulationParams>,
     Json(payload): Json<UpdatePaymentPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "payment",
+            "payments",
+            vec![
+                params.payment_number.to_string(),
+                params.invoice_id.to_string(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
+    scope
+        .ensure_includes_invoice(params.invoice_id, db.get_ref())
+        .await?;
+
+    let new_invoice_id: Option<i32> = payload.invoice_id.into();
+    if let Some(invoice_id) = new_invoice_id {
+        scope
+            .ensure_includes_invoice(invoice_id, db.get_ref())
+            .await?;
+    }
+
     let city_to_update =
-        Payment::select(params.payment_number, params.invoice_id, db.get_ref())
+        Payment::select(params.payment_number, params.invoice_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -217,14 +474,14 @@ async fn update_payment_partially(
             })?;
 
     let updated_payment = UpdatePayment {
-        invoice_id: payload.invoice_id.into(),
+        invoice_id: new_invoice_id,
         amount_paid: payload.amount_paid.into(),
         payment_date: payload.payment_date.into(),
         payment_type: payload.payment_type.into(),
         card_number: payload.card_number.into(),
         card_bank: payload.card_bank.into(),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -233,11 +490,19 @@ async fn update_payment_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("payments", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the payment from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_payment,
     }))
@@ -255,14 +520,41 @@ struct UpdatePaymentCompletelyPayload {
     card_bank: String
 }
 
+#[routes]
 #[put("/")]
+#[put("/{invoice_id}/{payment_number}/")]
 async fn update_payment_completely(
-    Query(params): Query<PaymentManipulationParams>,
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<PaymentManipulationParams>,
     Json(payload): Json<UpdatePaymentCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "payment",
+            "payments",
+            vec![
+                params.payment_number.to_string(),
+                params.invoice_id.to_string(),
+            ],
+            &mut *transaction,
+        )
+        .await?;
+
+    scope
+        .ensure_includes_invoice(params.invoice_id, db.get_ref())
+        .await?;
+    scope
+        .ensure_includes_invoice(payload.invoice_id, db.get_ref())
+        .await?;
+
     let city_to_update =
-        Payment::select(params.payment_number, params.invoice_id, db.get_ref())
+        Payment::select(params.payment_number, params.invoice_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -281,7 +573,7 @@ async fn update_payment_completely(
         card_number: Some(payload.card_number),
         card_bank: Some(payload.card_bank),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -290,23 +582,42 @@ async fn update_payment_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("payments", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the payment from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_payment,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{invoice_id}/{payment_number}/")]
 async fn delete_payment(
-    Query(params): Query<PaymentManipulationParams>,
+    _: Authorized<InvoicesWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<PaymentManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    scope
+        .ensure_includes_invoice(params.invoice_id, db.get_ref())
+        .await?;
+
     let deleted_payment =
-        Payment::delete(params.payment_number, params.invoice_id, db.get_ref())
+        Payment::delete(params.payment_number, params.invoice_id, &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -317,6 +628,11 @@ async fn delete_payment(
                 ),
             })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_payment,
     }))
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,21 +13,34 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::product::{InsertProduct, Product, UpdateProduct},
-    services::pagination_params::PaginationParams,
+    models::product::{InsertProduct, Product, ProductFilter, UpdateProduct},
+    services::pagination_params::SoftDeletablePaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    services::suggestion_params::SuggestionParams,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        concurrency::{select_entity_tag, Precondition},
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_products)
+        .service(suggest_products)
         .service(fetch_product)
         .service(create_product)
         .service(update_product_partially)
         .service(update_product_completely)
-        .service(delete_product);
+        .service(delete_product)
+        .service(restore_product);
 }
 
 #[derive(Deserialize)]
@@ -39,16 +55,20 @@ struct CreateProductPayload {
 
 #[post("/")]
 async fn create_product(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateProductPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_product = InsertProduct {
         name: payload.name,
         description: payload.description,
         is_ecologic: payload.is_ecologic,
         supply_line_id: payload.supply_line_id,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -62,6 +82,11 @@ async fn create_product(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_product,
     }))
@@ -69,9 +94,40 @@ async fn create_product(
 
 #[get("/")]
 async fn fetch_products(
-    Query(pagination_params): Query<PaginationParams>,
+    _: Authorized<CatalogRead>,
+    Query(pagination_params): Query<SoftDeletablePaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ProductFilter>()?;
+    let fieldset = pagination_params.fieldset(Product::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_products = fetch_products_after_cursor(
+            limit,
+            cursor,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_products.prev_cursor,
+            fetched_products.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_products.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -102,35 +158,67 @@ async fn fetch_products(
             ));
         }
 
-        let fetched_products = fetch_products_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_products = Product::count(db.get_ref())
+        let fetched_products = fetch_products_paginated(
+            per_page,
+            page_no,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_products = match fetched_products.total {
+            Some(total_products) => total_products,
+            None => Product::count(
+                pagination_params.include_deleted,
+                &list_filter,
+                db.get_ref(),
+            )
             .await
-            .context("Failed to count the products from the database")?;
+            .context("Failed to count the products from the database")?,
+        };
 
+        let pagination = Pagination::new(total_products, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_products,
-                pagination: Pagination::new(total_products, page_no, per_page),
+                data: fieldset.select_all(fetched_products.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_products = fetch_all_products(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let products = Product::stream_all(pagination_params.include_deleted, &list_filter, db);
+            fieldset.select_stream(products)
+        }));
+    }
+
+    let fetched_products = fetch_all_products(
+        pagination_params.include_deleted,
+        &list_filter,
+        db.get_ref(),
+    )
+    .await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_products,
+            data: fieldset.select_all(fetched_products),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_products(db: &Pool<Postgres>) -> Result<Vec<Product>, ServiceError> {
-    let fetched_products = Product::select_all(db)
+async fn fetch_all_products(
+    include_deleted: bool,
+    list_filter: &ListFilter<ProductFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Product>, ServiceError> {
+    let fetched_products = Product::select_all(include_deleted, list_filter, db)
         .await
         .context("Failed to fetch the products from the database")?;
     Ok(fetched_products)
@@ -139,14 +227,52 @@ async fn fetch_all_products(db: &Pool<Postgres>) -> Result<Vec<Product>, Service
 async fn fetch_products_paginated(
     per_page: i64,
     page_no: i64,
+    include_deleted: bool,
+    list_filter: &ListFilter<ProductFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Product>, ServiceError> {
+) -> Result<Page<Product>, ServiceError> {
     let fetched_products = Product::paginate(per_page)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the products from the database for the provided page")?;
 
-    Ok(fetched_products.items)
+    Ok(fetched_products)
+}
+
+async fn fetch_products_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Product>>,
+    include_deleted: bool,
+    list_filter: &ListFilter<ProductFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Product>, ServiceError> {
+    let fetched_products = Product::paginate(limit)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the products from the database for the provided cursor")?;
+
+    Ok(fetched_products)
+}
+
+#[get("/suggest/")]
+async fn suggest_products(
+    _: Authorized<CatalogRead>,
+    Query(params): Query<SuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = params.limit()?;
+
+    let suggested_products = Product::select_suggestions(params.prefix, limit, db.get_ref())
+        .await
+        .context("Failed to fetch the product suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_products,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -156,26 +282,57 @@ struct ProductManipulationParams {
     id: i32,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct ProductViewParams {
+    id: i32,
+    #[serde(default)]
+    include_deleted: bool,
+}
+
+#[routes]
 #[get("/view/")]
+#[get("/{id}/")]
 async fn fetch_product(
-    Query(params): Query<ProductManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ProductViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_product =
-        Product::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the product from the database"),
-                ),
-            })?;
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_product,
-    }))
+    let entity_tag = select_entity_tag(
+        "product",
+        "products",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_product = Product::select(params.id, params.include_deleted, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the product from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_product,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -189,23 +346,38 @@ struct UpdateProductPartiallyPayload {
     supply_line_id: MaybeAbsent<i32>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{id}/")]
 async fn update_product_partially(
-    Query(params): Query<ProductManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ProductManipulationParams>,
     Json(payload): Json<UpdateProductPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update =
-        Product::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the product to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "product",
+            "products",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Product::select(params.id, false, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the product to update from the database"),
+            ),
+        })?;
 
     let updated_product = UpdateProduct {
         name: payload.name.into(),
@@ -213,7 +385,7 @@ async fn update_product_partially(
         is_ecologic: payload.is_ecologic.into(),
         supply_line_id: payload.supply_line_id.into(),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -227,6 +399,11 @@ async fn update_product_partially(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_product,
     }))
@@ -242,23 +419,38 @@ struct UpdateProductCompletelyPayload {
     supply_line_id: i32,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{id}/")]
 async fn update_product_completely(
-    Query(params): Query<ProductManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ProductManipulationParams>,
     Json(payload): Json<UpdateProductCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update =
-        Product::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the product to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "product",
+            "products",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Product::select(params.id, false, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the product to update from the database"),
+            ),
+        })?;
 
     let updated_product = UpdateProduct {
         name: Some(payload.name),
@@ -266,7 +458,7 @@ async fn update_product_completely(
         is_ecologic: Some(payload.is_ecologic),
         supply_line_id: Some(payload.supply_line_id),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -280,29 +472,76 @@ async fn update_product_completely(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_product,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{id}/")]
 async fn delete_product(
-    Query(params): Query<ProductManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ProductManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_product =
-        Product::delete(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the product to delete from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_product = Product::delete(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("product".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the product to delete from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
 
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_product,
     }))
 }
+
+#[routes]
+#[post("/restore/")]
+#[post("/{id}/restore/")]
+async fn restore_product(
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ProductManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let restored_product = Product::restore(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("deleted product".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to restore the product from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: restored_product,
+    }))
+}
@@ -0,0 +1,336 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::product_fluid_type::{
+        InsertProductFluidType, ProductFluidType, ProductFluidTypeFilter,
+    },
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        check_violations::check_violation_error,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_products_fluid_types)
+        .service(fetch_product_fluid_type)
+        .service(create_product_fluid_type)
+        .service(delete_product_fluid_type);
+}
+
+const FLUIDS: [&str; 3] = ["gearbox-oil", "engine-oil", "engine-coolant"];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct CreateProductFluidTypePayload {
+    product_id: i32,
+    fluid: String,
+    fluid_type: String,
+}
+
+#[post("/")]
+async fn create_product_fluid_type(
+    _: Authorized<CatalogWrite>,
+    Json(payload): Json<CreateProductFluidTypePayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    if !FLUIDS.contains(&payload.fluid.as_str()) {
+        return Err(ServiceError::invalid_field(
+            "fluid",
+            format!(
+                "The fluid must be one of the following: {}",
+                FLUIDS.join(", ")
+            ),
+        ));
+    }
+
+    let fluid_type = payload.fluid_type.trim();
+
+    if fluid_type.is_empty() {
+        return Err(ServiceError::invalid_field(
+            "fluidType",
+            "The fluidType must not be empty".to_string(),
+        ));
+    }
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let created_product_fluid_type = InsertProductFluidType {
+        product_id: payload.product_id,
+        fluid: payload.fluid,
+        fluid_type: fluid_type.to_string(),
+    }
+    .insert(&mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified product is already compatible with the specified fluid type"
+                    .to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified productId does not exist".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("products_fluid_types", db_err.as_ref())
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to insert the product fluid type into the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: created_product_fluid_type,
+    }))
+}
+
+#[get("/")]
+async fn fetch_products_fluid_types(
+    _: Authorized<CatalogRead>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ProductFluidTypeFilter>()?;
+    let fieldset = pagination_params.fieldset(ProductFluidType::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_products_fluid_types =
+            fetch_products_fluid_types_after_cursor(limit, cursor, &list_filter, db.get_ref())
+                .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_products_fluid_types.prev_cursor,
+            fetched_products_fluid_types.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_products_fluid_types.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if pagination_params.per_page.is_none() && pagination_params.page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (pagination_params.per_page, pagination_params.page_no)
+    {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_products_fluid_types =
+            fetch_products_fluid_types_paginated(per_page, page_no, &list_filter, db.get_ref())
+                .await?;
+
+        let total_products_fluid_types = match fetched_products_fluid_types.total {
+            Some(total_products_fluid_types) => total_products_fluid_types,
+            None => ProductFluidType::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the product fluid types from the database")?,
+        };
+
+        let pagination = Pagination::new(total_products_fluid_types, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_products_fluid_types.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let products_fluid_types = ProductFluidType::stream_all(&list_filter, db);
+            fieldset.select_stream(products_fluid_types)
+        }));
+    }
+
+    let fetched_products_fluid_types =
+        fetch_all_products_fluid_types(&list_filter, db.get_ref()).await?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .json(NonPaginatedResponseDto {
+            data: fieldset.select_all(fetched_products_fluid_types),
+        });
+
+    Ok(response)
+}
+
+async fn fetch_all_products_fluid_types(
+    list_filter: &ListFilter<ProductFluidTypeFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<ProductFluidType>, ServiceError> {
+    let fetched_products_fluid_types = ProductFluidType::select_all(list_filter, db)
+        .await
+        .context("Failed to fetch the product fluid types from the database")?;
+    Ok(fetched_products_fluid_types)
+}
+
+async fn fetch_products_fluid_types_paginated(
+    per_page: i64,
+    page_no: i64,
+    list_filter: &ListFilter<ProductFluidTypeFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<ProductFluidType>, ServiceError> {
+    let fetched_products_fluid_types = ProductFluidType::paginate(per_page)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context(
+            "Failed to fetch the product fluid types from the database for the provided page",
+        )?;
+
+    Ok(fetched_products_fluid_types)
+}
+
+async fn fetch_products_fluid_types_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<ProductFluidType>>,
+    list_filter: &ListFilter<ProductFluidTypeFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<ProductFluidType>, ServiceError> {
+    let fetched_products_fluid_types = ProductFluidType::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context(
+            "Failed to fetch the product fluid types from the database for the provided cursor",
+        )?;
+
+    Ok(fetched_products_fluid_types)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct ProductFluidTypeManipulationParams {
+    product_id: i32,
+    fluid: String,
+    fluid_type: String,
+}
+
+#[routes]
+#[get("/view/")]
+#[get("/{product_id}/{fluid}/{fluid_type}/")]
+async fn fetch_product_fluid_type(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ProductFluidTypeManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_product_fluid_type = ProductFluidType::select(
+        params.product_id,
+        params.fluid,
+        params.fluid_type,
+        db.get_ref(),
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("product fluid type".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the product fluid type from the database"),
+        ),
+    })?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_product_fluid_type,
+    }))
+}
+
+#[routes]
+#[delete("/")]
+#[delete("/{product_id}/{fluid}/{fluid_type}/")]
+async fn delete_product_fluid_type(
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ProductFluidTypeManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_product_fluid_type = ProductFluidType::delete(
+        params.product_id,
+        params.fluid,
+        params.fluid_type,
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("product fluid type".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err)
+                .context("Failed to fetch the product fluid type to delete from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: deleted_product_fluid_type,
+    }))
+}
@@ -0,0 +1,305 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::product_vehicle_model::{
+        InsertProductVehicleModel, ProductVehicleModel, ProductVehicleModelFilter,
+    },
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_products_vehicle_models)
+        .service(fetch_product_vehicle_model)
+        .service(create_product_vehicle_model)
+        .service(delete_product_vehicle_model);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct CreateProductVehicleModelPayload {
+    product_id: i32,
+    vehicle_model_id: i32,
+}
+
+#[post("/")]
+async fn create_product_vehicle_model(
+    _: Authorized<CatalogWrite>,
+    Json(payload): Json<CreateProductVehicleModelPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let created_product_vehicle_model = InsertProductVehicleModel {
+        product_id: payload.product_id,
+        vehicle_model_id: payload.vehicle_model_id,
+    }
+    .insert(&mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified product is already compatible with the specified vehicle model"
+                    .to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified productId or vehicleModelId does not exist".to_string(),
+                anyhow!(err),
+            )
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to insert the product vehicle model into the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: created_product_vehicle_model,
+    }))
+}
+
+#[get("/")]
+async fn fetch_products_vehicle_models(
+    _: Authorized<CatalogRead>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ProductVehicleModelFilter>()?;
+    let fieldset = pagination_params.fieldset(ProductVehicleModel::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_products_vehicle_models =
+            fetch_products_vehicle_models_after_cursor(limit, cursor, &list_filter, db.get_ref())
+                .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_products_vehicle_models.prev_cursor,
+            fetched_products_vehicle_models.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_products_vehicle_models.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if pagination_params.per_page.is_none() && pagination_params.page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (pagination_params.per_page, pagination_params.page_no)
+    {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_products_vehicle_models =
+            fetch_products_vehicle_models_paginated(per_page, page_no, &list_filter, db.get_ref())
+                .await?;
+
+        let total_products_vehicle_models = match fetched_products_vehicle_models.total {
+            Some(total_products_vehicle_models) => total_products_vehicle_models,
+            None => ProductVehicleModel::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the product vehicle models from the database")?,
+        };
+
+        let pagination = Pagination::new(total_products_vehicle_models, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_products_vehicle_models.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let products_vehicle_models = ProductVehicleModel::stream_all(&list_filter, db);
+            fieldset.select_stream(products_vehicle_models)
+        }));
+    }
+
+    let fetched_products_vehicle_models =
+        fetch_all_products_vehicle_models(&list_filter, db.get_ref()).await?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .json(NonPaginatedResponseDto {
+            data: fieldset.select_all(fetched_products_vehicle_models),
+        });
+
+    Ok(response)
+}
+
+async fn fetch_all_products_vehicle_models(
+    list_filter: &ListFilter<ProductVehicleModelFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<ProductVehicleModel>, ServiceError> {
+    let fetched_products_vehicle_models = ProductVehicleModel::select_all(list_filter, db)
+        .await
+        .context("Failed to fetch the product vehicle models from the database")?;
+    Ok(fetched_products_vehicle_models)
+}
+
+async fn fetch_products_vehicle_models_paginated(
+    per_page: i64,
+    page_no: i64,
+    list_filter: &ListFilter<ProductVehicleModelFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<ProductVehicleModel>, ServiceError> {
+    let fetched_products_vehicle_models = ProductVehicleModel::paginate(per_page)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context(
+            "Failed to fetch the product vehicle models from the database for the provided page",
+        )?;
+
+    Ok(fetched_products_vehicle_models)
+}
+
+async fn fetch_products_vehicle_models_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<ProductVehicleModel>>,
+    list_filter: &ListFilter<ProductVehicleModelFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<ProductVehicleModel>, ServiceError> {
+    let fetched_products_vehicle_models = ProductVehicleModel::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context(
+            "Failed to fetch the product vehicle models from the database for the provided cursor",
+        )?;
+
+    Ok(fetched_products_vehicle_models)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct ProductVehicleModelManipulationParams {
+    product_id: i32,
+    vehicle_model_id: i32,
+}
+
+#[routes]
+#[get("/view/")]
+#[get("/{product_id}/{vehicle_model_id}/")]
+async fn fetch_product_vehicle_model(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ProductVehicleModelManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_product_vehicle_model =
+        ProductVehicleModel::select(params.product_id, params.vehicle_model_id, db.get_ref())
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => ServiceError::ResourceNotFound(
+                    "product vehicle model".to_string(),
+                    anyhow!(err),
+                ),
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err)
+                        .context("Failed to fetch the product vehicle model from the database"),
+                ),
+            })?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_product_vehicle_model,
+    }))
+}
+
+#[routes]
+#[delete("/")]
+#[delete("/{product_id}/{vehicle_model_id}/")]
+async fn delete_product_vehicle_model(
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ProductVehicleModelManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_product_vehicle_model = ProductVehicleModel::delete(
+        params.product_id,
+        params.vehicle_model_id,
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("product vehicle model".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err)
+                .context("Failed to fetch the product vehicle model to delete from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: deleted_product_vehicle_model,
+    }))
+}
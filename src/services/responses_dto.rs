@@ -1,5 +1,7 @@
 use serde::Serialize;
 
+use super::service_error::FieldErrors;
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NonPaginatedResponseDto<T: Serialize> {
@@ -13,28 +15,63 @@ pub struct Pagination {
     pub page: i64,
     pub pages: i64,
     pub per_page: i64,
+    pub has_next: bool,
+    pub has_prev: bool,
 }
 
 impl Pagination {
     pub fn new(total: i64, page: i64, per_page: i64) -> Pagination {
+        let pages = (total + per_page - 1) / per_page;
+
         Pagination {
-            total: total,
-            page: page,
-            pages: total / per_page + total % per_page,
-            per_page: per_page,
+            total,
+            page,
+            pages,
+            per_page,
+            has_next: page < pages,
+            has_prev: page > 1,
         }
     }
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct PaginatedResponseDto<T: Serialize> {
+pub struct PaginatedResponseDto<T: Serialize, P: Serialize = Pagination> {
     pub data: T,
-    pub pagination: Pagination,
+    pub pagination: P,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPagination {
+    pub limit: i64,
+    pub prev_cursor: Option<String>,
+    pub next_cursor: Option<String>,
+    pub has_next: bool,
+    pub has_prev: bool,
+}
+
+impl CursorPagination {
+    pub fn new(
+        limit: i64,
+        prev_cursor: Option<String>,
+        next_cursor: Option<String>,
+    ) -> CursorPagination {
+        CursorPagination {
+            limit,
+            has_next: next_cursor.is_some(),
+            has_prev: prev_cursor.is_some(),
+            prev_cursor,
+            next_cursor,
+        }
+    }
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ErrorResponseDto {
     pub error: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<FieldErrors>,
 }
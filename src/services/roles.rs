@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,16 +13,29 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::role::{InsertRole, Role, UpdateRole},
+    models::dependency::Dependency,
+    models::role::{InsertRole, Role, RoleFilter, UpdateRole},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, StaffRead, StaffWrite},
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_roles)
+        .service(fetch_role_dependencies)
         .service(fetch_role)
         .service(create_role)
         .service(update_role_partially)
@@ -37,25 +53,56 @@ struct CreateRolePayload {
 
 #[post("/roles/")]
 async fn create_role(
+    _: Authorized<StaffWrite>,
     Json(payload): Json<CreateRolePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_role = InsertRole {
         name: payload.name,
         description: payload.description,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .context("Failed to insert the role into the database")?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: created_role }))
 }
 
 #[get("/roles/")]
 async fn fetch_roles(
+    _: Authorized<StaffRead>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<RoleFilter>()?;
+    let fieldset = pagination_params.fieldset(Role::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_roles =
+            fetch_roles_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination =
+            CursorPagination::new(limit, fetched_roles.prev_cursor, fetched_roles.next_cursor);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_roles.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -86,35 +133,51 @@ async fn fetch_roles(
             ));
         }
 
-        let fetched_roles = fetch_roles_paginated(per_page, page_no, db.get_ref()).await?;
+        let fetched_roles =
+            fetch_roles_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_roles = Role::count(db.get_ref())
-            .await
-            .context("Failed to count the roles from the database")?;
+        let total_roles = match fetched_roles.total {
+            Some(total_roles) => total_roles,
+            None => Role::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the roles from the database")?,
+        };
 
+        let pagination = Pagination::new(total_roles, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_roles,
-                pagination: Pagination::new(total_roles, page_no, per_page),
+                data: fieldset.select_all(fetched_roles.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_roles = fetch_all_roles(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let roles = Role::stream_all(&list_filter, db);
+            fieldset.select_stream(roles)
+        }));
+    }
+
+    let fetched_roles = fetch_all_roles(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_roles,
+            data: fieldset.select_all(fetched_roles),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_roles(db: &Pool<Postgres>) -> Result<Vec<Role>, ServiceError> {
-    let fetched_roles = Role::select_all(db)
+async fn fetch_all_roles(
+    list_filter: &ListFilter<RoleFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Role>, ServiceError> {
+    let fetched_roles = Role::select_all(list_filter, db)
         .await
         .context("Failed to fetch the roles from the database")?;
     Ok(fetched_roles)
@@ -123,14 +186,31 @@ async fn fetch_all_roles(db: &Pool<Postgres>) -> Result<Vec<Role>, ServiceError>
 async fn fetch_roles_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<RoleFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Role>, ServiceError> {
+) -> Result<Page<Role>, ServiceError> {
     let fetched_roles = Role::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the roles from the database for the provided page")?;
 
-    Ok(fetched_roles.items)
+    Ok(fetched_roles)
+}
+
+async fn fetch_roles_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Role>>,
+    list_filter: &ListFilter<RoleFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Role>, ServiceError> {
+    let fetched_roles = Role::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the roles from the database for the provided cursor")?;
+
+    Ok(fetched_roles)
 }
 
 #[derive(Deserialize)]
@@ -140,12 +220,25 @@ struct RoleManipulationParams {
     id: i32,
 }
 
+#[routes]
 #[get("/roles/view/")]
+#[get("/roles/{id}/")]
 async fn fetch_role(
-    Query(params): Query<RoleManipulationParams>,
+    _: Authorized<StaffRead>,
+    PathQuery(params): PathQuery<RoleManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_role = Role::select(params.id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "role",
+        "roles",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_role = Role::select(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -156,7 +249,17 @@ async fn fetch_role(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto { data: fetched_role }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto { data: fetched_role });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -168,31 +271,53 @@ struct UpdateRolePartiallyPayload {
     description: MaybeAbsent<String>,
 }
 
+#[routes]
 #[patch("/roles/")]
+#[patch("/roles/{id}/")]
 async fn update_role_partially(
-    Query(params): Query<RoleManipulationParams>,
+    _: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<RoleManipulationParams>,
     Json(payload): Json<UpdateRolePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let role_to_update = Role::select(params.id, db.get_ref())
-        .await
-        .map_err(|err| match &err {
-            sqlx::Error::RowNotFound => {
-                ServiceError::ResourceNotFound("role".to_string(), anyhow!(err))
-            }
-            _ => ServiceError::UnexpectedError(
-                anyhow!(err).context("Failed to fetch the role to update from the database"),
-            ),
-        })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "role",
+            "roles",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let role_to_update =
+        Role::select(params.id, &mut *transaction)
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("role".to_string(), anyhow!(err))
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err).context("Failed to fetch the role to update from the database"),
+                ),
+            })?;
 
     let updated_role = UpdateRole {
         name: payload.name.into(),
         description: payload.description.into(),
     }
-    .update(role_to_update, db.get_ref())
+    .update(role_to_update, &mut *transaction)
     .await
     .context("Failed to update the role from the database")?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: updated_role }))
 }
 
@@ -204,49 +329,111 @@ struct UpdateRoleCompletelyPayload {
     description: String,
 }
 
+#[routes]
 #[put("/roles/")]
+#[put("/roles/{id}/")]
 async fn update_role_completely(
-    Query(params): Query<RoleManipulationParams>,
+    _: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<RoleManipulationParams>,
     Json(payload): Json<UpdateRoleCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let role_to_update = Role::select(params.id, db.get_ref())
-        .await
-        .map_err(|err| match &err {
-            sqlx::Error::RowNotFound => {
-                ServiceError::ResourceNotFound("role".to_string(), anyhow!(err))
-            }
-            _ => ServiceError::UnexpectedError(
-                anyhow!(err).context("Failed to fetch the roles to update from the database"),
-            ),
-        })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "role",
+            "roles",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let role_to_update =
+        Role::select(params.id, &mut *transaction)
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("role".to_string(), anyhow!(err))
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err).context("Failed to fetch the roles to update from the database"),
+                ),
+            })?;
 
     let updated_role = UpdateRole {
         name: Some(payload.name),
         description: Some(payload.description),
     }
-    .update(role_to_update, db.get_ref())
+    .update(role_to_update, &mut *transaction)
     .await
     .context("Failed to update the role from the database")?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto { data: updated_role }))
 }
 
+#[routes]
 #[delete("/roles/")]
+#[delete("/roles/{id}/")]
 async fn delete_role(
-    Query(params): Query<RoleManipulationParams>,
+    _: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<RoleManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_role = Role::delete(params.id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable("role", "roles", vec![params.id.to_string()], &mut *transaction).await?;
+
+    let deleted_role =
+        Role::delete(params.id, &mut *transaction)
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("role".to_string(), anyhow!(err))
+                }
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    ServiceError::DeleteConflictError(
+                        "The role is still referenced by other resources".to_string(),
+                    )
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err).context("Failed to fetch the roles to delete from the database"),
+                ),
+            })?;
+
+    transaction
+        .commit()
         .await
-        .map_err(|err| match &err {
-            sqlx::Error::RowNotFound => {
-                ServiceError::ResourceNotFound("role".to_string(), anyhow!(err))
-            }
-            _ => ServiceError::UnexpectedError(
-                anyhow!(err).context("Failed to fetch the roles to delete from the database"),
-            ),
-        })?;
+        .context("Failed to commit the transaction")?;
 
     Ok(Json(NonPaginatedResponseDto { data: deleted_role }))
 }
+
+#[routes]
+#[get("/roles/dependencies/")]
+#[get("/roles/{id}/dependencies/")]
+async fn fetch_role_dependencies(
+    _: Authorized<StaffRead>,
+    PathQuery(params): PathQuery<RoleManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "roles".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the role from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
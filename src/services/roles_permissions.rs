@@ -0,0 +1,295 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::role_permission::{InsertRolePermission, RolePermission, RolePermissionFilter},
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        audit::AuditContext,
+        authorization::{AllDealerships, Authorized, StaffRead, StaffWrite},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_roles_permissions)
+        .service(fetch_role_permission)
+        .service(create_role_permission)
+        .service(delete_role_permission);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct CreateRolePermissionPayload {
+    role_id: i32,
+    permission: String,
+}
+
+#[post("/")]
+async fn create_role_permission(
+    _: Authorized<StaffWrite>,
+    _: Authorized<AllDealerships>,
+    Json(payload): Json<CreateRolePermissionPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let created_role_permission = InsertRolePermission {
+        role_id: payload.role_id,
+        permission: payload.permission,
+    }
+    .insert(&mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified role already has the specified permission".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified roleId or permission does not exist".to_string(),
+                anyhow!(err),
+            )
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to insert the role permission into the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: created_role_permission,
+    }))
+}
+
+#[get("/")]
+async fn fetch_roles_permissions(
+    _: Authorized<StaffRead>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<RolePermissionFilter>()?;
+    let fieldset = pagination_params.fieldset(RolePermission::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_roles_permissions =
+            fetch_roles_permissions_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_roles_permissions.prev_cursor,
+            fetched_roles_permissions.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_roles_permissions.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if pagination_params.per_page.is_none() && pagination_params.page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (pagination_params.per_page, pagination_params.page_no)
+    {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_roles_permissions =
+            fetch_roles_permissions_paginated(per_page, page_no, &list_filter, db.get_ref())
+                .await?;
+
+        let total_roles_permissions = match fetched_roles_permissions.total {
+            Some(total_roles_permissions) => total_roles_permissions,
+            None => RolePermission::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the role permissions from the database")?,
+        };
+
+        let pagination = Pagination::new(total_roles_permissions, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_roles_permissions.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let roles_permissions = RolePermission::stream_all(&list_filter, db);
+            fieldset.select_stream(roles_permissions)
+        }));
+    }
+
+    let fetched_roles_permissions = fetch_all_roles_permissions(&list_filter, db.get_ref()).await?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .json(NonPaginatedResponseDto {
+            data: fieldset.select_all(fetched_roles_permissions),
+        });
+
+    Ok(response)
+}
+
+async fn fetch_all_roles_permissions(
+    list_filter: &ListFilter<RolePermissionFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<RolePermission>, ServiceError> {
+    let fetched_roles_permissions = RolePermission::select_all(list_filter, db)
+        .await
+        .context("Failed to fetch the role permissions from the database")?;
+    Ok(fetched_roles_permissions)
+}
+
+async fn fetch_roles_permissions_paginated(
+    per_page: i64,
+    page_no: i64,
+    list_filter: &ListFilter<RolePermissionFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<RolePermission>, ServiceError> {
+    let fetched_roles_permissions = RolePermission::paginate(per_page)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context("Failed to fetch the role permissions from the database for the provided page")?;
+
+    Ok(fetched_roles_permissions)
+}
+
+async fn fetch_roles_permissions_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<RolePermission>>,
+    list_filter: &ListFilter<RolePermissionFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<RolePermission>, ServiceError> {
+    let fetched_roles_permissions = RolePermission::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context(
+            "Failed to fetch the role permissions from the database for the provided cursor",
+        )?;
+
+    Ok(fetched_roles_permissions)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct RolePermissionManipulationParams {
+    role_id: i32,
+    permission: String,
+}
+
+#[routes]
+#[get("/view/")]
+#[get("/{role_id}/{permission}/")]
+async fn fetch_role_permission(
+    _: Authorized<StaffRead>,
+    PathQuery(params): PathQuery<RolePermissionManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_role_permission =
+        RolePermission::select(params.role_id, params.permission, db.get_ref())
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("role permission".to_string(), anyhow!(err))
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err).context("Failed to fetch the role permission from the database"),
+                ),
+            })?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_role_permission,
+    }))
+}
+
+#[routes]
+#[delete("/")]
+#[delete("/{role_id}/{permission}/")]
+async fn delete_role_permission(
+    _: Authorized<StaffWrite>,
+    _: Authorized<AllDealerships>,
+    PathQuery(params): PathQuery<RolePermissionManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_role_permission =
+        RolePermission::delete(params.role_id, params.permission, &mut *transaction)
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("role permission".to_string(), anyhow!(err))
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err)
+                        .context("Failed to fetch the role permission to delete from the database"),
+                ),
+            })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: deleted_role_permission,
+    }))
+}
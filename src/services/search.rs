@@ -0,0 +1,73 @@
+use actix_web::{
+    get,
+    web::{Data, ServiceConfig},
+    Responder,
+};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::role_permission::RolePermission,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::authorization::{Authorized, ClientsRead, Permission, StaffRead},
+    utils::extractors::{Json, Query},
+    views::search_result::SearchResult,
+};
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 100;
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration.service(search);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct SearchParams {
+    q: String,
+    limit: Option<i64>,
+}
+
+#[get("/")]
+async fn search(
+    authorization: Authorized<ClientsRead>,
+    Query(params): Query<SearchParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let search_terms = params.q.trim().to_string();
+
+    if search_terms.is_empty() {
+        return Err(ServiceError::InvalidQueryParamValueError(
+            "Query param q can't be empty".to_string(),
+        ));
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+    if limit <= 0 || limit > MAX_SEARCH_LIMIT {
+        return Err(ServiceError::InvalidQueryParamValueError(format!(
+            "Query param limit must be between 1 and {}",
+            MAX_SEARCH_LIMIT
+        )));
+    }
+
+    let include_staff = RolePermission::is_granted_to_employee(
+        authorization.employee.national_id,
+        StaffRead::NAME.to_string(),
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the permissions of the employee from the database")?;
+
+    let fetched_results =
+        SearchResult::select_all_matching(search_terms, include_staff, limit, db.get_ref())
+            .await
+            .context("Failed to fetch the search results from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_results,
+    }))
+}
@@ -1,14 +1,23 @@
 use actix_web::{
-    http::{header::ContentType, StatusCode},
+    http::{
+        header::{ContentType, WWW_AUTHENTICATE},
+        StatusCode,
+    },
     HttpResponse, ResponseError,
 };
 
+use std::collections::BTreeMap;
+
 use super::responses_dto::ErrorResponseDto;
 
+pub type FieldErrors = BTreeMap<String, Vec<String>>;
+
 #[derive(thiserror::Error, Debug)]
 pub enum ServiceError {
     #[error("{0}")]
     DomainValidationError(String),
+    #[error("One or more fields have invalid values")]
+    FieldValidationError(FieldErrors),
     #[error("There is not any {0} with the given id")]
     ResourceNotFound(String, #[source] anyhow::Error),
     #[error("{0}")]
@@ -16,30 +25,81 @@ pub enum ServiceError {
     #[error("{0}")]
     InvalidQueryParamValueError(String),
     #[error("{0}")]
+    InvalidPathParamValueError(String),
+    #[error("{0}")]
     InvalidUpdateError(String, #[source] anyhow::Error),
     #[error("{0}")]
     InvalidCreateError(String, #[source] anyhow::Error),
+    #[error("{0}")]
+    DeleteConflictError(String),
+    #[error("{0}")]
+    PreconditionFailedError(String),
+    #[error("{0}")]
+    AuthenticationError(String, #[source] anyhow::Error),
+    #[error("{0}")]
+    AuthorizationError(String),
     #[error("")]
     UnexpectedError(#[from] anyhow::Error),
 }
 
+impl ServiceError {
+    pub fn invalid_field(field: &str, message: String) -> ServiceError {
+        ServiceError::FieldValidationError(FieldErrors::from([(field.to_string(), vec![message])]))
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServiceError::DomainValidationError(_) => "domain-validation-error",
+            ServiceError::FieldValidationError(_) => "field-validation-error",
+            ServiceError::ResourceNotFound(_, _) => "resource-not-found",
+            ServiceError::MissingQueryParamError(_) => "missing-query-param",
+            ServiceError::InvalidQueryParamValueError(_) => "invalid-query-param-value",
+            ServiceError::InvalidPathParamValueError(_) => "invalid-path-param-value",
+            ServiceError::InvalidUpdateError(_, _) => "invalid-update",
+            ServiceError::InvalidCreateError(_, _) => "invalid-create",
+            ServiceError::DeleteConflictError(_) => "delete-conflict",
+            ServiceError::PreconditionFailedError(_) => "precondition-failed",
+            ServiceError::AuthenticationError(_, _) => "authentication-error",
+            ServiceError::AuthorizationError(_) => "authorization-error",
+            ServiceError::UnexpectedError(_) => "unexpected-error",
+        }
+    }
+}
+
 impl ResponseError for ServiceError {
     fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
-        HttpResponse::build(self.status_code())
+        let mut response = HttpResponse::build(self.status_code());
+
+        if let ServiceError::AuthenticationError(_, _) = self {
+            response.insert_header((WWW_AUTHENTICATE, "Bearer"));
+        }
+
+        response
             .content_type(ContentType::json())
             .json(ErrorResponseDto {
                 error: format!("{}", self),
+                code: self.code().to_string(),
+                fields: match self {
+                    ServiceError::FieldValidationError(fields) => Some(fields.clone()),
+                    _ => None,
+                },
             })
     }
 
     fn status_code(&self) -> StatusCode {
         match self {
             ServiceError::DomainValidationError(_) => StatusCode::BAD_REQUEST,
+            ServiceError::FieldValidationError(_) => StatusCode::BAD_REQUEST,
             ServiceError::ResourceNotFound(_, _) => StatusCode::NOT_FOUND,
             ServiceError::MissingQueryParamError(_) => StatusCode::BAD_REQUEST,
             ServiceError::InvalidQueryParamValueError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ServiceError::InvalidPathParamValueError(_) => StatusCode::NOT_FOUND,
             ServiceError::InvalidUpdateError(_, _) => StatusCode::BAD_REQUEST,
             ServiceError::InvalidCreateError(_, _) => StatusCode::BAD_REQUEST,
+            ServiceError::DeleteConflictError(_) => StatusCode::CONFLICT,
+            ServiceError::PreconditionFailedError(_) => StatusCode::PRECONDITION_FAILED,
+            ServiceError::AuthenticationError(_, _) => StatusCode::UNAUTHORIZED,
+            ServiceError::AuthorizationError(_) => StatusCode::FORBIDDEN,
             ServiceError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
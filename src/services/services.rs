@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,16 +13,33 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::service::{InsertService, Service, UpdateService},
+    models::dependency::Dependency,
+    models::domains::NationalId,
+    models::service::{InsertService, Service, ServiceFilter, UpdateService},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    services::suggestion_params::SuggestionParams,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_services)
+        .service(suggest_services)
+        .service(fetch_service_dependencies)
         .service(fetch_service)
         .service(create_service)
         .service(update_service_partially)
@@ -33,20 +53,24 @@ pub fn configure(configuration: &mut ServiceConfig) {
 struct CreateServicePayload {
     name: String,
     description: String,
-    coordinator_national_id: String,
+    coordinator_national_id: NationalId,
 }
 
 #[post("/")]
 async fn create_service(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateServicePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_service = InsertService {
         name: payload.name,
         description: payload.description,
-        coordinator_national_id: payload.coordinator_national_id,
+        coordinator_national_id: payload.coordinator_national_id.into(),
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -55,11 +79,19 @@ async fn create_service(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("services", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the service into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_service,
     }))
@@ -67,9 +99,34 @@ async fn create_service(
 
 #[get("/")]
 async fn fetch_services(
+    _: Authorized<CatalogRead>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<ServiceFilter>()?;
+    let fieldset = pagination_params.fieldset(Service::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_services =
+            fetch_services_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_services.prev_cursor,
+            fetched_services.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_services.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -100,35 +157,51 @@ async fn fetch_services(
             ));
         }
 
-        let fetched_services = fetch_services_paginated(per_page, page_no, db.get_ref()).await?;
+        let fetched_services =
+            fetch_services_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_services = Service::count(db.get_ref())
-            .await
-            .context("Failed to count the services from the database")?;
+        let total_services = match fetched_services.total {
+            Some(total_services) => total_services,
+            None => Service::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the services from the database")?,
+        };
 
+        let pagination = Pagination::new(total_services, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_services,
-                pagination: Pagination::new(total_services, page_no, per_page),
+                data: fieldset.select_all(fetched_services.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_services = fetch_all_services(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let services = Service::stream_all(&list_filter, db);
+            fieldset.select_stream(services)
+        }));
+    }
+
+    let fetched_services = fetch_all_services(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_services,
+            data: fieldset.select_all(fetched_services),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_services(db: &Pool<Postgres>) -> Result<Vec<Service>, ServiceError> {
-    let fetched_services = Service::select_all(db)
+async fn fetch_all_services(
+    list_filter: &ListFilter<ServiceFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Service>, ServiceError> {
+    let fetched_services = Service::select_all(list_filter, db)
         .await
         .context("Failed to fetch the services from the database")?;
     Ok(fetched_services)
@@ -137,14 +210,48 @@ async fn fetch_all_services(db: &Pool<Postgres>) -> Result<Vec<Service>, Service
 async fn fetch_services_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<ServiceFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Service>, ServiceError> {
+) -> Result<Page<Service>, ServiceError> {
     let fetched_services = Service::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the services from the database for the provided page")?;
 
-    Ok(fetched_services.items)
+    Ok(fetched_services)
+}
+
+async fn fetch_services_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Service>>,
+    list_filter: &ListFilter<ServiceFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Service>, ServiceError> {
+    let fetched_services = Service::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the services from the database for the provided cursor")?;
+
+    Ok(fetched_services)
+}
+
+#[get("/suggest/")]
+async fn suggest_services(
+    _: Authorized<CatalogRead>,
+    Query(params): Query<SuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = params.limit()?;
+
+    let suggested_services = Service::select_suggestions(params.prefix, limit, db.get_ref())
+        .await
+        .context("Failed to fetch the service suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_services,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -154,26 +261,48 @@ struct ServiceManipulationParams {
     id: i32,
 }
 
+#[routes]
 #[get("/view/")]
+#[get("/{id}/")]
 async fn fetch_service(
-    Query(params): Query<ServiceManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ServiceManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_service =
-        Service::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the service from the database"),
-                ),
-            })?;
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_service,
-    }))
+    let entity_tag = select_entity_tag(
+        "service",
+        "services",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_service = Service::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the service from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_service,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -183,33 +312,48 @@ async fn fetch_service(
 struct UpdateServicePartiallyPayload {
     name: MaybeAbsent<String>,
     description: MaybeAbsent<String>,
-    coordinator_national_id: MaybeAbsent<String>,
+    coordinator_national_id: MaybeAbsent<NationalId>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{id}/")]
 async fn update_service_partially(
-    Query(params): Query<ServiceManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ServiceManipulationParams>,
     Json(payload): Json<UpdateServicePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let service_to_update =
-        Service::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the service to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "service",
+            "services",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let service_to_update = Service::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the service to update from the database"),
+            ),
+        })?;
 
     let updated_service = UpdateService {
         name: payload.name.into(),
         description: payload.description.into(),
-        coordinator_national_id: payload.coordinator_national_id.into(),
+        coordinator_national_id: payload.coordinator_national_id.map(String::from).into(),
     }
-    .update(service_to_update, db.get_ref())
+    .update(service_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -218,11 +362,19 @@ async fn update_service_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("services", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the service into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_service,
     }))
@@ -234,33 +386,48 @@ async fn update_service_partially(
 struct UpdateServiceCompletelyPayload {
     name: String,
     description: String,
-    coordinator_national_id: String,
+    coordinator_national_id: NationalId,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{id}/")]
 async fn update_service_completely(
-    Query(params): Query<ServiceManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ServiceManipulationParams>,
     Json(payload): Json<UpdateServiceCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let service_to_update =
-        Service::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the service to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "service",
+            "services",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let service_to_update = Service::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the service to update from the database"),
+            ),
+        })?;
 
     let updated_service = UpdateService {
         name: Some(payload.name),
         description: Some(payload.description),
-        coordinator_national_id: Some(payload.coordinator_national_id),
+        coordinator_national_id: Some(payload.coordinator_national_id.into()),
     }
-    .update(service_to_update, db.get_ref())
+    .update(service_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
@@ -269,34 +436,86 @@ async fn update_service_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("services", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the service into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_service,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{id}/")]
 async fn delete_service(
-    Query(params): Query<ServiceManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<ServiceManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_service =
-        Service::delete(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the service to delete from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "service",
+        "services",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_service = Service::delete(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("service".to_string(), anyhow!(err))
+            }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ServiceError::DeleteConflictError(
+                    "The service is still referenced by other resources".to_string(),
+                )
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the service to delete from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
 
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_service,
     }))
 }
+
+#[routes]
+#[get("/dependencies/")]
+#[get("/{id}/dependencies/")]
+async fn fetch_service_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<ServiceManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "services".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the service from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -11,13 +14,26 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::employee::{Employee, InsertEmployee, UpdateEmployee},
-    services::pagination_params::PaginationParams,
+    models::domains::{Email, NationalId, PhoneNo, Rif},
+    models::employee::{Employee, EmployeeFilter, InsertEmployee, UpdateEmployee},
+    models::role_permission::RolePermission,
+    services::pagination_params::SoftDeletablePaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
     utils::{
+        audit::AuditContext,
+        authorization::{
+            ensure_permission, AllDealerships, Authorized, Permission, SalariesWrite, StaffRead,
+            StaffWrite,
+        },
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
         deserialization::{MaybeAbsent, MaybeNull},
-        pagination::Paginable,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
     },
 };
 
@@ -28,41 +44,65 @@ pub fn configure(configuration: &mut ServiceConfig) {
         .service(create_employee)
         .service(update_employee_partially)
         .service(update_employee_completely)
-        .service(delete_employee);
+        .service(delete_employee)
+        .service(restore_employee);
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct CreateEmployeePayload {
-    national_id: String,
+    national_id: NationalId,
     full_name: String,
-    main_phone_no: String,
-    secondary_phone_no: String,
-    email: String,
+    main_phone_no: PhoneNo,
+    secondary_phone_no: PhoneNo,
+    email: Email,
     address: String,
-    helped_dealership_rif: Option<String>,
+    helped_dealership_rif: Option<Rif>,
     role_id: i32,
     salary: BigDecimal,
 }
 
 #[post("/")]
 async fn create_employee(
+    authorization: Authorized<StaffWrite>,
+    _: Authorized<SalariesWrite>,
     Json(payload): Json<CreateEmployeePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let grants_all_dealerships = RolePermission::select(
+        payload.role_id,
+        AllDealerships::NAME.to_string(),
+        db.get_ref(),
+    )
+    .await
+    .map(|_| true)
+    .or_else(|err| match &err {
+        sqlx::Error::RowNotFound => Ok(false),
+        _ => Err(ServiceError::UnexpectedError(anyhow!(err).context(
+            "Failed to fetch the permissions of the role from the database",
+        ))),
+    })?;
+
+    if grants_all_dealerships {
+        ensure_permission(&authorization.employee, AllDealerships::NAME, db.get_ref()).await?;
+    }
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_employee = InsertEmployee {
-        national_id: payload.national_id,
+        national_id: payload.national_id.into(),
         full_name: payload.full_name,
-        main_phone_no: payload.main_phone_no,
-        secondary_phone_no: payload.secondary_phone_no,
-        email: payload.email,
+        main_phone_no: payload.main_phone_no.into(),
+        secondary_phone_no: payload.secondary_phone_no.into(),
+        email: payload.email.into(),
         address: payload.address,
-        helped_dealership_rif: payload.helped_dealership_rif,
+        helped_dealership_rif: payload.helped_dealership_rif.map(String::from),
         role_id: payload.role_id,
         salary: payload.salary,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -77,11 +117,19 @@ async fn create_employee(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("staff", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the employee into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_employee,
     }))
@@ -89,9 +137,37 @@ async fn create_employee(
 
 #[get("/")]
 async fn fetch_staff(
-    Query(pagination_params): Query<PaginationParams>,
+    _: Authorized<StaffRead>,
+    Query(pagination_params): Query<SoftDeletablePaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<EmployeeFilter>()?;
+    let fieldset = pagination_params.fieldset(Employee::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_staff = fetch_staff_after_cursor(
+            limit,
+            cursor,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination =
+            CursorPagination::new(limit, fetched_staff.prev_cursor, fetched_staff.next_cursor);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_staff.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -122,35 +198,67 @@ async fn fetch_staff(
             ));
         }
 
-        let fetched_staff = fetch_staff_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_staff = Employee::count(db.get_ref())
+        let fetched_staff = fetch_staff_paginated(
+            per_page,
+            page_no,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_staff = match fetched_staff.total {
+            Some(total_staff) => total_staff,
+            None => Employee::count(
+                pagination_params.include_deleted,
+                &list_filter,
+                db.get_ref(),
+            )
             .await
-            .context("Failed to count the staff from the database")?;
+            .context("Failed to count the staff from the database")?,
+        };
 
+        let pagination = Pagination::new(total_staff, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_staff,
-                pagination: Pagination::new(total_staff, page_no, per_page),
+                data: fieldset.select_all(fetched_staff.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_staff = fetch_all_staff(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let staff = Employee::stream_all(pagination_params.include_deleted, &list_filter, db);
+            fieldset.select_stream(staff)
+        }));
+    }
+
+    let fetched_staff = fetch_all_staff(
+        pagination_params.include_deleted,
+        &list_filter,
+        db.get_ref(),
+    )
+    .await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_staff,
+            data: fieldset.select_all(fetched_staff),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_staff(db: &Pool<Postgres>) -> Result<Vec<Employee>, ServiceError> {
-    let fetched_staff = Employee::select_all(db)
+async fn fetch_all_staff(
+    include_deleted: bool,
+    list_filter: &ListFilter<EmployeeFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Employee>, ServiceError> {
+    let fetched_staff = Employee::select_all(include_deleted, list_filter, db)
         .await
         .context("Failed to fetch the staff from the database")?;
     Ok(fetched_staff)
@@ -159,42 +267,99 @@ async fn fetch_all_staff(db: &Pool<Postgres>) -> Result<Vec<Employee>, ServiceEr
 async fn fetch_staff_paginated(
     per_page: i64,
     page_no: i64,
+    include_deleted: bool,
+    list_filter: &ListFilter<EmployeeFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Employee>, ServiceError> {
+) -> Result<Page<Employee>, ServiceError> {
     let fetched_staff = Employee::paginate(per_page)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the staff from the database for the provided page")?;
 
-    Ok(fetched_staff.items)
+    Ok(fetched_staff)
+}
+
+async fn fetch_staff_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Employee>>,
+    include_deleted: bool,
+    list_filter: &ListFilter<EmployeeFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Employee>, ServiceError> {
+    let fetched_staff = Employee::paginate(limit)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the staff from the database for the provided cursor")?;
+
+    Ok(fetched_staff)
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 struct EmployeeManipulationParams {
-    national_id: String,
+    national_id: NationalId,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct EmployeeViewParams {
+    national_id: NationalId,
+    #[serde(default)]
+    include_deleted: bool,
+}
+
+#[routes]
 #[get("/view/")]
+#[get("/{national_id}/")]
 async fn fetch_employee(
-    Query(params): Query<EmployeeManipulationParams>,
+    _: Authorized<StaffRead>,
+    PathQuery(params): PathQuery<EmployeeViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_employee = Employee::select(params.national_id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "employee",
+        "staff",
+        vec![params.national_id.clone().into()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_employee = Employee::select(
+        params.national_id.into(),
+        params.include_deleted,
+        &mut *transaction,
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("employee".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the employee from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
         .await
-        .map_err(|err| match &err {
-            sqlx::Error::RowNotFound => {
-                ServiceError::ResourceNotFound("employee".to_string(), anyhow!(err))
-            }
-            _ => ServiceError::UnexpectedError(
-                anyhow!(err).context("Failed to fetch the employee from the database"),
-            ),
-        })?;
+        .context("Failed to commit the transaction")?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_employee,
-    }))
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_employee,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -202,24 +367,40 @@ async fn fetch_employee(
 #[serde(deny_unknown_fields)]
 #[serde(default)]
 struct UpdateEmployeePartiallyPayload {
-    national_id: MaybeAbsent<String>,
+    national_id: MaybeAbsent<NationalId>,
     full_name: MaybeAbsent<String>,
-    main_phone_no: MaybeAbsent<String>,
-    secondary_phone_no: MaybeAbsent<String>,
-    email: MaybeAbsent<String>,
+    main_phone_no: MaybeAbsent<PhoneNo>,
+    secondary_phone_no: MaybeAbsent<PhoneNo>,
+    email: MaybeAbsent<Email>,
     address: MaybeAbsent<String>,
-    helped_dealership_rif: MaybeAbsent<MaybeNull<String>>,
+    helped_dealership_rif: MaybeAbsent<MaybeNull<Rif>>,
     role_id: MaybeAbsent<i32>,
     salary: MaybeAbsent<BigDecimal>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{national_id}/")]
 async fn update_employee_partially(
-    Query(params): Query<EmployeeManipulationParams>,
+    authorization: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<EmployeeManipulationParams>,
     Json(payload): Json<UpdateEmployeePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let employee_to_update = Employee::select(params.national_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "employee",
+            "staff",
+            vec![params.national_id.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let employee_to_update = Employee::select(params.national_id.into(), false, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -230,18 +411,34 @@ async fn update_employee_partially(
             ),
         })?;
 
+    let new_salary: Option<BigDecimal> = payload.salary.into();
+    if new_salary
+        .as_ref()
+        .is_some_and(|salary| *salary != employee_to_update.salary)
+    {
+        ensure_permission(&authorization.employee, SalariesWrite::NAME, db.get_ref()).await?;
+    }
+
+    let new_role_id: Option<i32> = payload.role_id.into();
+    if new_role_id.is_some_and(|role_id| role_id != employee_to_update.role_id) {
+        ensure_permission(&authorization.employee, AllDealerships::NAME, db.get_ref()).await?;
+    }
+
     let updated_employee = UpdateEmployee {
-        national_id: payload.national_id.into(),
+        national_id: payload.national_id.map(String::from).into(),
         full_name: payload.full_name.into(),
-        main_phone_no: payload.main_phone_no.into(),
-        secondary_phone_no: payload.secondary_phone_no.into(),
-        email: payload.email.into(),
+        main_phone_no: payload.main_phone_no.map(String::from).into(),
+        secondary_phone_no: payload.secondary_phone_no.map(String::from).into(),
+        email: payload.email.map(String::from).into(),
         address: payload.address.into(),
-        helped_dealership_rif: payload.helped_dealership_rif.into(),
-        role_id: payload.role_id.into(),
-        salary: payload.salary.into(),
+        helped_dealership_rif: payload
+            .helped_dealership_rif
+            .map(|rif| rif.map(String::from))
+            .into(),
+        role_id: new_role_id,
+        salary: new_salary,
     }
-    .update(employee_to_update, db.get_ref())
+    .update(employee_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -256,11 +453,19 @@ async fn update_employee_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("staff", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the employee from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_employee,
     }))
@@ -270,24 +475,40 @@ async fn update_employee_partially(
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 struct UpdateEmployeeCompletelyPayload {
-    national_id: String,
+    national_id: NationalId,
     full_name: String,
-    main_phone_no: String,
-    secondary_phone_no: String,
-    email: String,
+    main_phone_no: PhoneNo,
+    secondary_phone_no: PhoneNo,
+    email: Email,
     address: String,
-    helped_dealership_rif: MaybeNull<String>,
+    helped_dealership_rif: MaybeNull<Rif>,
     role_id: i32,
     salary: BigDecimal,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{national_id}/")]
 async fn update_employee_completely(
-    Query(params): Query<EmployeeManipulationParams>,
+    authorization: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<EmployeeManipulationParams>,
     Json(payload): Json<UpdateEmployeeCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let employee_to_update = Employee::select(params.national_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "employee",
+            "staff",
+            vec![params.national_id.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let employee_to_update = Employee::select(params.national_id.into(), false, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -298,18 +519,26 @@ async fn update_employee_completely(
             ),
         })?;
 
+    if payload.salary != employee_to_update.salary {
+        ensure_permission(&authorization.employee, SalariesWrite::NAME, db.get_ref()).await?;
+    }
+
+    if payload.role_id != employee_to_update.role_id {
+        ensure_permission(&authorization.employee, AllDealerships::NAME, db.get_ref()).await?;
+    }
+
     let updated_employee = UpdateEmployee {
-        national_id: Some(payload.national_id),
+        national_id: Some(payload.national_id.into()),
         full_name: Some(payload.full_name),
-        main_phone_no: Some(payload.main_phone_no),
-        secondary_phone_no: Some(payload.secondary_phone_no),
-        email: Some(payload.email),
+        main_phone_no: Some(payload.main_phone_no.into()),
+        secondary_phone_no: Some(payload.secondary_phone_no.into()),
+        email: Some(payload.email.into()),
         address: Some(payload.address),
-        helped_dealership_rif: Some(payload.helped_dealership_rif.into()),
+        helped_dealership_rif: Some(payload.helped_dealership_rif.map(String::from).into()),
         role_id: Some(payload.role_id),
         salary: Some(payload.salary),
     }
-    .update(employee_to_update, db.get_ref())
+    .update(employee_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -324,22 +553,36 @@ async fn update_employee_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("staff", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the employee from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_employee,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{national_id}/")]
 async fn delete_employee(
-    Query(params): Query<EmployeeManipulationParams>,
+    _: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<EmployeeManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_employee = Employee::delete(params.national_id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_employee = Employee::delete(params.national_id.into(), &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -350,7 +593,44 @@ async fn delete_employee(
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_employee,
     }))
 }
+
+#[routes]
+#[post("/restore/")]
+#[post("/{national_id}/restore/")]
+async fn restore_employee(
+    _: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<EmployeeManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let restored_employee = Employee::restore(params.national_id.into(), &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("deleted employee".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to restore the employee from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: restored_employee,
+    }))
+}
@@ -0,0 +1,418 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{self, Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::domains::NationalId,
+    models::staff_account::{
+        InsertStaffAccount, StaffAccount, StaffAccountFilter, UpdateStaffAccount,
+    },
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        audit::AuditContext,
+        authentication::hash_password,
+        authorization::{Authorized, DealershipScope, StaffRead, StaffWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_staff_accounts)
+        .service(fetch_staff_account)
+        .service(create_staff_account)
+        .service(update_staff_account_password)
+        .service(delete_staff_account);
+}
+
+async fn hash_valid_password(password: String) -> Result<String, ServiceError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ServiceError::invalid_field(
+            "password",
+            format!(
+                "The password must have at least {} characters",
+                MIN_PASSWORD_LENGTH
+            ),
+        ));
+    }
+
+    let password_hash = web::block(move || hash_password(&password))
+        .await
+        .context("Failed to hash the password")?
+        .context("Failed to hash the password")?;
+
+    Ok(password_hash)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct CreateStaffAccountPayload {
+    national_id: NationalId,
+    password: String,
+}
+
+#[post("/")]
+async fn create_staff_account(
+    _: Authorized<StaffWrite>,
+    Json(payload): Json<CreateStaffAccountPayload>,
+    scope: DealershipScope,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    scope
+        .ensure_includes_employee(payload.national_id.clone().into(), db.get_ref())
+        .await?;
+
+    let password_hash = hash_valid_password(payload.password).await?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let created_staff_account = InsertStaffAccount {
+        national_id: payload.national_id.into(),
+        password_hash,
+    }
+    .insert(&mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified staff member already has an account".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidCreateError(
+                "The specified nationalId doesn't belong to any staff member".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("staff_accounts", db_err.as_ref())
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to insert the staff account into the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: created_staff_account,
+    }))
+}
+
+#[get("/")]
+async fn fetch_staff_accounts(
+    _: Authorized<StaffRead>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<StaffAccountFilter>()?;
+    let fieldset = pagination_params.fieldset(StaffAccount::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_staff_accounts =
+            fetch_staff_accounts_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_staff_accounts.prev_cursor,
+            fetched_staff_accounts.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_staff_accounts.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if pagination_params.per_page.is_none() && pagination_params.page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (pagination_params.per_page, pagination_params.page_no)
+    {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_staff_accounts =
+            fetch_staff_accounts_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
+
+        let total_staff_accounts = match fetched_staff_accounts.total {
+            Some(total_staff_accounts) => total_staff_accounts,
+            None => StaffAccount::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the staff accounts from the database")?,
+        };
+
+        let pagination = Pagination::new(total_staff_accounts, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_staff_accounts.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let staff_accounts = StaffAccount::stream_all(&list_filter, db);
+            fieldset.select_stream(staff_accounts)
+        }));
+    }
+
+    let fetched_staff_accounts = fetch_all_staff_accounts(&list_filter, db.get_ref()).await?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .json(NonPaginatedResponseDto {
+            data: fieldset.select_all(fetched_staff_accounts),
+        });
+
+    Ok(response)
+}
+
+async fn fetch_all_staff_accounts(
+    list_filter: &ListFilter<StaffAccountFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<StaffAccount>, ServiceError> {
+    let fetched_staff_accounts = StaffAccount::select_all(list_filter, db)
+        .await
+        .context("Failed to fetch the staff accounts from the database")?;
+    Ok(fetched_staff_accounts)
+}
+
+async fn fetch_staff_accounts_paginated(
+    per_page: i64,
+    page_no: i64,
+    list_filter: &ListFilter<StaffAccountFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<StaffAccount>, ServiceError> {
+    let fetched_staff_accounts = StaffAccount::paginate(per_page)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context("Failed to fetch the staff accounts from the database for the provided page")?;
+
+    Ok(fetched_staff_accounts)
+}
+
+async fn fetch_staff_accounts_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<StaffAccount>>,
+    list_filter: &ListFilter<StaffAccountFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<StaffAccount>, ServiceError> {
+    let fetched_staff_accounts = StaffAccount::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the staff accounts from the database for the provided cursor")?;
+
+    Ok(fetched_staff_accounts)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct StaffAccountManipulationParams {
+    national_id: NationalId,
+}
+
+#[routes]
+#[get("/view/")]
+#[get("/{national_id}/")]
+async fn fetch_staff_account(
+    _: Authorized<StaffRead>,
+    PathQuery(params): PathQuery<StaffAccountManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "staff account",
+        "staff_accounts",
+        vec![params.national_id.clone().into()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_staff_account = StaffAccount::select(params.national_id.into(), &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("staff account".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the staff account from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_staff_account,
+        });
+
+    Ok(response)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct UpdateStaffAccountPasswordPayload {
+    password: String,
+}
+
+#[routes]
+#[put("/")]
+#[put("/{national_id}/")]
+async fn update_staff_account_password(
+    authorization: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<StaffAccountManipulationParams>,
+    Json(payload): Json<UpdateStaffAccountPasswordPayload>,
+    scope: DealershipScope,
+    precondition: Precondition,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "staff account",
+            "staff_accounts",
+            vec![params.national_id.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let staff_account_to_update =
+        StaffAccount::select(params.national_id.into(), &mut *transaction)
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("staff account".to_string(), anyhow!(err))
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err)
+                        .context("Failed to fetch the staff account to update from the database"),
+                ),
+            })?;
+
+    if staff_account_to_update.national_id != authorization.employee.national_id {
+        scope
+            .ensure_includes_employee(staff_account_to_update.national_id.clone(), db.get_ref())
+            .await?;
+    }
+
+    let password_hash = hash_valid_password(payload.password).await?;
+
+    let updated_staff_account = UpdateStaffAccount {
+        password_hash: Some(password_hash),
+    }
+    .update(staff_account_to_update, &mut *transaction)
+    .await
+    .context("Failed to update the staff account from the database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: updated_staff_account,
+    }))
+}
+
+#[routes]
+#[delete("/")]
+#[delete("/{national_id}/")]
+async fn delete_staff_account(
+    _: Authorized<StaffWrite>,
+    PathQuery(params): PathQuery<StaffAccountManipulationParams>,
+    scope: DealershipScope,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    scope
+        .ensure_includes_employee(params.national_id.clone().into(), db.get_ref())
+        .await?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_staff_account = StaffAccount::delete(params.national_id.into(), &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("staff account".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to delete the staff account from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: deleted_staff_account,
+    }))
+}
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,16 +13,31 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::state::{InsertState, State, UpdateState},
+    models::dependency::Dependency,
+    models::state::{InsertState, State, StateFilter, UpdateState},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    services::suggestion_params::SuggestionParams,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_states)
+        .service(suggest_states)
+        .service(fetch_state_dependencies)
         .service(fetch_state)
         .service(create_state)
         .service(update_state_partially)
@@ -36,11 +54,15 @@ struct CreateStatePayload {
 
 #[post("/states/")]
 async fn create_state(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateStatePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_state = InsertState { name: payload.name }
-        .insert(db.get_ref())
+        .insert(&mut *transaction)
         .await
         .map_err(|err| match &err {
             _ => ServiceError::UnexpectedError(
@@ -48,6 +70,11 @@ async fn create_state(
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_state,
     }))
@@ -55,9 +82,34 @@ async fn create_state(
 
 #[get("/states/")]
 async fn fetch_states(
+    _: Authorized<CatalogRead>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<StateFilter>()?;
+    let fieldset = pagination_params.fieldset(State::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_states =
+            fetch_states_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_states.prev_cursor,
+            fetched_states.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_states.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -88,35 +140,51 @@ async fn fetch_states(
             ));
         }
 
-        let fetched_states = fetch_states_paginated(per_page, page_no, db.get_ref()).await?;
+        let fetched_states =
+            fetch_states_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_states = State::count(db.get_ref())
-            .await
-            .context("Failed to count the states from the database")?;
+        let total_states = match fetched_states.total {
+            Some(total_states) => total_states,
+            None => State::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the states from the database")?,
+        };
 
+        let pagination = Pagination::new(total_states, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_states,
-                pagination: Pagination::new(total_states, page_no, per_page),
+                data: fieldset.select_all(fetched_states.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_states = fetch_all_states(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let states = State::stream_all(&list_filter, db);
+            fieldset.select_stream(states)
+        }));
+    }
+
+    let fetched_states = fetch_all_states(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_states,
+            data: fieldset.select_all(fetched_states),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_states(db: &Pool<Postgres>) -> Result<Vec<State>, ServiceError> {
-    let fetched_states = State::select_all(db)
+async fn fetch_all_states(
+    list_filter: &ListFilter<StateFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<State>, ServiceError> {
+    let fetched_states = State::select_all(list_filter, db)
         .await
         .context("Failed to fetch the states from the database")?;
     Ok(fetched_states)
@@ -125,14 +193,48 @@ async fn fetch_all_states(db: &Pool<Postgres>) -> Result<Vec<State>, ServiceErro
 async fn fetch_states_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<StateFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<State>, ServiceError> {
+) -> Result<Page<State>, ServiceError> {
     let fetched_states = State::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the states from the database for the provided page")?;
 
-    Ok(fetched_states.items)
+    Ok(fetched_states)
+}
+
+async fn fetch_states_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<State>>,
+    list_filter: &ListFilter<StateFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<State>, ServiceError> {
+    let fetched_states = State::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the states from the database for the provided cursor")?;
+
+    Ok(fetched_states)
+}
+
+#[get("/states/suggest/")]
+async fn suggest_states(
+    _: Authorized<CatalogRead>,
+    Query(params): Query<SuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = params.limit()?;
+
+    let suggested_states = State::select_suggestions(params.prefix, limit, db.get_ref())
+        .await
+        .context("Failed to fetch the state suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_states,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -142,12 +244,25 @@ struct StateManipulationParams {
     id: i32,
 }
 
+#[routes]
 #[get("/states/view/")]
+#[get("/states/{id}/")]
 async fn fetch_state(
-    Query(params): Query<StateManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<StateManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_state = State::select(params.id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "state",
+        "states",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_state = State::select(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -158,9 +273,19 @@ async fn fetch_state(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_state,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_state,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -171,28 +296,43 @@ struct UpdateStatePartiallyPayload {
     name: MaybeAbsent<String>,
 }
 
+#[routes]
 #[patch("/states/")]
+#[patch("/states/{id}/")]
 async fn update_state_partially(
-    Query(params): Query<StateManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<StateManipulationParams>,
     Json(payload): Json<UpdateStatePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let state_to_update =
-        State::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the state to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "state",
+            "states",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let state_to_update = State::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the state to update from the database"),
+            ),
+        })?;
 
     let updated_state = UpdateState {
         name: payload.name.into(),
     }
-    .update(state_to_update, db.get_ref())
+    .update(state_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         _ => ServiceError::UnexpectedError(
@@ -200,6 +340,11 @@ async fn update_state_partially(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_state,
     }))
@@ -212,28 +357,43 @@ struct UpdateStateCompletelyPayload {
     name: String,
 }
 
+#[routes]
 #[put("/states/")]
+#[put("/states/{id}/")]
 async fn update_state_completely(
-    Query(params): Query<StateManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<StateManipulationParams>,
     Json(payload): Json<UpdateStateCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let state_to_update =
-        State::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the state to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "state",
+            "states",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let state_to_update = State::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the state to update from the database"),
+            ),
+        })?;
 
     let updated_state = UpdateState {
         name: Some(payload.name),
     }
-    .update(state_to_update, db.get_ref())
+    .update(state_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         _ => ServiceError::UnexpectedError(
@@ -241,28 +401,73 @@ async fn update_state_completely(
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_state,
     }))
 }
 
+#[routes]
 #[delete("/states/")]
+#[delete("/states/{id}/")]
 async fn delete_state(
-    Query(params): Query<StateManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<StateManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_state = State::delete(params.id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable("state", "states", vec![params.id.to_string()], &mut *transaction).await?;
+
+    let deleted_state =
+        State::delete(params.id, &mut *transaction)
+            .await
+            .map_err(|err| match &err {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
+                }
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    ServiceError::DeleteConflictError(
+                        "The state is still referenced by other resources".to_string(),
+                    )
+                }
+                _ => ServiceError::UnexpectedError(
+                    anyhow!(err).context("Failed to get the state to delete from the database"),
+                ),
+            })?;
+
+    transaction
+        .commit()
         .await
-        .map_err(|err| match &err {
-            sqlx::Error::RowNotFound => {
-                ServiceError::ResourceNotFound("state".to_string(), anyhow!(err))
-            }
-            _ => ServiceError::UnexpectedError(
-                anyhow!(err).context("Failed to get the state to delete from the database"),
-            ),
-        })?;
+        .context("Failed to commit the transaction")?;
 
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_state,
     }))
 }
+
+#[routes]
+#[get("/states/dependencies/")]
+#[get("/states/{id}/dependencies/")]
+async fn fetch_state_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<StateManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "states".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the state from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,9 +1,12 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
-    HttpResponse, Responder,
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
+    Either, HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
 use bigdecimal::BigDecimal;
@@ -11,21 +14,46 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::stock_item::{InsertStockItem, StockItem, UpdateStockItem},
+    models::dealership::Dealership,
+    models::dependency::Dependency,
+    models::domains::Rif,
+    models::stock_item::{InsertStockItem, StockItem, StockItemFilter, UpdateStockItem},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, DealershipScope, StockRead, StockWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        expansion::{Expandable, Expanded, Expansion},
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
+    views::reorder_suggestion::ReorderSuggestion,
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_stock)
+        .service(fetch_stock_item_dependencies)
         .service(fetch_stock_item)
         .service(create_stock_item)
         .service(update_stock_item_partially)
         .service(update_stock_item_completely)
-        .service(delete_stock_item);
+        .service(delete_stock_item)
+        .service(fetch_reorder_suggestions);
+}
+
+pub fn configure_nested(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_dealership_stock)
+        .service(create_dealership_stock_item);
 }
 
 #[derive(Deserialize)]
@@ -33,29 +61,56 @@ pub fn configure(configuration: &mut ServiceConfig) {
 #[serde(deny_unknown_fields)]
 struct CreateStockItemPayload {
     product_id: i32,
-    dealership_rif: String,
+    dealership_rif: Rif,
     product_cost: BigDecimal,
     product_count: i32,
-    vendor_name: String,
+    vendor_id: i32,
     max_capacity: i32,
     min_capacity: i32,
 }
 
 #[post("/")]
 async fn create_stock_item(
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
     Json(payload): Json<CreateStockItemPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_stock_item(payload, scope, audit, db).await
+}
+
+#[post("/{dealership_rif}/stock/")]
+async fn create_dealership_stock_item(
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    PathJson(payload): PathJson<CreateStockItemPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_stock_item(payload, scope, audit, db).await
+}
+
+async fn insert_stock_item(
+    payload: CreateStockItemPayload,
+    scope: DealershipScope,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(payload.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_stock_item = InsertStockItem {
         product_id: payload.product_id,
-        dealership_rif: payload.dealership_rif,
+        dealership_rif: payload.dealership_rif.into(),
         product_cost: payload.product_cost,
         product_count: payload.product_count,
-        vendor_name: payload.vendor_name,
+        vendor_id: payload.vendor_id,
         max_capacity: payload.max_capacity,
         min_capacity: payload.min_capacity,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -66,15 +121,23 @@ async fn create_stock_item(
         },
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
             ServiceError::InvalidCreateError(
-                "One of the specified values for one of the following keys does not exist: productId, dealershipRif".to_string(),
+                "One of the specified values for one of the following keys does not exist: productId, dealershipRif, vendorId".to_string(),
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("stock", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to insert the stock item into the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_stock_item,
     }))
@@ -82,9 +145,104 @@ async fn create_stock_item(
 
 #[get("/")]
 async fn fetch_stock(
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<StockItemFilter>()?;
+
+    list_stock(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/{dealership_rif}/stock/")]
+async fn fetch_dealership_stock(
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
+    dealership_rif: Path<Rif>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let dealership_rif = dealership_rif.into_inner();
+
+    scope.ensure_includes(dealership_rif.as_str())?;
+
+    Dealership::select(dealership_rif.clone().into(), false, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("dealership".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the dealership from the database"),
+            ),
+        })?;
+
+    let mut list_filter = pagination_params.list_filter::<StockItemFilter>()?;
+    list_filter.conditions.dealership_rif.eq = Some(dealership_rif);
+
+    list_stock(
+        pagination_params,
+        list_filter,
+        scope,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_stock(
+    pagination_params: PaginationParams,
+    list_filter: ListFilter<StockItemFilter>,
+    scope: DealershipScope,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(StockItem::FIELDS)?;
+    let expansion = pagination_params.expansion(StockItem::RELATIONS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_stock_items = fetch_stock_after_cursor(
+            limit,
+            cursor,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let expanded_stock_items =
+            expand_stock(fetched_stock_items.items, &expansion, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_stock_items.prev_cursor,
+            fetched_stock_items.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(expanded_stock_items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -115,35 +273,65 @@ async fn fetch_stock(
             ));
         }
 
-        let fetched_stock_items = fetch_stock_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_stock_items = StockItem::count(db.get_ref())
-            .await
-            .context("Failed to count the stock from the database")?;
-
+        let fetched_stock_items = fetch_stock_paginated(
+            per_page,
+            page_no,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_stock_items = match fetched_stock_items.total {
+            Some(total_stock_items) => total_stock_items,
+            None => StockItem::count(scope.dealership_rif, &list_filter, db.get_ref())
+                .await
+                .context("Failed to count the stock from the database")?,
+        };
+
+        let expanded_stock_items =
+            expand_stock(fetched_stock_items.items, &expansion, db.get_ref()).await?;
+
+        let pagination = Pagination::new(total_stock_items, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_stock_items,
-                pagination: Pagination::new(total_stock_items, page_no, per_page),
+                data: fieldset.select_all(expanded_stock_items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_stock_items = fetch_all_stock(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        expansion.ensure_streamable()?;
+
+        return Ok(ndjson_response(db, move |db| {
+            let stock_items = StockItem::stream_all(scope.dealership_rif, &list_filter, db);
+            fieldset.select_stream(stock_items)
+        }));
+    }
+
+    let fetched_stock_items =
+        fetch_all_stock(scope.dealership_rif, &list_filter, db.get_ref()).await?;
+    let expanded_stock_items = expand_stock(fetched_stock_items, &expansion, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_stock_items,
+            data: fieldset.select_all(expanded_stock_items),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_stock(db: &Pool<Postgres>) -> Result<Vec<StockItem>, ServiceError> {
-    let fetched_stock_items = StockItem::select_all(db)
+async fn fetch_all_stock(
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<StockItemFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<StockItem>, ServiceError> {
+    let fetched_stock_items = StockItem::select_all(dealership_rif, list_filter, db)
         .await
         .context("Failed to fetch the stock from the database")?;
     Ok(fetched_stock_items)
@@ -152,14 +340,47 @@ async fn fetch_all_stock(db: &Pool<Postgres>) -> Result<Vec<StockItem>, ServiceE
 async fn fetch_stock_paginated(
     per_page: i64,
     page_no: i64,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<StockItemFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<StockItem>, ServiceError> {
+) -> Result<Page<StockItem>, ServiceError> {
     let fetched_stock_items = StockItem::paginate(per_page)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the stock from the database for the provided page")?;
 
-    Ok(fetched_stock_items.items)
+    Ok(fetched_stock_items)
+}
+
+async fn fetch_stock_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<StockItem>>,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<StockItemFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<StockItem>, ServiceError> {
+    let fetched_stock_items = StockItem::paginate(limit)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the stock from the database for the provided cursor")?;
+
+    Ok(fetched_stock_items)
+}
+
+async fn expand_stock(
+    stock_items: Vec<StockItem>,
+    expansion: &Expansion,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Expanded<StockItem>>, ServiceError> {
+    let expanded_stock_items = StockItem::expand(stock_items, expansion, db)
+        .await
+        .context("Failed to fetch the related resources of the stock from the database")?;
+
+    Ok(expanded_stock_items)
 }
 
 #[derive(Deserialize)]
@@ -167,15 +388,42 @@ async fn fetch_stock_paginated(
 #[serde(deny_unknown_fields)]
 struct StockItemManipulationParams {
     product_id: i32,
-    dealership_rif: String
+    dealership_rif: Rif
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct StockItemViewParams {
+    product_id: i32,
+    dealership_rif: Rif,
+    expand: Option<String>,
 }
 
+#[routes]
 #[get("/view/")]
+#[get("/{dealership_rif}/{product_id}/")]
 async fn fetch_stock_item(
-    Query(params): Query<StockItemManipulationParams>,
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockItemViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_stock_item = StockItem::select(params.product_id, params.dealership_rif, db.get_ref())
+    let expansion = Expansion::parse(StockItem::RELATIONS, params.expand.as_deref())?;
+
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "stock item",
+        "stock",
+        vec![params.product_id.to_string(), params.dealership_rif.clone().into()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_stock_item = StockItem::select(params.product_id, params.dealership_rif.into(), &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -186,9 +434,23 @@ async fn fetch_stock_item(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_stock_item,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let expanded_stock_item = StockItem::expand_one(fetched_stock_item, &expansion, db.get_ref())
+        .await
+        .context("Failed to fetch the related resources of the stock item from the database")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: expanded_stock_item,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -197,22 +459,46 @@ async fn fetch_stock_item(
 #[serde(default)]
 struct UpdateStockItemPartiallyPayload {
     product_id: MaybeAbsent<i32>,
-    dealership_rif: MaybeAbsent<String>,
+    dealership_rif: MaybeAbsent<Rif>,
     product_cost: MaybeAbsent<BigDecimal>,
     product_count: MaybeAbsent<i32>,
-    vendor_name: MaybeAbsent<String>,
+    vendor_id: MaybeAbsent<i32>,
     max_capacity: MaybeAbsent<i32>,
     min_capacity: MaybeAbsent<i32>,
 }
 
+#[routes]
 #[patch("/")]
+#[patch("/{dealership_rif}/{product_id}/")]
 async fn update_stock_item_partially(
-    Query(params): Query<StockItemManipulationParams>,
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockItemManipulationParams>,
     Json(payload): Json<UpdateStockItemPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let new_dealership_rif: Option<String> = payload.dealership_rif.map(String::from).into();
+    if let Some(dealership_rif) = &new_dealership_rif {
+        scope.ensure_includes(dealership_rif)?;
+    }
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "stock item",
+            "stock",
+            vec![params.product_id.to_string(), params.dealership_rif.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
     let state_to_update =
-        StockItem::select(params.product_id, params.dealership_rif, db.get_ref())
+        StockItem::select(params.product_id, params.dealership_rif.into(), &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -225,14 +511,14 @@ async fn update_stock_item_partially(
 
     let updated_stock_item = UpdateStockItem {
         product_id: payload.product_id.into(),
-        dealership_rif: payload.dealership_rif.into(),
+        dealership_rif: new_dealership_rif,
         product_cost: payload.product_cost.into(),
         product_count: payload.product_count.into(),
-        vendor_name: payload.vendor_name.into(),
+        vendor_id: payload.vendor_id.into(),
         max_capacity: payload.max_capacity.into(),
         min_capacity: payload.min_capacity.into(),
     }
-    .update(state_to_update, db.get_ref())
+    .update(state_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -243,15 +529,23 @@ async fn update_stock_item_partially(
         },
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
             ServiceError::InvalidUpdateError(
-                "One of the specified values for one of the following keys does not exist: productId, dealershipRif".to_string(),
+                "One of the specified values for one of the following keys does not exist: productId, dealershipRif, vendorId".to_string(),
                 anyhow!(err),
             )
         },
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("stock", db_err.as_ref())
+        },
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the stock item from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_stock_item,
     }))
@@ -262,22 +556,42 @@ async fn update_stock_item_partially(
 #[serde(deny_unknown_fields)]
 struct UpdateStockItemCompletelyPayload {
     product_id: i32,
-    dealership_rif: String,
+    dealership_rif: Rif,
     product_cost: BigDecimal,
     product_count: i32,
-    vendor_name: String,
+    vendor_id: i32,
     max_capacity: i32,
     min_capacity: i32,
 }
 
+#[routes]
 #[put("/")]
+#[put("/{dealership_rif}/{product_id}/")]
 async fn update_stock_item_completely(
-    Query(params): Query<StockItemManipulationParams>,
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockItemManipulationParams>,
     Json(payload): Json<UpdateStockItemCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+    scope.ensure_includes(payload.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "stock item",
+            "stock",
+            vec![params.product_id.to_string(), params.dealership_rif.clone().into()],
+            &mut *transaction,
+        )
+        .await?;
+
     let state_to_update =
-        StockItem::select(params.product_id, params.dealership_rif, db.get_ref())
+        StockItem::select(params.product_id, params.dealership_rif.into(), &mut *transaction)
             .await
             .map_err(|err| match &err {
                 sqlx::Error::RowNotFound => {
@@ -290,14 +604,14 @@ async fn update_stock_item_completely(
 
     let updated_stock_item = UpdateStockItem {
         product_id: Some(payload.product_id),
-        dealership_rif: Some(payload.dealership_rif),
+        dealership_rif: Some(payload.dealership_rif.into()),
         product_cost: Some(payload.product_cost),
         product_count: Some(payload.product_count),
-        vendor_name: Some(payload.vendor_name),
+        vendor_id: Some(payload.vendor_id),
         max_capacity: Some(payload.max_capacity),
         min_capacity: Some(payload.min_capacity),
     }
-    .update(state_to_update, db.get_ref())
+    .update(state_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -308,37 +622,155 @@ async fn update_stock_item_completely(
         },
         sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
             ServiceError::InvalidUpdateError(
-                "One of the specified values for one of the following keys does not exist: productId, dealershipRif".to_string(),
+                "One of the specified values for one of the following keys does not exist: productId, dealershipRif, vendorId".to_string(),
                 anyhow!(err),
             )
         },
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("stock", db_err.as_ref())
+        },
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the stock item from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_stock_item,
     }))
 }
 
+#[routes]
 #[delete("/")]
+#[delete("/{dealership_rif}/{product_id}/")]
 async fn delete_stock_item(
-    Query(params): Query<StockItemManipulationParams>,
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockItemManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_stock_item = StockItem::delete(params.product_id, params.dealership_rif, db.get_ref())
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "stock item",
+        "stock",
+        vec![params.product_id.to_string(), params.dealership_rif.clone().into()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_stock_item = StockItem::delete(params.product_id, params.dealership_rif.into(), &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
                 ServiceError::ResourceNotFound("stock item".to_string(), anyhow!(err))
             }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ServiceError::DeleteConflictError(
+                    "The stock item is still referenced by other resources".to_string(),
+                )
+            }
             _ => ServiceError::UnexpectedError(
                 anyhow!(err).context("Failed to get the stock item to delete from the database"),
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_stock_item,
     }))
 }
+
+#[routes]
+#[get("/dependencies/")]
+#[get("/{dealership_rif}/{product_id}/dependencies/")]
+async fn fetch_stock_item_dependencies(
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockItemManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let fetched_dependencies = Dependency::select_all(
+        "stock".to_string(),
+        vec![params.product_id.to_string(), params.dealership_rif.clone().into()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the stock item from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct FetchReorderSuggestionsParams {
+    dealership_rif: Rif,
+    consumption_window_days: Option<i32>,
+    cover_days: Option<i32>,
+}
+
+#[get("/reorder-suggestions/")]
+async fn fetch_reorder_suggestions(
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
+    Query(params): Query<FetchReorderSuggestionsParams>,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<Either<HttpResponse, impl Responder>, ServiceError> {
+    scope.ensure_includes(params.dealership_rif.as_str())?;
+
+    let consumption_window_days = params.consumption_window_days.unwrap_or(30);
+    let cover_days = params.cover_days.unwrap_or(7);
+
+    if consumption_window_days <= 0 {
+        return Err(ServiceError::InvalidQueryParamValueError(
+            "Query param consumption-window-days must be greater than 0".to_string(),
+        ));
+    }
+
+    if cover_days < 0 {
+        return Err(ServiceError::InvalidQueryParamValueError(
+            "Query param cover-days must be greater than or equal to 0".to_string(),
+        ));
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(Either::Left(ndjson_response(db, move |db| {
+            ReorderSuggestion::stream_all_by_dealership(
+                params.dealership_rif.into(),
+                consumption_window_days,
+                cover_days,
+                db,
+            )
+        })));
+    }
+
+    let fetched_reorder_suggestions = ReorderSuggestion::select_all_by_dealership(
+        params.dealership_rif.into(),
+        consumption_window_days,
+        cover_days,
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the reorder suggestions from the database")?;
+
+    Ok(Either::Right(Json(NonPaginatedResponseDto {
+        data: fetched_reorder_suggestions,
+    })))
+}
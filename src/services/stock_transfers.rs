@@ -0,0 +1,530 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{PgConnection, Pool, Postgres};
+
+use crate::{
+    models::domains::Rif,
+    models::{
+        stock_item::StockItem,
+        stock_transfer::{InsertStockTransfer, StockTransfer, StockTransferFilter},
+    },
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, DealershipScope, StockRead, StockWrite},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_stock_transfers)
+        .service(fetch_stock_transfer)
+        .service(create_stock_transfer)
+        .service(confirm_stock_transfer)
+        .service(cancel_stock_transfer);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct CreateStockTransferPayload {
+    product_id: i32,
+    source_dealership_rif: Rif,
+    destination_dealership_rif: Rif,
+    product_count: i32,
+}
+
+#[post("/")]
+async fn create_stock_transfer(
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    Json(payload): Json<CreateStockTransferPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    scope.ensure_includes(payload.source_dealership_rif.as_str())?;
+
+    if payload.product_count <= 0 {
+        return Err(ServiceError::invalid_field(
+            "productCount",
+            "The productCount must be greater than 0".to_string(),
+        ));
+    }
+
+    if payload.source_dealership_rif == payload.destination_dealership_rif {
+        return Err(ServiceError::invalid_field(
+            "destinationDealershipRif",
+            "The sourceDealershipRif and the destinationDealershipRif must be different"
+                .to_string(),
+        ));
+    }
+
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let (source_stock_item, destination_stock_item) =
+        if payload.source_dealership_rif < payload.destination_dealership_rif {
+            let source_stock_item = lock_stock_item(
+                payload.product_id,
+                payload.source_dealership_rif.as_str(),
+                &mut transaction,
+            )
+            .await?;
+            let destination_stock_item = lock_stock_item(
+                payload.product_id,
+                payload.destination_dealership_rif.as_str(),
+                &mut transaction,
+            )
+            .await?;
+            (source_stock_item, destination_stock_item)
+        } else {
+            let destination_stock_item = lock_stock_item(
+                payload.product_id,
+                payload.destination_dealership_rif.as_str(),
+                &mut transaction,
+            )
+            .await?;
+            let source_stock_item = lock_stock_item(
+                payload.product_id,
+                payload.source_dealership_rif.as_str(),
+                &mut transaction,
+            )
+            .await?;
+            (source_stock_item, destination_stock_item)
+        };
+
+    if source_stock_item.product_count - payload.product_count < source_stock_item.min_capacity {
+        return Err(ServiceError::DomainValidationError(format!(
+            "The source dealership can't transfer {} products without going below its min capacity of {} (it has {})",
+            payload.product_count, source_stock_item.min_capacity, source_stock_item.product_count
+        )));
+    }
+
+    let incoming_products = StockTransfer::count_incoming_products(
+        payload.product_id,
+        payload.destination_dealership_rif.clone().into(),
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to count the products in transit to the destination dealership")?;
+
+    if i64::from(destination_stock_item.product_count)
+        + incoming_products
+        + i64::from(payload.product_count)
+        > i64::from(destination_stock_item.max_capacity)
+    {
+        return Err(ServiceError::DomainValidationError(format!(
+            "The destination dealership can't receive {} products without going above its max capacity of {} (it has {} and {} in transit)",
+            payload.product_count,
+            destination_stock_item.max_capacity,
+            destination_stock_item.product_count,
+            incoming_products
+        )));
+    }
+
+    StockItem::adjust_product_count(
+        payload.product_id,
+        payload.source_dealership_rif.clone().into(),
+        -payload.product_count,
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to take the products out of the source dealership stock")?;
+
+    let created_stock_transfer = InsertStockTransfer {
+        product_id: payload.product_id,
+        source_dealership_rif: payload.source_dealership_rif.into(),
+        destination_dealership_rif: payload.destination_dealership_rif.into(),
+        product_count: payload.product_count,
+    }
+    .insert(&mut *transaction)
+    .await
+    .context("Failed to insert the stock transfer into the database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the stock transfer creation")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: created_stock_transfer,
+    }))
+}
+
+async fn lock_stock_item(
+    product_id: i32,
+    dealership_rif: &str,
+    connection: &mut PgConnection,
+) -> Result<StockItem, ServiceError> {
+    StockItem::select_for_update(product_id, dealership_rif.to_string(), connection)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => ServiceError::InvalidCreateError(
+                format!(
+                    "The dealership with rif {} does not have a stock item for the specified productId",
+                    dealership_rif
+                ),
+                anyhow!(err),
+            ),
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the stock item to transfer from the database"),
+            ),
+        })
+}
+
+#[get("/")]
+async fn fetch_stock_transfers(
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<StockTransferFilter>()?;
+    let fieldset = pagination_params.fieldset(StockTransfer::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_stock_transfers = fetch_stock_transfers_after_cursor(
+            limit,
+            cursor,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_stock_transfers.prev_cursor,
+            fetched_stock_transfers.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_stock_transfers.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if pagination_params.per_page.is_none() && pagination_params.page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (pagination_params.per_page, pagination_params.page_no)
+    {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_stock_transfers = fetch_stock_transfers_paginated(
+            per_page,
+            page_no,
+            scope.dealership_rif.clone(),
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_stock_transfers = match fetched_stock_transfers.total {
+            Some(total_stock_transfers) => total_stock_transfers,
+            None => StockTransfer::count(scope.dealership_rif, &list_filter, db.get_ref())
+                .await
+                .context("Failed to count the stock transfers from the database")?,
+        };
+
+        let pagination = Pagination::new(total_stock_transfers, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_stock_transfers.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let stock_transfers = StockTransfer::stream_all(scope.dealership_rif, &list_filter, db);
+            fieldset.select_stream(stock_transfers)
+        }));
+    }
+
+    let fetched_stock_transfers =
+        fetch_all_stock_transfers(scope.dealership_rif, &list_filter, db.get_ref()).await?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .json(NonPaginatedResponseDto {
+            data: fieldset.select_all(fetched_stock_transfers),
+        });
+
+    Ok(response)
+}
+
+async fn fetch_all_stock_transfers(
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<StockTransferFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<StockTransfer>, ServiceError> {
+    let fetched_stock_transfers = StockTransfer::select_all(dealership_rif, list_filter, db)
+        .await
+        .context("Failed to fetch the stock transfers from the database")?;
+    Ok(fetched_stock_transfers)
+}
+
+async fn fetch_stock_transfers_paginated(
+    per_page: i64,
+    page_no: i64,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<StockTransferFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<StockTransfer>, ServiceError> {
+    let fetched_stock_transfers = StockTransfer::paginate(per_page)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context("Failed to fetch the stock transfers from the database for the provided page")?;
+
+    Ok(fetched_stock_transfers)
+}
+
+async fn fetch_stock_transfers_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<StockTransfer>>,
+    dealership_rif: Option<String>,
+    list_filter: &ListFilter<StockTransferFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<StockTransfer>, ServiceError> {
+    let fetched_stock_transfers = StockTransfer::paginate(limit)
+        .in_dealership(dealership_rif)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the stock transfers from the database for the provided cursor")?;
+
+    Ok(fetched_stock_transfers)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct StockTransferManipulationParams {
+    id: i32,
+}
+
+#[routes]
+#[get("/view/")]
+#[get("/{id}/")]
+async fn fetch_stock_transfer(
+    _: Authorized<StockRead>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockTransferManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_stock_transfer = StockTransfer::select(params.id, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("stock transfer".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the stock transfer from the database"),
+            ),
+        })?;
+
+    scope
+        .ensure_includes(&fetched_stock_transfer.source_dealership_rif)
+        .or_else(|_| scope.ensure_includes(&fetched_stock_transfer.destination_dealership_rif))?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_stock_transfer,
+    }))
+}
+
+#[routes]
+#[post("/confirm/")]
+#[post("/{id}/confirm/")]
+async fn confirm_stock_transfer(
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockTransferManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let stock_transfer_to_confirm =
+        lock_in_transit_stock_transfer(params.id, &mut transaction).await?;
+
+    scope.ensure_includes(&stock_transfer_to_confirm.destination_dealership_rif)?;
+
+    let destination_stock_item = StockItem::select_for_update(
+        stock_transfer_to_confirm.product_id,
+        stock_transfer_to_confirm.destination_dealership_rif.clone(),
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to fetch the destination stock item from the database")?;
+
+    if destination_stock_item.product_count + stock_transfer_to_confirm.product_count
+        > destination_stock_item.max_capacity
+    {
+        return Err(ServiceError::DomainValidationError(format!(
+            "The destination dealership can't receive {} products without going above its max capacity of {} (it has {})",
+            stock_transfer_to_confirm.product_count,
+            destination_stock_item.max_capacity,
+            destination_stock_item.product_count
+        )));
+    }
+
+    StockItem::adjust_product_count(
+        stock_transfer_to_confirm.product_id,
+        stock_transfer_to_confirm.destination_dealership_rif,
+        stock_transfer_to_confirm.product_count,
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to put the products into the destination dealership stock")?;
+
+    let confirmed_stock_transfer = StockTransfer::resolve(
+        stock_transfer_to_confirm.id,
+        "received".to_string(),
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to confirm the stock transfer from the database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the stock transfer confirmation")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: confirmed_stock_transfer,
+    }))
+}
+
+#[routes]
+#[post("/cancel/")]
+#[post("/{id}/cancel/")]
+async fn cancel_stock_transfer(
+    _: Authorized<StockWrite>,
+    scope: DealershipScope,
+    PathQuery(params): PathQuery<StockTransferManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let stock_transfer_to_cancel =
+        lock_in_transit_stock_transfer(params.id, &mut transaction).await?;
+
+    scope.ensure_includes(&stock_transfer_to_cancel.source_dealership_rif)?;
+
+    let source_stock_item = StockItem::select_for_update(
+        stock_transfer_to_cancel.product_id,
+        stock_transfer_to_cancel.source_dealership_rif.clone(),
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to fetch the source stock item from the database")?;
+
+    if source_stock_item.product_count + stock_transfer_to_cancel.product_count
+        > source_stock_item.max_capacity
+    {
+        return Err(ServiceError::DomainValidationError(format!(
+            "The source dealership can't take back {} products without going above its max capacity of {} (it has {})",
+            stock_transfer_to_cancel.product_count,
+            source_stock_item.max_capacity,
+            source_stock_item.product_count
+        )));
+    }
+
+    StockItem::adjust_product_count(
+        stock_transfer_to_cancel.product_id,
+        stock_transfer_to_cancel.source_dealership_rif,
+        stock_transfer_to_cancel.product_count,
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to put the products back into the source dealership stock")?;
+
+    let cancelled_stock_transfer = StockTransfer::resolve(
+        stock_transfer_to_cancel.id,
+        "cancelled".to_string(),
+        &mut *transaction,
+    )
+    .await
+    .context("Failed to cancel the stock transfer from the database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the stock transfer cancellation")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: cancelled_stock_transfer,
+    }))
+}
+
+async fn lock_in_transit_stock_transfer(
+    id: i32,
+    connection: &mut PgConnection,
+) -> Result<StockTransfer, ServiceError> {
+    let stock_transfer = StockTransfer::select_for_update(id, connection)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("stock transfer".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the stock transfer from the database"),
+            ),
+        })?;
+
+    if stock_transfer.status != "in-transit" {
+        return Err(ServiceError::DomainValidationError(format!(
+            "The stock transfer was already resolved as {}",
+            stock_transfer.status
+        )));
+    }
+
+    Ok(stock_transfer)
+}
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+use crate::services::service_error::ServiceError;
+
+const DEFAULT_SUGGESTION_LIMIT: i64 = 10;
+const MAX_SUGGESTION_LIMIT: i64 = 50;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct SuggestionParams {
+    #[serde(default)]
+    pub prefix: String,
+    pub limit: Option<i64>,
+}
+
+impl SuggestionParams {
+    pub fn limit(&self) -> Result<i64, ServiceError> {
+        parse_suggestion_limit(self.limit)
+    }
+}
+
+pub fn parse_suggestion_limit(limit: Option<i64>) -> Result<i64, ServiceError> {
+    let limit = limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT);
+
+    if limit <= 0 || limit > MAX_SUGGESTION_LIMIT {
+        return Err(ServiceError::InvalidQueryParamValueError(format!(
+            "Query param limit must be between 1 and {}",
+            MAX_SUGGESTION_LIMIT
+        )));
+    }
+
+    Ok(limit)
+}
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -10,16 +13,29 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::supply_line::{InsertSupplyLine, SupplyLine, UpdateSupplyLine},
+    models::dependency::Dependency,
+    models::supply_line::{InsertSupplyLine, SupplyLine, SupplyLineFilter, UpdateSupplyLine},
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_supply_lines)
+        .service(fetch_supply_line_dependencies)
         .service(fetch_supply_line)
         .service(create_supply_line)
         .service(update_supply_line_partially)
@@ -36,14 +52,23 @@ struct CreateSupplyLinePayload {
 
 #[post("/supply-lines/")]
 async fn create_supply_line(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateSupplyLinePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_supply_line = InsertSupplyLine { name: payload.name }
-        .insert(db.get_ref())
+        .insert(&mut *transaction)
         .await
         .context("Failed to insert the supply line into the database")?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_supply_line,
     }))
@@ -51,9 +76,34 @@ async fn create_supply_line(
 
 #[get("/supply-lines/")]
 async fn fetch_supply_lines(
+    _: Authorized<CatalogRead>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<SupplyLineFilter>()?;
+    let fieldset = pagination_params.fieldset(SupplyLine::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_supply_lines =
+            fetch_supply_lines_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_supply_lines.prev_cursor,
+            fetched_supply_lines.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_supply_lines.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -85,35 +135,50 @@ async fn fetch_supply_lines(
         }
 
         let fetched_supply_lines =
-            fetch_supply_lines_paginated(per_page, page_no, db.get_ref()).await?;
+            fetch_supply_lines_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_supply_lines = SupplyLine::count(db.get_ref())
-            .await
-            .context("Failed to count the supply lines from the database")?;
+        let total_supply_lines = match fetched_supply_lines.total {
+            Some(total_supply_lines) => total_supply_lines,
+            None => SupplyLine::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the supply lines from the database")?,
+        };
 
+        let pagination = Pagination::new(total_supply_lines, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_supply_lines,
-                pagination: Pagination::new(total_supply_lines, page_no, per_page),
+                data: fieldset.select_all(fetched_supply_lines.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_supply_lines = fetch_all_supply_lines(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let supply_lines = SupplyLine::stream_all(&list_filter, db);
+            fieldset.select_stream(supply_lines)
+        }));
+    }
+
+    let fetched_supply_lines = fetch_all_supply_lines(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_supply_lines,
+            data: fieldset.select_all(fetched_supply_lines),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_supply_lines(db: &Pool<Postgres>) -> Result<Vec<SupplyLine>, ServiceError> {
-    let fetched_supply_lines = SupplyLine::select_all(db)
+async fn fetch_all_supply_lines(
+    list_filter: &ListFilter<SupplyLineFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<SupplyLine>, ServiceError> {
+    let fetched_supply_lines = SupplyLine::select_all(list_filter, db)
         .await
         .context("Failed to fetch the supply lines from the database")?;
     Ok(fetched_supply_lines)
@@ -122,14 +187,31 @@ async fn fetch_all_supply_lines(db: &Pool<Postgres>) -> Result<Vec<SupplyLine>,
 async fn fetch_supply_lines_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<SupplyLineFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<SupplyLine>, ServiceError> {
+) -> Result<Page<SupplyLine>, ServiceError> {
     let fetched_supply_lines = SupplyLine::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the supply lines from the database for the provided page")?;
 
-    Ok(fetched_supply_lines.items)
+    Ok(fetched_supply_lines)
+}
+
+async fn fetch_supply_lines_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<SupplyLine>>,
+    list_filter: &ListFilter<SupplyLineFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<SupplyLine>, ServiceError> {
+    let fetched_supply_lines = SupplyLine::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the supply lines from the database for the provided cursor")?;
+
+    Ok(fetched_supply_lines)
 }
 
 #[derive(Deserialize)]
@@ -139,12 +221,25 @@ struct SupplyLineManipulationParams {
     id: i32,
 }
 
+#[routes]
 #[get("/supply-lines/view/")]
+#[get("/supply-lines/{id}/")]
 async fn fetch_supply_line(
-    Query(params): Query<SupplyLineManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<SupplyLineManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_supply_line = SupplyLine::select(params.id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "supply line",
+        "supply_lines",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_supply_line = SupplyLine::select(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -155,9 +250,19 @@ async fn fetch_supply_line(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_supply_line,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_supply_line,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -168,32 +273,51 @@ struct UpdateSupplyLinePartiallyPayload {
     name: MaybeAbsent<String>,
 }
 
+#[routes]
 #[patch("/supply-lines/")]
+#[patch("/supply-lines/{id}/")]
 async fn update_supply_line_partially(
-    Query(params): Query<SupplyLineManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<SupplyLineManipulationParams>,
     Json(payload): Json<UpdateSupplyLinePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let supply_line_to_update =
-        SupplyLine::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("supply line".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err)
-                        .context("Failed to fetch the supply line to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "supply line",
+            "supply_lines",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let supply_line_to_update = SupplyLine::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("supply line".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the supply line to update from the database"),
+            ),
+        })?;
 
     let updated_supply_line = UpdateSupplyLine {
         name: payload.name.into(),
     }
-    .update(supply_line_to_update, db.get_ref())
+    .update(supply_line_to_update, &mut *transaction)
     .await
     .context("Failed to update the supply line from the database")?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_supply_line,
     }))
@@ -206,54 +330,118 @@ struct UpdateSupplyLineCompletelyPayload {
     name: String,
 }
 
+#[routes]
 #[put("/supply-lines/")]
+#[put("/supply-lines/{id}/")]
 async fn update_supply_line_completely(
-    Query(params): Query<SupplyLineManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<SupplyLineManipulationParams>,
     Json(payload): Json<UpdateSupplyLineCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let supply_line_to_update =
-        SupplyLine::select(params.id, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("supply line".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err)
-                        .context("Failed to fetch the supply line to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "supply line",
+            "supply_lines",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let supply_line_to_update = SupplyLine::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("supply line".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the supply line to update from the database"),
+            ),
+        })?;
 
     let updated_supply_line = UpdateSupplyLine {
         name: Some(payload.name),
     }
-    .update(supply_line_to_update, db.get_ref())
+    .update(supply_line_to_update, &mut *transaction)
     .await
     .context("Failed to update the supply line from the database")?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_supply_line,
     }))
 }
 
+#[routes]
 #[delete("/supply-lines/")]
+#[delete("/supply-lines/{id}/")]
 async fn delete_supply_line(
-    Query(params): Query<SupplyLineManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<SupplyLineManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_supply_line = SupplyLine::delete(params.id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "supply line",
+        "supply_lines",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_supply_line = SupplyLine::delete(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
                 ServiceError::ResourceNotFound("supply line".to_string(), anyhow!(err))
             }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ServiceError::DeleteConflictError(
+                    "The supply line is still referenced by other resources".to_string(),
+                )
+            }
             _ => ServiceError::UnexpectedError(
                 anyhow!(err).context("Failed to fetch the supply line to delete from the database"),
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_supply_line,
     }))
 }
+
+#[routes]
+#[get("/supply-lines/dependencies/")]
+#[get("/supply-lines/{id}/dependencies/")]
+async fn fetch_supply_line_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<SupplyLineManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "supply_lines".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the supply line from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,8 +1,11 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
     HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
@@ -11,16 +14,34 @@ use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    models::vehicle_model::{InsertVehicleModel, UpdateVehicleModel, VehicleModel},
+    models::dependency::Dependency,
+    models::vehicle_model::{
+        InsertVehicleModel, UpdateVehicleModel, VehicleModel, VehicleModelFilter,
+    },
     services::pagination_params::PaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
-    utils::{deserialization::MaybeAbsent, pagination::Paginable},
+    services::suggestion_params::SuggestionParams,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::MaybeAbsent,
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
     configuration
         .service(fetch_vehicle_models)
+        .service(suggest_vehicle_models)
+        .service(fetch_vehicle_model_dependencies)
         .service(fetch_vehicle_model)
         .service(create_vehicle_model)
         .service(update_vehicle_model_partially)
@@ -43,9 +64,13 @@ struct CreateVehicleModelPayload {
 
 #[post("/vehicle-models/")]
 async fn create_vehicle_model(
+    _: Authorized<CatalogWrite>,
     Json(payload): Json<CreateVehicleModelPayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_vehicle_model = InsertVehicleModel {
         name: payload.name,
         seat_count: payload.seat_count,
@@ -55,14 +80,22 @@ async fn create_vehicle_model(
         engine_oil_type: payload.engine_oil_type,
         engine_coolant_type: payload.engine_coolant_type,
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vehicle_models", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to create the vehicle model from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_vehicle_model,
     }))
@@ -70,9 +103,34 @@ async fn create_vehicle_model(
 
 #[get("/vehicle-models/")]
 async fn fetch_vehicle_models(
+    _: Authorized<CatalogRead>,
     Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<VehicleModelFilter>()?;
+    let fieldset = pagination_params.fieldset(VehicleModel::FIELDS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_vehicle_models =
+            fetch_vehicle_models_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_vehicle_models.prev_cursor,
+            fetched_vehicle_models.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_vehicle_models.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -104,35 +162,50 @@ async fn fetch_vehicle_models(
         }
 
         let fetched_vehicle_models =
-            fetch_vehicle_models_paginated(per_page, page_no, db.get_ref()).await?;
+            fetch_vehicle_models_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
 
-        let total_vehicle_models = VehicleModel::count(db.get_ref())
-            .await
-            .context("Failed to count the vehicle models from the database")?;
+        let total_vehicle_models = match fetched_vehicle_models.total {
+            Some(total_vehicle_models) => total_vehicle_models,
+            None => VehicleModel::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the vehicle models from the database")?,
+        };
 
+        let pagination = Pagination::new(total_vehicle_models, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_vehicle_models,
-                pagination: Pagination::new(total_vehicle_models, page_no, per_page),
+                data: fieldset.select_all(fetched_vehicle_models.items),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_vehicle_models = fetch_all_vehicle_models(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let vehicle_models = VehicleModel::stream_all(&list_filter, db);
+            fieldset.select_stream(vehicle_models)
+        }));
+    }
+
+    let fetched_vehicle_models = fetch_all_vehicle_models(&list_filter, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_vehicle_models,
+            data: fieldset.select_all(fetched_vehicle_models),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_vehicle_models(db: &Pool<Postgres>) -> Result<Vec<VehicleModel>, ServiceError> {
-    let fetched_vehicle_models = VehicleModel::select_all(db)
+async fn fetch_all_vehicle_models(
+    list_filter: &ListFilter<VehicleModelFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<VehicleModel>, ServiceError> {
+    let fetched_vehicle_models = VehicleModel::select_all(list_filter, db)
         .await
         .context("Failed to fetch the vehicle models from the database")?;
     Ok(fetched_vehicle_models)
@@ -141,14 +214,49 @@ async fn fetch_all_vehicle_models(db: &Pool<Postgres>) -> Result<Vec<VehicleMode
 async fn fetch_vehicle_models_paginated(
     per_page: i64,
     page_no: i64,
+    list_filter: &ListFilter<VehicleModelFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<VehicleModel>, ServiceError> {
+) -> Result<Page<VehicleModel>, ServiceError> {
     let fetched_vehicle_models = VehicleModel::paginate(per_page)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the vehicle models from the database for the provided page")?;
 
-    Ok(fetched_vehicle_models.items)
+    Ok(fetched_vehicle_models)
+}
+
+async fn fetch_vehicle_models_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<VehicleModel>>,
+    list_filter: &ListFilter<VehicleModelFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<VehicleModel>, ServiceError> {
+    let fetched_vehicle_models = VehicleModel::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the vehicle models from the database for the provided cursor")?;
+
+    Ok(fetched_vehicle_models)
+}
+
+#[get("/vehicle-models/suggest/")]
+async fn suggest_vehicle_models(
+    _: Authorized<CatalogRead>,
+    Query(params): Query<SuggestionParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let limit = params.limit()?;
+
+    let suggested_vehicle_models =
+        VehicleModel::select_suggestions(params.prefix, limit, db.get_ref())
+            .await
+            .context("Failed to fetch the vehicle model suggestions from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: suggested_vehicle_models,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -158,12 +266,25 @@ struct VehicleModelManipulationParams {
     id: i32,
 }
 
+#[routes]
 #[get("/vehicle-models/view/")]
+#[get("/vehicle-models/{id}/")]
 async fn fetch_vehicle_model(
-    Query(params): Query<VehicleModelManipulationParams>,
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<VehicleModelManipulationParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_vehicle_model = VehicleModel::select(params.id, db.get_ref())
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "vehicle model",
+        "vehicle_models",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_vehicle_model = VehicleModel::select(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -174,9 +295,19 @@ async fn fetch_vehicle_model(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_vehicle_model,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_vehicle_model,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -193,13 +324,29 @@ struct UpdateVehicleModelPartiallyPayload {
     engine_coolant_type: MaybeAbsent<String>,
 }
 
+#[routes]
 #[patch("/vehicle-models/")]
+#[patch("/vehicle-models/{id}/")]
 async fn update_vehicle_model_partially(
-    Query(params): Query<VehicleModelManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<VehicleModelManipulationParams>,
     Json(payload): Json<UpdateVehicleModelPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let vehicle_model_to_update = VehicleModel::select(params.id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "vehicle model",
+            "vehicle_models",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let vehicle_model_to_update = VehicleModel::select(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -220,14 +367,22 @@ async fn update_vehicle_model_partially(
         engine_oil_type: payload.engine_oil_type.into(),
         engine_coolant_type: payload.engine_coolant_type.into(),
     }
-    .update(vehicle_model_to_update, db.get_ref())
+    .update(vehicle_model_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vehicle_models", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the vehicle model from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_vehicle_model,
     }))
@@ -246,13 +401,29 @@ struct UpdateVehicleModelCompletelyPayload {
     engine_coolant_type: String,
 }
 
+#[routes]
 #[put("/vehicle-models/")]
+#[put("/vehicle-models/{id}/")]
 async fn update_vehicle_model_completely(
-    Query(params): Query<VehicleModelManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<VehicleModelManipulationParams>,
     Json(payload): Json<UpdateVehicleModelCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let vehicle_model_to_update = VehicleModel::select(params.id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "vehicle model",
+            "vehicle_models",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let vehicle_model_to_update = VehicleModel::select(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -273,36 +444,89 @@ async fn update_vehicle_model_completely(
         engine_oil_type: Some(payload.engine_oil_type),
         engine_coolant_type: Some(payload.engine_coolant_type),
     }
-    .update(vehicle_model_to_update, db.get_ref())
+    .update(vehicle_model_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vehicle_models", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the vehicle model from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_vehicle_model,
     }))
 }
 
+#[routes]
 #[delete("/vehicle-models/")]
+#[delete("/vehicle-models/{id}/")]
 async fn delete_vehicle_model(
-    Query(params): Query<VehicleModelManipulationParams>,
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<VehicleModelManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_vehicle_model = VehicleModel::delete(params.id, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "vehicle model",
+        "vehicle_models",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_vehicle_model = VehicleModel::delete(params.id, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
                 ServiceError::ResourceNotFound("vehicle model".to_string(), anyhow!(err))
             }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ServiceError::DeleteConflictError(
+                    "The vehicle model is still referenced by other resources".to_string(),
+                )
+            }
             _ => ServiceError::UnexpectedError(
                 anyhow!(err).context("Failed to get the vehicle model to delete from the database"),
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_vehicle_model,
     }))
 }
+
+#[routes]
+#[get("/vehicle-models/dependencies/")]
+#[get("/vehicle-models/{id}/dependencies/")]
+async fn fetch_vehicle_model_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<VehicleModelManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "vehicle_models".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the vehicle model from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
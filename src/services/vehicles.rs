@@ -1,9 +1,12 @@
 use actix_web::{
-    delete, get,
-    http::{header::ContentType, StatusCode},
-    patch, post, put,
-    web::{Data, Json, Query, ServiceConfig},
-    HttpResponse, Responder,
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, Path, ServiceConfig},
+    Either, HttpResponse, Responder,
 };
 use anyhow::{anyhow, Context};
 use serde::Deserialize;
@@ -11,14 +14,26 @@ use sqlx::{Pool, Postgres};
 use time::Date;
 
 use crate::{
-    models::vehicle::{InsertVehicle, UpdateVehicle, Vehicle},
-    services::pagination_params::PaginationParams,
+    models::client::Client,
+    models::domains::{NationalId, Rif},
+    models::vehicle::{InsertVehicle, UpdateVehicle, Vehicle, VehicleFilter},
+    services::pagination_params::SoftDeletablePaginationParams,
     services::responses_dto::*,
     services::service_error::ServiceError,
     utils::{
+        audit::AuditContext,
+        authorization::{Authorized, ClientsRead, ClientsWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
         deserialization::{MaybeAbsent, MaybeNull},
-        pagination::Paginable,
+        expansion::{Expandable, Expanded, Expansion},
+        extractors::{Json, PathJson, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
     },
+    views::compatible_product::CompatibleProduct,
 };
 
 pub fn configure(configuration: &mut ServiceConfig) {
@@ -28,7 +43,11 @@ pub fn configure(configuration: &mut ServiceConfig) {
         .service(create_vehicle)
         .service(update_vehicle_partially)
         .service(update_vehicle_completely)
-        .service(delete_vehicle);
+        .service(delete_vehicle)
+        .service(restore_vehicle)
+        .service(fetch_compatible_products)
+        .service(fetch_client_vehicles)
+        .service(create_client_vehicle);
 }
 
 #[derive(Deserialize)]
@@ -44,14 +63,36 @@ struct CreateVehiclePayload {
     purchase_date: Date,
     additional_info: MaybeNull<String>,
     maintenance_summary: MaybeNull<String>,
-    owner_national_id: String,
+    owner_national_id: NationalId,
 }
 
 #[post("/vehicles/")]
 async fn create_vehicle(
+    _: Authorized<ClientsWrite>,
     Json(payload): Json<CreateVehiclePayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    insert_vehicle(payload, audit, db).await
+}
+
+#[post("/clients/{owner_national_id}/vehicles/")]
+async fn create_client_vehicle(
+    _: Authorized<ClientsWrite>,
+    PathJson(payload): PathJson<CreateVehiclePayload>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
+    insert_vehicle(payload, audit, db).await
+}
+
+async fn insert_vehicle(
+    payload: CreateVehiclePayload,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
     let created_vehicle = InsertVehicle {
         plate: payload.plate,
         brand: payload.brand,
@@ -62,9 +103,9 @@ async fn create_vehicle(
         purchase_date: payload.purchase_date,
         additional_info: payload.additional_info.into(),
         maintenance_summary: payload.maintenance_summary.into(),
-        owner_national_id: payload.owner_national_id,
+        owner_national_id: payload.owner_national_id.into(),
     }
-    .insert(db.get_ref())
+    .insert(&mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -79,11 +120,19 @@ async fn create_vehicle(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vehicles", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to create the client from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: created_vehicle,
     }))
@@ -91,9 +140,101 @@ async fn create_vehicle(
 
 #[get("/vehicles/")]
 async fn fetch_vehicles(
-    Query(pagination_params): Query<PaginationParams>,
+    _: Authorized<ClientsRead>,
+    Query(pagination_params): Query<SoftDeletablePaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<VehicleFilter>()?;
+
+    list_vehicles(
+        pagination_params,
+        list_filter,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+#[get("/clients/{owner_national_id}/vehicles/")]
+async fn fetch_client_vehicles(
+    _: Authorized<ClientsRead>,
+    owner_national_id: Path<NationalId>,
+    Query(pagination_params): Query<SoftDeletablePaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
     db: Data<Pool<Postgres>>,
 ) -> Result<HttpResponse, ServiceError> {
+    let owner_national_id = owner_national_id.into_inner();
+
+    Client::select(
+        owner_national_id.clone().into(),
+        pagination_params.include_deleted,
+        db.get_ref(),
+    )
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::RowNotFound => {
+            ServiceError::ResourceNotFound("client".to_string(), anyhow!(err))
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to fetch the client from the database"),
+        ),
+    })?;
+
+    let mut list_filter = pagination_params.list_filter::<VehicleFilter>()?;
+    list_filter.conditions.owner_national_id.eq = Some(owner_national_id);
+
+    list_vehicles(
+        pagination_params,
+        list_filter,
+        page_links,
+        response_format,
+        db,
+    )
+    .await
+}
+
+async fn list_vehicles(
+    pagination_params: SoftDeletablePaginationParams,
+    list_filter: ListFilter<VehicleFilter>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let fieldset = pagination_params.fieldset(Vehicle::FIELDS)?;
+    let expansion = pagination_params.expansion(Vehicle::RELATIONS)?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_vehicles = fetch_vehicles_after_cursor(
+            limit,
+            cursor,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let expanded_vehicles =
+            expand_vehicles(fetched_vehicles.items, &expansion, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_vehicles.prev_cursor,
+            fetched_vehicles.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(expanded_vehicles),
+                pagination,
+            });
+        return Ok(response);
+    }
+
     if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
         return Err(ServiceError::MissingQueryParamError(
             "Missing query param page-no".to_string(),
@@ -124,35 +265,73 @@ async fn fetch_vehicles(
             ));
         }
 
-        let fetched_vehicles = fetch_vehicles_paginated(per_page, page_no, db.get_ref()).await?;
-
-        let total_vehicles = Vehicle::count(db.get_ref())
+        let fetched_vehicles = fetch_vehicles_paginated(
+            per_page,
+            page_no,
+            pagination_params.include_deleted,
+            &list_filter,
+            db.get_ref(),
+        )
+        .await?;
+
+        let total_vehicles = match fetched_vehicles.total {
+            Some(total_vehicles) => total_vehicles,
+            None => Vehicle::count(
+                pagination_params.include_deleted,
+                &list_filter,
+                db.get_ref(),
+            )
             .await
-            .context("Failed to count the vehicles from the database")?;
+            .context("Failed to count the vehicles from the database")?,
+        };
 
+        let expanded_vehicles =
+            expand_vehicles(fetched_vehicles.items, &expansion, db.get_ref()).await?;
+
+        let pagination = Pagination::new(total_vehicles, page_no, per_page);
         let response = HttpResponse::build(StatusCode::OK)
             .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
             .json(PaginatedResponseDto {
-                data: fetched_vehicles,
-                pagination: Pagination::new(total_vehicles, page_no, per_page),
+                data: fieldset.select_all(expanded_vehicles),
+                pagination,
             });
 
         return Ok(response);
     }
 
-    let fetched_vehicles = fetch_all_vehicles(db.get_ref()).await?;
+    if response_format == ResponseFormat::Ndjson {
+        expansion.ensure_streamable()?;
+
+        return Ok(ndjson_response(db, move |db| {
+            let vehicles = Vehicle::stream_all(pagination_params.include_deleted, &list_filter, db);
+            fieldset.select_stream(vehicles)
+        }));
+    }
+
+    let fetched_vehicles = fetch_all_vehicles(
+        pagination_params.include_deleted,
+        &list_filter,
+        db.get_ref(),
+    )
+    .await?;
+    let expanded_vehicles = expand_vehicles(fetched_vehicles, &expansion, db.get_ref()).await?;
 
     let response = HttpResponse::build(StatusCode::OK)
         .content_type(ContentType::json())
         .json(NonPaginatedResponseDto {
-            data: fetched_vehicles,
+            data: fieldset.select_all(expanded_vehicles),
         });
 
     Ok(response)
 }
 
-async fn fetch_all_vehicles(db: &Pool<Postgres>) -> Result<Vec<Vehicle>, ServiceError> {
-    let fetched_vehicles = Vehicle::select_all(db)
+async fn fetch_all_vehicles(
+    include_deleted: bool,
+    list_filter: &ListFilter<VehicleFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Vehicle>, ServiceError> {
+    let fetched_vehicles = Vehicle::select_all(include_deleted, list_filter, db)
         .await
         .context("Failed to fetch the vehicles from the database")?;
     Ok(fetched_vehicles)
@@ -161,14 +340,47 @@ async fn fetch_all_vehicles(db: &Pool<Postgres>) -> Result<Vec<Vehicle>, Service
 async fn fetch_vehicles_paginated(
     per_page: i64,
     page_no: i64,
+    include_deleted: bool,
+    list_filter: &ListFilter<VehicleFilter>,
     db: &Pool<Postgres>,
-) -> Result<Vec<Vehicle>, ServiceError> {
+) -> Result<Page<Vehicle>, ServiceError> {
     let fetched_vehicles = Vehicle::paginate(per_page)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
         .get_page(page_no, db)
         .await
         .context("Failed to fetch the vehicles from the database for the provided page")?;
 
-    Ok(fetched_vehicles.items)
+    Ok(fetched_vehicles)
+}
+
+async fn fetch_vehicles_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Vehicle>>,
+    include_deleted: bool,
+    list_filter: &ListFilter<VehicleFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Vehicle>, ServiceError> {
+    let fetched_vehicles = Vehicle::paginate(limit)
+        .including_deleted(include_deleted)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the vehicles from the database for the provided cursor")?;
+
+    Ok(fetched_vehicles)
+}
+
+async fn expand_vehicles(
+    vehicles: Vec<Vehicle>,
+    expansion: &Expansion,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Expanded<Vehicle>>, ServiceError> {
+    let expanded_vehicles = Vehicle::expand(vehicles, expansion, db)
+        .await
+        .context("Failed to fetch the related resources of the vehicles from the database")?;
+
+    Ok(expanded_vehicles)
 }
 
 #[derive(Deserialize)]
@@ -178,12 +390,37 @@ struct VehicleManipulationParams {
     plate: String,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct VehicleViewParams {
+    plate: String,
+    #[serde(default)]
+    include_deleted: bool,
+    expand: Option<String>,
+}
+
+#[routes]
 #[get("/vehicles/view/")]
+#[get("/vehicles/{plate}/")]
 async fn fetch_vehicle(
-    Query(params): Query<VehicleManipulationParams>,
+    _: Authorized<ClientsRead>,
+    PathQuery(params): PathQuery<VehicleViewParams>,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let fetched_vehicle = Vehicle::select(params.plate, db.get_ref())
+    let expansion = Expansion::parse(Vehicle::RELATIONS, params.expand.as_deref())?;
+
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "vehicle",
+        "vehicles",
+        vec![params.plate.clone()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_vehicle = Vehicle::select(params.plate, params.include_deleted, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -194,9 +431,23 @@ async fn fetch_vehicle(
             ),
         })?;
 
-    Ok(Json(NonPaginatedResponseDto {
-        data: fetched_vehicle,
-    }))
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let expanded_vehicle = Vehicle::expand_one(fetched_vehicle, &expansion, db.get_ref())
+        .await
+        .context("Failed to fetch the related resources of the vehicle from the database")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: expanded_vehicle,
+        });
+
+    Ok(response)
 }
 
 #[derive(Deserialize, Default)]
@@ -213,26 +464,41 @@ struct UpdateVehiclePartiallyPayload {
     purchase_date: MaybeAbsent<Date>,
     additional_info: MaybeAbsent<MaybeNull<String>>,
     maintenance_summary: MaybeAbsent<MaybeNull<String>>,
-    owner_national_id: MaybeAbsent<String>,
+    owner_national_id: MaybeAbsent<NationalId>,
 }
 
+#[routes]
 #[patch("/vehicles/")]
+#[patch("/vehicles/{plate}/")]
 async fn update_vehicle_partially(
-    Query(params): Query<VehicleManipulationParams>,
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<VehicleManipulationParams>,
     Json(payload): Json<UpdateVehiclePartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update =
-        Vehicle::select(params.plate, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("vehicle".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the vehicle to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "vehicle",
+            "vehicles",
+            vec![params.plate.clone()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Vehicle::select(params.plate, false, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vehicle".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vehicle to update from the database"),
+            ),
+        })?;
 
     let updated_vehicle = UpdateVehicle {
         plate: payload.plate.into(),
@@ -244,9 +510,9 @@ async fn update_vehicle_partially(
         purchase_date: payload.purchase_date.into(),
         additional_info: payload.additional_info.into(),
         maintenance_summary: payload.maintenance_summary.into(),
-        owner_national_id: payload.owner_national_id.into(),
+        owner_national_id: payload.owner_national_id.map(String::from).into(),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -261,11 +527,19 @@ async fn update_vehicle_partially(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vehicles", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the vehicle from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_vehicle,
     }))
@@ -284,26 +558,41 @@ struct UpdateVehicleCompletelyPayload {
     purchase_date: Date,
     additional_info: MaybeNull<String>,
     maintenance_summary: MaybeNull<String>,
-    owner_national_id: String,
+    owner_national_id: NationalId,
 }
 
+#[routes]
 #[put("/vehicles/")]
+#[put("/vehicles/{plate}/")]
 async fn update_vehicle_completely(
-    Query(params): Query<VehicleManipulationParams>,
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<VehicleManipulationParams>,
     Json(payload): Json<UpdateVehicleCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let city_to_update =
-        Vehicle::select(params.plate, db.get_ref())
-            .await
-            .map_err(|err| match &err {
-                sqlx::Error::RowNotFound => {
-                    ServiceError::ResourceNotFound("vehicle".to_string(), anyhow!(err))
-                }
-                _ => ServiceError::UnexpectedError(
-                    anyhow!(err).context("Failed to fetch the vehicle to update from the database"),
-                ),
-            })?;
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "vehicle",
+            "vehicles",
+            vec![params.plate.clone()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let city_to_update = Vehicle::select(params.plate, false, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vehicle".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vehicle to update from the database"),
+            ),
+        })?;
 
     let updated_vehicle = UpdateVehicle {
         plate: Some(payload.plate),
@@ -315,9 +604,9 @@ async fn update_vehicle_completely(
         purchase_date: Some(payload.purchase_date),
         additional_info: Some(payload.additional_info.into()),
         maintenance_summary: Some(payload.maintenance_summary.into()),
-        owner_national_id: Some(payload.owner_national_id),
+        owner_national_id: Some(payload.owner_national_id.into()),
     }
-    .update(city_to_update, db.get_ref())
+    .update(city_to_update, &mut *transaction)
     .await
     .map_err(|err| match &err {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
@@ -332,22 +621,36 @@ async fn update_vehicle_completely(
                 anyhow!(err),
             )
         }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vehicles", db_err.as_ref())
+        }
         _ => ServiceError::UnexpectedError(
             anyhow!(err).context("Failed to update the vehicle from the database"),
         ),
     })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: updated_vehicle,
     }))
 }
 
+#[routes]
 #[delete("/vehicles/")]
+#[delete("/vehicles/{plate}/")]
 async fn delete_vehicle(
-    Query(params): Query<VehicleManipulationParams>,
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<VehicleManipulationParams>,
+    audit: AuditContext,
     db: Data<Pool<Postgres>>,
 ) -> Result<impl Responder, ServiceError> {
-    let deleted_vehicle = Vehicle::delete(params.plate, db.get_ref())
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let deleted_vehicle = Vehicle::delete(params.plate, &mut *transaction)
         .await
         .map_err(|err| match &err {
             sqlx::Error::RowNotFound => {
@@ -358,7 +661,89 @@ async fn delete_vehicle(
             ),
         })?;
 
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
     Ok(Json(NonPaginatedResponseDto {
         data: deleted_vehicle,
     }))
 }
+
+#[routes]
+#[post("/vehicles/restore/")]
+#[post("/vehicles/{plate}/restore/")]
+async fn restore_vehicle(
+    _: Authorized<ClientsWrite>,
+    PathQuery(params): PathQuery<VehicleManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let restored_vehicle = Vehicle::restore(params.plate, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("deleted vehicle".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to restore the vehicle from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: restored_vehicle,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct FetchCompatibleProductsParams {
+    dealership_rif: Rif,
+}
+
+#[get("/vehicles/{plate}/compatible-products/")]
+async fn fetch_compatible_products(
+    _: Authorized<ClientsRead>,
+    plate: Path<String>,
+    Query(params): Query<FetchCompatibleProductsParams>,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<Either<HttpResponse, impl Responder>, ServiceError> {
+    let vehicle = Vehicle::select(plate.into_inner(), false, db.get_ref())
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vehicle".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vehicle from the database"),
+            ),
+        })?;
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(Either::Left(ndjson_response(db, move |db| {
+            CompatibleProduct::stream_all_in_stock(vehicle.plate, params.dealership_rif.into(), db)
+        })));
+    }
+
+    let fetched_products = CompatibleProduct::select_all_in_stock(
+        vehicle.plate,
+        params.dealership_rif.into(),
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the compatible products from the database")?;
+
+    Ok(Either::Right(Json(NonPaginatedResponseDto {
+        data: fetched_products,
+    })))
+}
@@ -0,0 +1,528 @@
+use actix_web::{
+    get,
+    http::{
+        header::{ContentType, ETag, LINK},
+        StatusCode,
+    },
+    post, routes,
+    web::{Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::dependency::Dependency,
+    models::domains::{Email, PhoneNo, Rif},
+    models::vendor::{InsertVendor, UpdateVendor, Vendor, VendorFilter},
+    services::pagination_params::PaginationParams,
+    services::responses_dto::*,
+    services::service_error::ServiceError,
+    utils::{
+        audit::AuditContext,
+        authorization::{Authorized, CatalogRead, CatalogWrite},
+        check_violations::check_violation_error,
+        concurrency::{select_entity_tag, Precondition},
+        dependencies::ensure_deletable,
+        deserialization::{MaybeAbsent, MaybeNull},
+        extractors::{Json, PathQuery, Query},
+        filtering::ListFilter,
+        pagination::{CursorFor, CursorPage, Page, PageLinks, Paginable},
+        snapshot::begin_read_only_snapshot,
+        streaming::{ndjson_response, ResponseFormat},
+    },
+};
+
+pub fn configure(configuration: &mut ServiceConfig) {
+    configuration
+        .service(fetch_vendors)
+        .service(fetch_vendor_dependencies)
+        .service(fetch_vendor)
+        .service(create_vendor)
+        .service(update_vendor_partially)
+        .service(update_vendor_completely)
+        .service(delete_vendor);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct CreateVendorPayload {
+    rif: MaybeNull<Rif>,
+    name: String,
+    phone_no: MaybeNull<PhoneNo>,
+    email: MaybeNull<Email>,
+    supply_line_ids: Vec<i32>,
+}
+
+#[post("/")]
+async fn create_vendor(
+    _: Authorized<CatalogWrite>,
+    Json(payload): Json<CreateVendorPayload>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    let created_vendor = InsertVendor {
+        rif: payload.rif.map(String::from).into(),
+        name: payload.name,
+        phone_no: payload.phone_no.map(String::from).into(),
+        email: payload.email.map(String::from).into(),
+        supply_line_ids: payload.supply_line_ids,
+    }
+    .insert(&mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidCreateError(
+                "Already exists a vendor with the specified rif or name".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidCreateError(
+                "One of the specified supplyLineIds does not exist".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vendors", db_err.as_ref())
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to insert the vendor into the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: created_vendor,
+    }))
+}
+
+#[get("/")]
+async fn fetch_vendors(
+    _: Authorized<CatalogRead>,
+    Query(pagination_params): Query<PaginationParams>,
+    page_links: PageLinks,
+    response_format: ResponseFormat,
+    db: Data<Pool<Postgres>>,
+) -> Result<HttpResponse, ServiceError> {
+    let list_filter = pagination_params.list_filter::<VendorFilter>()?;
+    let fieldset = pagination_params.fieldset(&[Vendor::FIELDS, &["supply-line-ids"]].concat())?;
+
+    if let Some((limit, cursor)) = pagination_params.cursor_pagination()? {
+        let fetched_vendors =
+            fetch_vendors_after_cursor(limit, cursor, &list_filter, db.get_ref()).await?;
+
+        let pagination = CursorPagination::new(
+            limit,
+            fetched_vendors.prev_cursor,
+            fetched_vendors.next_cursor,
+        );
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_cursors(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_vendors.items),
+                pagination,
+            });
+        return Ok(response);
+    }
+
+    if pagination_params.per_page.is_some() && pagination_params.page_no.is_none() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param page-no".to_string(),
+        ));
+    }
+
+    if pagination_params.per_page.is_none() && pagination_params.page_no.is_some() {
+        return Err(ServiceError::MissingQueryParamError(
+            "Missing query param per-page".to_string(),
+        ));
+    }
+
+    if let (Some(per_page), Some(page_no)) = (pagination_params.per_page, pagination_params.page_no)
+    {
+        if page_no <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param page-no must be greater than 0".to_string(),
+            ));
+        }
+
+        if per_page <= 0 {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param per-page must be greater than 0".to_string(),
+            ));
+        }
+
+        let fetched_vendors =
+            fetch_vendors_paginated(per_page, page_no, &list_filter, db.get_ref()).await?;
+
+        let total_vendors = match fetched_vendors.total {
+            Some(total_vendors) => total_vendors,
+            None => Vendor::count(&list_filter, db.get_ref())
+                .await
+                .context("Failed to count the vendors from the database")?,
+        };
+
+        let pagination = Pagination::new(total_vendors, page_no, per_page);
+        let response = HttpResponse::build(StatusCode::OK)
+            .content_type(ContentType::json())
+            .insert_header((LINK, page_links.for_pages(&pagination)))
+            .json(PaginatedResponseDto {
+                data: fieldset.select_all(fetched_vendors.items),
+                pagination,
+            });
+
+        return Ok(response);
+    }
+
+    if response_format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(db, move |db| {
+            let vendors = Vendor::stream_all(&list_filter, db);
+            fieldset.select_stream(vendors)
+        }));
+    }
+
+    let fetched_vendors = fetch_all_vendors(&list_filter, db.get_ref()).await?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .json(NonPaginatedResponseDto {
+            data: fieldset.select_all(fetched_vendors),
+        });
+
+    Ok(response)
+}
+
+async fn fetch_all_vendors(
+    list_filter: &ListFilter<VendorFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Vec<Vendor>, ServiceError> {
+    let fetched_vendors = Vendor::select_all(list_filter, db)
+        .await
+        .context("Failed to fetch the vendors from the database")?;
+    Ok(fetched_vendors)
+}
+
+async fn fetch_vendors_paginated(
+    per_page: i64,
+    page_no: i64,
+    list_filter: &ListFilter<VendorFilter>,
+    db: &Pool<Postgres>,
+) -> Result<Page<Vendor>, ServiceError> {
+    let fetched_vendors = Vendor::paginate(per_page)
+        .filtered_by(list_filter.clone())
+        .get_page(page_no, db)
+        .await
+        .context("Failed to fetch the vendors from the database for the provided page")?;
+
+    Ok(fetched_vendors)
+}
+
+async fn fetch_vendors_after_cursor(
+    limit: i64,
+    cursor: Option<CursorFor<Vendor>>,
+    list_filter: &ListFilter<VendorFilter>,
+    db: &Pool<Postgres>,
+) -> Result<CursorPage<Vendor>, ServiceError> {
+    let fetched_vendors = Vendor::paginate(limit)
+        .filtered_by(list_filter.clone())
+        .get_page_after(cursor, db)
+        .await
+        .context("Failed to fetch the vendors from the database for the provided cursor")?;
+
+    Ok(fetched_vendors)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct VendorManipulationParams {
+    id: i32,
+}
+
+#[routes]
+#[get("/view/")]
+#[get("/{id}/")]
+async fn fetch_vendor(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<VendorManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = begin_read_only_snapshot(db.get_ref()).await?;
+
+    let entity_tag = select_entity_tag(
+        "vendor",
+        "vendors",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let fetched_vendor = Vendor::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vendor".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vendor from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    let response = HttpResponse::build(StatusCode::OK)
+        .content_type(ContentType::json())
+        .insert_header(ETag(entity_tag))
+        .json(NonPaginatedResponseDto {
+            data: fetched_vendor,
+        });
+
+    Ok(response)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+struct UpdateVendorPartiallyPayload {
+    rif: MaybeAbsent<MaybeNull<Rif>>,
+    name: MaybeAbsent<String>,
+    phone_no: MaybeAbsent<MaybeNull<PhoneNo>>,
+    email: MaybeAbsent<MaybeNull<Email>>,
+    supply_line_ids: MaybeAbsent<Vec<i32>>,
+}
+
+#[routes]
+#[patch("/")]
+#[patch("/{id}/")]
+async fn update_vendor_partially(
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<VendorManipulationParams>,
+    Json(payload): Json<UpdateVendorPartiallyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "vendor",
+            "vendors",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let vendor_to_update = Vendor::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vendor".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vendor to update from the database"),
+            ),
+        })?;
+
+    let updated_vendor = UpdateVendor {
+        rif: payload.rif.map(|rif| rif.map(String::from)).into(),
+        name: payload.name.into(),
+        phone_no: payload.phone_no.map(|phone_no| phone_no.map(String::from)).into(),
+        email: payload.email.map(|email| email.map(String::from)).into(),
+        supply_line_ids: payload.supply_line_ids.into(),
+    }
+    .update(vendor_to_update, &mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidUpdateError(
+                "Already exists a vendor with the specified rif or name".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidUpdateError(
+                "One of the specified supplyLineIds does not exist".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vendors", db_err.as_ref())
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to update the vendor from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: updated_vendor,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct UpdateVendorCompletelyPayload {
+    rif: MaybeNull<Rif>,
+    name: String,
+    phone_no: MaybeNull<PhoneNo>,
+    email: MaybeNull<Email>,
+    supply_line_ids: Vec<i32>,
+}
+
+#[routes]
+#[put("/")]
+#[put("/{id}/")]
+async fn update_vendor_completely(
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<VendorManipulationParams>,
+    Json(payload): Json<UpdateVendorCompletelyPayload>,
+    precondition: Precondition,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    precondition
+        .ensure_matches(
+            "vendor",
+            "vendors",
+            vec![params.id.to_string()],
+            &mut *transaction,
+        )
+        .await?;
+
+    let vendor_to_update = Vendor::select(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vendor".to_string(), anyhow!(err))
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vendor to update from the database"),
+            ),
+        })?;
+
+    let updated_vendor = UpdateVendor {
+        rif: Some(payload.rif.map(String::from).into()),
+        name: Some(payload.name),
+        phone_no: Some(payload.phone_no.map(String::from).into()),
+        email: Some(payload.email.map(String::from).into()),
+        supply_line_ids: Some(payload.supply_line_ids),
+    }
+    .update(vendor_to_update, &mut *transaction)
+    .await
+    .map_err(|err| match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ServiceError::InvalidUpdateError(
+                "Already exists a vendor with the specified rif or name".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ServiceError::InvalidUpdateError(
+                "One of the specified supplyLineIds does not exist".to_string(),
+                anyhow!(err),
+            )
+        }
+        sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+            check_violation_error("vendors", db_err.as_ref())
+        }
+        _ => ServiceError::UnexpectedError(
+            anyhow!(err).context("Failed to update the vendor from the database"),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: updated_vendor,
+    }))
+}
+
+#[routes]
+#[delete("/")]
+#[delete("/{id}/")]
+async fn delete_vendor(
+    _: Authorized<CatalogWrite>,
+    PathQuery(params): PathQuery<VendorManipulationParams>,
+    audit: AuditContext,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let mut transaction = audit.begin_transaction(db.get_ref()).await?;
+
+    ensure_deletable(
+        "vendor",
+        "vendors",
+        vec![params.id.to_string()],
+        &mut *transaction,
+    )
+    .await?;
+
+    let deleted_vendor = Vendor::delete(params.id, &mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::RowNotFound => {
+                ServiceError::ResourceNotFound("vendor".to_string(), anyhow!(err))
+            }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ServiceError::DeleteConflictError(
+                    "The vendor is still referenced by other resources".to_string(),
+                )
+            }
+            _ => ServiceError::UnexpectedError(
+                anyhow!(err).context("Failed to fetch the vendor to delete from the database"),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the transaction")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: deleted_vendor,
+    }))
+}
+
+#[routes]
+#[get("/dependencies/")]
+#[get("/{id}/dependencies/")]
+async fn fetch_vendor_dependencies(
+    _: Authorized<CatalogRead>,
+    PathQuery(params): PathQuery<VendorManipulationParams>,
+    db: Data<Pool<Postgres>>,
+) -> Result<impl Responder, ServiceError> {
+    let fetched_dependencies = Dependency::select_all(
+        "vendors".to_string(),
+        vec![params.id.to_string()],
+        db.get_ref(),
+    )
+    .await
+    .context("Failed to fetch the dependencies of the vendor from the database")?;
+
+    Ok(Json(NonPaginatedResponseDto {
+        data: fetched_dependencies,
+    }))
+}
@@ -1,2 +1,14 @@
 pub mod deserialization;
-pub mod pagination;
\ No newline at end of file
+pub mod pagination;
+pub mod authentication;
+pub mod authorization;
+pub mod audit;
+pub mod dependencies;
+pub mod concurrency;
+pub mod check_violations;
+pub mod extractors;
+pub mod filtering;
+pub mod streaming;
+pub mod expansion;
+pub mod fieldsets;
+pub mod snapshot;
\ No newline at end of file
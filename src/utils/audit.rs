@@ -0,0 +1,64 @@
+use std::future::{ready, Ready};
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use anyhow::{anyhow, Context};
+use sqlx::{Pool, Postgres, Transaction};
+use tracing_actix_web::RequestId;
+
+use crate::{
+    models::audit_entry::AuditEntry, services::service_error::ServiceError,
+    utils::authentication::AuthenticatedEmployee,
+};
+
+pub struct AuditContext {
+    actor_national_id: String,
+    request_id: String,
+}
+
+impl AuditContext {
+    pub async fn begin_transaction(
+        &self,
+        db: &Pool<Postgres>,
+    ) -> Result<Transaction<'static, Postgres>, ServiceError> {
+        let mut transaction = db
+            .begin()
+            .await
+            .context("Failed to start the audited transaction")?;
+
+        AuditEntry::set_transaction_context(
+            self.actor_national_id.clone(),
+            self.request_id.clone(),
+            &mut *transaction,
+        )
+        .await
+        .context("Failed to set the audit context of the transaction")?;
+
+        Ok(transaction)
+    }
+}
+
+impl FromRequest for AuditContext {
+    type Error = ServiceError;
+    type Future = Ready<Result<AuditContext, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let employee = match AuthenticatedEmployee::from_request(request, payload).into_inner() {
+            Ok(employee) => employee,
+            Err(err) => return ready(Err(err)),
+        };
+
+        let request_id = match RequestId::from_request(request, payload).into_inner() {
+            Ok(request_id) => request_id,
+            Err(err) => {
+                return ready(Err(ServiceError::UnexpectedError(
+                    anyhow!(err.to_string()).context("Failed to get the request id"),
+                )))
+            }
+        };
+
+        ready(Ok(AuditContext {
+            actor_national_id: employee.national_id,
+            request_id: request_id.to_string(),
+        }))
+    }
+}
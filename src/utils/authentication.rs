@@ -0,0 +1,241 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header::AUTHORIZATION, Method},
+    web::Data,
+    Error, FromRequest, HttpMessage, HttpRequest,
+};
+use anyhow::anyhow;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use crate::services::service_error::ServiceError;
+
+const PUBLIC_ROUTES: [(Method, &str); 2] = [
+    (Method::POST, "/auth/login/"),
+    (Method::POST, "/auth/refresh/"),
+];
+
+pub const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$vN7GO9T4VEd9rIi8eo8CRQ$J+aeIvoJxgnUxGSxsKXIrmu/i9okcLpnf9/KOVItiiU";
+
+const ACCESS_TOKEN_LIFETIME: Duration = Duration::minutes(15);
+const REFRESH_TOKEN_LIFETIME: Duration = Duration::days(7);
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenKind {
+    Access,
+    Refresh,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub kind: TokenKind,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+pub struct TokenKeys {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    pub expires_in: i64,
+}
+
+impl TokenKeys {
+    pub fn new(secret: &[u8]) -> TokenKeys {
+        TokenKeys {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    pub fn issue(&self, national_id: &str) -> Result<TokenPair, ServiceError> {
+        Ok(TokenPair {
+            access_token: self.encode(national_id, TokenKind::Access, ACCESS_TOKEN_LIFETIME)?,
+            refresh_token: self.encode(national_id, TokenKind::Refresh, REFRESH_TOKEN_LIFETIME)?,
+            token_type: "Bearer",
+            expires_in: ACCESS_TOKEN_LIFETIME.whole_seconds(),
+        })
+    }
+
+    pub fn verify(&self, token: &str, expected_kind: TokenKind) -> Result<Claims, ServiceError> {
+        let claims =
+            jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &Validation::default())
+                .map_err(|err| {
+                    ServiceError::AuthenticationError(
+                        "The provided token is invalid or has expired".to_string(),
+                        anyhow!(err),
+                    )
+                })?
+                .claims;
+
+        if claims.kind != expected_kind {
+            return Err(ServiceError::AuthenticationError(
+                "The provided token is not of the expected kind".to_string(),
+                anyhow!("Unexpected token kind"),
+            ));
+        }
+
+        Ok(claims)
+    }
+
+    fn encode(
+        &self,
+        national_id: &str,
+        kind: TokenKind,
+        lifetime: Duration,
+    ) -> Result<String, ServiceError> {
+        let issued_at = OffsetDateTime::now_utc();
+        let claims = Claims {
+            sub: national_id.to_string(),
+            kind,
+            iat: issued_at.unix_timestamp(),
+            exp: (issued_at + lifetime).unix_timestamp(),
+        };
+
+        jsonwebtoken::encode(&Header::default(), &claims, &self.encoding_key).map_err(|err| {
+            ServiceError::UnexpectedError(anyhow!(err).context("Failed to sign the token"))
+        })
+    }
+}
+
+pub fn hash_password(password: &str) -> Result<String, anyhow::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow!(err.to_string()))?;
+    Ok(password_hash.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, anyhow::Error> {
+    let password_hash = PasswordHash::new(password_hash).map_err(|err| anyhow!(err.to_string()))?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &password_hash)
+        .is_ok())
+}
+
+#[derive(Clone)]
+pub struct AuthenticatedEmployee {
+    pub national_id: String,
+}
+
+impl FromRequest for AuthenticatedEmployee {
+    type Error = ServiceError;
+    type Future = Ready<Result<AuthenticatedEmployee, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            request
+                .extensions()
+                .get::<AuthenticatedEmployee>()
+                .cloned()
+                .ok_or_else(|| {
+                    ServiceError::AuthenticationError(
+                        "Missing bearer token".to_string(),
+                        anyhow!("The request was not authenticated"),
+                    )
+                }),
+        )
+    }
+}
+
+pub struct Authentication;
+
+impl<S, B> Transform<S, ServiceRequest> for Authentication
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AuthenticationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<AuthenticationMiddleware<S>, ()>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthenticationMiddleware { service }))
+    }
+}
+
+pub struct AuthenticationMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthenticationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let is_public_route = PUBLIC_ROUTES
+            .iter()
+            .any(|(method, path)| request.method() == method && request.path() == *path);
+
+        if !is_public_route {
+            match authenticate(&request) {
+                Ok(employee) => {
+                    request.extensions_mut().insert(employee);
+                }
+                Err(err) => {
+                    let response = request.error_response(err).map_into_right_body();
+                    return Box::pin(async { Ok(response) });
+                }
+            }
+        }
+
+        let response = self.service.call(request);
+        Box::pin(async { response.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+fn authenticate(request: &ServiceRequest) -> Result<AuthenticatedEmployee, ServiceError> {
+    let token_keys = request.app_data::<Data<TokenKeys>>().ok_or_else(|| {
+        ServiceError::UnexpectedError(anyhow!("The token keys were not configured"))
+    })?;
+
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or_else(|| {
+            ServiceError::AuthenticationError(
+                "Missing bearer token".to_string(),
+                anyhow!("The Authorization header is missing or malformed"),
+            )
+        })?;
+
+    let claims = token_keys.verify(token, TokenKind::Access)?;
+
+    Ok(AuthenticatedEmployee {
+        national_id: claims.sub,
+    })
+}
@@ -0,0 +1,223 @@
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+use actix_web::{dev::Payload, web::Data, FromRequest, HttpRequest};
+use anyhow::{anyhow, Context};
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::{employee::Employee, invoice::Invoice, role_permission::RolePermission},
+    services::service_error::ServiceError,
+    utils::authentication::AuthenticatedEmployee,
+};
+
+pub trait Permission {
+    const NAME: &'static str;
+}
+
+macro_rules! permissions {
+    ($($marker:ident => $name:literal),* $(,)?) => {
+        $(
+            pub struct $marker;
+
+            impl Permission for $marker {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+permissions! {
+    CatalogRead => "catalog:read",
+    CatalogWrite => "catalog:write",
+    ClientsRead => "clients:read",
+    ClientsWrite => "clients:write",
+    DealershipsRead => "dealerships:read",
+    DealershipsWrite => "dealerships:write",
+    StaffRead => "staff:read",
+    StaffWrite => "staff:write",
+    SalariesWrite => "salaries:write",
+    StockRead => "stock:read",
+    StockWrite => "stock:write",
+    InvoicesRead => "invoices:read",
+    InvoicesWrite => "invoices:write",
+    InvoicesDelete => "invoices:delete",
+    ReportsRead => "reports:read",
+    AllDealerships => "dealerships:all",
+    AuditRead => "audit:read",
+}
+
+pub async fn ensure_permission(
+    employee: &AuthenticatedEmployee,
+    permission: &str,
+    db: &Pool<Postgres>,
+) -> Result<(), ServiceError> {
+    let is_granted = RolePermission::is_granted_to_employee(
+        employee.national_id.clone(),
+        permission.to_string(),
+        db,
+    )
+    .await
+    .context("Failed to fetch the permissions of the employee from the database")?;
+
+    if !is_granted {
+        return Err(ServiceError::AuthorizationError(format!(
+            "The permission {} is required to perform this action",
+            permission
+        )));
+    }
+
+    Ok(())
+}
+
+pub struct Authorized<P: Permission> {
+    pub employee: AuthenticatedEmployee,
+    permission: PhantomData<P>,
+}
+
+impl<P: Permission + 'static> FromRequest for Authorized<P> {
+    type Error = ServiceError;
+    type Future = Pin<Box<dyn Future<Output = Result<Authorized<P>, ServiceError>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let employee = AuthenticatedEmployee::from_request(request, payload).into_inner();
+        let db = request.app_data::<Data<Pool<Postgres>>>().cloned();
+
+        Box::pin(async move {
+            let employee = employee?;
+            let db = db.ok_or_else(|| {
+                ServiceError::UnexpectedError(anyhow!("The database pool was not configured"))
+            })?;
+
+            ensure_permission(&employee, P::NAME, db.get_ref()).await?;
+
+            Ok(Authorized {
+                employee,
+                permission: PhantomData,
+            })
+        })
+    }
+}
+
+pub struct DealershipScope {
+    pub dealership_rif: Option<String>,
+}
+
+impl DealershipScope {
+    pub fn ensure_includes(&self, dealership_rif: &str) -> Result<(), ServiceError> {
+        match &self.dealership_rif {
+            Some(scoped_dealership_rif) if scoped_dealership_rif != dealership_rif => {
+                Err(ServiceError::AuthorizationError(format!(
+                    "The employee can only access the data of the dealership {}",
+                    scoped_dealership_rif
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn ensure_includes_order(
+        &self,
+        order_id: i32,
+        db: &Pool<Postgres>,
+    ) -> Result<(), ServiceError> {
+        if self.dealership_rif.is_none() {
+            return Ok(());
+        }
+
+        let order_dealership_rif = Invoice::select_order_dealership_rif(order_id, db)
+            .await
+            .context("Failed to fetch the dealership of the order from the database")?;
+
+        match order_dealership_rif {
+            Some(dealership_rif) => self.ensure_includes(&dealership_rif),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn ensure_includes_invoice(
+        &self,
+        invoice_id: i32,
+        db: &Pool<Postgres>,
+    ) -> Result<(), ServiceError> {
+        if self.dealership_rif.is_none() {
+            return Ok(());
+        }
+
+        let invoice_dealership_rif = Invoice::select_dealership_rif(invoice_id, db)
+            .await
+            .context("Failed to fetch the dealership of the invoice from the database")?;
+
+        match invoice_dealership_rif {
+            Some(dealership_rif) => self.ensure_includes(&dealership_rif),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn ensure_includes_employee(
+        &self,
+        national_id: String,
+        db: &Pool<Postgres>,
+    ) -> Result<(), ServiceError> {
+        if self.dealership_rif.is_none() {
+            return Ok(());
+        }
+
+        let employee_dealership_rif =
+            match Employee::select_assigned_dealership_rif(national_id, db).await {
+                Ok(dealership_rif) => dealership_rif,
+                Err(sqlx::Error::RowNotFound) => return Ok(()),
+                Err(err) => {
+                    return Err(anyhow!(err)
+                        .context("Failed to fetch the dealership of the employee from the database")
+                        .into())
+                }
+            };
+
+        self.ensure_includes(employee_dealership_rif.as_deref().unwrap_or_default())
+    }
+}
+
+impl FromRequest for DealershipScope {
+    type Error = ServiceError;
+    type Future = Pin<Box<dyn Future<Output = Result<DealershipScope, ServiceError>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let employee = AuthenticatedEmployee::from_request(request, payload).into_inner();
+        let db = request.app_data::<Data<Pool<Postgres>>>().cloned();
+
+        Box::pin(async move {
+            let employee = employee?;
+            let db = db.ok_or_else(|| {
+                ServiceError::UnexpectedError(anyhow!("The database pool was not configured"))
+            })?;
+
+            let has_access_to_all_dealerships = RolePermission::is_granted_to_employee(
+                employee.national_id.clone(),
+                AllDealerships::NAME.to_string(),
+                db.get_ref(),
+            )
+            .await
+            .context("Failed to fetch the permissions of the employee from the database")?;
+
+            if has_access_to_all_dealerships {
+                return Ok(DealershipScope {
+                    dealership_rif: None,
+                });
+            }
+
+            let dealership_rif =
+                Employee::select_assigned_dealership_rif(employee.national_id, db.get_ref())
+                    .await
+                    .context("Failed to fetch the dealership of the employee from the database")?
+                    .ok_or_else(|| {
+                        ServiceError::AuthorizationError(
+                            "The employee is not assigned to any dealership".to_string(),
+                        )
+                    })?;
+
+            Ok(DealershipScope {
+                dealership_rif: Some(dealership_rif),
+            })
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use sqlx::error::DatabaseError;
+
+use crate::services::service_error::{FieldErrors, ServiceError};
+
+const NATIONAL_ID_MESSAGE: &str = "The {field} must be a national id such as V-12345678";
+const RIF_MESSAGE: &str = "The {field} must be a rif such as J-12345678";
+const PHONE_NO_MESSAGE: &str = "The {field} must be a valid phone number";
+const EMAIL_MESSAGE: &str = "The {field} must be a valid email address";
+
+const CHECK_VIOLATIONS: &[(&str, &str, &[&str], &str)] = &[
+    ("clients", "valid_national_id", &["nationalId"], NATIONAL_ID_MESSAGE),
+    ("clients", "valid_phone_no", &["mainPhoneNo", "secondaryPhoneNo"], PHONE_NO_MESSAGE),
+    ("clients", "valid_email", &["email"], EMAIL_MESSAGE),
+    ("vehicles", "valid_national_id", &["ownerNationalId"], NATIONAL_ID_MESSAGE),
+    ("staff", "valid_national_id", &["nationalId"], NATIONAL_ID_MESSAGE),
+    ("staff", "valid_phone_no", &["mainPhoneNo", "secondaryPhoneNo"], PHONE_NO_MESSAGE),
+    ("staff", "valid_email", &["email"], EMAIL_MESSAGE),
+    ("staff", "valid_rif", &["helpedDealershipRif"], RIF_MESSAGE),
+    ("staff", "valid_salary", &["salary"], "The {field} must be greater than or equal to 0"),
+    ("staff_accounts", "valid_national_id", &["nationalId"], NATIONAL_ID_MESSAGE),
+    ("services", "valid_national_id", &["coordinatorNationalId"], NATIONAL_ID_MESSAGE),
+    ("dealerships", "valid_rif", &["rif"], RIF_MESSAGE),
+    ("vendors", "valid_rif", &["rif"], RIF_MESSAGE),
+    ("vendors", "valid_phone_no", &["phoneNo"], PHONE_NO_MESSAGE),
+    ("vendors", "valid_email", &["email"], EMAIL_MESSAGE),
+    ("vehicle_models", "valid_seat_count", &["seatCount"], "The {field} must be greater than 0"),
+    ("vehicle_models", "valid_weight_in_kg", &["weightInKg"], "The {field} must be greater than 0"),
+    ("vehicle_models", "valid_octane_rating", &["octaneRating"], "The {field} must be 91 or 95"),
+    ("activities", "valid_price_per_hour", &["pricePerHour"], "The {field} must be greater than or equal to 0"),
+    ("activities_prices", "valid_rif", &["dealershipRif"], RIF_MESSAGE),
+    ("activities_prices", "valid_price_per_hour", &["pricePerHour"], "The {field} must be greater than or equal to 0"),
+    ("discounts", "valid_rif", &["dealershipRif"], RIF_MESSAGE),
+    ("discounts", "valid_discount_percentage", &["discountPercentage"], "The {field} must be between 0 and 1"),
+    ("discounts", "valid_required_annual_service_usage_count", &["requiredAnnualServiceUsageCount"], "The {field} must be greater than or equal to 0"),
+    ("payments", "valid_amount_paid", &["amountPaid"], "The {field} must be greater than 0"),
+    ("payments", "valid_payment_type", &["paymentType"], "The {field} must be one of the following: bolivares, foreign-currency, transfer, debit-card, credit-card"),
+    ("stock", "valid_rif", &["dealershipRif"], RIF_MESSAGE),
+    ("stock", "valid_product_cost", &["productCost"], "The {field} must be greater than or equal to 0"),
+    ("stock", "valid_min_capacity", &["minCapacity"], "The {field} must be greater than or equal to 0"),
+    ("stock", "consistency_between_min_capacity_and_product_count", &["productCount", "minCapacity"], "The productCount must be greater than or equal to the minCapacity"),
+    ("stock", "consistency_between_min_capacity_and_max_capacity", &["minCapacity", "maxCapacity"], "The maxCapacity must be greater than or equal to the minCapacity"),
+    ("products_fluid_types", "valid_fluid", &["fluid"], "The {field} must be one of the following: gearbox-oil, engine-oil, engine-coolant"),
+];
+
+pub fn check_violation_error(table: &str, db_err: &dyn DatabaseError) -> ServiceError {
+    let constraint = db_err.constraint().unwrap_or_default();
+
+    let violation =
+        CHECK_VIOLATIONS
+            .iter()
+            .find(|(violation_table, violation_constraint, _, _)| {
+                *violation_table == table && *violation_constraint == constraint
+            });
+
+    match violation {
+        Some((_, _, fields, message)) => ServiceError::FieldValidationError(
+            fields
+                .iter()
+                .map(|field| (field.to_string(), vec![message.replace("{field}", field)]))
+                .collect::<FieldErrors>(),
+        ),
+        None => ServiceError::DomainValidationError(format!(
+            "The provided values violate the constraint {}",
+            constraint
+        )),
+    }
+}
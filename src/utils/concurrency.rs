@@ -0,0 +1,93 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    dev::Payload,
+    http::header::{EntityTag, Header, IfMatch, IF_MATCH},
+    FromRequest, HttpRequest,
+};
+use anyhow::{anyhow, Context};
+use sqlx::{Executor, Postgres};
+
+use crate::{models::row_version::RowVersion, services::service_error::ServiceError};
+
+pub async fn select_entity_tag(
+    resource: &str,
+    table: &str,
+    key: Vec<String>,
+    connection: impl Executor<'_, Database = Postgres>,
+) -> Result<EntityTag, ServiceError> {
+    let row_version = RowVersion::select(table.to_string(), key, connection)
+        .await
+        .context("Failed to fetch the row version from the database")?;
+
+    match row_version {
+        Some(row_version) => Ok(EntityTag::new_strong(row_version)),
+        None => Err(ServiceError::ResourceNotFound(
+            resource.to_string(),
+            anyhow!("There is no row version for the {}", resource),
+        )),
+    }
+}
+
+pub struct Precondition {
+    if_match: Option<IfMatch>,
+}
+
+impl Precondition {
+    pub async fn ensure_matches(
+        &self,
+        resource: &str,
+        table: &str,
+        key: Vec<String>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<(), ServiceError> {
+        let entity_tags = match &self.if_match {
+            Some(IfMatch::Items(entity_tags)) => entity_tags,
+            _ => return Ok(()),
+        };
+
+        let row_version = RowVersion::select_for_update(table.to_string(), key, connection)
+            .await
+            .context("Failed to lock the row version from the database")?;
+
+        let current_entity_tag = match row_version {
+            Some(row_version) => EntityTag::new_strong(row_version),
+            None => return Ok(()),
+        };
+
+        if entity_tags
+            .iter()
+            .any(|entity_tag| entity_tag.strong_eq(&current_entity_tag))
+        {
+            return Ok(());
+        }
+
+        Err(ServiceError::PreconditionFailedError(format!(
+            "The {} has been modified since it was fetched",
+            resource
+        )))
+    }
+}
+
+impl FromRequest for Precondition {
+    type Error = ServiceError;
+    type Future = Ready<Result<Precondition, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if !request.headers().contains_key(IF_MATCH) {
+            return ready(Ok(Precondition { if_match: None }));
+        }
+
+        ready(
+            IfMatch::parse(request)
+                .map(|if_match| Precondition {
+                    if_match: Some(if_match),
+                })
+                .map_err(|_| {
+                    ServiceError::DomainValidationError(
+                        "The If-Match header must be * or a list of ETags".to_string(),
+                    )
+                }),
+        )
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Context;
+use sqlx::{Executor, Postgres};
+
+use crate::{models::dependency::Dependency, services::service_error::ServiceError};
+
+pub async fn ensure_deletable(
+    resource: &str,
+    table: &str,
+    key: Vec<String>,
+    connection: impl Executor<'_, Database = Postgres>,
+) -> Result<(), ServiceError> {
+    let dependencies = Dependency::select_all(table.to_string(), key, connection)
+        .await
+        .context("Failed to fetch the dependencies from the database")?;
+
+    ensure_unreferenced(resource, dependencies)
+}
+
+pub async fn ensure_soft_deletable(
+    resource: &str,
+    table: &str,
+    key: Vec<String>,
+    relations: &[&str],
+    connection: impl Executor<'_, Database = Postgres>,
+) -> Result<(), ServiceError> {
+    let dependencies = Dependency::select_all_active(table.to_string(), key, connection)
+        .await
+        .context("Failed to fetch the active dependencies from the database")?
+        .into_iter()
+        .filter(|dependency| relations.contains(&dependency.relation.as_str()))
+        .collect();
+
+    ensure_unreferenced(resource, dependencies)
+}
+
+fn ensure_unreferenced(resource: &str, dependencies: Vec<Dependency>) -> Result<(), ServiceError> {
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+
+    let blocking_relations = dependencies
+        .iter()
+        .map(|dependency| match dependency.referencing_rows {
+            1 => format!("1 row of {}", dependency.relation),
+            referencing_rows => format!("{} rows of {}", referencing_rows, dependency.relation),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Err(ServiceError::DeleteConflictError(format!(
+        "The {} can't be deleted because it is still referenced by {}",
+        resource, blocking_relations
+    )))
+}
@@ -6,6 +6,12 @@ pub struct MaybeNull<T: DeserializeOwned>(
     #[serde(deserialize_with = "double_option::deserialize")] Option<Option<T>>,
 );
 
+impl<T: DeserializeOwned> MaybeNull<T> {
+    pub fn map<U: DeserializeOwned>(self, f: impl FnOnce(T) -> U) -> MaybeNull<U> {
+        MaybeNull(self.0.map(|value| value.map(f)))
+    }
+}
+
 impl<T: DeserializeOwned> From<Option<T>> for MaybeNull<T> {
     fn from(value: Option<T>) -> MaybeNull<T> {
         MaybeNull(Some(value))
@@ -31,6 +37,12 @@ pub struct MaybeAbsent<T: DeserializeOwned>(
     #[serde(deserialize_with = "deserialize_as_inner")] Option<T>,
 );
 
+impl<T: DeserializeOwned> MaybeAbsent<T> {
+    pub fn map<U: DeserializeOwned>(self, f: impl FnOnce(T) -> U) -> MaybeAbsent<U> {
+        MaybeAbsent(self.0.map(f))
+    }
+}
+
 impl<T: DeserializeOwned> Default for MaybeAbsent<T> {
     fn default() -> MaybeAbsent<T> {
         MaybeAbsent(None)
@@ -0,0 +1,148 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sqlx::{Pool, Postgres};
+
+use crate::services::service_error::ServiceError;
+
+#[derive(Default)]
+pub struct Expansion {
+    relations: BTreeSet<String>,
+}
+
+impl Expansion {
+    pub fn parse(relations: &[&str], expand: Option<&str>) -> Result<Expansion, ServiceError> {
+        let mut expansion = Expansion::default();
+
+        for relation in expand.unwrap_or_default().split(',').map(str::trim) {
+            if relation.is_empty() {
+                continue;
+            }
+
+            if !relations.contains(&relation) {
+                return Err(ServiceError::InvalidQueryParamValueError(format!(
+                    "Query param expand contains the unknown relation {}",
+                    relation
+                )));
+            }
+
+            expansion.relations.insert(relation.to_string());
+        }
+
+        Ok(expansion)
+    }
+
+    pub fn includes(&self, relation: &str) -> bool {
+        self.relations.contains(relation)
+    }
+
+    pub fn ensure_streamable(&self) -> Result<(), ServiceError> {
+        if !self.relations.is_empty() {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param expand can't be combined with NDJSON responses".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct Expanded<T: Serialize> {
+    #[serde(flatten)]
+    pub item: T,
+    #[serde(flatten)]
+    pub relations: Map<String, Value>,
+}
+
+impl<T: Serialize> Expanded<T> {
+    pub fn wrap_all(items: Vec<T>) -> Vec<Expanded<T>> {
+        items
+            .into_iter()
+            .map(|item| Expanded {
+                item,
+                relations: Map::new(),
+            })
+            .collect()
+    }
+
+    pub fn embed_one<R, K>(
+        expanded_items: &mut [Expanded<T>],
+        relation: &str,
+        related_items: Vec<R>,
+        item_key: impl Fn(&T) -> K,
+        related_key: impl Fn(&R) -> K,
+    ) where
+        R: Serialize,
+        K: Eq + Hash,
+    {
+        let related_items_by_key: HashMap<K, Value> = related_items
+            .into_iter()
+            .map(|related_item| (related_key(&related_item), json!(related_item)))
+            .collect();
+
+        for expanded_item in expanded_items {
+            let related_item = related_items_by_key
+                .get(&item_key(&expanded_item.item))
+                .cloned()
+                .unwrap_or(Value::Null);
+            expanded_item
+                .relations
+                .insert(relation.to_string(), related_item);
+        }
+    }
+
+    pub fn embed_many<R, K>(
+        expanded_items: &mut [Expanded<T>],
+        relation: &str,
+        related_items: Vec<R>,
+        item_key: impl Fn(&T) -> K,
+        related_key: impl Fn(&R) -> K,
+    ) where
+        R: Serialize,
+        K: Eq + Hash,
+    {
+        let mut related_items_by_key: HashMap<K, Vec<Value>> = HashMap::new();
+        for related_item in related_items {
+            related_items_by_key
+                .entry(related_key(&related_item))
+                .or_default()
+                .push(json!(related_item));
+        }
+
+        for expanded_item in expanded_items {
+            let related_items = related_items_by_key
+                .get(&item_key(&expanded_item.item))
+                .cloned()
+                .unwrap_or_default();
+            expanded_item
+                .relations
+                .insert(relation.to_string(), Value::Array(related_items));
+        }
+    }
+}
+
+#[async_trait]
+pub trait Expandable: Serialize + Send + Sized {
+    const RELATIONS: &'static [&'static str];
+
+    async fn expand(
+        items: Vec<Self>,
+        expansion: &Expansion,
+        connection: &Pool<Postgres>,
+    ) -> Result<Vec<Expanded<Self>>, sqlx::Error>;
+
+    async fn expand_one(
+        item: Self,
+        expansion: &Expansion,
+        connection: &Pool<Postgres>,
+    ) -> Result<Expanded<Self>, sqlx::Error> {
+        let mut expanded_items = Self::expand(vec![item], expansion, connection).await?;
+        Ok(expanded_items.remove(0))
+    }
+}
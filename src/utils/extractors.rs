@@ -0,0 +1,227 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+
+use actix_web::{
+    body::BoxBody,
+    dev::Payload,
+    error::JsonPayloadError,
+    web::{self, JsonConfig, PathConfig},
+    FromRequest, HttpRequest, HttpResponse, Responder,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{services::service_error::ServiceError, utils::fieldsets::to_camel_case};
+
+pub struct Json<T>(pub T);
+
+pub struct Query<T>(pub T);
+
+pub struct PathQuery<T>(pub T);
+
+pub struct PathJson<T>(pub T);
+
+impl<T: DeserializeOwned + 'static> FromRequest for Json<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Json<T>, actix_web::Error>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let body = web::Json::<serde_json::Value>::from_request(request, payload);
+
+        Box::pin(async move {
+            let web::Json(body) = body.await?;
+
+            serde_path_to_error::deserialize(body)
+                .map(Json)
+                .map_err(|err| body_field_error(err.path().to_string(), err.inner()).into())
+        })
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for PathJson<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<PathJson<T>, actix_web::Error>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let path_params: Vec<(String, Value)> = request
+            .match_info()
+            .iter()
+            .map(|(name, value)| (to_camel_case(&name.replace('_', "-")), path_value(value)))
+            .collect();
+        let body = web::Json::<Value>::from_request(request, payload);
+
+        Box::pin(async move {
+            let web::Json(mut body) = body.await?;
+
+            if let Value::Object(fields) = &mut body {
+                for (field, value) in path_params {
+                    if fields.contains_key(&field) {
+                        return Err(ServiceError::invalid_field(
+                            &field,
+                            format!("The {} is already given by the route", field),
+                        )
+                        .into());
+                    }
+
+                    fields.insert(field, value);
+                }
+            }
+
+            serde_path_to_error::deserialize(body)
+                .map(PathJson)
+                .map_err(|err| body_field_error(err.path().to_string(), err.inner()).into())
+        })
+    }
+}
+
+impl<T: Serialize> Responder for Json<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, request: &HttpRequest) -> HttpResponse<BoxBody> {
+        web::Json(self.0).respond_to(request).map_into_boxed_body()
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Query<T> {
+    type Error = ServiceError;
+    type Future = Ready<Result<Query<T>, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(deserialize_query_params(request.query_string()).map(Query))
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for PathQuery<T> {
+    type Error = ServiceError;
+    type Future = Ready<Result<PathQuery<T>, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let mut params = form_urlencoded::Serializer::new(String::new());
+        let mut path_params = Vec::new();
+
+        for (name, value) in request.match_info().iter() {
+            let name = name.replace('_', "-");
+            params.append_pair(&name, value);
+            path_params.push(name);
+        }
+
+        params.extend_pairs(form_urlencoded::parse(request.query_string().as_bytes()));
+
+        ready(
+            deserialize_params(&params.finish())
+                .map(PathQuery)
+                .map_err(|err| {
+                    let param = err.path().to_string();
+
+                    match path_params.contains(&param) {
+                        true => ServiceError::InvalidPathParamValueError(format!(
+                            "Path param {} is invalid: {}",
+                            param,
+                            err.inner()
+                        )),
+                        false => query_param_error(param, err.inner()),
+                    }
+                }),
+        )
+    }
+}
+
+pub fn json_config() -> JsonConfig {
+    JsonConfig::default().error_handler(|err, _| {
+        let message = match &err {
+            JsonPayloadError::ContentType => {
+                "The request body must have the application/json content type".to_string()
+            }
+            JsonPayloadError::Deserialize(err) => {
+                format!("The request body is not valid JSON: {}", err)
+            }
+            _ => format!("The request body couldn't be read: {}", err),
+        };
+
+        ServiceError::DomainValidationError(message).into()
+    })
+}
+
+pub fn path_config() -> PathConfig {
+    PathConfig::default().error_handler(|err, _| {
+        ServiceError::InvalidPathParamValueError(format!("The path params are invalid: {}", err))
+            .into()
+    })
+}
+
+fn path_value(value: &str) -> Value {
+    match value.parse::<i64>() {
+        Ok(number) => Value::from(number),
+        Err(_) => Value::from(value),
+    }
+}
+
+fn deserialize_query_params<T: DeserializeOwned>(query_string: &str) -> Result<T, ServiceError> {
+    deserialize_params(query_string)
+        .map_err(|err| query_param_error(err.path().to_string(), err.inner()))
+}
+
+fn deserialize_params<T: DeserializeOwned>(
+    query_string: &str,
+) -> Result<T, serde_path_to_error::Error<serde_urlencoded::de::Error>> {
+    let params =
+        serde_urlencoded::Deserializer::new(form_urlencoded::parse(query_string.as_bytes()));
+
+    serde_path_to_error::deserialize(params)
+}
+
+fn missing_field(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+}
+
+fn is_unknown_field(message: &str) -> bool {
+    message.starts_with("unknown field `")
+}
+
+fn body_field_error(path: String, err: &serde_json::Error) -> ServiceError {
+    let message = err.to_string();
+
+    if is_unknown_field(&message) {
+        return ServiceError::invalid_field(&path, format!("The {} is not an allowed field", path));
+    }
+
+    if path != "." {
+        return ServiceError::invalid_field(&path, format!("The {} is invalid: {}", path, message));
+    }
+
+    match missing_field(&message) {
+        Some(field) => ServiceError::invalid_field(field, format!("The {} is required", field)),
+        None => {
+            ServiceError::DomainValidationError(format!("The request body is invalid: {}", message))
+        }
+    }
+}
+
+fn query_param_error(path: String, err: &serde_urlencoded::de::Error) -> ServiceError {
+    let message = err.to_string();
+
+    if is_unknown_field(&message) {
+        return ServiceError::InvalidQueryParamValueError(format!("Unknown query param {}", path));
+    }
+
+    if path != "." {
+        return ServiceError::InvalidQueryParamValueError(format!(
+            "Query param {} is invalid: {}",
+            path, message
+        ));
+    }
+
+    match missing_field(&message) {
+        Some(param) => {
+            ServiceError::MissingQueryParamError(format!("Missing query param {}", param))
+        }
+        None => ServiceError::InvalidQueryParamValueError(format!(
+            "The query params are invalid: {}",
+            message
+        )),
+    }
+}
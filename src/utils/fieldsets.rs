@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
+use serde::{ser::Error, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::services::service_error::ServiceError;
+
+#[derive(Clone, Default)]
+pub struct Fieldset {
+    excluded_keys: Arc<Vec<String>>,
+}
+
+impl Fieldset {
+    pub fn parse(fields: &[&str], requested: Option<&str>) -> Result<Fieldset, ServiceError> {
+        let requested_fields = match requested {
+            Some(requested) => requested
+                .split(',')
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+                .collect::<Vec<&str>>(),
+            None => return Ok(Fieldset::default()),
+        };
+
+        if requested_fields.is_empty() {
+            return Err(ServiceError::InvalidQueryParamValueError(
+                "Query param fields can't be empty".to_string(),
+            ));
+        }
+
+        if let Some(field) = requested_fields
+            .iter()
+            .find(|field| !fields.contains(field))
+        {
+            return Err(ServiceError::InvalidQueryParamValueError(format!(
+                "Query param fields contains the unknown field {}, the allowed fields are: {}",
+                field,
+                fields.join(", ")
+            )));
+        }
+
+        let excluded_keys = fields
+            .iter()
+            .filter(|field| !requested_fields.contains(field))
+            .map(|field| to_camel_case(field))
+            .collect();
+
+        Ok(Fieldset {
+            excluded_keys: Arc::new(excluded_keys),
+        })
+    }
+
+    pub fn select<T: Serialize>(&self, item: T) -> Sparse<T> {
+        Sparse {
+            item,
+            excluded_keys: self.excluded_keys.clone(),
+        }
+    }
+
+    pub fn select_all<T: Serialize>(&self, items: Vec<T>) -> Vec<Sparse<T>> {
+        items.into_iter().map(|item| self.select(item)).collect()
+    }
+
+    pub fn select_stream<'s, T: Serialize + Send + 's>(
+        self,
+        items: BoxStream<'s, Result<T, sqlx::Error>>,
+    ) -> BoxStream<'s, Result<Sparse<T>, sqlx::Error>> {
+        items.map_ok(move |item| self.select(item)).boxed()
+    }
+}
+
+pub struct Sparse<T: Serialize> {
+    item: T,
+    excluded_keys: Arc<Vec<String>>,
+}
+
+impl<T: Serialize> Serialize for Sparse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.excluded_keys.is_empty() {
+            return self.item.serialize(serializer);
+        }
+
+        let mut value = serde_json::to_value(&self.item).map_err(S::Error::custom)?;
+        if let Value::Object(object) = &mut value {
+            for key in self.excluded_keys.iter() {
+                object.remove(key);
+            }
+        }
+
+        value.serialize(serializer)
+    }
+}
+
+pub fn to_camel_case(field: &str) -> String {
+    let mut words = field.split('-');
+    let mut key = words.next().unwrap_or_default().to_string();
+
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first_char) = chars.next() {
+            key.extend(first_char.to_uppercase());
+            key.push_str(chars.as_str());
+        }
+    }
+
+    key
+}
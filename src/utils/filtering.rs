@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use bigdecimal::BigDecimal;
+use time::{macros::format_description, Date};
+
+use crate::{
+    models::domains::{NationalId, Rif},
+    services::service_error::ServiceError,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl Operator {
+    fn parse(operator: &str) -> Option<Operator> {
+        match operator {
+            "eq" => Some(Operator::Eq),
+            "ne" => Some(Operator::Ne),
+            "lt" => Some(Operator::Lt),
+            "lte" => Some(Operator::Lte),
+            "gt" => Some(Operator::Gt),
+            "gte" => Some(Operator::Gte),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operator::Eq => "eq",
+            Operator::Ne => "ne",
+            Operator::Lt => "lt",
+            Operator::Lte => "lte",
+            Operator::Gt => "gt",
+            Operator::Gte => "gte",
+        }
+    }
+}
+
+pub const EQUALITY: &[Operator] = &[Operator::Eq, Operator::Ne];
+
+pub const ORDERING: &[Operator] = &[
+    Operator::Eq,
+    Operator::Ne,
+    Operator::Lt,
+    Operator::Lte,
+    Operator::Gt,
+    Operator::Gte,
+];
+
+pub struct FilterField {
+    pub name: &'static str,
+    pub operators: &'static [Operator],
+}
+
+impl FilterField {
+    pub const fn new(name: &'static str, operators: &'static [Operator]) -> FilterField {
+        FilterField { name, operators }
+    }
+}
+
+pub trait FilterValue: Sized {
+    fn parse(value: &str) -> Option<Self>;
+}
+
+impl FilterValue for i16 {
+    fn parse(value: &str) -> Option<i16> {
+        value.parse().ok()
+    }
+}
+
+impl FilterValue for i32 {
+    fn parse(value: &str) -> Option<i32> {
+        value.parse().ok()
+    }
+}
+
+impl FilterValue for bool {
+    fn parse(value: &str) -> Option<bool> {
+        value.parse().ok()
+    }
+}
+
+impl FilterValue for String {
+    fn parse(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+}
+
+impl FilterValue for BigDecimal {
+    fn parse(value: &str) -> Option<BigDecimal> {
+        value.parse().ok()
+    }
+}
+
+impl FilterValue for Date {
+    fn parse(value: &str) -> Option<Date> {
+        Date::parse(value, format_description!("[year]-[month]-[day]")).ok()
+    }
+}
+
+impl FilterValue for Rif {
+    fn parse(value: &str) -> Option<Rif> {
+        Rif::try_from(value.to_string()).ok()
+    }
+}
+
+impl FilterValue for NationalId {
+    fn parse(value: &str) -> Option<NationalId> {
+        NationalId::try_from(value.to_string()).ok()
+    }
+}
+
+#[derive(Clone)]
+pub struct Comparison<T> {
+    pub eq: Option<T>,
+    pub ne: Option<T>,
+    pub lt: Option<T>,
+    pub lte: Option<T>,
+    pub gt: Option<T>,
+    pub gte: Option<T>,
+}
+
+impl<T> Default for Comparison<T> {
+    fn default() -> Comparison<T> {
+        Comparison {
+            eq: None,
+            ne: None,
+            lt: None,
+            lte: None,
+            gt: None,
+            gte: None,
+        }
+    }
+}
+
+impl<T: FilterValue> Comparison<T> {
+    pub fn apply(&mut self, operator: Operator, value: &str) -> bool {
+        let Some(value) = T::parse(value) else {
+            return false;
+        };
+
+        let operand = match operator {
+            Operator::Eq => &mut self.eq,
+            Operator::Ne => &mut self.ne,
+            Operator::Lt => &mut self.lt,
+            Operator::Lte => &mut self.lte,
+            Operator::Gt => &mut self.gt,
+            Operator::Gte => &mut self.gte,
+        };
+        *operand = Some(value);
+
+        true
+    }
+}
+
+pub trait Filter: Default + Clone + Send + Sync {
+    const FIELDS: &'static [FilterField];
+    const SORT_FIELDS: &'static [&'static str];
+
+    fn apply(&mut self, field: &str, operator: Operator, value: &str) -> bool;
+}
+
+#[derive(Clone, Default)]
+pub struct ListFilter<F: Filter> {
+    pub conditions: F,
+    pub sort_field: Option<&'static str>,
+    pub sort_descending: bool,
+}
+
+impl<F: Filter> ListFilter<F> {
+    pub fn parse(
+        filter: &BTreeMap<String, String>,
+        sort: Option<&str>,
+    ) -> Result<ListFilter<F>, ServiceError> {
+        let mut conditions = F::default();
+
+        for (param, value) in filter {
+            let (field, operator) = parse_filter_param(param)?;
+            let filter_field = F::FIELDS
+                .iter()
+                .find(|filter_field| filter_field.name == field)
+                .ok_or_else(|| {
+                    ServiceError::InvalidQueryParamValueError(format!(
+                        "Query param {} must use one of the following fields: {}",
+                        param,
+                        F::FIELDS
+                            .iter()
+                            .map(|filter_field| filter_field.name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+
+            let operator = Operator::parse(operator)
+                .filter(|operator| filter_field.operators.contains(operator))
+                .ok_or_else(|| {
+                    ServiceError::InvalidQueryParamValueError(format!(
+                        "Query param {} must use one of the following operators: {}",
+                        param,
+                        filter_field
+                            .operators
+                            .iter()
+                            .map(Operator::name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+
+            if !conditions.apply(field, operator, value) {
+                return Err(ServiceError::InvalidQueryParamValueError(format!(
+                    "Query param {} has an invalid value",
+                    param
+                )));
+            }
+        }
+
+        let (sort_field, sort_descending) = match sort {
+            Some(sort) => {
+                let (field, sort_descending) = match sort.strip_prefix('-') {
+                    Some(field) => (field, true),
+                    None => (sort, false),
+                };
+                let sort_field = F::SORT_FIELDS
+                    .iter()
+                    .find(|sort_field| **sort_field == field)
+                    .ok_or_else(|| {
+                        ServiceError::InvalidQueryParamValueError(format!(
+                            "Query param sort must use one of the following fields: {}",
+                            F::SORT_FIELDS.join(", ")
+                        ))
+                    })?;
+
+                (Some(*sort_field), sort_descending)
+            }
+            None => (None, false),
+        };
+
+        Ok(ListFilter {
+            conditions,
+            sort_field,
+            sort_descending,
+        })
+    }
+}
+
+fn parse_filter_param(param: &str) -> Result<(&str, &str), ServiceError> {
+    let selector = param
+        .strip_prefix("filter[")
+        .and_then(|selector| selector.strip_suffix(']'))
+        .ok_or_else(|| {
+            ServiceError::InvalidQueryParamValueError(format!("Unknown query param {}", param))
+        })?;
+
+    match selector.split_once("][") {
+        Some((field, operator)) => Ok((field, operator)),
+        None => Ok((selector, "eq")),
+    }
+}
@@ -1,13 +1,34 @@
-use std::marker::PhantomData;
+use std::{
+    future::{ready, Ready},
+    marker::PhantomData,
+};
 
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
 use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sqlx::{Executor, Postgres};
 
+use super::filtering::{Filter, ListFilter};
+use crate::services::{
+    responses_dto::{CursorPagination, Pagination},
+    service_error::ServiceError,
+};
+
 #[async_trait]
 pub trait Paginable<T>: Sized {
+    const FIELDS: &'static [&'static str];
+
+    type Filter: Filter;
+
+    type CursorKey: Serialize + DeserializeOwned + Send + Sync;
+
     fn paginate(per_page: i64) -> Pages<T, Self> {
         Pages {
             per_page,
+            dealership_rif: None,
+            include_deleted: false,
+            filter: ListFilter::default(),
             phantom_pages: PhantomData,
             phantom_paginable: PhantomData,
         }
@@ -18,21 +39,91 @@ pub trait Paginable<T>: Sized {
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<T>, sqlx::Error>;
+
+    fn cursor_key(item: &T) -> Self::CursorKey;
+
+    async fn get_items_after(
+        pages: &Pages<T, Self>,
+        limit: i64,
+        cursor: Option<&Cursor<Self::CursorKey>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<T>, sqlx::Error>;
 }
 
 pub struct Pages<T, P: Paginable<T>> {
     pub per_page: i64,
+    pub dealership_rif: Option<String>,
+    pub include_deleted: bool,
+    pub filter: ListFilter<P::Filter>,
     phantom_pages: PhantomData<Vec<Page<T>>>,
     phantom_paginable: PhantomData<P>,
 }
 
 impl<T, P: Paginable<T>> Pages<T, P> {
+    pub fn in_dealership(mut self, dealership_rif: Option<String>) -> Pages<T, P> {
+        self.dealership_rif = dealership_rif;
+        self
+    }
+
+    pub fn including_deleted(mut self, include_deleted: bool) -> Pages<T, P> {
+        self.include_deleted = include_deleted;
+        self
+    }
+
+    pub fn filtered_by(mut self, filter: ListFilter<P::Filter>) -> Pages<T, P> {
+        self.filter = filter;
+        self
+    }
+
     pub async fn get_page(
         &self,
         page_no: i64,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Page<T>, sqlx::Error> {
-        Paginable::<T>::get_page(self, page_no, connection).await
+        let mut page = Paginable::<T>::get_page(self, page_no, connection).await?;
+
+        if page.items.is_empty() && page_no == 1 {
+            page.total = Some(0);
+        }
+
+        Ok(page)
+    }
+
+    pub async fn get_page_after(
+        &self,
+        cursor: Option<Cursor<P::CursorKey>>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<CursorPage<T>, sqlx::Error> {
+        let mut items =
+            P::get_items_after(self, self.per_page + 1, cursor.as_ref(), connection).await?;
+
+        let has_more = items.len() as i64 > self.per_page;
+        items.truncate(self.per_page as usize);
+
+        let backward = cursor.as_ref().is_some_and(|cursor| cursor.backward);
+        if backward {
+            items.reverse();
+        }
+
+        let (has_prev, has_next) = match backward {
+            true => (has_more, true),
+            false => (cursor.is_some(), has_more),
+        };
+
+        let prev_cursor = items
+            .first()
+            .filter(|_| has_prev)
+            .map(|item| Cursor::new(P::cursor_key(item), true).encode());
+        let next_cursor = items
+            .last()
+            .filter(|_| has_next)
+            .map(|item| Cursor::new(P::cursor_key(item), false).encode());
+
+        Ok(CursorPage {
+            items,
+            prev_cursor,
+            next_cursor,
+        })
     }
 }
 
@@ -40,4 +131,121 @@ pub struct Page<T> {
     pub per_page: i64,
     pub page_no: i64,
     pub items: Vec<T>,
+    pub total: Option<i64>,
+}
+
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub prev_cursor: Option<String>,
+    pub next_cursor: Option<String>,
+}
+
+pub type CursorFor<T> = Cursor<<T as Paginable<T>>::CursorKey>;
+
+#[derive(Serialize, Deserialize)]
+pub struct Cursor<K> {
+    pub key: K,
+    pub backward: bool,
+}
+
+impl<K: Serialize + DeserializeOwned> Cursor<K> {
+    pub fn new(key: K, backward: bool) -> Cursor<K> {
+        Cursor { key, backward }
+    }
+
+    pub fn decode(cursor: &str) -> Result<Cursor<K>, ServiceError> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|cursor| serde_json::from_slice::<Cursor<K>>(&cursor).ok())
+            .ok_or_else(|| {
+                ServiceError::InvalidQueryParamValueError(
+                    "Query param cursor is invalid".to_string(),
+                )
+            })
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+}
+
+const PAGINATION_PARAMS: [&str; 4] = ["per-page", "page-no", "cursor", "limit"];
+
+pub struct PageLinks {
+    path: String,
+    params: Vec<(String, String)>,
+}
+
+impl PageLinks {
+    pub fn for_pages(&self, pagination: &Pagination) -> String {
+        let link = |page_no: i64, rel: &str| {
+            self.link(
+                &[
+                    ("per-page", pagination.per_page.to_string()),
+                    ("page-no", page_no.to_string()),
+                ],
+                rel,
+            )
+        };
+
+        let last_page = pagination.pages.max(1);
+
+        let mut links = vec![link(1, "first")];
+        if pagination.has_prev {
+            links.push(link((pagination.page - 1).min(last_page), "prev"));
+        }
+        if pagination.has_next {
+            links.push(link(pagination.page + 1, "next"));
+        }
+        links.push(link(last_page, "last"));
+
+        links.join(", ")
+    }
+
+    pub fn for_cursors(&self, pagination: &CursorPagination) -> String {
+        let link = |cursor: Option<&String>, rel: &str| {
+            let mut params = vec![("limit", pagination.limit.to_string())];
+            if let Some(cursor) = cursor {
+                params.push(("cursor", cursor.clone()));
+            }
+            self.link(&params, rel)
+        };
+
+        let mut links = vec![link(None, "first")];
+        if let Some(prev_cursor) = &pagination.prev_cursor {
+            links.push(link(Some(prev_cursor), "prev"));
+        }
+        if let Some(next_cursor) = &pagination.next_cursor {
+            links.push(link(Some(next_cursor), "next"));
+        }
+
+        links.join(", ")
+    }
+
+    fn link(&self, pagination_params: &[(&str, String)], rel: &str) -> String {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(&self.params)
+            .extend_pairs(pagination_params)
+            .finish();
+
+        format!("<{}?{}>; rel=\"{}\"", self.path, query, rel)
+    }
+}
+
+impl FromRequest for PageLinks {
+    type Error = ServiceError;
+    type Future = Ready<Result<PageLinks, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let params = form_urlencoded::parse(request.query_string().as_bytes())
+            .filter(|(param, _)| !PAGINATION_PARAMS.contains(&param.as_ref()))
+            .map(|(param, value)| (param.into_owned(), value.into_owned()))
+            .collect();
+
+        ready(Ok(PageLinks {
+            path: request.path().to_string(),
+            params,
+        }))
+    }
 }
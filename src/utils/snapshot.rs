@@ -0,0 +1,20 @@
+use anyhow::Context;
+use sqlx::{Executor, Pool, Postgres, Transaction};
+
+use crate::services::service_error::ServiceError;
+
+pub async fn begin_read_only_snapshot(
+    db: &Pool<Postgres>,
+) -> Result<Transaction<'static, Postgres>, ServiceError> {
+    let mut transaction = db
+        .begin()
+        .await
+        .context("Failed to start the read-only transaction")?;
+
+    transaction
+        .execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .await
+        .context("Failed to set the isolation level of the read-only transaction")?;
+
+    Ok(transaction)
+}
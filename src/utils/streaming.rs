@@ -0,0 +1,101 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    dev::Payload,
+    http::{header::ACCEPT, StatusCode},
+    rt,
+    web::{Bytes, Data},
+    FromRequest, HttpRequest, HttpResponse,
+};
+use anyhow::anyhow;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use tokio::sync::mpsc;
+
+use crate::services::service_error::ServiceError;
+
+const NDJSON: &str = "application/x-ndjson";
+
+const BUFFERED_ROWS: usize = 64;
+
+#[derive(PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    Ndjson,
+}
+
+impl FromRequest for ResponseFormat {
+    type Error = ServiceError;
+    type Future = Ready<Result<ResponseFormat, ServiceError>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let preferred_media_type = request
+            .headers()
+            .get_all(ACCEPT)
+            .filter_map(|accept| accept.to_str().ok())
+            .flat_map(|accept| accept.split(','))
+            .map(parse_media_range)
+            .fold(
+                None,
+                |preferred: Option<(&str, f32)>, (media_type, quality)| match preferred {
+                    Some((_, preferred_quality)) if preferred_quality >= quality => preferred,
+                    _ => Some((media_type, quality)),
+                },
+            );
+
+        match preferred_media_type {
+            Some((media_type, quality))
+                if quality > 0.0 && media_type.eq_ignore_ascii_case(NDJSON) =>
+            {
+                ready(Ok(ResponseFormat::Ndjson))
+            }
+            _ => ready(Ok(ResponseFormat::Json)),
+        }
+    }
+}
+
+fn parse_media_range(media_range: &str) -> (&str, f32) {
+    let mut parts = media_range.split(';').map(str::trim);
+    let media_type = parts.next().unwrap_or_default();
+    let quality = parts
+        .filter_map(|param| param.strip_prefix("q="))
+        .find_map(|quality| quality.parse().ok())
+        .unwrap_or(1.0);
+
+    (media_type, quality)
+}
+
+pub fn ndjson_response<T, F>(db: Data<Pool<Postgres>>, select: F) -> HttpResponse
+where
+    T: Serialize + 'static,
+    F: for<'c> FnOnce(&'c Pool<Postgres>) -> BoxStream<'c, Result<T, sqlx::Error>> + 'static,
+{
+    let (sender, mut receiver) = mpsc::channel(BUFFERED_ROWS);
+
+    rt::spawn(async move {
+        let mut rows = select(db.get_ref());
+
+        while let Some(row) = rows.next().await {
+            let line = row
+                .map_err(|err| anyhow!(err).context("Failed to stream the rows from the database"))
+                .and_then(|row| {
+                    let mut line = serde_json::to_vec(&row)?;
+                    line.push(b'\n');
+                    Ok(Bytes::from(line))
+                })
+                .map_err(ServiceError::UnexpectedError);
+
+            let failed = line.is_err();
+            if sender.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let lines = stream::poll_fn(move |context| receiver.poll_recv(context));
+
+    HttpResponse::build(StatusCode::OK)
+        .content_type(NDJSON)
+        .streaming(lines)
+}
@@ -1,3 +1,5 @@
+pub mod compatible_product;
+pub mod inventory_valuation;
 pub mod least_employed_employee;
 pub mod least_profitable_dealership;
 pub mod least_requested_service;
@@ -9,4 +11,6 @@ pub mod most_profitable_dealership;
 pub mod most_requested_service;
 pub mod most_used_product;
 pub mod no_show_client;
+pub mod reorder_suggestion;
+pub mod search_result;
 pub mod vehicle_applied_service;
@@ -0,0 +1,85 @@
+use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::Serialize;
+use sqlx::{Executor, Postgres};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibleProduct {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub is_ecologic: bool,
+    pub supply_line_id: i32,
+    pub product_cost: BigDecimal,
+    pub product_count: i32,
+}
+
+impl CompatibleProduct {
+    pub async fn select_all_in_stock(
+        vehicle_plate: String,
+        dealership_rif: String,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<CompatibleProduct>, sqlx::Error> {
+        CompatibleProduct::stream_all_in_stock(vehicle_plate, dealership_rif, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all_in_stock<'e, 'c: 'e>(
+        vehicle_plate: String,
+        dealership_rif: String,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<CompatibleProduct, sqlx::Error>> {
+        sqlx::query_as!(
+            CompatibleProduct,
+            r#"
+            SELECT
+                p.id,
+                p.name,
+                p.description,
+                p.is_ecologic,
+                p.supply_line_id,
+                s.product_cost,
+                s.product_count
+            FROM
+                vehicles AS v
+                INNER JOIN vehicle_models AS vm ON v.model_id = vm.id
+                INNER JOIN products AS p ON (
+                    EXISTS (
+                        SELECT 1
+                        FROM products_vehicle_models AS pvm
+                        WHERE
+                            pvm.product_id = p.id
+                            AND pvm.vehicle_model_id = vm.id
+                    )
+                    OR EXISTS (
+                        SELECT 1
+                        FROM products_fluid_types AS pft
+                        WHERE
+                            pft.product_id = p.id
+                            AND LOWER(TRIM(pft.fluid_type)) = LOWER(TRIM(
+                                CASE pft.fluid
+                                    WHEN 'gearbox-oil' THEN vm.gearbox_oil_type
+                                    WHEN 'engine-oil' THEN vm.engine_oil_type
+                                    WHEN 'engine-coolant' THEN vm.engine_coolant_type
+                                END
+                            ))
+                    )
+                )
+                INNER JOIN stock AS s ON p.id = s.product_id
+            WHERE
+                v.plate = $1
+                AND s.dealership_rif = $2
+                AND s.product_count > 0
+                AND p.deleted_at IS NULL
+            ORDER BY
+                p.name ASC,
+                p.id ASC
+            "#,
+            vehicle_plate,
+            dealership_rif
+        )
+        .fetch(connection)
+    }
+}
@@ -0,0 +1,122 @@
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+use sqlx::{Executor, Postgres};
+use time::Date;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DealershipInventoryValuation {
+    pub rif: String,
+    pub name: String,
+    pub product_count: i64,
+    pub total_value: BigDecimal,
+}
+
+impl DealershipInventoryValuation {
+    pub async fn select_all(
+        as_of_date: Option<Date>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<DealershipInventoryValuation>, sqlx::Error> {
+        sqlx::query_as!(
+            DealershipInventoryValuation,
+            r#"
+            SELECT
+                d.rif,
+                d.name,
+                SUM(vs.product_count) AS "product_count!",
+                SUM(vs.product_count * vs.product_cost) AS "total_value!"
+            FROM
+                select_valued_stock($1) AS vs
+                INNER JOIN dealerships AS d ON vs.dealership_rif = d.rif
+            GROUP BY
+                d.rif,
+                d.name
+            ORDER BY
+                d.rif ASC
+            "#,
+            as_of_date
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupplyLineInventoryValuation {
+    pub id: i32,
+    pub name: String,
+    pub product_count: i64,
+    pub total_value: BigDecimal,
+}
+
+impl SupplyLineInventoryValuation {
+    pub async fn select_all(
+        as_of_date: Option<Date>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<SupplyLineInventoryValuation>, sqlx::Error> {
+        sqlx::query_as!(
+            SupplyLineInventoryValuation,
+            r#"
+            SELECT
+                sl.id,
+                sl.name,
+                SUM(vs.product_count) AS "product_count!",
+                SUM(vs.product_count * vs.product_cost) AS "total_value!"
+            FROM
+                select_valued_stock($1) AS vs
+                INNER JOIN products AS p ON vs.product_id = p.id
+                INNER JOIN supply_lines AS sl ON p.supply_line_id = sl.id
+            GROUP BY
+                sl.id,
+                sl.name
+            ORDER BY
+                sl.id ASC
+            "#,
+            as_of_date
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductInventoryValuation {
+    pub id: i32,
+    pub name: String,
+    pub supply_line_id: i32,
+    pub product_count: i64,
+    pub total_value: BigDecimal,
+}
+
+impl ProductInventoryValuation {
+    pub async fn select_all(
+        as_of_date: Option<Date>,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ProductInventoryValuation>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductInventoryValuation,
+            r#"
+            SELECT
+                p.id,
+                p.name,
+                p.supply_line_id,
+                SUM(vs.product_count) AS "product_count!",
+                SUM(vs.product_count * vs.product_cost) AS "total_value!"
+            FROM
+                select_valued_stock($1) AS vs
+                INNER JOIN products AS p ON vs.product_id = p.id
+            GROUP BY
+                p.id,
+                p.name,
+                p.supply_line_id
+            ORDER BY
+                p.id ASC
+            "#,
+            as_of_date
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -13,6 +14,14 @@ impl LeastEmployedEmployee {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<LeastEmployedEmployee>, sqlx::Error> {
+        LeastEmployedEmployee::stream_all(connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<LeastEmployedEmployee, sqlx::Error>> {
         sqlx::query_as!(
             LeastEmployedEmployee,
             r#"
@@ -48,7 +57,6 @@ impl LeastEmployedEmployee {
                 rscpe.realized_services_count = (SELECT MIN(realized_services_count) FROM realized_services_count_per_employee)
             "#,
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
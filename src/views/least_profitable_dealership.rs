@@ -1,4 +1,5 @@
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 use time::Date;
@@ -17,6 +18,16 @@ impl LeastProfitableDealership {
         to_date: Date,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<LeastProfitableDealership>, sqlx::Error> {
+        LeastProfitableDealership::stream_all_in_range(from_date, to_date, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all_in_range<'e, 'c: 'e>(
+        from_date: Date,
+        to_date: Date,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<LeastProfitableDealership, sqlx::Error>> {
         sqlx::query_as!(
             LeastProfitableDealership,
             r#"
@@ -47,7 +58,6 @@ impl LeastProfitableDealership {
             from_date,
             to_date
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
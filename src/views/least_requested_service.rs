@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -13,6 +14,14 @@ impl LeastRequestedService {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<LeastRequestedService>, sqlx::Error> {
+        LeastRequestedService::stream_all(connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<LeastRequestedService, sqlx::Error>> {
         sqlx::query_as!(
             LeastRequestedService,
             r#"
@@ -39,7 +48,6 @@ impl LeastRequestedService {
                 rcps.count = (SELECT MIN(count) FROM requests_count_per_service)            
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -13,6 +14,12 @@ impl LeastUsedProduct {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<LeastUsedProduct>, sqlx::Error> {
+        LeastUsedProduct::stream_all(connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<LeastUsedProduct, sqlx::Error>> {
         sqlx::query_as!(
             LeastUsedProduct,
             r#"
@@ -47,7 +54,6 @@ impl LeastUsedProduct {
                 puc.count = (SELECT MIN(count) FROM product_usage_count)
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
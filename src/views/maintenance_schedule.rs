@@ -1,4 +1,5 @@
 use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -17,6 +18,14 @@ impl MaintenanceSchedule {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<MaintenanceSchedule>, sqlx::Error> {
+        MaintenanceSchedule::stream_all(connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<MaintenanceSchedule, sqlx::Error>> {
         sqlx::query_as!(
             MaintenanceSchedule,
             r#"
@@ -33,7 +42,6 @@ impl MaintenanceSchedule {
                 INNER JOIN vehicle_models AS vm ON rs.vehicle_model_id = vm.id
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
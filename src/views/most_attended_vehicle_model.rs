@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 use time::Date;
@@ -16,6 +17,16 @@ impl MostAttendedVehicleModel {
         to_date: Date,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<MostAttendedVehicleModel>, sqlx::Error> {
+        MostAttendedVehicleModel::stream_all_in_range(from_date, to_date, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all_in_range<'e, 'c: 'e>(
+        from_date: Date,
+        to_date: Date,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<MostAttendedVehicleModel, sqlx::Error>> {
         sqlx::query_as!(
             MostAttendedVehicleModel,
             r#"
@@ -53,14 +64,22 @@ impl MostAttendedVehicleModel {
             from_date,
             to_date
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 
     pub async fn select_all_by_name(
         name: String,
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<MostAttendedVehicleModel>, sqlx::Error> {
+        MostAttendedVehicleModel::stream_all_by_name(name, connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all_by_name<'e, 'c: 'e>(
+        name: String,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<MostAttendedVehicleModel, sqlx::Error>> {
         sqlx::query_as!(
             MostAttendedVehicleModel,
             r#"
@@ -100,7 +119,6 @@ impl MostAttendedVehicleModel {
             "#,
             name
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
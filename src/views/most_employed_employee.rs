@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -13,6 +14,14 @@ impl MostEmployedEmployee {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<MostEmployedEmployee>, sqlx::Error> {
+        MostEmployedEmployee::stream_all(connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<MostEmployedEmployee, sqlx::Error>> {
         sqlx::query_as!(
             MostEmployedEmployee,
             r#"
@@ -48,7 +57,6 @@ impl MostEmployedEmployee {
                 rscpe.realized_services_count = (SELECT MAX(realized_services_count) FROM realized_services_count_per_employee)
             "#,
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -13,6 +14,14 @@ impl MostRequestedService {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<MostRequestedService>, sqlx::Error> {
+        MostRequestedService::stream_all(connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<MostRequestedService, sqlx::Error>> {
         sqlx::query_as!(
             MostRequestedService,
             r#"
@@ -39,7 +48,6 @@ impl MostRequestedService {
                 rcps.count = (SELECT MAX(count) FROM requests_count_per_service)            
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
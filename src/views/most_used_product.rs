@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -13,6 +14,12 @@ impl MostUsedProduct {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<MostUsedProduct>, sqlx::Error> {
+        MostUsedProduct::stream_all(connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<MostUsedProduct, sqlx::Error>> {
         sqlx::query_as!(
             MostUsedProduct,
             r#"
@@ -47,7 +54,6 @@ impl MostUsedProduct {
                 puc.count = (SELECT MAX(count) FROM product_usage_count)
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 
@@ -12,6 +13,12 @@ impl NoShowClient {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<NoShowClient>, sqlx::Error> {
+        NoShowClient::stream_all(connection).try_collect().await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<NoShowClient, sqlx::Error>> {
         sqlx::query_as!(
             NoShowClient,
             r#"
@@ -27,7 +34,6 @@ impl NoShowClient {
                 i.id IS NULL
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }
@@ -0,0 +1,124 @@
+use bigdecimal::BigDecimal;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use serde::Serialize;
+use sqlx::{Executor, Postgres};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderSuggestion {
+    pub product_id: i32,
+    pub product_name: String,
+    pub dealership_rif: String,
+    pub product_count: i32,
+    pub incoming_product_count: i64,
+    pub min_capacity: i32,
+    pub max_capacity: i32,
+    pub average_daily_consumption: BigDecimal,
+    pub days_of_cover: Option<BigDecimal>,
+    pub suggested_quantity: i64,
+}
+
+impl ReorderSuggestion {
+    pub async fn select_all_by_dealership(
+        dealership_rif: String,
+        consumption_window_days: i32,
+        cover_days: i32,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<ReorderSuggestion>, sqlx::Error> {
+        ReorderSuggestion::stream_all_by_dealership(
+            dealership_rif,
+            consumption_window_days,
+            cover_days,
+            connection,
+        )
+        .try_collect()
+        .await
+    }
+
+    pub fn stream_all_by_dealership<'e, 'c: 'e>(
+        dealership_rif: String,
+        consumption_window_days: i32,
+        cover_days: i32,
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<ReorderSuggestion, sqlx::Error>> {
+        sqlx::query_as!(
+            ReorderSuggestion,
+            r#"
+            WITH consumptions AS (
+                SELECT
+                    pa.product_id,
+                    SUM(pa.application_count) AS consumed_count
+                FROM
+                    products_applications AS pa
+                    INNER JOIN orders AS o ON pa.order_id = o.id
+                WHERE
+                    o.dealership_rif = $1
+                    AND COALESCE(o.checkin_timestamp, o.reservation_timestamp)
+                        >= LOCALTIMESTAMP - MAKE_INTERVAL(days => $2)
+                GROUP BY
+                    pa.product_id
+            ),
+            incoming_transfers AS (
+                SELECT
+                    product_id,
+                    SUM(product_count) AS incoming_count
+                FROM stock_transfers
+                WHERE
+                    destination_dealership_rif = $1
+                    AND status = 'in-transit'
+                GROUP BY
+                    product_id
+            ),
+            coverages AS (
+                SELECT
+                    s.product_id,
+                    p.name AS product_name,
+                    s.dealership_rif,
+                    s.product_count,
+                    COALESCE(it.incoming_count, 0) AS incoming_product_count,
+                    s.min_capacity,
+                    s.max_capacity,
+                    COALESCE(c.consumed_count, 0)::NUMERIC / $2 AS average_daily_consumption
+                FROM
+                    stock AS s
+                    INNER JOIN products AS p ON s.product_id = p.id
+                    LEFT JOIN consumptions AS c ON s.product_id = c.product_id
+                    LEFT JOIN incoming_transfers AS it ON s.product_id = it.product_id
+                WHERE
+                    s.dealership_rif = $1
+                    AND p.deleted_at IS NULL
+            )
+            SELECT
+                product_id,
+                product_name,
+                dealership_rif AS "dealership_rif!",
+                product_count,
+                incoming_product_count AS "incoming_product_count!",
+                min_capacity,
+                max_capacity,
+                ROUND(average_daily_consumption, 2) AS "average_daily_consumption!",
+                CASE
+                    WHEN average_daily_consumption > 0
+                        THEN ROUND(
+                            (product_count + incoming_product_count - min_capacity)
+                                / average_daily_consumption,
+                            2
+                        )
+                END AS days_of_cover,
+                GREATEST(max_capacity - product_count - incoming_product_count, 0) AS "suggested_quantity!"
+            FROM coverages
+            WHERE
+                product_count + incoming_product_count <= min_capacity
+                OR product_count + incoming_product_count - min_capacity
+                    < average_daily_consumption * $3::INTEGER
+            ORDER BY
+                days_of_cover ASC NULLS LAST,
+                product_id ASC
+            "#,
+            dealership_rif,
+            consumption_window_days,
+            cover_days
+        )
+        .fetch(connection)
+    }
+}
@@ -0,0 +1,87 @@
+use serde::Serialize;
+use sqlx::{Executor, Postgres};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub kind: String,
+    pub key: String,
+    pub title: String,
+    pub snippet: String,
+    pub rank: f32,
+}
+
+impl SearchResult {
+    pub async fn select_all_matching(
+        search_terms: String,
+        include_staff: bool,
+        limit: i64,
+        connection: impl Executor<'_, Database = Postgres>,
+    ) -> Result<Vec<SearchResult>, sqlx::Error> {
+        sqlx::query_as!(
+            SearchResult,
+            r#"
+            SELECT
+                r.kind AS "kind!",
+                r.key AS "key!",
+                r.title AS "title!",
+                search_snippet(r.search_text, $1) AS "snippet!",
+                (
+                    ts_rank(to_tsvector('simple', r.search_text), to_search_query($1))
+                    + word_similarity($1, r.search_text)
+                ) AS "rank!"
+            FROM (
+                SELECT
+                    'client' AS kind,
+                    c.national_id::TEXT AS key,
+                    c.full_name AS title,
+                    c.search_text
+                FROM clients AS c
+                WHERE
+                    c.deleted_at IS NULL
+                    AND (
+                        to_tsvector('simple', c.search_text) @@ to_search_query($1)
+                        OR c.search_text ~* to_search_pattern($1)
+                    )
+                UNION ALL
+                SELECT
+                    'vehicle' AS kind,
+                    v.plate AS key,
+                    v.brand || ' ' || v.plate AS title,
+                    v.search_text
+                FROM vehicles AS v
+                WHERE
+                    v.deleted_at IS NULL
+                    AND (
+                        to_tsvector('simple', v.search_text) @@ to_search_query($1)
+                        OR v.search_text ~* to_search_pattern($1)
+                    )
+                UNION ALL
+                SELECT
+                    'staff' AS kind,
+                    s.national_id::TEXT AS key,
+                    s.full_name AS title,
+                    s.search_text
+                FROM staff AS s
+                WHERE
+                    $2
+                    AND s.deleted_at IS NULL
+                    AND (
+                        to_tsvector('simple', s.search_text) @@ to_search_query($1)
+                        OR s.search_text ~* to_search_pattern($1)
+                    )
+            ) AS r
+            ORDER BY
+                "rank!" DESC,
+                r.kind ASC,
+                r.key ASC
+            LIMIT $3
+            "#,
+            search_terms,
+            include_staff,
+            limit
+        )
+        .fetch_all(connection)
+        .await
+    }
+}
@@ -1,3 +1,4 @@
+use futures_util::{stream::BoxStream, TryStreamExt};
 use serde::Serialize;
 use sqlx::{Executor, Postgres};
 use time::PrimitiveDateTime;
@@ -14,6 +15,14 @@ impl VehicleAppliedService {
     pub async fn select_all(
         connection: impl Executor<'_, Database = Postgres>,
     ) -> Result<Vec<VehicleAppliedService>, sqlx::Error> {
+        VehicleAppliedService::stream_all(connection)
+            .try_collect()
+            .await
+    }
+
+    pub fn stream_all<'e, 'c: 'e>(
+        connection: impl Executor<'c, Database = Postgres> + 'e,
+    ) -> BoxStream<'e, Result<VehicleAppliedService, sqlx::Error>> {
         sqlx::query_as!(
             VehicleAppliedService,
             r#"
@@ -43,7 +52,6 @@ impl VehicleAppliedService {
                 po.checkin_timestamp ASC
             "#
         )
-        .fetch_all(connection)
-        .await
+        .fetch(connection)
     }
 }